@@ -0,0 +1,54 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, PRESETS, random_text};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+/// Пресеты, которые имеет смысл гонять в criterion
+const MAX_BENCH_BLOCKS: usize = 12;
+
+fn encrypt_throughput(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(1);
+    for preset in PRESETS.iter().filter(|p| p.blocks <= MAX_BENCH_BLOCKS) {
+        let cfg = ConfigData::generate(&mut rng, "latin", preset.blocks);
+        let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        let initial_state = machine.save_state();
+        let alphabet = machine.alphabet().to_vec();
+
+        let mut group = c.benchmark_group(format!("encrypt/{}", preset.name));
+        for size in [1_000, 100_000, 1_000_000] {
+            let text = random_text(&mut rng, &alphabet, size);
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &text, |b, text| {
+                b.iter(|| {
+                    machine.load_state(&initial_state);
+                    black_box(machine.encrypt(black_box(text)))
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Миллион символов обычного текста: заглавные, пробелы и знаки препинания
+/// проходят той же дорогой, что и буквы, без промежуточных буферов
+fn encrypt_mixed_text(c: &mut Criterion) {
+    let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(2), "latin", 4);
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let initial_state = machine.save_state();
+    let phrase = "The Quick, Brown FOX jumps over the lazy dog! ";
+    let text: String = phrase.chars().cycle().take(1_000_000).collect();
+
+    let mut group = c.benchmark_group("encrypt_mixed");
+    group.throughput(Throughput::Elements(1_000_000));
+    group.bench_function("1000000", |b| {
+        b.iter(|| {
+            machine.load_state(&initial_state);
+            black_box(machine.encrypt(black_box(&text)))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encrypt_throughput, encrypt_mixed_text);
+criterion_main!(benches);
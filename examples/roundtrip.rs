@@ -0,0 +1,31 @@
+//! Машина как библиотека: конфиг в коде, шифрование и расшифровка.
+//!
+//! cargo run --example roundtrip
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+
+fn main() {
+    // два цветных блока и одна пара plugboard; остальное — по умолчанию
+    let cfg = ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'q')],
+        blocks: vec!["КБЧ".into(), "З".into()],
+        rotor_positions: vec![vec![1, 2, 3], vec![4]],
+        ..Default::default()
+    };
+    cfg.validate().expect("конфиг собран правильно");
+
+    let plaintext = "attack at dawn, hold the bridge!";
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).expect("конфиг проверен");
+    let ciphertext = machine.encrypt(plaintext);
+
+    // расшифровка начинается с тех же стартовых позиций
+    machine.reset();
+    let decrypted = machine.decrypt(&ciphertext).expect("омофонов нет");
+
+    println!("plaintext:  {}", plaintext);
+    println!("ciphertext: {}", ciphertext);
+    println!("decrypted:  {}", decrypted);
+    assert_ne!(ciphertext, plaintext);
+    assert_eq!(decrypted, plaintext);
+}
@@ -0,0 +1,21 @@
+//! Произвольные байты как файл конфига: разбор и построение машины
+//! не должны паниковать, только возвращать ошибку.
+
+#![no_main]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(cfg) = serde_json::from_slice::<ConfigData>(data) else {
+        return;
+    };
+    if cfg.estimated_memory_bytes() > 64 << 20 {
+        return;
+    }
+    if let Ok(mut machine) = EnigmaSudnogoDnya::new(&cfg) {
+        let cipher = machine.encrypt("fuzz фазз 0123");
+        machine.reset();
+        assert_eq!(machine.decrypt(&cipher).unwrap(), "fuzz фазз 0123");
+    }
+});
@@ -0,0 +1,77 @@
+//! Структурно верные, но дикие конфиги: огромные позиции, неизвестные
+//! алфавиты, символы в паре сами с собой, пустые и длинные блоки.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct WildConfig {
+    alphabet: u8,
+    plugboard: Vec<(char, char)>,
+    blocks: Vec<String>,
+    rotor_positions: Vec<Vec<usize>>,
+    block_shifts: Vec<Vec<usize>>,
+    authenticate: bool,
+    digit_rotor_shift: Option<usize>,
+    reflector: u8,
+    homophonic: bool,
+    reversed: Vec<Vec<bool>>,
+    normalize: bool,
+    step_interval: u8,
+    step_every: Vec<u64>,
+    plugboard_map: Vec<(char, char)>,
+    plugboards: Vec<Vec<(char, char)>>,
+    use_reflector: bool,
+    constant_work: bool,
+    allow_degenerate: bool,
+}
+
+fuzz_target!(|wild: WildConfig| {
+    let alphabet = match wild.alphabet % 5 {
+        0 => "latin",
+        1 => "cyrillic",
+        2 => "bytes",
+        3 => "",
+        _ => "klingon",
+    };
+    let cfg = ConfigData {
+        alphabet: alphabet.into(),
+        plugboard: wild.plugboard,
+        blocks: wild.blocks,
+        rotor_positions: wild.rotor_positions,
+        block_shifts: wild.block_shifts,
+        authenticate: wild.authenticate,
+        digit_rotor_shift: wild.digit_rotor_shift,
+        lang: None,
+        reflector_kind: match wild.reflector % 5 {
+            0 => "reverse",
+            1 => "identity-pairs",
+            2 => "ukw-b",
+            3 => "ukw-c",
+            _ => "ukw-z",
+        }
+        .into(),
+        homophonic: wild.homophonic,
+        reversed: wild.reversed,
+        normalize: wild.normalize,
+        step_interval: wild.step_interval as usize,
+        step_every: wild.step_every,
+        plugboard_map: wild.plugboard_map,
+        plugboards: wild.plugboards,
+        use_reflector: wild.use_reflector,
+        constant_work: wild.constant_work,
+        allow_degenerate: wild.allow_degenerate,
+    };
+    match EnigmaSudnogoDnya::new(&cfg) {
+        Ok(mut machine) => {
+            assert!(cfg.validate().is_ok());
+            machine.encrypt("fuzz фазз 0123");
+            machine.describe();
+            machine.period();
+        }
+        Err(_) => assert!(cfg.validate().is_err()),
+    }
+});
@@ -0,0 +1,195 @@
+//! Алфавиты машины и быстрый поиск символа в них.
+
+use crate::messages;
+use crate::prelude::*;
+
+/// Имя байтового алфавита: все 256 значений байта, как символы U+0000..U+00FF
+pub const BYTES_ALPHABET: &str = "bytes";
+
+/// Встроенный алфавит. Буквы — только в нижнем регистре: машина приводит
+/// к нему текст посимвольно (`char::to_lowercase`), так что заглавные
+/// шифруются своими строчными.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    /// Имя в конфиге (`"alphabet"`)
+    pub name: &'static str,
+    /// Название для меню, по-русски и по-английски
+    pub title: &'static str,
+    pub title_en: &'static str,
+    pub letters: &'static [char],
+}
+
+const LATIN: [char; 26] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+const CYRILLIC: [char; 33] = [
+    'а', 'б', 'в', 'г', 'д', 'е', 'ё', 'ж', 'з', 'и', 'й', 'к', 'л', 'м', 'н', 'о', 'п', 'р', 'с',
+    'т', 'у', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ъ', 'ы', 'ь', 'э', 'ю', 'я',
+];
+/// Без ъ, ё, ы, э; с ґ, є, і, ї. Апостроф (м'ясо) — не буква и идёт как есть.
+const UKRAINIAN: [char; 33] = [
+    'а', 'б', 'в', 'г', 'ґ', 'д', 'е', 'є', 'ж', 'з', 'и', 'і', 'ї', 'й', 'к', 'л', 'м', 'н', 'о',
+    'п', 'р', 'с', 'т', 'у', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ь', 'ю', 'я',
+];
+/// Без и, щ, ъ; с і и ў
+const BELARUSIAN: [char; 32] = [
+    'а', 'б', 'в', 'г', 'д', 'е', 'ё', 'ж', 'з', 'і', 'й', 'к', 'л', 'м', 'н', 'о', 'п', 'р', 'с',
+    'т', 'у', 'ў', 'ф', 'х', 'ц', 'ч', 'ш', 'ы', 'ь', 'э', 'ю', 'я',
+];
+/// Монотоника: гласные с тоносом и диалитикой — отдельные буквы, как ё и й
+/// в кириллице, иначе почти каждое слово оставляло бы в шифротексте
+/// открытую букву. Конечная ς — тоже своя буква, рядом с σ: строчный текст
+/// расшифровывается с той же орфографией. Заглавная Σ без контекста
+/// слова всегда становится σ, и «ΟΔΟΣ» расшифруется как «οδοσ». У ΐ и ΰ
+/// нет заглавных: «Ϊ́» сворачивается в ι с двумя комбинируемыми знаками
+/// и доходит до буквы только с `normalize`.
+const GREEK: [char; 36] = [
+    'α', 'ά', 'β', 'γ', 'δ', 'ε', 'έ', 'ζ', 'η', 'ή', 'θ', 'ι', 'ί', 'ϊ', 'ΐ', 'κ', 'λ', 'μ', 'ν',
+    'ξ', 'ο', 'ό', 'π', 'ρ', 'σ', 'ς', 'τ', 'υ', 'ύ', 'ϋ', 'ΰ', 'φ', 'χ', 'ψ', 'ω', 'ώ',
+];
+
+/// Латиница с пробелом, запятой, точкой и цифрами: в шифротексте не видно
+/// ни границ слов, ни знаков препинания. Цифры — буквы алфавита, поэтому
+/// омофоны с ним недоступны, а цифровой ротор не нужен. Пробел — такая же
+/// буква, как остальные, и может стоять по краям шифротекста, а меню
+/// обрезает края сообщения из одной строки: такой шифротекст вводят
+/// через `<<`.
+const LATIN_EXT: [char; 39] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z', ' ', ',', '.', '0', '1', '2', '3', '4', '5', '6', '7', '8',
+    '9',
+];
+
+/// Байт `i` — символ с кодом `i`
+const BYTES: [char; 256] = {
+    let mut bytes = ['\0'; 256];
+    let mut i = 0;
+    while i < 256 {
+        bytes[i] = i as u8 as char;
+        i += 1;
+    }
+    bytes
+};
+
+impl Alphabet {
+    /// Все встроенные алфавиты; меню мастера показывает их в этом порядке,
+    /// кроме байтового
+    pub const ALL: &[Alphabet] = &[
+        Alphabet {
+            name: "latin",
+            title: "Латиница",
+            title_en: "Latin",
+            letters: &LATIN,
+        },
+        Alphabet {
+            name: "cyrillic",
+            title: "Кириллица (русский)",
+            title_en: "Cyrillic (Russian)",
+            letters: &CYRILLIC,
+        },
+        Alphabet {
+            name: "ukrainian",
+            title: "Украинский",
+            title_en: "Ukrainian",
+            letters: &UKRAINIAN,
+        },
+        Alphabet {
+            name: "belarusian",
+            title: "Белорусский",
+            title_en: "Belarusian",
+            letters: &BELARUSIAN,
+        },
+        Alphabet {
+            name: "greek",
+            title: "Греческий",
+            title_en: "Greek",
+            letters: &GREEK,
+        },
+        Alphabet {
+            name: "latin-ext",
+            title: "Латиница с пробелом, знаками и цифрами",
+            title_en: "Latin with space, punctuation and digits",
+            letters: &LATIN_EXT,
+        },
+        Alphabet {
+            name: BYTES_ALPHABET,
+            title: "Байты",
+            title_en: "Bytes",
+            letters: &BYTES,
+        },
+    ];
+
+    /// Буквы алфавита по имени из конфига; `None` — такого алфавита нет
+    pub fn by_name(name: &str) -> Option<&'static [char]> {
+        Self::find(name).map(|a| a.letters)
+    }
+
+    pub fn find(name: &str) -> Option<&'static Alphabet> {
+        Self::ALL.iter().find(|a| a.name == name)
+    }
+
+    /// Название на текущем языке сообщений
+    pub fn localized_title(&self) -> &'static str {
+        match messages::lang() {
+            messages::Lang::Ru => self.title,
+            messages::Lang::En => self.title_en,
+        }
+    }
+}
+
+/// Имена встроенных алфавитов через запятую — для сообщений об ошибках
+pub(crate) fn names() -> String {
+    Alphabet::ALL
+        .iter()
+        .map(|a| a.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Символы алфавита по имени. Неизвестное имя — кириллица, как было всегда;
+/// конфиг с таким именем отсекает `validate`.
+pub(crate) fn chars(name: &str) -> Vec<char> {
+    Alphabet::by_name(name).unwrap_or(&CYRILLIC).to_vec()
+}
+
+/// Индекс символа в алфавите. Алфавиты заведомо короче 65536 символов,
+/// поэтому внутренние таблицы хранятся в u16 — меньше давления на кеш.
+/// Публичное API по-прежнему принимает и отдаёт `usize`.
+pub(crate) type Idx = u16;
+
+/// Таблица: символ → индекс в алфавите
+#[derive(Clone)]
+pub struct AlphabetIndex {
+    min: u32,
+    indices: Vec<Option<Idx>>,
+}
+
+impl AlphabetIndex {
+    pub fn new(alphabet: &[char]) -> Self {
+        let codes: Vec<u32> = alphabet.iter().map(|&c| c as u32).collect();
+        let &min = codes.iter().min().unwrap();
+        let &max = codes.iter().max().unwrap();
+        let size = (max - min + 1) as usize;
+        let mut indices = vec![None; size];
+        for (i, &c) in alphabet.iter().enumerate() {
+            indices[(c as u32 - min) as usize] = Some(i as Idx);
+        }
+        AlphabetIndex { min, indices }
+    }
+
+    #[inline]
+    pub fn get(&self, c: char) -> Option<usize> {
+        let code = c as u32;
+        if code < self.min || code > self.min + (self.indices.len() - 1) as u32 {
+            None
+        } else {
+            self.indices[(code - self.min) as usize].map(usize::from)
+        }
+    }
+
+    /// Сколько символов в алфавите
+    pub fn alphabet_len(&self) -> usize {
+        self.indices.iter().flatten().count()
+    }
+}
@@ -0,0 +1,242 @@
+//! Частотный анализ текста и оценка пространства ключей.
+
+use crate::prelude::*;
+use crate::{ConfigData, Reflector, log2_factorial};
+#[cfg(feature = "rand")]
+use crate::{CipherError, config_bitness};
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "json")]
+use serde::Serialize;
+use alloc::collections::BTreeMap;
+
+/// Сколько самых частых биграмм попадает в отчёт
+pub const TOP_BIGRAMS: usize = 10;
+
+/// Частоты символов алфавита в тексте; остальные символы не считаются
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct FreqReport {
+    /// Символов алфавита в тексте
+    pub total: usize,
+    /// Счётчики в порядке алфавита, включая нулевые
+    pub counts: Vec<(char, usize)>,
+    /// Хи-квадрат против равномерного распределения (0 — идеально ровно)
+    pub chi_square: f64,
+    /// Индекс совпадений; у равномерного текста ≈ 1/длина алфавита
+    pub index_of_coincidence: f64,
+    /// Самые частые пары соседних букв (соседство считается после
+    /// выбрасывания символов вне алфавита)
+    pub top_bigrams: Vec<(String, usize)>,
+}
+
+/// Символ для таблиц отчёта: пробел — `␣`, управляющие — как escape.
+/// В JSON символы остаются как есть.
+pub fn readable(c: char) -> String {
+    match c {
+        ' ' => "␣".into(),
+        c => c.escape_debug().to_string(),
+    }
+}
+
+pub fn frequency_profile(text: &str, alphabet: &[char]) -> FreqReport {
+    let n = alphabet.len();
+    let letters: Vec<usize> = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(|c| alphabet.iter().position(|&a| a == c))
+        .collect();
+    let total = letters.len();
+
+    let mut counts = vec![0usize; n];
+    for &i in &letters {
+        counts[i] += 1;
+    }
+    let mut bigrams = vec![0usize; n * n];
+    for pair in letters.windows(2) {
+        bigrams[pair[0] * n + pair[1]] += 1;
+    }
+
+    let chi_square = if total == 0 {
+        0.0
+    } else {
+        let expected = total as f64 / n as f64;
+        counts
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum()
+    };
+    let index_of_coincidence = if total < 2 {
+        0.0
+    } else {
+        let same: usize = counts.iter().map(|&c| c * c.saturating_sub(1)).sum();
+        same as f64 / (total * (total - 1)) as f64
+    };
+
+    let mut top: Vec<(usize, usize)> = bigrams
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| c > 0)
+        .map(|(i, &c)| (i, c))
+        .collect();
+    // при равенстве — в порядке алфавита, чтобы отчёт был воспроизводим
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let top_bigrams = top
+        .into_iter()
+        .take(TOP_BIGRAMS)
+        .map(|(i, c)| ([alphabet[i / n], alphabet[i % n]].iter().collect(), c))
+        .collect();
+
+    FreqReport {
+        total,
+        counts: alphabet.iter().copied().zip(counts).collect(),
+        chi_square,
+        index_of_coincidence,
+        top_bigrams,
+    }
+}
+
+/// Оценка стойкости конфига в битах
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct KeyspaceEstimate {
+    /// Наивно: все стартовые позиции роторов плюс выбор пар plugboard
+    pub naive_bits: f64,
+    /// С учётом того, что роторы на самом деле дают (см. `estimate_keyspace`)
+    pub effective_bits: f64,
+    /// Вклад plugboard (входит в обе оценки)
+    pub plugboard_bits: f64,
+    /// log2 периода роторов: дальше поток сдвигов повторяется
+    pub period_bits: f64,
+}
+
+/// Наивная и эффективная битность конфига.
+///
+/// Ротор прибавляет к индексу `сдвиг + позиция` на прямом проходе и вычитает
+/// то же на обратном, поэтому весь набор блоков — это один сдвиг
+/// `S = Σ (сдвиг + позиция) mod n`, и символ шифруется как
+/// `P(R(P(x) + S) - S)`, для `reverse` это `P(n - 1 - 2S - P(x))`. Отсюда:
+/// - цвета и порядок роторов важны только через сумму — это одно число mod n,
+///   а разных отражателей `R(y + S) - S` среди них ещё меньше: у `reverse`
+///   при чётном n их n/2, у `identity-pairs` — всего 2;
+/// - однороторный блок меняет S линейно и ничего не добавляет к этому числу;
+/// - в блоке из r роторов со временем важны только переносы, а их расписание
+///   задают младшие r-1 позиций: n^(r-1) вариантов;
+/// - блоки с одинаковым числом роторов складываются, их порядок не важен —
+///   считаем мультимножества, а не кортежи.
+///
+/// Получается верхняя граница: совпадения сумм по модулю n её только уменьшат.
+pub fn estimate_keyspace(cfg: &ConfigData) -> KeyspaceEstimate {
+    let n = cfg.alphabet_chars().len();
+    let log2_n = (n as f64).log2();
+    // p пар и k проводов `plugboard_map`: выбор пар плюс не больше n!/(n-k)!
+    let board_bits = |pairs: usize, wires: usize| {
+        log2_factorial(n)
+            - log2_factorial(n.saturating_sub(2 * pairs + wires))
+            - (pairs as f64)
+            - log2_factorial(pairs)
+    };
+    // стопка панелей — всё равно одна перестановка: не больше n!
+    let stacked: f64 = cfg.plugboards.iter().map(|board| board_bits(board.len(), 0)).sum();
+    let plugboard_bits = (board_bits(cfg.plugboard.len(), cfg.plugboard_map.len()) + stacked)
+        .min(log2_factorial(n));
+    let positions_bits = cfg.rotor_count() as f64 * log2_n;
+
+    let mut blocks_by_rotors: BTreeMap<usize, usize> = BTreeMap::new();
+    let rotor_counts = cfg
+        .blocks
+        .iter()
+        .map(|b| b.chars().count())
+        .chain(cfg.block_shifts.iter().map(Vec::len));
+    for rotors in rotor_counts.filter(|&r| r > 0) {
+        *blocks_by_rotors.entry(rotors).or_default() += 1;
+    }
+    let max_rotors = blocks_by_rotors.keys().next_back().copied().unwrap_or(0);
+
+    let offset_bits = if blocks_by_rotors.is_empty() {
+        0.0
+    } else {
+        (distinct_offsets(cfg) as f64).log2()
+    };
+    let schedule_bits: f64 = blocks_by_rotors
+        .iter()
+        .filter(|&(&r, _)| r > 1)
+        .map(|(&r, &m)| log2_multisets((r - 1) as f64 * log2_n, m))
+        .sum();
+    let rotor_bits = (offset_bits + schedule_bits).min(positions_bits);
+
+    KeyspaceEstimate {
+        naive_bits: positions_bits + plugboard_bits,
+        effective_bits: rotor_bits + plugboard_bits,
+        plugboard_bits,
+        period_bits: max_rotors as f64 * log2_n,
+    }
+}
+
+/// Один случайный конфиг обзора
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct SurveyEntry {
+    pub blocks: usize,
+    pub rotors: usize,
+    /// `config_bitness` конфига
+    pub bitness: f64,
+}
+
+/// Обзор для выбора числа блоков: по `count` случайных конфигов с `pairs`
+/// парами plugboard на каждое число блоков из `block_counts`, по порядку.
+/// Конфиги только оцениваются и не возвращаются.
+#[cfg(feature = "rand")]
+pub fn survey<R: Rng>(
+    rng: &mut R,
+    alphabet: &str,
+    block_counts: &[usize],
+    count: usize,
+    pairs: usize,
+) -> Result<Vec<SurveyEntry>, CipherError> {
+    let mut entries = Vec::with_capacity(block_counts.len() * count);
+    for &blocks in block_counts {
+        for _ in 0..count {
+            let cfg = ConfigData::generate_with_pairs(rng, alphabet, blocks, pairs)?;
+            let (rotors, bitness) = (cfg.rotor_count(), config_bitness(&cfg));
+            entries.push(SurveyEntry { blocks, rotors, bitness });
+        }
+    }
+    Ok(entries)
+}
+
+/// Сколько разных отражателей `R(y + S) - S` дают n сдвигов `S`: n, делённое
+/// на число сдвигов, под которыми отражатель не меняется
+fn distinct_offsets(cfg: &ConfigData) -> usize {
+    let alphabet = cfg.alphabet_chars();
+    let n = alphabet.len();
+    let Ok(reflector) = Reflector::new(&alphabet, &cfg.reflector_kind) else {
+        return n;
+    };
+    let r = |y: usize| reflector.reflect_index(y);
+    let invariant = (0..n)
+        .filter(|&t| (0..n).all(|y| (r((y + t) % n) + n - t) % n == r(y)))
+        .count();
+    n / invariant
+}
+
+/// log2 числа мультимножеств из `m` элементов над `2^log2_kinds` видами:
+/// C(N + m - 1, m)
+fn log2_multisets(log2_kinds: f64, m: usize) -> f64 {
+    let kinds = log2_kinds.exp2();
+    if m <= 1_000_000 {
+        return (1..=m)
+            .map(|i| (kinds - 1.0 + i as f64).log2() - (i as f64).log2())
+            .sum();
+    }
+    // Стирлинг для гигантских пресетов: точность тут не важна
+    let ln_fact = |x: f64| {
+        if x < 1.0 {
+            0.0
+        } else {
+            x * x.ln() - x + 0.5 * (core::f64::consts::TAU * x).ln()
+        }
+    };
+    let m = m as f64;
+    (ln_fact(kinds + m - 1.0) - ln_fact(m) - ln_fact(kinds - 1.0)) / core::f64::consts::LN_2
+}
@@ -0,0 +1,149 @@
+//! ASCII-броня для шифротекста: кириллица и байты в hex или base64, чтобы
+//! их не портили системы, где проходит только ASCII.
+//!
+//! Строка кодируется в UTF-8, байты — в hex (строчные цифры) или base64
+//! (RFC 4648, с `=` в конце), и результат переносится по `ARMOR_COLS`
+//! символов. При разборе пробелы и переводы строк любого вида, включая
+//! `\r\n`, пропускаются, регистр hex не важен.
+
+use crate::CipherError;
+use crate::prelude::*;
+
+/// Ширина строк брони
+pub const ARMOR_COLS: usize = 64;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Во что заворачивается шифротекст
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Как есть, без брони
+    None,
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    /// `hex`, `base64` или `none`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Encoding::None),
+            "hex" => Some(Encoding::Hex),
+            "base64" => Some(Encoding::Base64),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::None => "none",
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+        }
+    }
+}
+
+/// Строка в броне `encoding`; `Encoding::None` возвращает её как есть
+pub fn encode(text: &str, encoding: Encoding) -> String {
+    match encoding {
+        Encoding::None => text.into(),
+        _ => encode_bytes(text.as_bytes(), encoding),
+    }
+}
+
+/// Обратное к `encode`. Битая броня или не UTF-8 внутри — `BadArmor`.
+pub fn decode(armored: &str, encoding: Encoding) -> Result<String, CipherError> {
+    match encoding {
+        Encoding::None => Ok(armored.into()),
+        _ => String::from_utf8(decode_bytes(armored, encoding)?)
+            .map_err(|_| CipherError::BadArmor(encoding.name())),
+    }
+}
+
+/// Байты в броне `encoding`, по `ARMOR_COLS` символов в строке. Для
+/// `Encoding::None` байты должны быть UTF-8, иначе они заменяются на U+FFFD.
+pub fn encode_bytes(data: &[u8], encoding: Encoding) -> String {
+    let body = match encoding {
+        Encoding::None => return String::from_utf8_lossy(data).into_owned(),
+        Encoding::Hex => to_hex(data),
+        Encoding::Base64 => to_base64(data),
+    };
+    crate::wrap::wrap(&body, ARMOR_COLS)
+}
+
+/// Обратное к `encode_bytes`; пробельные символы где угодно пропускаются
+pub fn decode_bytes(armored: &str, encoding: Encoding) -> Result<Vec<u8>, CipherError> {
+    let digits: Vec<char> = armored.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = match encoding {
+        Encoding::None => return Ok(armored.as_bytes().to_vec()),
+        Encoding::Hex => from_hex(&digits),
+        Encoding::Base64 => from_base64(&digits),
+    };
+    data.ok_or(CipherError::BadArmor(encoding.name()))
+}
+
+fn to_hex(data: &[u8]) -> String {
+    let nibble = |v: u8| char::from_digit(v as u32, 16).expect("полубайт");
+    data.iter().flat_map(|&b| [nibble(b >> 4), nibble(b & 0xf)]).collect()
+}
+
+fn from_hex(digits: &[char]) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| Some((pair[0].to_digit(16)? << 4 | pair[1].to_digit(16)?) as u8))
+        .collect()
+}
+
+fn to_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().fold(0u32, |acc, &b| acc << 8 | b as u32);
+        let bits = bits << (8 * (3 - chunk.len()));
+        // n байт дают n + 1 символ, остальное до четырёх — `=`
+        for i in 0..4 {
+            let c = BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize] as char;
+            out.push(if i <= chunk.len() { c } else { '=' });
+        }
+    }
+    out
+}
+
+fn from_base64(digits: &[char]) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(4) {
+        return None;
+    }
+    let quads = digits.len() / 4;
+    let mut out = Vec::with_capacity(quads * 3);
+    for (q, quad) in digits.chunks(4).enumerate() {
+        // `=` только в конце последней четвёрки: одно или два
+        let pad = quad.iter().rev().take_while(|&&c| c == '=').count();
+        if pad > 2 || (pad > 0 && q + 1 != quads) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for &c in &quad[..4 - pad] {
+            bits = bits << 6 | BASE64.iter().position(|&b| b as char == c)? as u32;
+        }
+        bits <<= 6 * pad;
+        let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        out.extend_from_slice(&bytes[..3 - pad]);
+    }
+    Some(out)
+}
+
+/// Броня, под которой лежат данные, начинающиеся с `magic`: так
+/// `decrypt-file` узнаёт файл `encrypt-file --encode` без `--decode`.
+/// Разбирается только начало текста.
+pub fn detect(armored: &str, magic: &[u8]) -> Option<Encoding> {
+    [Encoding::Hex, Encoding::Base64].into_iter().find(|&encoding| {
+        let need = match encoding {
+            Encoding::Base64 => magic.len().div_ceil(3) * 4,
+            _ => magic.len() * 2,
+        };
+        let head: String = armored.chars().filter(|c| !c.is_whitespace()).take(need).collect();
+        decode_bytes(&head, encoding).is_ok_and(|data| data.starts_with(magic))
+    })
+}
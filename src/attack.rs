@@ -0,0 +1,189 @@
+//! Учебная атака перебором по известному началу открытого текста (crib).
+//!
+//! Годится только для маленьких машин: 1–2 блока по паре роторов, без
+//! plugboard. Цвета не перебираются: сдвиг ротора складывается с его
+//! позицией, так что любой цвет заменяется на `К` с поправкой позиции
+//! старшего ротора блока (см. `analysis::estimate_keyspace`).
+
+use crate::{BYTES_ALPHABET, CipherError, ConfigData, EnigmaSudnogoDnya};
+use rayon::prelude::*;
+use zeroize::Zeroize;
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+/// Больше наборов стартовых позиций `crack_positions` не перебирает:
+/// 26^4 латиницы влезает, 26^5 — уже нет
+pub const MAX_POSITION_CANDIDATES: u64 = 1 << 20;
+
+/// Больше вариантов `brute_force` не перебирает, считая все формы вместе:
+/// 2 блока по 2 ротора кириллицы (~1,3 млн) влезают, 3 блока — уже нет
+pub const MAX_SEARCH_CANDIDATES: u64 = 1 << 22;
+
+/// Границы перебора
+#[derive(Debug, Clone)]
+pub struct SearchSpace {
+    pub alphabet: String,
+    pub max_blocks: usize,
+    pub max_rotors: usize,
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        SearchSpace {
+            alphabet: ConfigData::default().alphabet,
+            max_blocks: 2,
+            max_rotors: 2,
+        }
+    }
+}
+
+/// Конфиг, с которым шифротекст начинается с crib, и вся расшифровка
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct CandidateConfig {
+    pub config: ConfigData,
+    pub plaintext: String,
+}
+
+/// Все формы машины из пространства: число роторов в каждом блоке
+fn shapes(space: &SearchSpace) -> Vec<Vec<usize>> {
+    let mut all = Vec::new();
+    let mut current: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in 0..space.max_blocks {
+        current = current
+            .into_iter()
+            .flat_map(|shape| {
+                (1..=space.max_rotors).map(move |r| {
+                    let mut next = shape.clone();
+                    next.push(r);
+                    next
+                })
+            })
+            .collect();
+        all.extend(current.iter().cloned());
+    }
+    all
+}
+
+/// Сколько вариантов переберёт `brute_force` по всем формам; `None` —
+/// больше `MAX_SEARCH_CANDIDATES`
+fn search_size(n: usize, space: &SearchSpace) -> Option<u64> {
+    let n = n as u64;
+    // вариантов на блок: n + n² + … + n^max_rotors
+    let mut per_block = 0u64;
+    let mut power = 1u64;
+    for _ in 0..space.max_rotors {
+        power = power.checked_mul(n)?;
+        per_block = per_block.checked_add(power)?;
+    }
+    let mut total = 0u64;
+    let mut shapes = 1u64;
+    for _ in 0..space.max_blocks {
+        shapes = shapes.checked_mul(per_block)?;
+        total = total.checked_add(shapes)?;
+    }
+    Some(total).filter(|&t| t <= MAX_SEARCH_CANDIDATES)
+}
+
+/// Перебирает формы и стартовые позиции; crib ищется в начале сообщения.
+/// Эквивалентные конфиги не схлопываются: их количество и есть разница
+/// между наивной и эффективной битностью. Вариантов больше
+/// `MAX_SEARCH_CANDIDATES` — ошибка `SearchSpaceTooLarge`, перебор не
+/// начинается.
+pub fn brute_force(
+    cipher: &str,
+    crib: &str,
+    space: &SearchSpace,
+) -> Result<Vec<CandidateConfig>, CipherError> {
+    let probe = ConfigData {
+        alphabet: space.alphabet.clone(),
+        blocks: vec!["К".into()],
+        ..Default::default()
+    };
+    probe.validate()?;
+    let n = probe.alphabet_chars().len();
+    if search_size(n, space).is_none() {
+        return Err(CipherError::SearchSpaceTooLarge { max: MAX_SEARCH_CANDIDATES });
+    }
+    let crib = crib.to_lowercase();
+    let head: String = cipher.chars().take(crib.chars().count()).collect();
+
+    let mut found = Vec::new();
+    for shape in shapes(space) {
+        // не переполняется: сумма по всем формам уже проверена
+        let total = n.pow(shape.iter().sum::<usize>() as u32);
+        let blocks: Vec<String> = shape.iter().map(|&r| "К".repeat(r)).collect();
+        let mut matches: Vec<CandidateConfig> = (0..total)
+            .into_par_iter()
+            .filter_map(|mut index| {
+                let rotor_positions = shape
+                    .iter()
+                    .map(|&r| {
+                        (0..r)
+                            .map(|_| {
+                                let p = index % n;
+                                index /= n;
+                                p
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let config = ConfigData {
+                    alphabet: space.alphabet.clone(),
+                    blocks: blocks.clone(),
+                    rotor_positions,
+                    ..Default::default()
+                };
+                let mut machine = EnigmaSudnogoDnya::new(&config).ok()?;
+                if machine.decrypt(&head).ok()? != crib {
+                    return None;
+                }
+                machine.reset();
+                let plaintext = machine.decrypt(cipher).ok()?;
+                Some(CandidateConfig { config, plaintext })
+            })
+            .collect();
+        found.append(&mut matches);
+    }
+    Ok(found)
+}
+
+/// Подбирает стартовые позиции роторов известной машины по паре «открытый
+/// фрагмент — его шифротекст» с начала сообщения. Позиции из `cfg`
+/// игнорируются. Варианты идут как показания счётчика, первый совпавший
+/// возвращается — это один из эквивалентных ключей, а на фрагменте короче
+/// оборота младшего ротора — возможно, и неверный дальше. `None`, если
+/// совпадений нет, конфиг негоден или вариантов больше
+/// `MAX_POSITION_CANDIDATES`.
+pub fn crack_positions(
+    cfg: &ConfigData,
+    known_plain: &str,
+    known_cipher: &str,
+) -> Option<Vec<Vec<usize>>> {
+    let mut bare = ConfigData { rotor_positions: Vec::new(), ..cfg.clone() };
+    let built = EnigmaSudnogoDnya::new(&bare);
+    bare.zeroize();
+    let mut machine = built.ok()?;
+    let n = machine.alphabet().len() as u64;
+    let mut positions = machine.save_state();
+    let rotors = positions.iter().map(Vec::len).sum::<usize>() as u32;
+    let total = n.checked_pow(rotors).filter(|&t| t <= MAX_POSITION_CANDIDATES)?;
+    let cipher = if cfg.alphabet == BYTES_ALPHABET {
+        known_cipher.to_string()
+    } else {
+        known_cipher.to_lowercase()
+    };
+
+    for mut index in 0..total {
+        for p in positions.iter_mut().flatten() {
+            *p = (index % n) as usize;
+            index /= n;
+        }
+        machine.reset();
+        machine.load_state(&positions);
+        if machine.encrypt_iter(known_plain.chars()).eq(cipher.chars()) {
+            return Some(positions);
+        }
+    }
+    None
+}
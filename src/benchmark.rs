@@ -0,0 +1,142 @@
+//! Замеры скорости: построение машины, шифрование и расшифровка случайных
+//! текстов нескольких размеров и проверка, что расшифровка вернула текст.
+//! Отчёт сериализуется — его читают CI и блокноты, меню печатает его
+//! таблицей.
+
+use crate::{BENCH_SEED, CipherError, ConfigData, EnigmaSudnogoDnya};
+use crate::{config_bitness, measure_roundtrip, random_text};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Размеры текстов по умолчанию, символов; на последнем меню меряет потоки
+pub const DEFAULT_SIZES: &[usize] = &[10, 100, 1_000, 10_000, 50_000, 1_000_000];
+
+/// Как мерить
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkOptions {
+    /// Зерно текстов: одно зерно — одни и те же тексты, и замеры до и после
+    /// изменения сравнимы
+    pub seed: u64,
+    /// Бюджет на весь замер. Размер, который по скорости предыдущего в него
+    /// уже не влезет, пропускается; первый меряется всегда.
+    pub max_time: Option<Duration>,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        BenchmarkOptions { seed: BENCH_SEED, max_time: None }
+    }
+}
+
+/// Замер одного размера
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SizeReport {
+    /// Символов в тексте
+    pub size: usize,
+    /// Только проход по тексту
+    pub encrypt_secs: f64,
+    pub decrypt_secs: f64,
+    pub encrypt_chars_per_sec: f64,
+    pub decrypt_chars_per_sec: f64,
+    /// Расшифровка вернула исходный текст
+    pub kat_ok: bool,
+}
+
+/// Отчёт `run`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    /// `ConfigData::fingerprint` в hex: какой конфиг мерили
+    pub fingerprint: String,
+    /// `config_bitness`
+    pub bitness: f64,
+    /// Период роторов, символов; `u128::MAX` — больше, чем влезает
+    pub period: u128,
+    /// Только `EnigmaSudnogoDnya::new`: машина строится один раз на весь
+    /// замер, между размерами — `reset`
+    pub construct_secs: f64,
+    /// Замеры в порядке размеров
+    pub sizes: Vec<SizeReport>,
+    /// Размеры, не влезшие в `BenchmarkOptions::max_time`
+    pub skipped: Vec<usize>,
+}
+
+impl BenchmarkReport {
+    /// Все замеры прошли проверку расшифровкой
+    pub fn kat_ok(&self) -> bool {
+        self.sizes.iter().all(|s| s.kat_ok)
+    }
+}
+
+/// Замер по размерам `sizes` с настройками по умолчанию
+pub fn run(cfg: &ConfigData, sizes: &[usize]) -> Result<BenchmarkReport, CipherError> {
+    run_with(cfg, sizes, &BenchmarkOptions::default())
+}
+
+/// Замер по размерам `sizes`. Тексты — из одного ГПСЧ, как у
+/// `bench_corpus`, и генерируются по одному, так что пропущенные размеры
+/// памяти не занимают.
+pub fn run_with(
+    cfg: &ConfigData,
+    sizes: &[usize],
+    options: &BenchmarkOptions,
+) -> Result<BenchmarkReport, CipherError> {
+    // построение входит в бюджет `max_time`: у гигантских пресетов оно
+    // дольше любого размера
+    let start = Instant::now();
+    let mut machine = EnigmaSudnogoDnya::new(cfg)?;
+    let construct_secs = start.elapsed().as_secs_f64();
+    let alphabet = machine.alphabet().to_vec();
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut report = BenchmarkReport {
+        fingerprint: cfg.fingerprint().iter().map(|b| format!("{:02x}", b)).collect(),
+        bitness: config_bitness(cfg),
+        period: machine.period(),
+        construct_secs,
+        sizes: Vec::with_capacity(sizes.len()),
+        skipped: Vec::new(),
+    };
+    for &size in sizes {
+        if let (Some(budget), Some(last)) = (options.max_time, report.sizes.last())
+            && start.elapsed().saturating_add(predicted(last, size)) > budget
+        {
+            report.skipped.push(size);
+            continue;
+        }
+        let text = random_text(&mut rng, &alphabet, size);
+        machine.reset();
+        let rt = measure_roundtrip(&mut machine, &text);
+        let per_sec = |secs: f64| size as f64 / secs.max(f64::MIN_POSITIVE);
+        report.sizes.push(SizeReport {
+            size,
+            encrypt_secs: rt.encrypt_secs,
+            decrypt_secs: rt.decrypt_secs,
+            encrypt_chars_per_sec: per_sec(rt.encrypt_secs),
+            decrypt_chars_per_sec: per_sec(rt.decrypt_secs),
+            kat_ok: rt.ok,
+        });
+    }
+    Ok(report)
+}
+
+/// Сколько займёт размер `size` при скорости замера `last`
+fn predicted(last: &SizeReport, size: usize) -> Duration {
+    let rate = last.encrypt_chars_per_sec.min(last.decrypt_chars_per_sec);
+    Duration::try_from_secs_f64(2.0 * size as f64 / rate)
+        .unwrap_or(Duration::MAX)
+}
+
+/// Размер с десятичным суффиксом: `500`, `1k`, `100K`, `10m`, `1g`.
+/// Ноль и переполнение — `None`.
+pub fn parse_size(s: &str) -> Option<usize> {
+    let s = s.trim();
+    let (digits, multiplier) = match s.char_indices().last()? {
+        (i, 'k' | 'K') => (&s[..i], 1_000),
+        (i, 'm' | 'M') => (&s[..i], 1_000_000),
+        (i, 'g' | 'G') => (&s[..i], 1_000_000_000),
+        _ => (s, 1),
+    };
+    let n: usize = digits.parse().ok()?;
+    n.checked_mul(multiplier).filter(|&n| n > 0)
+}
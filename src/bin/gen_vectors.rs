@@ -0,0 +1,56 @@
+//! Генератор эталонных векторов.
+//!
+//! `gen_vectors [каталог]` — сверяет шифротексты в `tests/vectors` с текущей
+//! сборкой и падает при расхождении. `gen_vectors --bless [каталог]` —
+//! перезаписывает их: только когда алгоритм изменён намеренно.
+
+use enigma_sd::messages::{self, Lang, Msg};
+use enigma_sd::vectors::{self, VECTORS_DIR};
+use std::path::Path;
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    messages::set_lang(messages::lang_from_env().unwrap_or(Lang::Ru));
+    let bless = match args.iter().position(|a| a == "--bless") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let dir = args.first().map_or(VECTORS_DIR, String::as_str);
+
+    let loaded = vectors::load_dir(Path::new(dir)).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::ReadFailed.fill(&[&dir, &e]));
+        std::process::exit(1);
+    });
+    let mut mismatched = 0;
+    for (path, mut vector) in loaded {
+        let actual = match vector.compute() {
+            Ok(actual) => actual,
+            Err(e) => {
+                eprintln!("{}", Msg::VectorFailed.fill(&[&vector.name, &e]));
+                mismatched += 1;
+                continue;
+            }
+        };
+        if actual == vector.ciphertext {
+            println!("{}", Msg::VectorOk.fill(&[&vector.name]));
+        } else if bless {
+            vector.ciphertext = actual;
+            if let Err(e) = vectors::save(&path, &vector) {
+                eprintln!("{}", Msg::WriteFailed.fill(&[&path.display(), &e]));
+                std::process::exit(1);
+            }
+            println!("{}", Msg::VectorBlessed.fill(&[&vector.name]));
+        } else {
+            let msg = Msg::VectorMismatch.fill(&[&vector.name, &vector.ciphertext, &actual]);
+            eprintln!("{}", msg);
+            mismatched += 1;
+        }
+    }
+    if mismatched > 0 {
+        eprintln!("{}", Msg::VectorsBlessHint.text());
+        std::process::exit(1);
+    }
+}
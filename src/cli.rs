@@ -0,0 +1,268 @@
+//! Разбор командной строки и ввод с клавиатуры — общее для всех режимов
+//! бинарника.
+
+use enigma_sd::armor::Encoding;
+use enigma_sd::messages::{Lang, Msg};
+use enigma_sd::{Alphabet, CipherError, ConfigData, MacKey};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::rngs::StdRng;
+use rand::{SeedableRng, rng};
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zeroize::Zeroize;
+
+/// Строка со stdin без пробелов по краям; `None` — конец ввода (Ctrl-D).
+/// Для вопросов меню — `Console`.
+pub fn read_line() -> io::Result<Option<String>> {
+    let mut s = String::new();
+    match io::stdin().read_line(&mut s)? {
+        0 => Ok(None),
+        _ => Ok(Some(s.trim().to_string())),
+    }
+}
+
+/// Файл конфига рядом с программой
+pub const CONFIG_FILE: &str = "esd_config.json";
+
+/// Как печатать результат шифрования
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Флаги командной строки, общие для всех режимов
+pub struct Options {
+    pub format: OutputFormat,
+    pub authenticate: bool,
+    pub passphrase: String,
+    pub advance_config: bool,
+    pub trace: bool,
+    /// `--interactive`: меню даже без терминала — для скриптов команд на stdin
+    pub interactive: bool,
+    pub seed: Option<u64>,
+    /// `--config`: путь к конфигу; `*.bin` читается и пишется в двоичном формате
+    pub config: String,
+    /// Все `--config`, если их больше одного: каскад машин по порядку
+    pub cascade: Vec<String>,
+    /// Без `--no-strict` машины каскада обязаны иметь один алфавит
+    pub strict: bool,
+    /// Язык из `--lang` или `GIORDANO_LANG`; без него действует поле `lang` конфига
+    pub lang: Option<Lang>,
+    /// `--quiet`: без индикаторов хода и предупреждений
+    pub quiet: bool,
+    /// `--input a b ...`: файлы вместо stdin, одним потоком
+    pub inputs: Vec<String>,
+    /// `--wrap <cols>`: перенос текстового шифротекста, см. `enigma_sd::wrap`
+    pub wrap: Option<usize>,
+    /// `--encode hex|base64|none`: броня вывода, см. `enigma_sd::armor`
+    pub encode: Encoding,
+    /// `--decode hex|base64|none`: броня входа; без флага `decrypt-file`
+    /// узнаёт её сам
+    pub decode: Option<Encoding>,
+    /// `--history-messages`: сообщения из строки команды попадают в историю
+    pub history_messages: bool,
+    /// `--allow-degenerate`: принять конфиг без блоков, см.
+    /// `ConfigData::allow_degenerate`
+    pub allow_degenerate: bool,
+    /// `--iv <n>`: перед каждым сообщением блоки шагают n раз, см.
+    /// `EnigmaSudnogoDnya::set_offset`
+    pub iv: Option<u64>,
+}
+
+/// Парольная фраза не остаётся в памяти после выхода
+impl Drop for Options {
+    fn drop(&mut self) {
+        self.passphrase.zeroize();
+    }
+}
+
+impl Options {
+    /// ГПСЧ для генерации конфига: с `--seed` один и тот же конфиг
+    /// получается при каждом запуске
+    pub fn config_rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rng()),
+        }
+    }
+
+    /// Ключ для тега целостности, если он включён флагом или конфигом
+    pub fn mac_key(&self, cfg: &ConfigData) -> Option<MacKey> {
+        (self.authenticate || cfg.authenticate).then(|| cfg.mac_key(&self.passphrase))
+    }
+}
+
+/// Вынимает из аргументов флаг `name`
+pub fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let pos = args.iter().position(|a| a == name);
+    pos.map(|pos| args.remove(pos)).is_some()
+}
+
+/// Вынимает из аргументов `name значение` или `name=значение`
+pub fn take_value(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let pos = args
+        .iter()
+        .position(|a| a == name || a.starts_with(&prefix))?;
+    let flag = args.remove(pos);
+    match flag.strip_prefix(&prefix) {
+        Some(v) => Some(v.to_string()),
+        None if pos < args.len() => Some(args.remove(pos)),
+        None => {
+            eprintln!("{}", Msg::MissingValue.fill(&[&name]));
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Вынимает `--alphabet имя`; имени нет в `Alphabet::ALL` — выход с ошибкой
+pub fn take_alphabet(args: &mut Vec<String>) -> Option<String> {
+    let name = take_value(args, "--alphabet")?;
+    if Alphabet::by_name(&name).is_none() {
+        eprintln!("{}", CipherError::UnknownAlphabet(name));
+        std::process::exit(2);
+    }
+    Some(name)
+}
+
+/// Вынимает `name` и все значения за ним до следующего флага
+pub fn take_values(args: &mut Vec<String>, name: &str) -> Vec<String> {
+    let Some(pos) = args.iter().position(|a| a == name) else {
+        return Vec::new();
+    };
+    let end = args[pos + 1..]
+        .iter()
+        .position(|a| a.starts_with("--"))
+        .map_or(args.len(), |i| pos + 1 + i);
+    if end == pos + 1 {
+        eprintln!("{}", Msg::MissingValue.fill(&[&name]));
+        std::process::exit(2);
+    }
+    args.drain(pos..end).skip(1).collect()
+}
+
+/// Явно выбранный язык: `--lang ru|en`, затем переменная `GIORDANO_LANG`.
+/// Без них язык берётся из конфига, потом из `LANG`, по умолчанию русский.
+pub fn take_lang(args: &mut Vec<String>) -> Option<Lang> {
+    if let Some(code) = take_value(args, "--lang") {
+        return Some(Lang::parse(&code).unwrap_or_else(|| {
+            eprintln!("{}", Msg::BadLang.text());
+            std::process::exit(2);
+        }));
+    }
+    std::env::var("GIORDANO_LANG").ok().and_then(|code| Lang::parse(&code))
+}
+
+pub fn take_options(args: &mut Vec<String>, lang: Option<Lang>) -> Options {
+    let format = match take_value(args, "--output-format").as_deref() {
+        None | Some("text") => OutputFormat::Text,
+        Some("json") => OutputFormat::Json,
+        Some(_) => {
+            eprintln!("{}", Msg::BadOutputFormat.text());
+            std::process::exit(2);
+        }
+    };
+    let configs: Vec<String> = std::iter::from_fn(|| take_value(args, "--config")).collect();
+    Options {
+        format,
+        authenticate: take_flag(args, "--authenticate"),
+        passphrase: take_value(args, "--passphrase").unwrap_or_default(),
+        advance_config: take_flag(args, "--advance-config"),
+        trace: take_flag(args, "--trace"),
+        interactive: take_flag(args, "--interactive"),
+        seed: take_value(args, "--seed").map(|seed| {
+            seed.parse().unwrap_or_else(|_| {
+                eprintln!("{}", Msg::BadSeed.text());
+                std::process::exit(2);
+            })
+        }),
+        config: configs.first().cloned().unwrap_or_else(|| CONFIG_FILE.to_string()),
+        cascade: if configs.len() > 1 { configs } else { Vec::new() },
+        strict: !take_flag(args, "--no-strict"),
+        lang,
+        quiet: take_flag(args, "--quiet"),
+        inputs: take_values(args, "--input"),
+        wrap: take_value(args, "--wrap").map(|cols| match cols.parse() {
+            Ok(cols) if cols > 0 => cols,
+            _ => {
+                eprintln!("{}", Msg::BadWrap.text());
+                std::process::exit(2);
+            }
+        }),
+        encode: take_encoding(args, "--encode").unwrap_or(Encoding::None),
+        decode: take_encoding(args, "--decode"),
+        history_messages: take_flag(args, "--history-messages"),
+        allow_degenerate: take_flag(args, "--allow-degenerate"),
+        iv: take_value(args, "--iv").map(|iv| {
+            iv.parse().unwrap_or_else(|_| {
+                eprintln!("{}", Msg::BadIv.text());
+                std::process::exit(2);
+            })
+        }),
+    }
+}
+
+/// `--encode` или `--decode` с именем брони
+fn take_encoding(args: &mut Vec<String>, name: &str) -> Option<Encoding> {
+    take_value(args, name).map(|value| {
+        Encoding::parse(&value).unwrap_or_else(|| {
+            eprintln!("{}", Msg::BadEncoding.text());
+            std::process::exit(2);
+        })
+    })
+}
+
+/// `--quiet` на весь процесс, как и язык: индикаторы нужны глубоко внутри
+/// команд, куда `Options` не доходит
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `--iv` на весь процесс: машины строит `build_machine`, а до него
+/// `Options` тоже не доходит
+static IV: OnceLock<u64> = OnceLock::new();
+
+pub fn set_iv(iv: Option<u64>) {
+    if let Some(iv) = iv {
+        IV.set(iv).ok();
+    }
+}
+
+pub fn iv() -> Option<u64> {
+    IV.get().copied()
+}
+
+/// Показывать ли ход долгих операций: не при `--quiet` и не когда stdout
+/// перенаправлен — тогда программу запускает скрипт
+pub fn show_progress() -> bool {
+    !is_quiet() && io::stdout().is_terminal()
+}
+
+/// Индикатор хода в stderr; для байтов — со скоростью и ETA. Без
+/// `show_progress` индикатор скрыт и ничего не рисует.
+pub fn progress_bar(label: Msg, total: Option<u64>, bytes: bool) -> ProgressBar {
+    if !show_progress() {
+        return ProgressBar::hidden();
+    }
+    let template = match (total, bytes) {
+        (Some(_), true) => "{msg} {bar:40} {bytes}/{total_bytes} {bytes_per_sec} ETA {eta}",
+        (Some(_), false) => "{msg} {bar:40} {pos}/{len} ETA {eta}",
+        (None, true) => "{msg} {spinner} {bytes} {bytes_per_sec}",
+        (None, false) => "{msg} {spinner} {pos}",
+    };
+    let bar = match total {
+        Some(total) => ProgressBar::new(total),
+        None => ProgressBar::new_spinner(),
+    };
+    bar.set_style(ProgressStyle::with_template(template).expect("шаблон корректен"));
+    bar.set_message(label.text());
+    bar
+}
@@ -0,0 +1,66 @@
+//! Шифрование прямо в буфере обмена: скопировал сообщение из чата,
+//! `encrypt --clipboard`, вставил шифротекст.
+//!
+//! Сама работа с буфером спрятана за `Clipboard`, чтобы логику можно было
+//! проверить без дисплея. Системный буфер (`SystemClipboard`, фича
+//! `clipboard`) на машине без графики не открывается — это ошибка
+//! `io::Error`, а не паника.
+
+use crate::{EnigmaSudnogoDnya, MacKey};
+use std::io;
+
+/// Текстовый буфер обмена
+pub trait Clipboard {
+    fn get_text(&mut self) -> io::Result<String>;
+    fn set_text(&mut self, text: &str) -> io::Result<()>;
+}
+
+/// Шифрует (или с `decrypt` расшифровывает) текст из буфера и кладёт
+/// результат обратно. С `key` шифротекст несёт тег целостности, как у
+/// `encrypt_authenticated`. Если расшифровать не вышло, буфер не трогается.
+/// Возвращает число символов результата.
+pub fn process(
+    clipboard: &mut dyn Clipboard,
+    machine: &mut EnigmaSudnogoDnya,
+    decrypt: bool,
+    key: Option<&MacKey>,
+) -> io::Result<usize> {
+    let text = clipboard.get_text()?;
+    let result = match (decrypt, key) {
+        (false, None) => Ok(machine.encrypt(&text)),
+        (false, Some(key)) => Ok(machine.encrypt_authenticated(&text, key)),
+        (true, None) => machine.decrypt(&text),
+        (true, Some(key)) => machine.decrypt_authenticated(&text, key),
+    };
+    let result = result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    clipboard.set_text(&result)?;
+    Ok(result.chars().count())
+}
+
+/// Системный буфер обмена через `arboard`
+#[cfg(feature = "clipboard")]
+pub struct SystemClipboard(arboard::Clipboard);
+
+#[cfg(feature = "clipboard")]
+fn unavailable(e: arboard::Error) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+#[cfg(feature = "clipboard")]
+impl SystemClipboard {
+    /// Без дисплея (SSH, CI, контейнер) — ошибка
+    pub fn open() -> io::Result<Self> {
+        arboard::Clipboard::new().map(SystemClipboard).map_err(unavailable)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl Clipboard for SystemClipboard {
+    fn get_text(&mut self) -> io::Result<String> {
+        self.0.get_text().map_err(unavailable)
+    }
+
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        self.0.set_text(text).map_err(unavailable)
+    }
+}
@@ -0,0 +1,631 @@
+//! Конфиг машины: что хранится в JSON и двоичном файле и как он
+//! проверяется перед сборкой.
+
+use crate::prelude::*;
+use crate::machine::{DEFAULT_REFLECTOR, Reflector, Rotor, color_shift, rotor_period};
+use crate::machine::canonical_colors;
+use crate::plugboard::Plugboard;
+#[cfg(feature = "rand")]
+use crate::progress::{GENERATE_BATCH, Progress};
+use crate::{
+    Alphabet, AlphabetIndex, BYTES_ALPHABET, CipherError, Idx, Redacted, alphabet, homophonic,
+    messages,
+};
+#[cfg(feature = "json")]
+use crate::MacKey;
+#[cfg(feature = "std")]
+use crate::{config_bin, mnemonic};
+#[cfg(feature = "rand")]
+use crate::{plugboard, random_blocks, random_positions};
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use sha2::{Digest, Sha256};
+use core::fmt;
+use zeroize::Zeroize;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Максимум роторов в одном блоке по умолчанию — защита от опечаток
+/// в ручных конфигах, которые создают патологически длинные блоки.
+pub const MAX_ROTORS_PER_BLOCK: usize = 64;
+
+/// Конфиг для (де)сериализации через JSON. `Debug` скрывает ключ,
+/// см. `debug_full`.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct ConfigData {
+    pub alphabet: String,                 // имя из `Alphabet::ALL`, напр. "latin"
+    pub plugboard: Vec<(char, char)>,     // пары замен
+    pub blocks: Vec<String>,              // цветовые метки, напр. "КБЧ" или "kbc"
+    pub rotor_positions: Vec<Vec<usize>>, // для каждого блока — вектор стартовых позиций роторов
+    /// Блоки с численными сдвигами роторов вместо цветов; в машине идут
+    /// после `blocks`, и `rotor_positions` описывает сначала те, потом эти
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub block_shifts: Vec<Vec<usize>>,
+    /// Добавлять к шифротексту тег целостности (HMAC-SHA256)
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "core::ops::Not::not"))]
+    pub authenticate: bool,
+    /// Сдвиг цифрового ротора: если задан, цифры 0-9 вне алфавита шифруются
+    /// между собой, а не проходят как есть
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub digit_rotor_shift: Option<usize>,
+    /// Язык интерфейса для этого конфига; `--lang` и `GIORDANO_LANG` важнее.
+    /// На шифр и отпечаток не влияет.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub lang: Option<messages::Lang>,
+    /// Имя отражателя из `REFLECTOR_KINDS`
+    #[cfg_attr(
+        feature = "json",
+        serde(default = "default_reflector", skip_serializing_if = "is_default_reflector")
+    )]
+    pub reflector_kind: String,
+    /// Омофоны для частых букв: алфавит дополняется цифрами, см. `homophonic`
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "core::ops::Not::not"))]
+    pub homophonic: bool,
+    /// Обратная разводка роторов, по вектору на блок в том же порядке, что
+    /// `rotor_positions`; пусто — все роторы прямые. Перевёрнутый ротор
+    /// вычитает свой сдвиг вместо прибавления. К стойкости это почти ничего
+    /// не добавляет: знак лишь меняет сумму сдвигов, см. `estimate_keyspace`.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub reversed: Vec<Vec<bool>>,
+    /// Приводить текст к NFC перед шифрованием, чтобы разложенные символы
+    /// («и» + U+0306) шифровались как буква алфавита («й»). Шифротекст
+    /// тогда может быть короче исходной строки.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "core::ops::Not::not"))]
+    pub normalize: bool,
+    /// Роторы шагают после каждого `step_interval`-го символа алфавита, а не
+    /// после каждого; 1 — обычное шагание. Фазы внутри интервала нет в
+    /// `rotor_positions`, поэтому продолжать поток с сохранённых позиций
+    /// можно, только если сообщение кончилось на границе интервала.
+    #[cfg_attr(
+        feature = "json",
+        serde(default = "default_step_interval", skip_serializing_if = "is_default_step_interval")
+    )]
+    pub step_interval: usize,
+    /// Делители шагания по блокам в порядке `rotor_positions`: блок `i`
+    /// шагает раз в `step_every[i]` тактов (такт — `step_interval`
+    /// символов). Пусто — все блоки шагают на каждом такте. Период растёт
+    /// без новых роторов, но медленный блок подолгу стоит на месте.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub step_every: Vec<u64>,
+    /// Панель «Uhr»: провода `(a, b)` из `a` в `b`, произвольная перестановка
+    /// вместо пар. На входе в роторы берётся она, на выходе — обратная.
+    /// Заменяет `plugboard`: задать оба поля — ошибка.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub plugboard_map: Vec<(char, char)>,
+    /// Ещё панели пар за `plugboard` (или `plugboard_map`), как входная и
+    /// выходная панели: на входе сигнал идёт через все по порядку, на
+    /// выходе — обратно в обратном порядке. Стопка пар даёт перестановку,
+    /// которой не собрать одной панелью из пар. Пусто — панель одна.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub plugboards: Vec<Vec<(char, char)>>,
+    /// Отражатель в цепи; без него шифрует один прямой проход по блокам,
+    /// а `decrypt` идёт обратным. Машина тогда не сама себе обратна: нет
+    /// исторической слабости «буква не шифруется в себя», но и `encrypt`
+    /// второй раз уже не расшифровывает.
+    #[cfg_attr(
+        feature = "json",
+        serde(default = "default_use_reflector", skip_serializing_if = "is_default_use_reflector")
+    )]
+    pub use_reflector: bool,
+    /// Одинаковая работа на каждый символ: символ вне алфавита (и цифра
+    /// цифрового ротора) тоже делает холостой прогон через роторы, чтобы
+    /// время `encrypt` не выдавало, сколько в тексте букв алфавита.
+    /// Шифротекст от флага не меняется; цена — лишний прогон на каждый
+    /// пропущенный символ.
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "core::ops::Not::not"))]
+    pub constant_work: bool,
+    /// Разрешить конфиг без единого блока. Такая машина — неизменная
+    /// простая замена plugboard → отражатель → plugboard, поэтому без флага
+    /// `validate` её не пропускает. Не сохраняется: разрешение даётся при
+    /// запуске (`--allow-degenerate`), а не в файле ключа.
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub allow_degenerate: bool,
+}
+
+impl ConfigData {
+    /// Общий для `Debug` и `debug_full` вывод; без `full` ключевые поля
+    /// заменены на `<redacted>`
+    fn fmt_fields(&self, f: &mut fmt::Formatter<'_>, full: bool) -> fmt::Result {
+        fn hide(full: bool, value: &dyn fmt::Debug) -> &dyn fmt::Debug {
+            if full { value } else { &Redacted }
+        }
+        let key = |value| hide(full, value);
+        f.debug_struct("ConfigData")
+            .field("alphabet", &self.alphabet)
+            .field("plugboard", key(&self.plugboard))
+            .field("blocks", key(&self.blocks))
+            .field("rotor_positions", key(&self.rotor_positions))
+            .field("block_shifts", key(&self.block_shifts))
+            .field("authenticate", &self.authenticate)
+            .field("digit_rotor_shift", key(&self.digit_rotor_shift))
+            .field("lang", &self.lang)
+            .field("reflector_kind", &self.reflector_kind)
+            .field("homophonic", &self.homophonic)
+            .field("reversed", key(&self.reversed))
+            .field("normalize", &self.normalize)
+            .field("step_interval", &self.step_interval)
+            .field("step_every", key(&self.step_every))
+            .field("plugboard_map", key(&self.plugboard_map))
+            .field("plugboards", key(&self.plugboards))
+            .field("use_reflector", &self.use_reflector)
+            .field("constant_work", &self.constant_work)
+            .field("allow_degenerate", &self.allow_degenerate)
+            .finish()
+    }
+
+    /// Полный `Debug` с ключом — для тестов и отладки, не для логов
+    pub fn debug_full(&self) -> String {
+        struct Full<'a>(&'a ConfigData);
+        impl fmt::Debug for Full<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_fields(f, true)
+            }
+        }
+        format!("{:?}", Full(self))
+    }
+}
+
+/// Ключ случайно не попадёт в логи и сообщения об ошибках
+impl fmt::Debug for ConfigData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_fields(f, false)
+    }
+}
+
+/// Затирает ключевые поля — те, что `Debug` прячет. Сам по себе конфиг при
+/// удалении не затирается: `Drop` сломал бы `..Default::default()`, так что
+/// временные копии ключа затирают явно.
+impl Zeroize for ConfigData {
+    fn zeroize(&mut self) {
+        self.plugboard.zeroize();
+        self.blocks.zeroize();
+        self.rotor_positions.zeroize();
+        self.block_shifts.zeroize();
+        self.digit_rotor_shift.zeroize();
+        self.reversed.zeroize();
+        self.step_every.zeroize();
+        self.plugboard_map.zeroize();
+        self.plugboards.zeroize();
+    }
+}
+
+/// Позиции равны с точностью до недостающих нулей: `[]`, `[[]]` и
+/// `[[0, 0]]` ставят роторы одинаково
+fn same_positions(a: &[Vec<usize>], b: &[Vec<usize>]) -> bool {
+    let at = |p: &[Vec<usize>], blk: usize, r: usize| {
+        p.get(blk).and_then(|v| v.get(r)).copied().unwrap_or(0)
+    };
+    (0..a.len().max(b.len())).all(|blk| {
+        let rotors = [a, b].iter().filter_map(|p| p.get(blk)).map(Vec::len).max();
+        (0..rotors.unwrap_or(0)).all(|r| at(a, blk, r) == at(b, blk, r))
+    })
+}
+
+fn default_reflector() -> String {
+    DEFAULT_REFLECTOR.into()
+}
+
+#[cfg(feature = "json")]
+fn is_default_reflector(kind: &str) -> bool {
+    kind == DEFAULT_REFLECTOR
+}
+
+#[cfg(feature = "json")]
+fn default_use_reflector() -> bool {
+    true
+}
+
+#[cfg(feature = "json")]
+fn is_default_use_reflector(use_reflector: &bool) -> bool {
+    *use_reflector
+}
+
+#[cfg(feature = "json")]
+fn default_step_interval() -> usize {
+    1
+}
+
+#[cfg(feature = "json")]
+fn is_default_step_interval(interval: &usize) -> bool {
+    *interval == 1
+}
+
+impl Default for ConfigData {
+    fn default() -> Self {
+        ConfigData {
+            alphabet: "latin".into(),
+            plugboard: Vec::new(),
+            blocks: Vec::new(),
+            rotor_positions: Vec::new(),
+            block_shifts: Vec::new(),
+            authenticate: false,
+            digit_rotor_shift: None,
+            lang: None,
+            reflector_kind: default_reflector(),
+            homophonic: false,
+            reversed: Vec::new(),
+            normalize: false,
+            step_interval: 1,
+            step_every: Vec::new(),
+            plugboard_map: Vec::new(),
+            plugboards: Vec::new(),
+            use_reflector: true,
+            constant_work: false,
+            allow_degenerate: false,
+        }
+    }
+}
+
+impl ConfigData {
+    /// Символы алфавита, выбранного в конфиге
+    pub fn alphabet_chars(&self) -> Vec<char> {
+        let mut chars = alphabet::chars(&self.alphabet);
+        if self.homophonic {
+            chars.extend(homophonic::HOMOPHONE_SYMBOLS.chars());
+        }
+        chars
+    }
+
+    /// Тот же ключ в байтовом режиме: блоки и позиции без изменений,
+    /// а пары и провода всех панелей переводятся в байты по номеру символа
+    /// в алфавите.
+    pub fn to_byte_mode(&self) -> ConfigData {
+        if self.alphabet == BYTES_ALPHABET {
+            return self.clone();
+        }
+        let chars = self.alphabet_chars();
+        let to_byte = |c: char| match chars.iter().position(|&a| a == c) {
+            Some(i) => char::from(i as u8),
+            None => c,
+        };
+        let to_bytes = |wires: &[(char, char)]| -> Vec<(char, char)> {
+            wires.iter().map(|&(a, b)| (to_byte(a), to_byte(b))).collect()
+        };
+        ConfigData {
+            alphabet: BYTES_ALPHABET.into(),
+            plugboard: to_bytes(&self.plugboard),
+            blocks: self.blocks.clone(),
+            rotor_positions: self.rotor_positions.clone(),
+            block_shifts: self.block_shifts.clone(),
+            authenticate: self.authenticate,
+            digit_rotor_shift: self.digit_rotor_shift,
+            lang: self.lang,
+            reflector_kind: self.reflector_kind.clone(),
+            // байты — не текст, сглаживать в них нечего
+            homophonic: false,
+            reversed: self.reversed.clone(),
+            // байты не нормализуются
+            normalize: false,
+            step_interval: self.step_interval,
+            step_every: self.step_every.clone(),
+            plugboard_map: to_bytes(&self.plugboard_map),
+            plugboards: self.plugboards.iter().map(|board| to_bytes(board)).collect(),
+            use_reflector: self.use_reflector,
+            constant_work: self.constant_work,
+            allow_degenerate: self.allow_degenerate,
+        }
+    }
+
+    /// Случайный конфиг: `blocks` блоков, стартовые позиции и
+    /// `plugboard::DEFAULT_PAIRS` пар plugboard
+    #[cfg(feature = "rand")]
+    pub fn generate<R: Rng>(rng: &mut R, alphabet: &str, blocks: usize) -> Self {
+        Self::generate_with_pairs(rng, alphabet, blocks, plugboard::DEFAULT_PAIRS)
+            .expect("пар по умолчанию хватает любому алфавиту")
+    }
+
+    /// То же, что `generate`, но с заданным числом пар plugboard
+    #[cfg(feature = "rand")]
+    pub fn generate_with_pairs<R: Rng>(
+        rng: &mut R,
+        alphabet: &str,
+        blocks: usize,
+        pairs: usize,
+    ) -> Result<Self, CipherError> {
+        Self::generate_with_progress(rng, alphabet, blocks, pairs, |_| {})
+    }
+
+    /// То же, что `generate_with_pairs`, но каждые `GENERATE_BATCH` блоков
+    /// зовёт `progress`. Блоки и позиции — два прохода, поэтому всего
+    /// `2 * blocks` шагов; поток ГПСЧ тот же, что без колбэка.
+    #[cfg(feature = "rand")]
+    pub fn generate_with_progress<R: Rng, F: FnMut(Progress)>(
+        rng: &mut R,
+        alphabet: &str,
+        blocks: usize,
+        pairs: usize,
+        mut progress: F,
+    ) -> Result<Self, CipherError> {
+        let total = Some(2 * blocks as u64);
+        let mut done = 0;
+        let mut cfg = ConfigData {
+            alphabet: alphabet.into(),
+            plugboard: Vec::new(),
+            blocks: Vec::with_capacity(blocks),
+            ..Default::default()
+        };
+        while cfg.blocks.len() < blocks {
+            let batch = GENERATE_BATCH.min(blocks - cfg.blocks.len());
+            cfg.blocks.extend(random_blocks(rng, batch));
+            done += batch as u64;
+            progress(Progress { done, total });
+        }
+        let alphabet_chars = cfg.alphabet_chars();
+        cfg.rotor_positions = Vec::with_capacity(blocks);
+        for batch in cfg.blocks.chunks(GENERATE_BATCH) {
+            let positions = random_positions(rng, batch, alphabet_chars.len());
+            cfg.rotor_positions.extend(positions);
+            done += batch.len() as u64;
+            progress(Progress { done, total });
+        }
+        cfg.plugboard = plugboard::random_pairs(&alphabet_chars, pairs, rng)?;
+        Ok(cfg)
+    }
+
+    /// Расшифрует ли `other` то, что зашифровано этим конфигом: совпадают
+    /// алфавит, панели, блоки, отражатель, стартовые позиции и всё, что
+    /// меняет шифротекст. Язык, `constant_work` и `allow_degenerate` не в
+    /// счёт, цвета сравниваются без учёта написания (`kbc` и `КБЧ`). Блок
+    /// цветами и тот же блок числами (`block_shifts`) считаются разными.
+    /// Панели сравниваются собранной перестановкой: `(a, b)` и `(b, a)`,
+    /// порядок пар и одна панель в `plugboard` или в `plugboards` — одно и
+    /// то же. Недостающие позиции — нули, как их и ставит машина.
+    pub fn compatible_for_decrypt(&self, other: &ConfigData) -> bool {
+        // без `..`: новое поле не скомпилируется, пока его не разберут здесь
+        let ConfigData {
+            alphabet,
+            plugboard,
+            blocks,
+            rotor_positions,
+            block_shifts,
+            authenticate,
+            digit_rotor_shift,
+            lang: _,
+            reflector_kind,
+            homophonic,
+            reversed,
+            normalize,
+            step_interval,
+            step_every,
+            plugboard_map,
+            plugboards,
+            use_reflector,
+            constant_work: _,
+            allow_degenerate: _,
+        } = self;
+        let same_blocks = blocks.len() == other.blocks.len()
+            && blocks
+                .iter()
+                .zip(&other.blocks)
+                .all(|(a, b)| canonical_colors(a) == canonical_colors(b));
+        let index = AlphabetIndex::new(&alphabet::chars(alphabet));
+        let board = |c: &ConfigData| {
+            Plugboard::new(&c.plugboard, &c.plugboard_map, &c.plugboards, &index)
+        };
+        let same_plugboard = match (board(self), board(other)) {
+            (Ok(a), Ok(b)) => a == b,
+            // неверную панель не собрать: тогда хотя бы буквальное совпадение
+            _ => {
+                *plugboard == other.plugboard
+                    && *plugboard_map == other.plugboard_map
+                    && *plugboards == other.plugboards
+            }
+        };
+        *alphabet == other.alphabet
+            && same_plugboard
+            && same_blocks
+            && same_positions(rotor_positions, &other.rotor_positions)
+            && *block_shifts == other.block_shifts
+            && *authenticate == other.authenticate
+            && *digit_rotor_shift == other.digit_rotor_shift
+            && *reflector_kind == other.reflector_kind
+            && *homophonic == other.homophonic
+            && *reversed == other.reversed
+            && *normalize == other.normalize
+            && *step_interval == other.step_interval
+            && *step_every == other.step_every
+            && *use_reflector == other.use_reflector
+    }
+
+    /// Отпечаток ключа: SHA-256 от JSON-представления конфига
+    #[cfg(feature = "json")]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        // язык — настройка интерфейса, а не ключа: один ключ на разных языках
+        // должен давать один отпечаток и один MAC. Так же и с `constant_work`,
+        // не меняющим шифротекст, и с написанием цветов: `kbc` и `КБЧ` — один
+        // и тот же блок
+        let blocks: Vec<String> = self.blocks.iter().map(|b| canonical_colors(b)).collect();
+        let json = if self.lang.is_none() && !self.constant_work && blocks == self.blocks {
+            serde_json::to_vec(self)
+        } else {
+            let key = ConfigData { lang: None, constant_work: false, blocks, ..self.clone() };
+            serde_json::to_vec(&key)
+        }
+        .expect("конфиг всегда сериализуется");
+        Sha256::digest(json).into()
+    }
+
+    /// Ключ для тега целостности: отпечаток конфига плюс необязательная
+    /// парольная фраза (пустая строка — без неё)
+    #[cfg(feature = "json")]
+    pub fn mac_key(&self, passphrase: &str) -> MacKey {
+        let mut hasher = Sha256::new();
+        hasher.update(b"esd-mac-v1");
+        hasher.update(self.fingerprint());
+        hasher.update(passphrase.as_bytes());
+        MacKey(hasher.finalize().into())
+    }
+
+    /// Конфиг одной строкой, которую удобно переслать: двоичный формат
+    /// (`*.bin`) в base64. Ошибка — как у `save_config_bin`.
+    #[cfg(feature = "std")]
+    pub fn export_code(&self) -> io::Result<String> {
+        config_bin::encode_code(self)
+    }
+
+    /// Обратное к `export_code`
+    #[cfg(feature = "std")]
+    pub fn import_code(code: &str) -> io::Result<ConfigData> {
+        config_bin::decode_code(code)
+    }
+
+    /// Конфиг словами из списка BIP39 со словом проверки, см. `mnemonic`.
+    /// Конфиг больше `MAX_MNEMONIC_WORDS` слов — ошибка `InvalidInput`.
+    #[cfg(feature = "std")]
+    pub fn to_mnemonic(&self) -> io::Result<String> {
+        mnemonic::encode(self)
+    }
+
+    /// Обратное к `to_mnemonic`
+    #[cfg(feature = "std")]
+    pub fn from_mnemonic(words: &str) -> io::Result<ConfigData> {
+        mnemonic::decode(words)
+    }
+
+    /// Примерный объём памяти под конфиг и построенную из него машину: блоки
+    /// и роторы машины (вместе со снимком стартовых позиций для `reset`) и
+    /// строки блоков с векторами позиций самого конфига
+    pub fn estimated_memory_bytes(&self) -> usize {
+        Self::memory_bytes(self.block_count(), self.rotor_count())
+    }
+
+    /// Та же оценка по одним числам блоков и роторов — по ней же считает
+    /// `Preset::estimated_memory_bytes`, так что предупреждения мастера и CLI
+    /// совпадают
+    pub(crate) fn memory_bytes(blocks: usize, rotors: usize) -> usize {
+        let machine = blocks * core::mem::size_of::<u8>()
+            + rotors * (core::mem::size_of::<Rotor>() + core::mem::size_of::<Idx>());
+        // на блок: строка цветов и вектор позиций — заголовки плюс содержимое
+        let config = blocks * 2 * core::mem::size_of::<Vec<u8>>()
+            + rotors * ('К'.len_utf8() + core::mem::size_of::<usize>());
+        machine + config
+    }
+
+    /// Число блоков: цветовые плюс численные
+    pub fn block_count(&self) -> usize {
+        self.blocks.len() + self.block_shifts.len()
+    }
+
+    /// Число роторов во всех блоках
+    pub fn rotor_count(&self) -> usize {
+        let colored: usize = self.blocks.iter().map(|b| b.chars().count()).sum();
+        colored + self.block_shifts.iter().map(Vec::len).sum::<usize>()
+    }
+
+    /// Период роторов по конфигу, без построения машины
+    /// (см. `EnigmaSudnogoDnya::period`)
+    pub fn period(&self) -> u128 {
+        let rotor_counts = self
+            .blocks
+            .iter()
+            .map(|b| b.chars().count())
+            .chain(self.block_shifts.iter().map(Vec::len));
+        let every = self.step_every.iter().copied().chain(core::iter::repeat(1));
+        rotor_period(self.alphabet_chars().len(), rotor_counts.zip(every), self.step_interval)
+    }
+
+    /// Проверка конфига с лимитом роторов по умолчанию
+    pub fn validate(&self) -> Result<(), CipherError> {
+        self.validate_with_max_rotors(MAX_ROTORS_PER_BLOCK)
+    }
+
+    pub fn validate_with_max_rotors(&self, max: usize) -> Result<(), CipherError> {
+        for (i, colors) in self.blocks.iter().enumerate() {
+            let rotors = colors.chars().count();
+            if rotors == 0 {
+                return Err(CipherError::EmptyBlock);
+            }
+            if rotors > max {
+                return Err(CipherError::TooManyRotors { block: i, rotors, max });
+            }
+            if let Some(c) = colors.chars().find(|&c| color_shift(c).is_none()) {
+                return Err(CipherError::UnknownColor(c));
+            }
+        }
+        if Alphabet::by_name(&self.alphabet).is_none() {
+            return Err(CipherError::UnknownAlphabet(self.alphabet.clone()));
+        }
+        let alphabet = self.alphabet_chars();
+        let alphabet_len = alphabet.len();
+        for (i, shifts) in self.block_shifts.iter().enumerate() {
+            let rotors = shifts.len();
+            if rotors == 0 {
+                return Err(CipherError::EmptyBlock);
+            }
+            if rotors > max {
+                let block = self.blocks.len() + i;
+                return Err(CipherError::TooManyRotors { block, rotors, max });
+            }
+            if let Some(&shift) = shifts.iter().find(|&&s| s >= alphabet_len) {
+                return Err(CipherError::ShiftOutOfRange { shift, alphabet_len });
+            }
+        }
+        let blocks = self.block_count();
+        let positions = self.rotor_positions.len();
+        if positions != 0 && positions != blocks {
+            return Err(CipherError::PositionBlockCountMismatch { blocks, positions });
+        }
+        let rotor_counts = || {
+            self.blocks
+                .iter()
+                .map(|b| b.chars().count())
+                .chain(self.block_shifts.iter().map(Vec::len))
+        };
+        for (block, (rotors, pos)) in rotor_counts().zip(&self.rotor_positions).enumerate() {
+            if pos.len() != rotors {
+                let positions = pos.len();
+                return Err(CipherError::RotorPositionCountMismatch { block, rotors, positions });
+            }
+        }
+        let reversed = self.reversed.len();
+        if reversed != 0 && reversed != blocks {
+            return Err(CipherError::ReversedBlockCountMismatch { blocks, reversed });
+        }
+        for (block, (rotors, flags)) in rotor_counts().zip(&self.reversed).enumerate() {
+            if flags.len() != rotors {
+                let flags = flags.len();
+                return Err(CipherError::ReversedRotorCountMismatch { block, rotors, flags });
+            }
+        }
+        if self.step_interval == 0 {
+            return Err(CipherError::ZeroStepInterval);
+        }
+        let (blocks, step_every) = (self.block_count(), self.step_every.len());
+        if step_every != 0 && step_every != blocks {
+            return Err(CipherError::StepEveryBlockCountMismatch { blocks, step_every });
+        }
+        if let Some(block) = self.step_every.iter().position(|&every| every == 0) {
+            return Err(CipherError::ZeroStepEvery { block });
+        }
+        let letters = alphabet::chars(&self.alphabet);
+        let digits_taken =
+            letters.iter().any(char::is_ascii_digit) || self.digit_rotor_shift.is_some();
+        if self.homophonic && digits_taken {
+            return Err(CipherError::HomophonicUnsupported);
+        }
+        let index = AlphabetIndex::new(&alphabet);
+        Plugboard::new(&self.plugboard, &self.plugboard_map, &self.plugboards, &index)?;
+        Reflector::new(&alphabet, &self.reflector_kind)?;
+        // последним: у конфига без блоков сначала видны ошибки в остальном
+        if self.block_count() == 0 && !self.allow_degenerate {
+            return Err(CipherError::NoBlocks);
+        }
+        Ok(())
+    }
+}
+
+/// Каскад: конфиги машин по порядку шифрования, см. `MultiEnigma`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CascadeConfig {
+    pub configs: Vec<ConfigData>,
+    /// Машины с разными алфавитами — ошибка; без этого флага символы вне
+    /// алфавита машины проходят её как есть
+    pub strict: bool,
+}
+
+/// Расширение двоичного конфига (см. `load_config`)
+pub const BIN_CONFIG_EXT: &str = ".bin";
@@ -0,0 +1,314 @@
+//! Компактный двоичный формат конфига для огромных пресетов: JSON на
+//! миллионах блоков разбирается долго, а здесь цвет ротора — один байт,
+//! числа — LEB128.
+//!
+//! Раскладка: `ESDB`, версия, алфавит, флаг `authenticate`, цифровой ротор
+//! (0 — нет, иначе сдвиг + 1; с версии 2), язык (0 — нет, 1 — ru, 2 — en;
+//! с версии 3), имя отражателя (с версии 4), флаг `homophonic` (с версии 5),
+//! пары plugboard, цветные блоки, числовые блоки, позиции, флаги `reversed`
+//! как 0/1 (с версии 6), флаг `normalize` (с версии 7), `step_interval`
+//! (с версии 8), `step_every` (с версии 9), `plugboard_map` (с версии 10),
+//! флаг `use_reflector` (с версии 11), панели `plugboards` (с версии 12),
+//! флаг `constant_work` (с версии 13). Списки и строки — длина и элементы.
+
+use crate::messages::Lang;
+use crate::machine::{color_shift, shift_color};
+use crate::{ConfigData, DEFAULT_REFLECTOR};
+use std::io;
+
+pub(crate) const MAGIC: &[u8; 4] = b"ESDB";
+const VERSION: u8 = 13;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn put_uint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn put_usizes(out: &mut Vec<u8>, values: &[usize]) {
+    put_uint(out, values.len() as u64);
+    for &v in values {
+        put_uint(out, v as u64);
+    }
+}
+
+fn put_chars(out: &mut Vec<u8>, pairs: &[(char, char)]) {
+    put_uint(out, pairs.len() as u64);
+    for &(a, b) in pairs {
+        put_uint(out, a as u64);
+        put_uint(out, b as u64);
+    }
+}
+
+/// Конфиг в байты. Неизвестный цвет ротора одним байтом не записать,
+/// поэтому такой конфиг — ошибка `InvalidInput`.
+pub fn encode(cfg: &ConfigData) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(16 + cfg.estimated_memory_bytes());
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    put_uint(&mut out, cfg.alphabet.len() as u64);
+    out.extend_from_slice(cfg.alphabet.as_bytes());
+    out.push(cfg.authenticate as u8);
+    // сдвиг по модулю 10 тот же ротор, а +1 не переполнится
+    put_uint(&mut out, cfg.digit_rotor_shift.map_or(0, |s| s as u64 % 10 + 1));
+    out.push(match cfg.lang {
+        None => 0,
+        Some(Lang::Ru) => 1,
+        Some(Lang::En) => 2,
+    });
+    put_uint(&mut out, cfg.reflector_kind.len() as u64);
+    out.extend_from_slice(cfg.reflector_kind.as_bytes());
+    out.push(cfg.homophonic as u8);
+
+    put_chars(&mut out, &cfg.plugboard);
+
+    put_uint(&mut out, cfg.blocks.len() as u64);
+    for colors in &cfg.blocks {
+        put_uint(&mut out, colors.chars().count() as u64);
+        for c in colors.chars() {
+            let shift = color_shift(c).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("unknown color {:?}", c))
+            })?;
+            out.push(shift as u8);
+        }
+    }
+
+    put_uint(&mut out, cfg.block_shifts.len() as u64);
+    for shifts in &cfg.block_shifts {
+        put_usizes(&mut out, shifts);
+    }
+    put_uint(&mut out, cfg.rotor_positions.len() as u64);
+    for positions in &cfg.rotor_positions {
+        put_usizes(&mut out, positions);
+    }
+    put_uint(&mut out, cfg.reversed.len() as u64);
+    for flags in &cfg.reversed {
+        put_usizes(&mut out, &flags.iter().map(|&f| f as usize).collect::<Vec<_>>());
+    }
+    out.push(cfg.normalize as u8);
+    put_uint(&mut out, cfg.step_interval as u64);
+    put_uint(&mut out, cfg.step_every.len() as u64);
+    for &every in &cfg.step_every {
+        put_uint(&mut out, every);
+    }
+    put_chars(&mut out, &cfg.plugboard_map);
+    out.push(cfg.use_reflector as u8);
+    put_uint(&mut out, cfg.plugboards.len() as u64);
+    for board in &cfg.plugboards {
+        put_chars(&mut out, board);
+    }
+    out.push(cfg.constant_work as u8);
+    Ok(out)
+}
+
+/// Читатель с проверкой границ: обрезанный или битый файл — ошибка, не паника
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> io::Result<u8> {
+        let (&b, rest) = self.data.split_first().ok_or_else(|| invalid("truncated"))?;
+        self.data = rest;
+        Ok(b)
+    }
+
+    fn bytes(&mut self, n: usize) -> io::Result<&[u8]> {
+        if n > self.data.len() {
+            return Err(invalid("truncated"));
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn uint(&mut self) -> io::Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            v |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    fn usize(&mut self) -> io::Result<usize> {
+        usize::try_from(self.uint()?).map_err(|_| invalid("value too large"))
+    }
+
+    /// Длина списка: каждый элемент занимает хотя бы байт, так что длина
+    /// больше остатка файла — заведомо мусор (и не повод выделять гигабайты)
+    fn len(&mut self) -> io::Result<usize> {
+        let n = self.usize()?;
+        if n > self.data.len() {
+            return Err(invalid("length exceeds data"));
+        }
+        Ok(n)
+    }
+
+    fn char(&mut self) -> io::Result<char> {
+        let v = u32::try_from(self.uint()?).map_err(|_| invalid("bad char"))?;
+        char::from_u32(v).ok_or_else(|| invalid("bad char"))
+    }
+
+    fn char_pairs(&mut self, n: usize) -> io::Result<Vec<(char, char)>> {
+        (0..n).map(|_| Ok((self.char()?, self.char()?))).collect()
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let n = self.len()?;
+        String::from_utf8(self.bytes(n)?.to_vec()).map_err(|_| invalid("bad string"))
+    }
+
+    fn usizes(&mut self) -> io::Result<Vec<usize>> {
+        let n = self.len()?;
+        (0..n).map(|_| self.usize()).collect()
+    }
+}
+
+/// Байты обратно в конфиг
+pub fn decode(data: &[u8]) -> io::Result<ConfigData> {
+    let mut r = Reader { data };
+    if r.bytes(MAGIC.len())? != MAGIC {
+        return Err(invalid("not a binary config"));
+    }
+    let version = r.byte()?;
+    if !(1..=VERSION).contains(&version) {
+        return Err(invalid("unsupported binary config version"));
+    }
+    let alphabet = r.string()?;
+    let authenticate = r.byte()? != 0;
+    let digit_rotor_shift = match version {
+        1 => None,
+        _ => r.usize()?.checked_sub(1),
+    };
+    let lang = match version {
+        1 | 2 => None,
+        _ => match r.byte()? {
+            0 => None,
+            1 => Some(Lang::Ru),
+            2 => Some(Lang::En),
+            _ => return Err(invalid("bad language")),
+        },
+    };
+    let reflector_kind = match version {
+        1..=3 => DEFAULT_REFLECTOR.to_string(),
+        _ => r.string()?,
+    };
+    let homophonic = version >= 5 && r.byte()? != 0;
+
+    let n = r.len()?;
+    let plugboard = r.char_pairs(n)?;
+
+    let n = r.len()?;
+    let mut blocks = Vec::with_capacity(n);
+    for _ in 0..n {
+        let rotors = r.len()?;
+        let colors = r
+            .bytes(rotors)?
+            .iter()
+            .map(|&s| shift_color(s as usize).ok_or_else(|| invalid("bad color")))
+            .collect::<io::Result<String>>()?;
+        blocks.push(colors);
+    }
+
+    let n = r.len()?;
+    let block_shifts = (0..n).map(|_| r.usizes()).collect::<io::Result<_>>()?;
+    let n = r.len()?;
+    let rotor_positions = (0..n).map(|_| r.usizes()).collect::<io::Result<_>>()?;
+    let n = if version >= 6 { r.len()? } else { 0 };
+    let reversed = (0..n)
+        .map(|_| Ok(r.usizes()?.into_iter().map(|f| f != 0).collect()))
+        .collect::<io::Result<_>>()?;
+    let normalize = version >= 7 && r.byte()? != 0;
+    let step_interval = if version >= 8 { r.usize()? } else { 1 };
+    let n = if version >= 9 { r.len()? } else { 0 };
+    let step_every = (0..n).map(|_| r.uint()).collect::<io::Result<_>>()?;
+    let n = if version >= 10 { r.len()? } else { 0 };
+    let plugboard_map = r.char_pairs(n)?;
+    let use_reflector = version < 11 || r.byte()? != 0;
+    let n = if version >= 12 { r.len()? } else { 0 };
+    let plugboards = (0..n)
+        .map(|_| {
+            let pairs = r.len()?;
+            r.char_pairs(pairs)
+        })
+        .collect::<io::Result<_>>()?;
+    let constant_work = version >= 13 && r.byte()? != 0;
+    if !r.data.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+
+    Ok(ConfigData {
+        alphabet,
+        plugboard,
+        blocks,
+        rotor_positions,
+        block_shifts,
+        authenticate,
+        digit_rotor_shift,
+        lang,
+        reflector_kind,
+        homophonic,
+        reversed,
+        normalize,
+        step_interval,
+        step_every,
+        plugboard_map,
+        plugboards,
+        use_reflector,
+        constant_work,
+        // разрешение запуска, а не часть ключа: в файл не пишется
+        allow_degenerate: false,
+    })
+}
+
+/// Алфавит base64 без `+` и `/` (RFC 4648, §5): строка целиком выделяется
+/// двойным щелчком и не ломается в URL
+const CODE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Конфиг одной строкой: двоичный формат в base64 без `=` в конце
+pub fn encode_code(cfg: &ConfigData) -> io::Result<String> {
+    let data = encode(cfg)?;
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().fold(0u32, |acc, &b| acc << 8 | b as u32);
+        let bits = bits << (8 * (3 - chunk.len()));
+        // n байт дают n + 1 символ
+        for i in 0..=chunk.len() {
+            out.push(CODE_ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    Ok(out)
+}
+
+/// Строка `encode_code` обратно в конфиг. Пробелы и переводы строк
+/// пропускаются — код мог перенестись при копировании.
+pub fn decode_code(code: &str) -> io::Result<ConfigData> {
+    let digits = code
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| {
+            let digit = CODE_ALPHABET.iter().position(|&c| c == b);
+            digit.map(|d| d as u32).ok_or_else(|| invalid("bad code character"))
+        })
+        .collect::<io::Result<Vec<u32>>>()?;
+    if digits.len() % 4 == 1 {
+        return Err(invalid("truncated code"));
+    }
+    let mut data = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let bits = chunk.iter().fold(0u32, |acc, &d| acc << 6 | d) << (6 * (4 - chunk.len()));
+        data.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+    decode(&data)
+}
@@ -0,0 +1,236 @@
+//! Ввод и вывод меню через `Console`: приглашения, вопросы, ответы и вывод
+//! команд основного цикла `run_menu`. Ошибки и прогресс идут мимо него, в
+//! stderr. Программа строит `Console` над stdin и stdout, тесты — над строкой
+//! сценария и буфером.
+//!
+//! С фичей `cli` на терминале можно включить редактор строк: стрелки,
+//! история из `HISTORY_FILE`, Ctrl-C. Тогда строки читает он, а не `R`.
+
+use crate::messages::{self, Msg};
+use crate::repl::{self, Command};
+use std::io::{self, BufRead, Write};
+
+#[cfg(feature = "cli")]
+use rustyline::error::ReadlineError;
+#[cfg(feature = "cli")]
+use rustyline::{Config, DefaultEditor};
+
+/// Файл истории команд меню, рядом с конфигом по умолчанию
+pub const HISTORY_FILE: &str = "esd_history";
+#[cfg(feature = "cli")]
+const HISTORY_SIZE: usize = 1000;
+/// Приглашение для строк многострочного сообщения
+pub const HEREDOC_PROMPT: &str = "| ";
+
+/// Чем закончился ввод строки
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Line(String),
+    /// Ctrl-C: строка брошена, программа работает дальше
+    Cancelled,
+    /// Конец ввода (Ctrl-D)
+    End,
+}
+
+/// Редактор строк с историей
+#[cfg(feature = "cli")]
+struct LineEditor {
+    editor: DefaultEditor,
+    history_messages: bool,
+}
+
+/// Ввод `R` и вывод `W` меню. Сам `Console` — тоже `Write`: обычный вывод
+/// меню пишется в него через `writeln!`.
+pub struct Console<R, W> {
+    input: R,
+    output: W,
+    #[cfg(feature = "cli")]
+    editor: Option<LineEditor>,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Console {
+            input,
+            output,
+            #[cfg(feature = "cli")]
+            editor: None,
+        }
+    }
+
+    /// Включает редактор строк с историей из `HISTORY_FILE`; зовётся, только
+    /// если stdin — терминал. С `history_messages` в историю попадают и
+    /// сообщения из строки команды. Если редактор не открылся, строки
+    /// по-прежнему читаются из `R`.
+    #[cfg(feature = "cli")]
+    pub fn enable_editor(&mut self, history_messages: bool) {
+        let open = || -> rustyline::Result<DefaultEditor> {
+            let config = Config::builder()
+                .auto_add_history(false)
+                .history_ignore_dups(true)?
+                .max_history_size(HISTORY_SIZE)?
+                .build();
+            DefaultEditor::with_config(config)
+        };
+        let Ok(mut editor) = open() else { return };
+        // при первом запуске файла ещё нет
+        editor.load_history(HISTORY_FILE).ok();
+        self.editor = Some(LineEditor { editor, history_messages });
+    }
+
+    /// Записывает строку меню в историю, без текста сообщения, если его не
+    /// разрешили. История — удобство: ошибка записи работе не мешает.
+    pub fn remember(&mut self, command: &Command, line: &str) {
+        #[cfg(feature = "cli")]
+        if let Some(LineEditor { editor, history_messages }) = &mut self.editor
+            && let Some(entry) = command.history_entry(line, *history_messages)
+        {
+            editor.add_history_entry(entry).ok();
+            editor.append_history(HISTORY_FILE).ok();
+        }
+        #[cfg(not(feature = "cli"))]
+        let _ = (command, line);
+    }
+
+    /// Приглашение и строка как есть, без обрезки пробелов и перевода строки
+    fn read_raw(&mut self, text: &str) -> io::Result<Input> {
+        #[cfg(feature = "cli")]
+        if let Some(LineEditor { editor, .. }) = &mut self.editor {
+            return match editor.readline(text) {
+                Ok(line) => Ok(Input::Line(line)),
+                Err(ReadlineError::Interrupted) => Ok(Input::Cancelled),
+                Err(ReadlineError::Eof) => Ok(Input::End),
+                Err(ReadlineError::Io(e)) => Err(e),
+                Err(e) => Err(io::Error::other(e)),
+            };
+        }
+        write!(self.output, "{}", text)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok(Input::End);
+        }
+        Ok(Input::Line(line.trim_end_matches(['\n', '\r']).to_string()))
+    }
+
+    /// Печатает приглашение и читает ответ без пробелов по краям. На конце
+    /// ввода переводит строку, чтобы следующий вывод не прилип к приглашению.
+    pub fn read_input(&mut self, text: &str) -> io::Result<Input> {
+        Ok(match self.read_raw(text)? {
+            Input::Line(line) => Input::Line(line.trim().to_string()),
+            other => other,
+        })
+    }
+
+    /// Как `read_input`, но Ctrl-C спрашивает заново
+    pub fn prompt(&mut self, text: &str) -> io::Result<Option<String>> {
+        loop {
+            match self.read_input(text)? {
+                Input::Line(line) => return Ok(Some(line)),
+                Input::Cancelled => continue,
+                Input::End => return Ok(None),
+            }
+        }
+    }
+
+    /// Сообщение для encrypt/decrypt: `inline` из строки команды или ответ на
+    /// приглашение. `<<` читает строки как есть до `repl::HEREDOC_END` и
+    /// склеивает их через `\n`. Ctrl-C бросает сообщение целиком.
+    pub fn read_message(&mut self, inline: Option<&str>) -> io::Result<Input> {
+        let first = match inline {
+            Some(text) => text.to_string(),
+            None => match self.read_input(Msg::MessagePrompt.text())? {
+                Input::Line(line) => line,
+                other => return Ok(other),
+            },
+        };
+        if !repl::is_heredoc_start(&first) {
+            return Ok(Input::Line(first));
+        }
+        let mut lines = Vec::new();
+        loop {
+            match self.read_raw(HEREDOC_PROMPT)? {
+                Input::Line(line) if repl::is_heredoc_end(&line) => {
+                    return Ok(Input::Line(lines.join("\n")));
+                }
+                Input::Line(line) => lines.push(line),
+                other => return Ok(other),
+            }
+        }
+    }
+
+    /// Вопрос «да/нет»: переспрашивает, пока ответ не распознан;
+    /// конец ввода — «нет»
+    pub fn confirm(&mut self, text: &str) -> io::Result<bool> {
+        while let Some(answer) = self.prompt(text)? {
+            match messages::parse_yes_no(&answer) {
+                Some(yes) => return Ok(yes),
+                None => writeln!(self.output, "{}", Msg::AnswerYesNo.text())?,
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Команды меню, которые выполняет программа. Их вывод пишется в `out` — тот
+/// же `Console`, что читает строки.
+pub trait Menu {
+    /// `encrypt` или `decrypt` над уже прочитанным сообщением; `trace` —
+    /// число блоков из `--trace[=K]`
+    fn message(
+        &mut self,
+        out: &mut dyn Write,
+        decrypt: bool,
+        trace: Option<usize>,
+        msg: &str,
+    ) -> io::Result<()>;
+
+    /// Любая другая команда, кроме `exit`; `false` — такой команды нет
+    fn command(&mut self, out: &mut dyn Write, name: &str, args: &[&str]) -> io::Result<bool>;
+}
+
+/// Основной цикл меню: приглашение, разбор строки, запись в историю и
+/// сообщение для encrypt/decrypt, а команды выполняет `menu`. Кончается на
+/// `exit` или конце ввода; Ctrl-C бросает строку или сообщение.
+pub fn run_menu<R: BufRead, W: Write, M: Menu>(
+    console: &mut Console<R, W>,
+    menu: &mut M,
+) -> io::Result<()> {
+    loop {
+        let line = match console.read_input(Msg::CommandPrompt.text())? {
+            Input::Line(line) => line,
+            Input::Cancelled => continue,
+            Input::End => return Ok(()),
+        };
+        let command = repl::parse_command(&line);
+        console.remember(&command, &line);
+        match command {
+            Command::Empty => {}
+            Command::Other { name: "exit", .. } => return Ok(()),
+            Command::Message { decrypt, trace, text } => {
+                let msg = match console.read_message(text)? {
+                    Input::Line(msg) => msg,
+                    Input::Cancelled => continue,
+                    Input::End => return Ok(()),
+                };
+                menu.message(console, decrypt, trace, &msg)?;
+            }
+            Command::Other { name, args } => {
+                if !menu.command(console, name, &args)? {
+                    writeln!(console, "{}", Msg::UnknownCommand.text())?;
+                }
+            }
+        }
+    }
+}
+
+impl<R, W: Write> Write for Console<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
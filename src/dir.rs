@@ -0,0 +1,312 @@
+//! Шифрование каталога целиком: `encrypt-dir` и `decrypt-dir`.
+//!
+//! Каждый обычный файл шифруется побайтно с конфигурированных стартовых
+//! позиций — файлы не зависят друг от друга, их можно шифровать в несколько
+//! потоков и расшифровать любой отдельно через `decrypt-file`. Перед
+//! шифротекстом — заголовок `FileHeader`. Структура каталогов повторяется;
+//! с `encrypt_names` каждое имя тоже шифруется с начальных позиций и
+//! пишется в hex (на нечувствительных к регистру ФС base64 бы склеивался).
+//!
+//! Исходные пути и SHA-256 содержимого лежат в манифесте `manifest.esd`,
+//! зашифрованном так же, как файлы: по нему `decrypt_dir` восстанавливает
+//! имена и проверяет каждый файл. Символические ссылки и прочие не обычные
+//! файлы пропускаются и попадают в отчёт.
+
+use crate::file_header::{FILE_HEADER_LEN, FileHeader};
+use crate::{ConfigData, EnigmaSudnogoDnya};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Имя манифеста в корне зашифрованного каталога
+pub const MANIFEST_FILE: &str = "manifest.esd";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct DirOptions {
+    /// Шифровать и имена файлов и каталогов
+    pub encrypt_names: bool,
+    /// Сколько файлов обрабатывать параллельно
+    pub jobs: usize,
+}
+
+impl Default for DirOptions {
+    fn default() -> Self {
+        DirOptions { encrypt_names: false, jobs: 1 }
+    }
+}
+
+/// Что сделано с каталогом
+#[derive(Debug, Default)]
+pub struct DirReport {
+    /// Обработано обычных файлов
+    pub files: usize,
+    /// Пропущено: символические ссылки, сокеты, устройства
+    pub skipped: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// Каталоги, включая пустые: исходный путь → путь в шифровке
+    dirs: Vec<ManifestEntry>,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Исходный путь относительно корня, через `/`
+    path: String,
+    /// Путь в зашифрованном каталоге, через `/`
+    stored: String,
+    /// SHA-256 открытого содержимого в hex; у каталогов пусто
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    sha256: String,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Читает или пишет насквозь и считает SHA-256 прошедших байтов
+struct Hashing<T> {
+    inner: T,
+    hasher: Sha256,
+}
+
+impl<T> Hashing<T> {
+    fn new(inner: T) -> Self {
+        Hashing { inner, hasher: Sha256::new() }
+    }
+
+    fn hex(self) -> String {
+        hex(&self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for Hashing<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Hashing<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Машина байтового режима: ею шифруются и содержимое, и имена
+fn byte_machine(cfg: &ConfigData) -> io::Result<EnigmaSudnogoDnya> {
+    EnigmaSudnogoDnya::new(&cfg.to_byte_mode())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+fn pool(jobs: usize) -> io::Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new().num_threads(jobs.max(1)).build().map_err(io::Error::other)
+}
+
+/// Обход `root`: каталоги и обычные файлы по порядку имён, пути — списки
+/// компонентов. `exclude` — сам выходной каталог, если он внутри `root`.
+fn walk(
+    root: &Path,
+    rel: &[String],
+    exclude: Option<&Path>,
+    dirs: &mut Vec<Vec<String>>,
+    files: &mut Vec<Vec<String>>,
+    skipped: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let here: PathBuf = rel.iter().fold(root.to_path_buf(), |p, c| p.join(c));
+    let mut entries = fs::read_dir(&here)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        // `file_type` у записи каталога не идёт по ссылке
+        let kind = entry.file_type()?;
+        if !kind.is_dir() && !kind.is_file() {
+            skipped.push(path);
+            continue;
+        }
+        // выходной каталог внутри исходного не шифруется сам в себя
+        let is_out = |out: &Path| fs::canonicalize(&path).is_ok_and(|p| p == out);
+        if kind.is_dir() && exclude.is_some_and(is_out) {
+            continue;
+        }
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| invalid(format!("{}: file name is not UTF-8", path.display())))?;
+        let child: Vec<String> = rel.iter().cloned().chain([name]).collect();
+        if kind.is_dir() {
+            dirs.push(child.clone());
+            walk(root, &child, exclude, dirs, files, skipped)?;
+        } else {
+            files.push(child);
+        }
+    }
+    Ok(())
+}
+
+/// Шифрует `data` целиком с начальных позиций и пишет с заголовком
+fn write_sealed(
+    machine: &mut EnigmaSudnogoDnya,
+    header: &[u8],
+    data: &[u8],
+    to: &Path,
+) -> io::Result<()> {
+    machine.reset();
+    let mut out = header.to_vec();
+    out.extend(machine.encrypt_bytes(data));
+    fs::write(to, out)
+}
+
+/// Шифрует каталог `src` в `out` (создаётся при необходимости)
+pub fn encrypt_dir(
+    cfg: &ConfigData,
+    src: &Path,
+    out: &Path,
+    opts: &DirOptions,
+) -> io::Result<DirReport> {
+    let template = byte_machine(cfg)?;
+    let header = FileHeader::for_config(cfg, true, false, None).to_bytes();
+    let exclude = fs::canonicalize(out).ok();
+    let (mut dirs, mut files, mut skipped) = (Vec::new(), Vec::new(), Vec::new());
+    walk(src, &[], exclude.as_deref(), &mut dirs, &mut files, &mut skipped)?;
+
+    // имя шифруется само по себе, с начальных позиций: одно имя — один шифр
+    let mut namer = template.clone();
+    let mut store = |rel: &[String]| -> String {
+        let parts: Vec<String> = rel
+            .iter()
+            .map(|name| {
+                if !opts.encrypt_names {
+                    return name.clone();
+                }
+                namer.reset();
+                hex(&namer.encrypt_bytes(name.as_bytes()))
+            })
+            .collect();
+        parts.join("/")
+    };
+    let dir_entries: Vec<ManifestEntry> = dirs
+        .iter()
+        .map(|rel| ManifestEntry {
+            path: rel.join("/"),
+            stored: store(rel),
+            sha256: String::new(),
+        })
+        .collect();
+    let stored: Vec<(String, String)> =
+        files.iter().map(|rel| (rel.join("/"), store(rel))).collect();
+    if stored.iter().any(|(_, s)| s == MANIFEST_FILE) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is reserved for the manifest; use --encrypt-names", MANIFEST_FILE),
+        ));
+    }
+
+    fs::create_dir_all(out)?;
+    for dir in &dir_entries {
+        fs::create_dir_all(out.join(&dir.stored))?;
+    }
+    let file_entries = pool(opts.jobs)?.install(|| {
+        stored
+            .par_iter()
+            .map_init(
+                || template.clone(),
+                |machine, (path, stored)| {
+                    machine.reset();
+                    let mut reader = Hashing::new(BufReader::new(File::open(src.join(path))?));
+                    let mut writer = BufWriter::new(File::create(out.join(stored))?);
+                    writer.write_all(&header)?;
+                    machine.encrypt_stream(&mut reader, writer)?;
+                    let sha256 = reader.hex();
+                    Ok(ManifestEntry { path: path.clone(), stored: stored.clone(), sha256 })
+                },
+            )
+            .collect::<io::Result<Vec<_>>>()
+    })?;
+
+    let report = DirReport { files: file_entries.len(), skipped };
+    let manifest = Manifest { version: MANIFEST_VERSION, dirs: dir_entries, files: file_entries };
+    let json = serde_json::to_vec(&manifest).expect("манифест всегда сериализуется");
+    write_sealed(&mut template.clone(), &header, &json, &out.join(MANIFEST_FILE))?;
+    Ok(report)
+}
+
+/// Путь из манифеста: только обычные компоненты, без `..` и корня
+fn safe_path(path: &str) -> io::Result<&Path> {
+    let p = Path::new(path);
+    let normal = p.components().all(|c| matches!(c, Component::Normal(_)));
+    if path.is_empty() || !normal {
+        return Err(invalid(format!("unsafe path in manifest: {:?}", path)));
+    }
+    Ok(p)
+}
+
+/// Отделяет и проверяет заголовок файла каталога
+fn check_header(cfg: &ConfigData, data: &[u8], path: &Path) -> io::Result<()> {
+    let context = |e: crate::CipherError| invalid(format!("{}: {}", path.display(), e));
+    match FileHeader::parse(data).map_err(context)? {
+        Some((header, _)) => header.check(cfg).map_err(context),
+        None => Err(invalid(format!("{}: no ESD1 header", path.display()))),
+    }
+}
+
+/// Обратное к `encrypt_dir`: имена и структура — из манифеста, каждый
+/// файл сверяется с его SHA-256
+pub fn decrypt_dir(cfg: &ConfigData, src: &Path, out: &Path, jobs: usize) -> io::Result<DirReport> {
+    let template = byte_machine(cfg)?;
+    let manifest_path = src.join(MANIFEST_FILE);
+    let sealed = fs::read(&manifest_path)?;
+    check_header(cfg, &sealed, &manifest_path)?;
+    let json = template.clone().decrypt_bytes(&sealed[FILE_HEADER_LEN..]);
+    let manifest: Manifest = serde_json::from_slice(&json)
+        .map_err(|e| invalid(format!("{}: {}", manifest_path.display(), e)))?;
+    if manifest.version != MANIFEST_VERSION {
+        return Err(invalid(format!("unsupported manifest version {}", manifest.version)));
+    }
+
+    fs::create_dir_all(out)?;
+    for dir in &manifest.dirs {
+        safe_path(&dir.stored)?;
+        fs::create_dir_all(out.join(safe_path(&dir.path)?))?;
+    }
+    pool(jobs)?.install(|| {
+        manifest.files.par_iter().try_for_each_init(
+            || template.clone(),
+            |machine, entry| {
+                let from = src.join(safe_path(&entry.stored)?);
+                let mut reader = BufReader::new(File::open(&from)?);
+                let mut header = vec![0; FILE_HEADER_LEN];
+                reader.read_exact(&mut header)?;
+                check_header(cfg, &header, &from)?;
+                machine.reset();
+                let to = File::create(out.join(safe_path(&entry.path)?))?;
+                let mut writer = Hashing::new(BufWriter::new(to));
+                machine.decrypt_stream(reader, &mut writer)?;
+                if writer.hex() != entry.sha256 {
+                    let msg = format!("{}: content does not match the manifest", entry.path);
+                    return Err(invalid(msg));
+                }
+                Ok(())
+            },
+        )
+    })?;
+    Ok(DirReport { files: manifest.files.len(), skipped: Vec::new() })
+}
@@ -0,0 +1,129 @@
+//! Заголовок файлов `encrypt-file`: по нему `decrypt-file` знает, как файл
+//! шифровали, и не расшифровывает чужим конфигом.
+//!
+//! Раскладка: `ESD1`, версия, алфавит (1 — latin, 2 — cyrillic, 3 — bytes,
+//! 4 — ukrainian, 5 — belarusian, 6 — greek, 7 — latin-ext), флаги (бит 0 —
+//! побайтно, бит 1 — с тегом целостности), ширина `--wrap` (u32 LE, 0 — без
+//! переносов), первые 8 байт отпечатка конфига. Дальше — шифротекст как
+//! есть. Незнакомая версия или флаг — ошибка, а не попытка расшифровать
+//! наугад.
+
+use crate::prelude::*;
+use crate::CipherError;
+use crate::alphabet::BYTES_ALPHABET;
+#[cfg(feature = "json")]
+use crate::ConfigData;
+
+pub const FILE_MAGIC: &[u8; 4] = b"ESD1";
+/// Версия заголовка, которую пишет эта сборка
+pub const FILE_VERSION: u8 = 1;
+/// Длина заголовка версии 1, байт
+pub const FILE_HEADER_LEN: usize = FILE_MAGIC.len() + 3 + 4 + 8;
+
+const FLAG_BINARY: u8 = 1;
+const FLAG_AUTHENTICATED: u8 = 2;
+
+/// Алфавиты по номеру в заголовке; имя вне списка машина читает как
+/// кириллицу, так оно и записывается. Новые алфавиты — только в конец:
+/// номер уже записан в файлах.
+const ALPHABETS: [&str; 7] =
+    ["latin", "cyrillic", BYTES_ALPHABET, "ukrainian", "belarusian", "greek", "latin-ext"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHeader {
+    /// Имя алфавита конфига из `ALPHABETS`
+    pub alphabet: &'static str,
+    /// Файл шифровали побайтно (не UTF-8 или `--binary`)
+    pub binary: bool,
+    /// В конце шифротекста тег целостности
+    pub authenticated: bool,
+    /// Ширина строк `--wrap`, если шифротекст переносили
+    pub wrap: Option<u32>,
+    /// Начало `ConfigData::fingerprint`
+    pub fingerprint: [u8; 8],
+}
+
+impl FileHeader {
+    /// Заголовок файла, зашифрованного конфигом `cfg`
+    #[cfg(feature = "json")]
+    pub fn for_config(
+        cfg: &ConfigData,
+        binary: bool,
+        authenticated: bool,
+        wrap: Option<u32>,
+    ) -> Self {
+        let alphabet = ALPHABETS.into_iter().find(|&a| a == cfg.alphabet).unwrap_or("cyrillic");
+        FileHeader {
+            alphabet,
+            binary,
+            authenticated,
+            wrap,
+            fingerprint: Self::fingerprint_of(cfg),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn fingerprint_of(cfg: &ConfigData) -> [u8; 8] {
+        let mut short = [0; 8];
+        short.copy_from_slice(&cfg.fingerprint()[..8]);
+        short
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(FILE_HEADER_LEN);
+        out.extend_from_slice(FILE_MAGIC);
+        out.push(FILE_VERSION);
+        let alphabet = ALPHABETS.iter().position(|&a| a == self.alphabet).unwrap_or(1);
+        out.push(alphabet as u8 + 1);
+        let binary = if self.binary { FLAG_BINARY } else { 0 };
+        out.push(binary | if self.authenticated { FLAG_AUTHENTICATED } else { 0 });
+        out.extend_from_slice(&self.wrap.unwrap_or(0).to_le_bytes());
+        out.extend_from_slice(&self.fingerprint);
+        out
+    }
+
+    /// Заголовок и шифротекст после него. `None` — заголовка нет (файл
+    /// из сборки до заголовков); с `ESD1`, но битый или из будущей
+    /// версии — ошибка.
+    pub fn parse(data: &[u8]) -> Result<Option<(FileHeader, &[u8])>, CipherError> {
+        if !data.starts_with(FILE_MAGIC) {
+            return Ok(None);
+        }
+        let Some(&version) = data.get(FILE_MAGIC.len()) else {
+            return Err(CipherError::BadFileHeader);
+        };
+        if version != FILE_VERSION {
+            return Err(CipherError::UnsupportedFileVersion(version));
+        }
+        let Some((header, body)) = data.split_at_checked(FILE_HEADER_LEN) else {
+            return Err(CipherError::BadFileHeader);
+        };
+        let fields = &header[FILE_MAGIC.len() + 1..];
+        let alphabet = match fields[0] {
+            id @ 1..=7 => ALPHABETS[id as usize - 1],
+            _ => return Err(CipherError::BadFileHeader),
+        };
+        let flags = fields[1];
+        if flags & !(FLAG_BINARY | FLAG_AUTHENTICATED) != 0 {
+            return Err(CipherError::BadFileHeader);
+        }
+        let wrap = u32::from_le_bytes(fields[2..6].try_into().expect("4 байта"));
+        let header = FileHeader {
+            alphabet,
+            binary: flags & FLAG_BINARY != 0,
+            authenticated: flags & FLAG_AUTHENTICATED != 0,
+            wrap: (wrap > 0).then_some(wrap),
+            fingerprint: fields[6..].try_into().expect("8 байт"),
+        };
+        Ok(Some((header, body)))
+    }
+
+    /// Файл зашифрован именно этим конфигом (с теми же стартовыми позициями)
+    #[cfg(feature = "json")]
+    pub fn check(&self, cfg: &ConfigData) -> Result<(), CipherError> {
+        if self.fingerprint != Self::fingerprint_of(cfg) {
+            return Err(CipherError::FileFingerprintMismatch);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Омофоны: частые буквы шифруются не одним символом, а одним из
+//! нескольких, чтобы сгладить частоты до роторов.
+//!
+//! Алфавит машины при `homophonic` — буквы и за ними цифры `0`–`9`
+//! (буквенно-цифровой алфавит). Каждая цифра — запасной символ одной из
+//! самых частых букв; какой из символов буквы взять, решает перемешанный
+//! номер шага машины, так что шифрование остаётся детерминированным,
+//! а `advance_by` и `encrypt_parallel` работают как раньше. Расшифровка
+//! сводит символ обратно к букве. Цифры открытого текста заняты омофонами
+//! и расшифруются буквами — режим для буквенных текстов.
+
+use crate::prelude::*;
+use crate::Idx;
+
+/// Символы-омофоны: идут в алфавите сразу после букв
+pub const HOMOPHONE_SYMBOLS: &str = "0123456789";
+
+/// Буква и её цифры, по убыванию частоты в английском: `e` встречается
+/// примерно втрое чаще средней буквы и получает две цифры
+const LATIN: &str = "e01 t2 a3 o4 i5 n6 s7 h8 r9";
+/// То же для русского
+const CYRILLIC: &str = "о01 е2 а3 и4 н5 т6 с7 р8 в9";
+const UKRAINIAN: &str = "о01 а2 н3 і4 и5 в6 т7 е8 р9";
+const BELARUSIAN: &str = "а01 о2 н3 і4 е5 р6 с7 т8 ы9";
+const GREEK: &str = "α01 ο2 ι3 ε4 τ5 σ6 ν7 η8 υ9";
+
+/// Множитель фибоначчиева хеширования: без перемешивания фраза чётной
+/// длины, повторённая много раз, ставила бы букву на один и тот же омофон
+const STEP_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Таблица омофонов для расширенного алфавита
+#[derive(Debug, Clone)]
+pub struct Homophones {
+    /// Для каждой буквы — все её символы, сама буква первой
+    options: Vec<Vec<Idx>>,
+    /// Для каждого символа расширенного алфавита — индекс его буквы
+    owner: Vec<Idx>,
+}
+
+impl Homophones {
+    /// `alphabet` — буквы алфавита `name` и за ними `HOMOPHONE_SYMBOLS`
+    pub fn new(name: &str, alphabet: &[char]) -> Self {
+        let letters = alphabet.len() - HOMOPHONE_SYMBOLS.len();
+        let index = |c: char| alphabet.iter().position(|&a| a == c).expect("символ из таблицы");
+        let mut options: Vec<Vec<Idx>> = (0..letters as Idx).map(|i| vec![i]).collect();
+        let mut owner: Vec<Idx> = (0..alphabet.len() as Idx).collect();
+        let table = match name {
+            "latin" => LATIN,
+            "ukrainian" => UKRAINIAN,
+            "belarusian" => BELARUSIAN,
+            "greek" => GREEK,
+            _ => CYRILLIC,
+        };
+        for group in table.split_whitespace() {
+            let mut chars = group.chars();
+            let letter = index(chars.next().expect("группа не пустая"));
+            for symbol in chars.map(index) {
+                options[letter].push(symbol as Idx);
+                owner[symbol] = letter as Idx;
+            }
+        }
+        Homophones { options, owner }
+    }
+
+    /// Символ для буквы `idx` на шаге `step`; цифры открытого текста
+    /// остаются собой
+    #[inline]
+    pub fn spread(&self, idx: usize, step: u64) -> usize {
+        match self.options.get(idx) {
+            Some(options) => {
+                let mixed = step.wrapping_mul(STEP_MIX) >> 32;
+                options[(mixed % options.len() as u64) as usize] as usize
+            }
+            None => idx,
+        }
+    }
+
+    /// Буква, за которую стоит символ `idx`
+    #[inline]
+    pub fn collapse(&self, idx: usize) -> usize {
+        self.owner[idx] as usize
+    }
+
+    /// Сколько в алфавите букв (без омофонов)
+    pub fn letters(&self) -> usize {
+        self.options.len()
+    }
+}
@@ -0,0 +1,551 @@
+//! Энигма Судного Дня. Без фичи `std` ядро собирается как `no_std` + `alloc`;
+//! `json`, `rand` и `passphrase` от std не зависят, так что отпечаток
+//! конфига, теги целостности, эталонные векторы и генерация ключей с заданным
+//! ГПСЧ или из парольной фразы есть и на микроконтроллере. Шифрование без
+//! выделения памяти — `encrypt_into`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// на целях с ОС std всё равно линкуется: без аллокатора и обработчика паники
+// cdylib не собрать. Код ядра его не трогает — прелюдия остаётся `no_std`
+#[cfg(all(not(feature = "std"), not(target_os = "none")))]
+extern crate std;
+
+use crate::prelude::*;
+use hmac::{Hmac, Mac};
+use messages::Msg;
+#[cfg(feature = "rand")]
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use sha2::Sha256;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::Instant;
+use zeroize::Zeroize;
+
+/// `Vec`, `String` и `format!` из `alloc`: в прелюдии `no_std` их нет
+mod prelude {
+    pub(crate) use alloc::format;
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec;
+    pub(crate) use alloc::vec::Vec;
+}
+
+mod alphabet;
+pub mod armor;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod attack;
+#[cfg(all(feature = "std", feature = "json", feature = "rand"))]
+pub mod benchmark;
+#[cfg(feature = "std")]
+pub mod clipboard;
+mod config;
+#[cfg(feature = "std")]
+pub mod console;
+#[cfg(feature = "std")]
+mod config_bin;
+#[cfg(feature = "fs")]
+pub mod dir;
+pub mod file_header;
+pub mod homophonic;
+#[cfg(feature = "std")]
+pub mod mnemonic;
+mod machine;
+pub mod messages;
+#[cfg(feature = "passphrase")]
+pub mod passphrase;
+pub mod plugboard;
+pub mod progress;
+pub mod repl;
+#[cfg(feature = "fs")]
+pub mod resume;
+#[cfg(feature = "json")]
+pub mod vectors;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(all(feature = "std", feature = "rand"))]
+pub mod wizard;
+pub mod wrap;
+
+pub(crate) use alphabet::Idx;
+pub use alphabet::{Alphabet, AlphabetIndex, BYTES_ALPHABET};
+pub use config::{BIN_CONFIG_EXT, CascadeConfig, ConfigData, MAX_ROTORS_PER_BLOCK};
+pub use machine::{
+    Block, CHECKSUM_LEN, CharStep, CharTrace, DEFAULT_REFLECTOR, EnigmaSudnogoDnya,
+    MULTI_CONFIG_FILE, MachineDescription, MultiEnigma, REFLECTOR_KINDS, Reflector, Rotor,
+    RotorDescription, SHORT_PERIOD, format_period,
+};
+
+/// Ошибки построения машины
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipherError {
+    /// Пустая строка цветов — блок без роторов
+    EmptyBlock,
+    /// Ни одного блока без `allow_degenerate`: шифр свёлся к простой замене
+    NoBlocks,
+    /// В блоке больше роторов, чем разрешено
+    TooManyRotors { block: usize, rotors: usize, max: usize },
+    /// Неизвестная цветовая метка ротора
+    UnknownColor(char),
+    /// Тег целостности отсутствует или не совпал
+    IntegrityFailure,
+    /// Номер блока за пределами машины
+    BlockOutOfRange { block: usize, blocks: usize },
+    /// Номер ротора за пределами блока
+    RotorOutOfRange { block: usize, rotor: usize, rotors: usize },
+    /// Число позиций не совпадает с числом роторов блока
+    RotorPositionCountMismatch { block: usize, rotors: usize, positions: usize },
+    /// Численный сдвиг ротора не меньше длины алфавита
+    ShiftOutOfRange { shift: usize, alphabet_len: usize },
+    /// Позиция ротора не меньше длины алфавита
+    PositionOutOfRange { block: usize, rotor: usize, position: usize, alphabet_len: usize },
+    /// Символ plugboard не из алфавита
+    PlugboardUnknownChar(char),
+    /// Символ встречается в plugboard в двух парах
+    PlugboardCharReused(char),
+    /// Пара plugboard из одного и того же символа
+    PlugboardSelfPair(char),
+    /// Цепочка машин без единого конфига
+    EmptyChain,
+    /// В `rotor_positions` не столько векторов, сколько блоков
+    PositionBlockCountMismatch { blocks: usize, positions: usize },
+    /// Неизвестное имя отражателя
+    UnknownReflector(String),
+    /// Неизвестное имя алфавита
+    UnknownAlphabet(String),
+    /// Отражатель не подходит к алфавиту (разводка для другой длины)
+    ReflectorAlphabetMismatch { kind: String, alphabet_len: usize },
+    /// Пар plugboard больше, чем половина алфавита
+    TooManyPlugboardPairs { pairs: usize, alphabet_len: usize },
+    /// Омофонам нужны свободные цифры: алфавит без цифр (не `bytes`
+    /// и не `latin-ext`) и без цифрового ротора
+    HomophonicUnsupported,
+    /// В `reversed` не столько векторов, сколько блоков
+    ReversedBlockCountMismatch { blocks: usize, reversed: usize },
+    /// Число флагов `reversed` не совпадает с числом роторов блока
+    ReversedRotorCountMismatch { block: usize, rotors: usize, flags: usize },
+    /// Результат `encrypt_into` не поместился в буфер; `written` байт
+    /// записано до ошибки
+    BufferTooSmall { written: usize },
+    /// `step_interval` равен нулю — роторы не шагали бы никогда
+    ZeroStepInterval,
+    /// У машины каскада не тот алфавит, что у предыдущей (`strict`)
+    CascadeAlphabetMismatch { machine: usize },
+    /// Машина каскада сворачивает регистр, а у предыдущей в алфавите есть
+    /// заглавные — шифротекст испортился бы
+    CascadeCaseMismatch { machine: usize },
+    /// В `step_every` не столько делителей, сколько блоков
+    StepEveryBlockCountMismatch { blocks: usize, step_every: usize },
+    /// Нулевой делитель в `step_every` — блок никогда бы не шагнул
+    ZeroStepEvery { block: usize },
+    /// В конфиге и `plugboard`, и `plugboard_map` — панель одна
+    PlugboardPairsAndMap,
+    /// Символ `plugboard_map` — цель провода, но сам никуда не ведёт
+    PlugboardMapOpen(char),
+    /// Версия вывода конфига из фразы, которую эта сборка не знает
+    UnsupportedDerivationVersion(u32),
+    /// argon2id не принял параметры вывода (память, проходы, дорожки)
+    BadDerivationParams,
+    /// Файл начинается с `ESD1`, но заголовок обрезан или испорчен
+    BadFileHeader,
+    /// Заголовок файла версии, которую эта сборка не знает
+    UnsupportedFileVersion(u8),
+    /// Файл зашифрован другим конфигом (или с другими стартовыми позициями)
+    FileFingerprintMismatch,
+    /// Текст не разбирается как броня `hex` или `base64`, см. `armor`
+    BadArmor(&'static str),
+    /// Перебор `attack::brute_force` больше `max` вариантов
+    SearchSpaceTooLarge { max: u64 },
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CipherError::EmptyBlock => Msg::EmptyBlock.fill(&[]),
+            CipherError::NoBlocks => Msg::NoBlocks.fill(&[]),
+            CipherError::TooManyRotors { block, rotors, max } => {
+                Msg::TooManyRotors.fill(&[block, rotors, max])
+            }
+            CipherError::UnknownColor(c) => Msg::UnknownColor.fill(&[&format!("{:?}", c)]),
+            CipherError::IntegrityFailure => Msg::IntegrityFailure.fill(&[]),
+            CipherError::BlockOutOfRange { block, blocks } => {
+                Msg::BlockOutOfRange.fill(&[block, blocks])
+            }
+            CipherError::RotorOutOfRange { block, rotor, rotors } => {
+                Msg::RotorOutOfRange.fill(&[block, rotor, rotors])
+            }
+            CipherError::RotorPositionCountMismatch { block, rotors, positions } => {
+                Msg::RotorPositionCountMismatch.fill(&[block, positions, rotors])
+            }
+            CipherError::ShiftOutOfRange { shift, alphabet_len } => {
+                Msg::ShiftOutOfRange.fill(&[shift, alphabet_len])
+            }
+            CipherError::PositionOutOfRange { block, rotor, position, alphabet_len } => {
+                Msg::PositionOutOfRange.fill(&[block, rotor, position, alphabet_len])
+            }
+            CipherError::PlugboardUnknownChar(c) => {
+                Msg::PlugboardUnknownChar.fill(&[&format!("{:?}", c)])
+            }
+            CipherError::PlugboardCharReused(c) => {
+                Msg::PlugboardCharReused.fill(&[&format!("{:?}", c)])
+            }
+            CipherError::PlugboardSelfPair(c) => {
+                Msg::PlugboardSelfPair.fill(&[&format!("{:?}", c)])
+            }
+            CipherError::EmptyChain => Msg::EmptyChain.fill(&[]),
+            CipherError::PositionBlockCountMismatch { blocks, positions } => {
+                Msg::PositionBlockCountMismatch.fill(&[positions, blocks])
+            }
+            CipherError::UnknownReflector(kind) => Msg::UnknownReflector.fill(&[kind]),
+            CipherError::UnknownAlphabet(name) => {
+                Msg::UnknownAlphabet.fill(&[&format!("{:?}", name), &alphabet::names()])
+            }
+            CipherError::ReflectorAlphabetMismatch { kind, alphabet_len } => {
+                Msg::ReflectorAlphabetMismatch.fill(&[kind, alphabet_len])
+            }
+            CipherError::TooManyPlugboardPairs { pairs, alphabet_len } => {
+                Msg::TooManyPlugboardPairs.fill(&[pairs, alphabet_len])
+            }
+            CipherError::HomophonicUnsupported => Msg::HomophonicUnsupported.fill(&[]),
+            CipherError::ReversedBlockCountMismatch { blocks, reversed } => {
+                Msg::ReversedBlockCountMismatch.fill(&[reversed, blocks])
+            }
+            CipherError::ReversedRotorCountMismatch { block, rotors, flags } => {
+                Msg::ReversedRotorCountMismatch.fill(&[block, flags, rotors])
+            }
+            CipherError::BufferTooSmall { written } => Msg::BufferTooSmall.fill(&[written]),
+            CipherError::ZeroStepInterval => Msg::ZeroStepInterval.fill(&[]),
+            CipherError::CascadeAlphabetMismatch { machine } => {
+                Msg::CascadeAlphabetMismatch.fill(&[machine])
+            }
+            CipherError::CascadeCaseMismatch { machine } => {
+                Msg::CascadeCaseMismatch.fill(&[machine])
+            }
+            CipherError::StepEveryBlockCountMismatch { blocks, step_every } => {
+                Msg::StepEveryBlockCountMismatch.fill(&[step_every, blocks])
+            }
+            CipherError::ZeroStepEvery { block } => Msg::ZeroStepEvery.fill(&[block]),
+            CipherError::PlugboardPairsAndMap => Msg::PlugboardPairsAndMap.fill(&[]),
+            CipherError::PlugboardMapOpen(c) => Msg::PlugboardMapOpen.fill(&[&format!("{:?}", c)]),
+            CipherError::UnsupportedDerivationVersion(version) => {
+                Msg::UnsupportedDerivationVersion.fill(&[version])
+            }
+            CipherError::BadDerivationParams => Msg::BadDerivationParams.fill(&[]),
+            CipherError::BadFileHeader => Msg::BadFileHeader.fill(&[]),
+            CipherError::UnsupportedFileVersion(version) => {
+                Msg::UnsupportedFileVersion.fill(&[version])
+            }
+            CipherError::FileFingerprintMismatch => Msg::FileFingerprintMismatch.fill(&[]),
+            CipherError::BadArmor(encoding) => Msg::BadArmor.fill(&[encoding]),
+            CipherError::SearchSpaceTooLarge { max } => Msg::SearchSpaceTooLarge.fill(&[max]),
+        };
+        f.write_str(&text)
+    }
+}
+
+impl core::error::Error for CipherError {}
+
+/// Разделитель между текстом и тегом в бронированном виде: `шифротекст#тег`
+pub const MAC_SEPARATOR: char = '#';
+
+/// Ключ HMAC-SHA256 для тегов целостности, см. `ConfigData::mac_key`
+#[derive(Clone)]
+pub struct MacKey([u8; 32]);
+
+impl Drop for MacKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Заглушка в `Debug` вместо ключевого материала
+pub(crate) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl MacKey {
+    fn hmac(&self, data: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC принимает любой ключ");
+        mac.update(data);
+        mac
+    }
+
+    /// Тег целостности для данных
+    pub fn tag(&self, data: &[u8]) -> [u8; 32] {
+        self.hmac(data).finalize().into_bytes().into()
+    }
+
+    /// Сравнение тега за постоянное время
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> Result<(), CipherError> {
+        self.hmac(data)
+            .verify_slice(tag)
+            .map_err(|_| CipherError::IntegrityFailure)
+    }
+
+    /// Текст с тегом в hex после `MAC_SEPARATOR`
+    pub fn seal(&self, ciphertext: &str) -> String {
+        let mut out = String::with_capacity(ciphertext.len() + 65);
+        out.push_str(ciphertext);
+        out.push(MAC_SEPARATOR);
+        for b in self.tag(ciphertext.as_bytes()) {
+            out.push_str(&format!("{:02x}", b));
+        }
+        out
+    }
+
+    /// Проверяет тег и возвращает текст без него
+    pub fn open<'a>(&self, sealed: &'a str) -> Result<&'a str, CipherError> {
+        let (text, hex) = sealed
+            .rsplit_once(MAC_SEPARATOR)
+            .ok_or(CipherError::IntegrityFailure)?;
+        let tag = decode_hex(hex).ok_or(CipherError::IntegrityFailure)?;
+        self.verify(text.as_bytes(), &tag)?;
+        Ok(text)
+    }
+
+    /// Байты с сырым тегом в конце
+    pub fn seal_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len() + 32);
+        out.extend_from_slice(data);
+        out.extend_from_slice(&self.tag(data));
+        out
+    }
+
+    /// Проверяет сырой тег в конце и возвращает данные без него
+    pub fn open_bytes<'a>(&self, sealed: &'a [u8]) -> Result<&'a [u8], CipherError> {
+        let split = sealed
+            .len()
+            .checked_sub(32)
+            .ok_or(CipherError::IntegrityFailure)?;
+        let (data, tag) = sealed.split_at(split);
+        self.verify(data, tag)?;
+        Ok(data)
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Вычисляет log2(n!)
+#[cfg(feature = "std")]
+pub fn log2_factorial(n: usize) -> f64 {
+    let mut sum = 0.0;
+    for i in 1..=n {
+        sum += (i as f64).log2();
+    }
+    sum
+}
+
+/// Битность конфигурации: стартовые позиции всех роторов плюс число
+/// способов выбрать пары plugboard. Это наивная оценка, реальная — в
+/// `analysis::estimate_keyspace`.
+#[cfg(feature = "std")]
+pub fn config_bitness(cfg: &ConfigData) -> f64 {
+    analysis::estimate_keyspace(cfg).naive_bits
+}
+
+/// Для меню: пресет
+#[derive(Clone)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub name_en: &'static str,
+    pub description_en: &'static str,
+    pub blocks: usize,
+    pub speed_idx: u8,
+    /// Сколько пар plugboard ставить при генерации
+    pub plugboard_pairs: usize,
+}
+
+/// Среднее число роторов в блоке от `random_blocks` (от 3 до 9)
+const AVG_RANDOM_ROTORS: usize = 6;
+
+impl Preset {
+    /// Название на текущем языке сообщений
+    pub fn localized_name(&self) -> &'static str {
+        match messages::lang() {
+            messages::Lang::Ru => self.name,
+            messages::Lang::En => self.name_en,
+        }
+    }
+
+    /// Описание на текущем языке сообщений
+    pub fn localized_description(&self) -> &'static str {
+        match messages::lang() {
+            messages::Lang::Ru => self.description,
+            messages::Lang::En => self.description_en,
+        }
+    }
+
+    /// Примерная память на сгенерированный конфиг и машину из него — считается
+    /// до генерации, по одному числу блоков
+    pub fn estimated_memory_bytes(&self) -> usize {
+        ConfigData::memory_bytes(self.blocks, self.blocks * AVG_RANDOM_ROTORS)
+    }
+}
+
+pub const ROTOR_COLORS: &[char] = &['К','Б','Ч','З','Р','О','Ф','С','Г','Л'];
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "минимально безопасный",
+        description: "3 блока, короткие роторы — быстро, но слабее.",
+        name_en: "minimally secure",
+        description_en: "3 blocks, short rotors — fast, but weaker.",
+        blocks: 3,
+        speed_idx: 8,
+        plugboard_pairs: 6,
+    },
+    Preset {
+        name: "безопасный",
+        description: "4 блока, средние роторы — хороший баланс.",
+        name_en: "secure",
+        description_en: "4 blocks, medium rotors — a good balance.",
+        blocks: 4,
+        speed_idx: 7,
+        plugboard_pairs: 8,
+    },
+    Preset {
+        name: "паранойя",
+        description: "12 блоков, длинные роторы — медленней, но максимум стойкости.",
+        name_en: "paranoia",
+        description_en: "12 blocks, long rotors — slower, but maximum strength.",
+        blocks: 12,
+        speed_idx: 4,
+        plugboard_pairs: 13,
+    },
+    Preset {
+        name: "Бладислав Ворон",
+        description: "О нём мало чего известно, ведь от него получали больше пиздюлей, чем информации, но что известно, так это то что пока одной рукой он делал тихий океан ещё тише, а другой рукой создавал эту бездарную планету и существовать с ним на одной планете это та ещё задача со звёздочкой, награда за которую не предусмотрена",
+        name_en: "Bladislav Voron",
+        description_en: "Little is known about him, since people got more beatings than information out of him. What is known is that with one hand he made the Pacific Ocean even quieter, with the other he created this worthless planet, and sharing a planet with him is a bonus-difficulty task with no reward.",
+        blocks: 8_388_608,
+        speed_idx: 1,
+        plugboard_pairs: 10,
+    },
+    Preset {
+        name: "Боронислав Владон",
+        description: "Пока Бладислав Ворон был занят со своим братом делами галактического масштаба, а мы не знали что делать и чем защищаться, с нами на связь вышел старший двоюрный брат Бладислава и его брата - Боронислав Владон.\nХотите верьте, хотите нет, но пытаясь хоть что либо хоть где либо узнать о Борониславе мы ничего не нашли, даже спрашивая напрямую у Бладислава - данные попросту засекречены всеми возможными грифами секретности, а те кто пытались что-то рассекретить, ну, они получали больше пиздюлей чем информации.\nЗа его работу он потребовал лишь 60 гигиабайт ОЗУ и побольше вычислительных мощностей, ведь его услуги не из дешёвых.",
+        name_en: "Boronislav Vladon",
+        description_en: "While Bladislav Voron and his brother were busy with matters of galactic scale, and we did not know what to do or how to defend ourselves, we were contacted by the elder cousin of Bladislav and his brother — Boronislav Vladon.\nBelieve it or not, trying to learn anything about Boronislav anywhere, we found nothing, even when asking Bladislav directly — the data is classified under every possible secrecy stamp, and those who tried to declassify anything, well, got more beatings than information.\nFor his work he asked only for 60 gigabytes of RAM and more computing power, because his services do not come cheap.",
+        blocks: 134_217_728,
+        speed_idx: 0,
+        plugboard_pairs: 13,
+    },
+    Preset {
+        name: "Александр \"42\"",
+        description: "Уважаемая личность на районе, так именуемый \"42\" в честь количества блоков внутри него.",
+        name_en: "Alexander \"42\"",
+        description_en: "A respected figure in the neighbourhood, called \"42\" after the number of blocks inside him.",
+        blocks: 42,
+        speed_idx: 5,
+        plugboard_pairs: 8,
+    },
+    Preset {
+        name: "Анаколий",
+        description: "В любой компании есть самый младший, тут тоже он есть.\n Он самый шустрый и самый малой в компании этих гигантов, но это не мешает ему быть хоть немного грозным, ведь внутри него целых 81.337 бит и хоть 81.337 бит это практически смешно для серьезной защиты, Анаколий предпочитает домашние посиделки за чаем, нежели защиту всего с грифом Top Secret как его старшие братья - а там 81 это вполне достаточно.",
+        name_en: "Anakoliy",
+        description_en: "Every company has a youngest member, and this one does too.\n He is the quickest and smallest among these giants, but that does not stop him from being at least a little fearsome, since he holds a whole 81.337 bits. And although 81.337 bits is practically laughable for serious protection, Anakoliy prefers cozy evenings over tea to guarding anything stamped Top Secret like his elder brothers — and there 81 is quite enough.",
+        blocks: 1,
+        speed_idx: 10,
+        plugboard_pairs: 8,
+    },
+];
+
+#[cfg(feature = "rand")]
+pub fn random_blocks<R: Rng>(rng: &mut R, blocks: usize) -> Vec<String> {
+    (0..blocks)
+        .map(|_| {
+            let k = rng.random_range(3..=9);
+            (0..k)
+                .map(|_| {
+                    let idx = rng.random_range(0..ROTOR_COLORS.len());
+                    ROTOR_COLORS[idx]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Случайные стартовые позиции для каждого ротора каждого блока
+#[cfg(feature = "rand")]
+pub fn random_positions<R: Rng>(
+    rng: &mut R,
+    blocks: &[String],
+    alphabet_len: usize,
+) -> Vec<Vec<usize>> {
+    blocks
+        .iter()
+        .map(|b| {
+            (0..b.chars().count())
+                .map(|_| rng.random_range(0..alphabet_len))
+                .collect()
+        })
+        .collect()
+}
+
+/// Случайный текст из символов алфавита — общий для benchmark, KAT и criterion
+#[cfg(feature = "rand")]
+pub fn random_text<R: Rng>(rng: &mut R, alphabet: &[char], size: usize) -> String {
+    (0..size)
+        .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+        .collect()
+}
+
+/// Зерно текстов benchmark без `--seed` ("bench" в ASCII): замеры от запуска
+/// к запуску идут на одних и тех же данных
+pub const BENCH_SEED: u64 = 0x62656e6368;
+
+/// Тексты benchmark, по одному на каждый размер из `sizes`, из одного ГПСЧ
+/// с зерном `seed`: одно зерно — один и тот же корпус
+#[cfg(feature = "rand")]
+pub fn bench_corpus(seed: u64, alphabet: &[char], sizes: &[usize]) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    sizes.iter().map(|&size| random_text(&mut rng, alphabet, size)).collect()
+}
+
+/// Результат прогона encrypt → decrypt на одной машине
+#[cfg(feature = "std")]
+pub struct RoundTrip {
+    pub encrypt_secs: f64,
+    pub decrypt_secs: f64,
+    pub ok: bool,
+}
+
+/// Шифрует и расшифровывает `text`, каждый раз начиная с текущего
+/// состояния машины, и замеряет только само шифрование. После вызова
+/// машина возвращается в исходное состояние — с позициями, счётчиком
+/// символов и цифровым ротором.
+#[cfg(feature = "std")]
+pub fn measure_roundtrip(machine: &mut EnigmaSudnogoDnya, text: &str) -> RoundTrip {
+    let state = machine.snapshot();
+
+    let t0 = Instant::now();
+    let cipher = machine.encrypt(text);
+    let encrypt_secs = t0.elapsed().as_secs_f64();
+
+    machine.restore(&state);
+    let t1 = Instant::now();
+    let recovered = machine.decrypt(&cipher);
+    let decrypt_secs = t1.elapsed().as_secs_f64();
+
+    machine.restore(&state);
+    RoundTrip {
+        encrypt_secs,
+        decrypt_secs,
+        ok: recovered.as_deref() == Ok(text),
+    }
+}
@@ -0,0 +1,1855 @@
+//! Сама машина: роторы, блоки, отражатель и `EnigmaSudnogoDnya` поверх них.
+
+use crate::prelude::*;
+use crate::homophonic::Homophones;
+use crate::plugboard::Plugboard;
+#[cfg(feature = "std")]
+use crate::progress::{Progress, STREAM_CHUNK};
+use crate::{
+    AlphabetIndex, BYTES_ALPHABET, CascadeConfig, CipherError, ConfigData, Idx, MacKey,
+    MAX_ROTORS_PER_BLOCK, Redacted,
+};
+#[cfg(feature = "std")]
+use crate::attack;
+#[cfg(feature = "fs")]
+use crate::{BIN_CONFIG_EXT, config_bin};
+use either::Either;
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use alloc::borrow::Cow;
+use core::fmt;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+use zeroize::Zeroize;
+
+/// Отражатель по умолчанию: `i ↔ n-1-i`
+pub const DEFAULT_REFLECTOR: &str = "reverse";
+
+/// Все отражатели по имени: `reverse`, соседние пары `0↔1, 2↔3, …`
+/// (при нечётном алфавите последний символ остаётся на месте) и
+/// исторические UKW-B и UKW-C — только для латиницы. Соседние пары не
+/// меняются от сдвига на 2, поэтому с ними роторы дают всего два варианта
+/// смещения — см. `analysis::estimate_keyspace`.
+pub const REFLECTOR_KINDS: &[&str] = &[DEFAULT_REFLECTOR, "identity-pairs", "ukw-b", "ukw-c"];
+
+/// Длина контрольной суммы `encrypt_with_tag` в символах алфавита
+pub const CHECKSUM_LEN: usize = 8;
+
+// Длина блока хранится в u8
+const _: () = assert!(MAX_ROTORS_PER_BLOCK <= u8::MAX as usize);
+
+/// Ротор (работает с индексами)
+#[derive(Clone)]
+pub struct Rotor {
+    shift: Idx,
+    position: Idx,
+    size: Idx,
+    /// Обратная разводка: сдвиг вычитается на прямом проходе и
+    /// прибавляется на обратном
+    reversed: bool,
+}
+
+/// Сдвиг и позиция — ключ: копии ротора затираются, где бы ни оказались
+impl Drop for Rotor {
+    fn drop(&mut self) {
+        self.shift.zeroize();
+        self.position.zeroize();
+    }
+}
+
+impl Rotor {
+    pub fn new(shift: usize, alphabet_len: usize) -> Self {
+        Rotor {
+            shift: (shift % alphabet_len) as Idx,
+            position: 0,
+            size: alphabet_len as Idx,
+            reversed: false,
+        }
+    }
+
+    /// Переворачивает разводку ротора; позиция шагает как обычно
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    #[inline]
+    pub fn encode_index(&self, idx: usize, reverse: bool) -> usize {
+        // idx, shift и position меньше size, так что вместо деления
+        // хватает одного-двух вычитаний
+        let size = self.size as usize;
+        debug_assert!(idx < size);
+        let mut offset = self.shift as usize + self.position as usize;
+        if offset >= size {
+            offset -= size;
+        }
+        let out = if reverse != self.reversed { idx + size - offset } else { idx + offset };
+        if out >= size { out - size } else { out }
+    }
+
+    #[inline]
+    pub fn rotate(&mut self) -> bool {
+        self.position += 1;
+        if self.position == self.size {
+            self.position = 0;
+        }
+        self.position == 0
+    }
+
+    /// Поворачивает ротор сразу на `n` шагов, возвращает число переносов
+    /// в следующий ротор (сколько раз позиция прошла через 0).
+    #[inline]
+    pub fn advance_by(&mut self, n: u64) -> u64 {
+        let total = self.position as u128 + n as u128;
+        let size = self.size as u128;
+        self.position = (total % size) as Idx;
+        (total / size) as u64
+    }
+
+    #[inline]
+    pub fn save_position(&self) -> usize {
+        self.position as usize
+    }
+
+    #[inline]
+    pub fn load_position(&mut self, pos: usize) {
+        self.position = (pos % self.size as usize) as Idx;
+    }
+}
+
+/// Латинские псевдонимы цветов для клавиатуры без кириллицы, по ISO 9:
+/// `C` — это Ч («č»), а С пишется как `S`
+const LATIN_COLORS: [(char, char); 10] = [
+    ('K', 'К'), ('B', 'Б'), ('C', 'Ч'), ('Z', 'З'), ('R', 'Р'),
+    ('O', 'О'), ('F', 'Ф'), ('S', 'С'), ('G', 'Г'), ('L', 'Л'),
+];
+
+/// Цвет в каноническом виде — заглавная кириллица: строчные буквы и
+/// латинские псевдонимы сводятся к ней. Не цвет возвращается как есть
+/// (в верхнем регистре, если он однозначен)
+pub(crate) fn canonical_color(col: char) -> char {
+    let mut upper = col.to_uppercase();
+    let col = match (upper.next(), upper.next()) {
+        (Some(c), None) => c,
+        // 'ß' → "SS" и подобные — точно не цвет
+        _ => return col,
+    };
+    LATIN_COLORS.iter().find(|&&(latin, _)| latin == col).map_or(col, |&(_, c)| c)
+}
+
+/// Строка цветов блока в каноническом виде, см. `canonical_color`
+pub(crate) fn canonical_colors(colors: &str) -> String {
+    colors.chars().map(canonical_color).collect()
+}
+
+/// Цвет ротора → сдвиг. Регистр не важен, вместо кириллицы можно латиницу
+pub(crate) fn color_shift(col: char) -> Option<usize> {
+    let shift = match canonical_color(col) {
+        'К' => 1, 'Б' => 2, 'Ч' => 3, 'З' => 5, 'Р' => 4,
+        'О' => 6, 'Ф' => 7, 'С' => 8, 'Г' => 9, 'Л' => 10,
+        _ => return None,
+    };
+    Some(shift)
+}
+
+/// Сдвиг → цвет ротора (обратно к `color_shift`)
+pub(crate) fn shift_color(shift: usize) -> Option<char> {
+    let col = match shift {
+        1 => 'К', 2 => 'Б', 3 => 'Ч', 5 => 'З', 4 => 'Р',
+        6 => 'О', 7 => 'Ф', 8 => 'С', 9 => 'Г', 10 => 'Л',
+        _ => return None,
+    };
+    Some(col)
+}
+
+/// Одометр одного блока: первый ротор шагает всегда, следующий — только
+/// при переносе. Перенос из последнего ротора пропадает: блоки — отдельные
+/// одометры, и блок из одного ротора просто шагает на каждом такте, не
+/// трогая соседние блоки.
+#[inline]
+fn rotate_rotors(rotors: &mut [Rotor]) {
+    for r in rotors {
+        if !r.rotate() {
+            break;
+        }
+    }
+}
+
+/// То же, что `n` вызовов `rotate_rotors`, но за O(число роторов):
+/// позиции блока — число в системе счисления по основанию алфавита.
+fn advance_rotors(rotors: &mut [Rotor], n: u64) {
+    let mut carry = n;
+    for r in rotors {
+        if carry == 0 {
+            break;
+        }
+        carry = r.advance_by(carry);
+    }
+}
+
+/// Сколько раз шагнут роторы за `n` символов после `done` уже
+/// обработанных, если они шагают после каждого `interval`-го символа
+fn rotations(done: u64, n: u64, interval: u64) -> u64 {
+    let (done, n, interval) = (done as u128, n as u128, interval as u128);
+    ((done + n) / interval - done / interval) as u64
+}
+
+/// Блок роторов
+#[derive(Clone)]
+pub struct Block {
+    rotors: Vec<Rotor>,
+}
+
+impl Block {
+    pub fn new(colors: &str, alphabet_len: usize) -> Result<Self, CipherError> {
+        if colors.is_empty() {
+            return Err(CipherError::EmptyBlock);
+        }
+        let rotors = colors
+            .chars()
+            .map(|col| {
+                let shift = color_shift(col).ok_or(CipherError::UnknownColor(col))?;
+                Ok(Rotor::new(shift, alphabet_len))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Block { rotors })
+    }
+
+    /// Блок с численными сдвигами, каждый в `0..alphabet_len`
+    pub fn from_shifts(shifts: &[usize], alphabet_len: usize) -> Result<Self, CipherError> {
+        if shifts.is_empty() {
+            return Err(CipherError::EmptyBlock);
+        }
+        let rotors = shifts
+            .iter()
+            .map(|&shift| {
+                if shift >= alphabet_len {
+                    return Err(CipherError::ShiftOutOfRange { shift, alphabet_len });
+                }
+                Ok(Rotor::new(shift, alphabet_len))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Block { rotors })
+    }
+
+    #[inline]
+    pub fn process_index(&self, mut idx: usize, reverse: bool) -> usize {
+        if !reverse {
+            for r in &self.rotors {
+                idx = r.encode_index(idx, false);
+            }
+        } else {
+            for r in self.rotors.iter().rev() {
+                idx = r.encode_index(idx, true);
+            }
+        }
+        idx
+    }
+
+    /// Шаг одометра блока, см. `rotate_rotors`
+    pub fn rotate(&mut self) {
+        rotate_rotors(&mut self.rotors);
+    }
+
+    pub fn advance_by(&mut self, n: u64) {
+        advance_rotors(&mut self.rotors, n);
+    }
+
+    /// Число роторов в блоке
+    pub fn len(&self) -> usize {
+        self.rotors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rotors.is_empty()
+    }
+
+    pub fn save_positions(&self) -> Vec<usize> {
+        self.rotors.iter().map(Rotor::save_position).collect()
+    }
+
+    pub fn load_positions(&mut self, pos: &[usize]) {
+        for (r, &p) in self.rotors.iter_mut().zip(pos.iter()) {
+            r.load_position(p);
+        }
+    }
+}
+
+/// Все роторы машины подряд в одном массиве; блок — отрезок длиной
+/// `lens[i]`. Так нет отдельной аллокации на каждый из миллионов блоков.
+#[derive(Clone)]
+struct RotorBank {
+    rotors: Vec<Rotor>,
+    lens: Vec<u8>,
+    /// Блок `i` шагает раз в `every[i]` тактов (`step_every`); пусто —
+    /// все блоки на каждом такте
+    every: Vec<u64>,
+    /// Тактов с построения или `reset`
+    ticks: u64,
+}
+
+/// Роторы затирают себя сами; длины блоков и делители — тоже часть ключа
+impl Drop for RotorBank {
+    fn drop(&mut self) {
+        self.lens.zeroize();
+        self.every.zeroize();
+        self.ticks.zeroize();
+    }
+}
+
+/// Отрезки блоков в общем массиве роторов
+fn split_blocks<'a>(
+    rotors: &'a mut [Rotor],
+    lens: &'a [u8],
+) -> impl Iterator<Item = &'a mut [Rotor]> {
+    let mut rest = rotors;
+    lens.iter().map(move |&len| {
+        let (blk, tail) = core::mem::take(&mut rest).split_at_mut(len as usize);
+        rest = tail;
+        blk
+    })
+}
+
+impl RotorBank {
+    fn with_capacity(blocks: usize) -> Self {
+        RotorBank {
+            rotors: Vec::new(),
+            lens: Vec::with_capacity(blocks),
+            every: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        self.lens.push(block.len() as u8);
+        self.rotors.extend(block.rotors);
+    }
+
+    fn blocks(&self) -> impl Iterator<Item = &[Rotor]> {
+        let mut rest = &self.rotors[..];
+        self.lens.iter().map(move |&len| {
+            let (blk, tail) = rest.split_at(len as usize);
+            rest = tail;
+            blk
+        })
+    }
+
+    fn blocks_mut(&mut self) -> impl Iterator<Item = &mut [Rotor]> {
+        split_blocks(&mut self.rotors, &self.lens)
+    }
+
+    /// Прямой проход: блоки по порядку, роторы внутри блока по порядку —
+    /// то есть просто весь массив слева направо
+    #[inline]
+    fn forward(&self, mut idx: usize) -> usize {
+        for r in &self.rotors {
+            idx = r.encode_index(idx, false);
+        }
+        idx
+    }
+
+    /// Обратный проход — весь массив справа налево
+    #[inline]
+    fn backward(&self, mut idx: usize) -> usize {
+        for r in self.rotors.iter().rev() {
+            idx = r.encode_index(idx, true);
+        }
+        idx
+    }
+
+    /// Один такт: шагают блоки, у которых он последний в их интервале.
+    /// Каждый блок — свой одометр, переносов между блоками нет.
+    fn rotate(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+        if self.every.is_empty() {
+            for blk in self.blocks_mut() {
+                rotate_rotors(blk);
+            }
+            return;
+        }
+        let ticks = self.ticks;
+        for (blk, &every) in split_blocks(&mut self.rotors, &self.lens).zip(&self.every) {
+            if ticks.is_multiple_of(every) {
+                rotate_rotors(blk);
+            }
+        }
+    }
+
+    fn advance_by(&mut self, n: u64) {
+        let done = self.ticks;
+        self.ticks = done.wrapping_add(n);
+        if self.every.is_empty() {
+            for blk in self.blocks_mut() {
+                advance_rotors(blk, n);
+            }
+            return;
+        }
+        for (blk, &every) in split_blocks(&mut self.rotors, &self.lens).zip(&self.every) {
+            advance_rotors(blk, rotations(done, n, every));
+        }
+    }
+
+    fn save_state(&self) -> Vec<Vec<usize>> {
+        self.blocks()
+            .map(|blk| blk.iter().map(Rotor::save_position).collect())
+            .collect()
+    }
+
+    fn load_state(&mut self, state: &[Vec<usize>]) {
+        for (blk, pos) in self.blocks_mut().zip(state.iter()) {
+            for (r, &p) in blk.iter_mut().zip(pos.iter()) {
+                r.load_position(p);
+            }
+        }
+    }
+
+    /// Флаги `ConfigData::reversed`, по вектору на блок
+    fn load_reversed(&mut self, reversed: &[Vec<bool>]) {
+        for (blk, flags) in self.blocks_mut().zip(reversed) {
+            for (r, &flag) in blk.iter_mut().zip(flags) {
+                r.set_reversed(flag);
+            }
+        }
+    }
+}
+
+/// Разводка отражателя UKW-B «Энигмы»: 13 пар латиницы
+const UKW_B: &str = "ay br cu dh eq fs gl ip jx kn mo tz vw";
+/// Разводка UKW-C
+const UKW_C: &str = "af bv cp dj ei go hy kr lz mx nw qt su";
+
+/// Рефлектор (работает с индексами)
+#[derive(Clone)]
+pub struct Reflector {
+    map_idx: Vec<Idx>,
+}
+
+impl Reflector {
+    /// Отражатель по имени (см. `REFLECTOR_KINDS`). Любая разводка должна
+    /// быть инволюцией: иначе машина перестанет быть обратной самой себе.
+    pub fn new(alphabet: &[char], kind: &str) -> Result<Self, CipherError> {
+        let len = alphabet.len();
+        let mismatch = || CipherError::ReflectorAlphabetMismatch {
+            kind: kind.to_string(),
+            alphabet_len: len,
+        };
+        let map: Vec<usize> = match kind {
+            "reverse" => (0..len).map(|i| len - 1 - i).collect(),
+            "identity-pairs" => (0..len).map(|i| if i ^ 1 < len { i ^ 1 } else { i }).collect(),
+            "ukw-b" | "ukw-c" => {
+                let wiring = if kind == "ukw-b" { UKW_B } else { UKW_C };
+                let index = AlphabetIndex::new(alphabet);
+                let mut map: Vec<usize> = (0..len).collect();
+                for pair in wiring.split(' ') {
+                    let mut ends = pair.chars().map(|c| index.get(c));
+                    let (Some(Some(a)), Some(Some(b))) = (ends.next(), ends.next()) else {
+                        return Err(mismatch());
+                    };
+                    (map[a], map[b]) = (b, a);
+                }
+                map
+            }
+            _ => return Err(CipherError::UnknownReflector(kind.to_string())),
+        };
+        if map.iter().enumerate().any(|(i, &j)| map[j] != i) {
+            return Err(mismatch());
+        }
+        Ok(Reflector { map_idx: map.into_iter().map(|j| j as Idx).collect() })
+    }
+
+    #[inline]
+    pub fn reflect_index(&self, idx: usize) -> usize {
+        self.map_idx[idx] as usize
+    }
+
+    /// Оставляет ли разводка какую-то позицию на месте. Так выходит при
+    /// нечётной длине алфавита (кириллица с «ё» — 33 буквы) и у UKW на
+    /// байтах, где связаны только латинские буквы: символ, дошедший до такой
+    /// позиции, шифруется сам в себя.
+    pub fn has_fixed_point(&self) -> bool {
+        self.map_idx.iter().enumerate().any(|(i, &j)| i == j as usize)
+    }
+}
+
+/// Снимок машины для отладки: `EnigmaSudnogoDnya::describe`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct MachineDescription {
+    pub alphabet_len: usize,
+    /// Пары plugboard; для `plugboard_map` и стопки панелей — все провода
+    /// `(a, b)` из `a` в `b` общей перестановки
+    pub plugboard: Vec<(char, char)>,
+    /// Для каждого блока — его роторы по порядку
+    pub blocks: Vec<Vec<RotorDescription>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct RotorDescription {
+    /// Цветовая метка, если сдвиг ей соответствует
+    pub color: Option<char>,
+    pub shift: usize,
+    /// Текущая позиция
+    pub position: usize,
+    /// Обратная разводка (`ConfigData::reversed`)
+    #[cfg_attr(feature = "json", serde(default, skip_serializing_if = "core::ops::Not::not"))]
+    pub reversed: bool,
+}
+
+/// Путь одного символа через машину: `EnigmaSudnogoDnya::encrypt_traced`.
+/// Все индексы — номера символов в алфавите.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct CharTrace {
+    pub input: char,
+    pub output: char,
+    /// Позиции роторов, с которыми шифровался символ
+    pub positions: Vec<Vec<usize>>,
+    pub after_plugboard: usize,
+    /// После каждого блока на прямом проходе, по порядку блоков; без
+    /// отражателя при расшифровке пусто
+    pub forward: Vec<usize>,
+    /// Без отражателя — индекс после единственного прохода по блокам
+    pub after_reflector: usize,
+    /// После каждого блока на обратном проходе — от последнего блока к первому;
+    /// без отражателя при шифровании пусто
+    pub backward: Vec<usize>,
+    pub after_plugboard_out: usize,
+}
+
+/// Шаг `EnigmaSudnogoDnya::encrypt_watched`: символ, позиции роторов
+/// первых блоков, с которыми он шифровался, и результат
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharStep {
+    pub input: char,
+    /// По вектору на блок, не больше запрошенного числа блоков
+    pub positions: Vec<Vec<usize>>,
+    pub output: char,
+}
+
+/// Всё, что меняет шифрование: позиции роторов, счётчик символов (от него
+/// зависят фаза `step_interval` и омофоны), такты банка (фаза `step_every`)
+/// и цифровой ротор. `save_state` отдаёт только позиции: конфигу их
+/// хватает, продолжению с середины интервала — нет.
+#[cfg(feature = "std")]
+pub(crate) struct Snapshot {
+    positions: Vec<Vec<usize>>,
+    steps: u64,
+    ticks: u64,
+    digit: Option<Idx>,
+}
+
+#[cfg(feature = "std")]
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        self.positions.zeroize();
+        self.digit.zeroize();
+    }
+}
+
+/// Длина пробы `assert_involution_on_sample`
+const INVOLUTION_PROBE_LEN: usize = 64;
+
+/// Больше блоков — проба обратимости пропускается: у пресетов на миллионы
+/// блоков копия банка в отладочной сборке стоила бы дороже построения
+const INVOLUTION_PROBE_MAX_BLOCKS: usize = 4096;
+
+/// Машина ЭСД.
+///
+/// Шагание: после каждого символа алфавита (или такта `step_interval`)
+/// шагает каждый блок — с `step_every` на своих тактах, — и каждый блок —
+/// свой одометр. Переносы между блоками не
+/// передаются: так было с первой версии, на этом держатся все сохранённые
+/// шифротексты, тестовые векторы и оценка `estimate_keyspace`. Поэтому
+/// период — НОК периодов блоков, то есть `n^r` для самого длинного блока
+/// из `r` роторов, а не произведение: ещё блоки той же длины его не
+/// удлиняют. Длиннее период дают длинные блоки и `step_every`.
+#[derive(Clone)]
+pub struct EnigmaSudnogoDnya {
+    alphabet: Vec<char>,
+    index_map: AlphabetIndex,
+    plugboard: Plugboard,
+    bank: RotorBank,
+    reflector: Reflector,
+    /// Стартовые позиции всех роторов подряд, блок за блоком — для `reset`
+    initial_positions: Vec<Idx>,
+    /// Приводить ли текст к нижнему регистру (не нужно в байтовом режиме)
+    fold_case: bool,
+    /// Приводить ли текст к NFC (`normalize`)
+    normalize: bool,
+    /// Ротор на 10 позиций для цифр вне алфавита (`digit_rotor_shift`)
+    digit_rotor: Option<Rotor>,
+    /// Сколько символов алфавита обработано с построения или `reset`
+    steps: u64,
+    /// Сколько символов вызвали шаг банка с построения; `reset` не трогает
+    steps_taken: u64,
+    /// Роторы шагают раз в столько символов (`step_interval`)
+    step_interval: u64,
+    /// Омофоны частых букв (`homophonic`)
+    homophones: Option<Homophones>,
+    /// Отражатель в цепи (`use_reflector`)
+    use_reflector: bool,
+    /// Идёт расшифровка без отражателя: блоки проходятся в обратную сторону
+    inverse: bool,
+    /// Холостой прогон на каждый пропущенный символ (`constant_work`)
+    constant_work: bool,
+}
+
+/// Ключ в `Debug` не попадает: только размеры машины и число шагов
+impl fmt::Debug for EnigmaSudnogoDnya {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnigmaSudnogoDnya")
+            .field("alphabet_len", &self.alphabet.len())
+            .field("blocks", &self.bank.lens.len())
+            .field("rotors", &self.bank.rotors.len())
+            .field("steps", &self.steps)
+            .field("plugboard", &Redacted)
+            .field("positions", &Redacted)
+            .finish()
+    }
+}
+
+/// Роторы, банк и панель затирают себя сами
+impl Drop for EnigmaSudnogoDnya {
+    fn drop(&mut self) {
+        self.initial_positions.zeroize();
+        self.steps.zeroize();
+    }
+}
+
+impl EnigmaSudnogoDnya {
+    pub fn new(cfg: &ConfigData) -> Result<Self, CipherError> {
+        Self::new_with_progress(cfg, |_, _| {})
+    }
+
+    /// То же, что `new`, но после каждого построенного блока вызывает
+    /// `progress(готово, всего)` — для конфигов с миллионами блоков.
+    pub fn new_with_progress<F>(cfg: &ConfigData, mut progress: F) -> Result<Self, CipherError>
+    where
+        F: FnMut(usize, usize),
+    {
+        cfg.validate()?;
+
+        let alphabet = cfg.alphabet_chars();
+        let alphabet_len = alphabet.len();
+
+        let index_map = AlphabetIndex::new(&alphabet);
+
+        let plugboard =
+            Plugboard::new(&cfg.plugboard, &cfg.plugboard_map, &cfg.plugboards, &index_map)?;
+
+        let total = cfg.block_count();
+        let mut bank = RotorBank::with_capacity(total);
+        for (i, s) in cfg.blocks.iter().enumerate() {
+            bank.push(Block::new(s, alphabet_len)?);
+            progress(i + 1, total);
+        }
+        for (i, shifts) in cfg.block_shifts.iter().enumerate() {
+            bank.push(Block::from_shifts(shifts, alphabet_len)?);
+            progress(cfg.blocks.len() + i + 1, total);
+        }
+
+        bank.load_state(&cfg.rotor_positions);
+        bank.load_reversed(&cfg.reversed);
+        bank.every = cfg.step_every.clone();
+
+        let reflector = Reflector::new(&alphabet, &cfg.reflector_kind)?;
+        let initial_positions = bank.rotors.iter().map(|r| r.position).collect();
+
+        let machine = EnigmaSudnogoDnya {
+            index_map,
+            plugboard,
+            bank,
+            reflector,
+            initial_positions,
+            fold_case: cfg.alphabet != BYTES_ALPHABET,
+            normalize: cfg.normalize,
+            digit_rotor: cfg.digit_rotor_shift.map(|shift| Rotor::new(shift, DIGITS)),
+            steps: 0,
+            steps_taken: 0,
+            step_interval: cfg.step_interval as u64,
+            homophones: cfg.homophonic.then(|| Homophones::new(&cfg.alphabet, &alphabet)),
+            use_reflector: cfg.use_reflector,
+            inverse: false,
+            constant_work: cfg.constant_work,
+            alphabet,
+        };
+        machine.assert_involution_on_sample();
+        Ok(machine)
+    }
+
+    /// Самопроверка обратимости: машина из стартовых позиций шифрует
+    /// фиксированную пробу из букв алфавита, после `reset` расшифровывает
+    /// её и должна получить пробу обратно. На этом держится схема «`reset`
+    /// перед каждым сообщением», поэтому `new` зовёт проверку сам и ломаное
+    /// изменение шагания или отражателя падает сразу. Проба идёт на копии
+    /// банка, сама машина не меняется. Работает только в отладочной сборке
+    /// (`debug_assert`) и не больше чем на `INVOLUTION_PROBE_MAX_BLOCKS`
+    /// блоках, в релизе ничего не стоит.
+    fn assert_involution_on_sample(&self) {
+        if !cfg!(debug_assertions) || self.bank.lens.len() > INVOLUTION_PROBE_MAX_BLOCKS {
+            return;
+        }
+        let letters = self.homophones.as_ref().map_or(self.alphabet.len(), Homophones::letters);
+        let probe: Vec<usize> = (0..letters).cycle().take(INVOLUTION_PROBE_LEN).collect();
+        let cipher = self.probe_pass(&probe, false);
+        let mut plain = self.probe_pass(&cipher, true);
+        if let Some(h) = &self.homophones {
+            plain.iter_mut().for_each(|idx| *idx = h.collapse(*idx));
+        }
+        debug_assert_eq!(plain, probe, "машина не обратна самой себе");
+    }
+
+    /// Проход пробы по копии банка из стартовых позиций — как `encrypt` или
+    /// `decrypt` сразу после `reset`: омофоны только при шифровании
+    fn probe_pass(&self, input: &[usize], decrypt: bool) -> Vec<usize> {
+        let mut bank = self.bank.clone();
+        bank.ticks = 0;
+        for (r, &p) in bank.rotors.iter_mut().zip(&self.initial_positions) {
+            r.position = p;
+        }
+        let inverse = decrypt && !self.use_reflector;
+        (0u64..)
+            .zip(input)
+            .map(|(step, &idx)| {
+                let idx = if decrypt { idx } else { self.spread(idx, step) };
+                let out = self.route(&bank, idx, inverse);
+                if (step + 1).is_multiple_of(self.step_interval) {
+                    bank.rotate();
+                }
+                out
+            })
+            .collect()
+    }
+
+    /// Полный `Debug` с ключом (через `describe`) — для тестов и отладки
+    pub fn debug_full(&self) -> String {
+        format!("{:?}", self.describe())
+    }
+
+    /// Возвращает все роторы в стартовые позиции из конфига,
+    /// не пересоздавая блоки — для шифрования нескольких сообщений подряд.
+    pub fn reset(&mut self) {
+        for (r, &p) in self.bank.rotors.iter_mut().zip(&self.initial_positions) {
+            r.position = p;
+        }
+        if let Some(r) = &mut self.digit_rotor {
+            r.position = 0;
+        }
+        self.steps = 0;
+        self.bank.ticks = 0;
+    }
+
+    /// Сколько символов алфавита машина обработала с построения или `reset`
+    /// (плюс `advance_by`). `load_state` и `set_block_positions` счётчик
+    /// не трогают. Это и смещение следующего сообщения: свежая машина после
+    /// `advance_by(steps())` продолжает поток с того же места.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Сколько символов с построения машины повернули роторы — для аудита
+    /// расхода ключевого потока. В отличие от `steps`, `reset` счётчик не
+    /// обнуляет, а `advance_by` не двигает; при `step_interval > 1` роторы
+    /// поворачивает только каждый `step_interval`-й символ алфавита.
+    pub fn steps_taken(&self) -> u64 {
+        self.steps_taken
+    }
+
+    /// Перематывает роторы на `n` символов вперёд без шифрования — за
+    /// O(число роторов), а не O(n): каждый блок — число по основанию
+    /// алфавита, к которому прибавляется `n`. Цифровой ротор не двигается:
+    /// он считает только цифры.
+    pub fn advance_by(&mut self, n: u64) {
+        self.bank.advance_by(rotations(self.steps, n, self.step_interval));
+        self.steps = self.steps.wrapping_add(n);
+    }
+
+    /// Вектор инициализации сообщения: все блоки шагают `steps` раз, как
+    /// после `steps * step_interval` символов. Зовётся на свежей машине или
+    /// сразу после `reset`, и расшифровке нужен тот же `steps`: тогда
+    /// одинаковые сообщения с разными IV дают разный шифротекст. Цифровой
+    /// ротор, как и в `advance_by`, стоит на месте.
+    pub fn set_offset(&mut self, steps: u64) {
+        self.advance_by(steps.wrapping_mul(self.step_interval));
+    }
+
+    /// Шаг всех блоков после зашифрованного символа — если он последний
+    /// в интервале `step_interval`
+    #[inline]
+    fn step(&mut self) {
+        self.steps = self.steps.wrapping_add(1);
+        // при обычном шагании — без деления на каждом символе
+        if self.step_interval == 1 || self.steps.is_multiple_of(self.step_interval) {
+            self.bank.rotate();
+            self.steps_taken = self.steps_taken.wrapping_add(1);
+        }
+    }
+
+    /// Символы, которые машина шифрует, в порядке индексов; всё остальное
+    /// проходит как есть.
+    ///
+    /// ```
+    /// use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+    ///
+    /// let cfg = ConfigData { blocks: vec!["КБ".into()], ..Default::default() };
+    /// let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    /// let letters: String = machine.alphabet().iter().collect();
+    /// assert_eq!(letters, "abcdefghijklmnopqrstuvwxyz");
+    /// assert!(machine.contains('q'));
+    /// assert!(!machine.contains('Q') && !machine.contains(' '));
+    /// ```
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    /// Есть ли символ в алфавите (без приведения регистра)
+    pub fn contains(&self, c: char) -> bool {
+        self.index_map.get(c).is_some()
+    }
+
+    /// Текущие позиции роторов: по вектору на блок
+    pub fn save_state(&self) -> Vec<Vec<usize>> {
+        self.bank.save_state()
+    }
+
+    /// Полное состояние хода машины, см. `Snapshot`
+    #[cfg(feature = "std")]
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            positions: self.save_state(),
+            steps: self.steps,
+            ticks: self.bank.ticks,
+            digit: self.digit_rotor.as_ref().map(|r| r.position),
+        }
+    }
+
+    /// Возвращает машину в состояние, снятое `snapshot`
+    #[cfg(feature = "std")]
+    pub(crate) fn restore(&mut self, snapshot: &Snapshot) {
+        self.load_state(&snapshot.positions);
+        (self.steps, self.bank.ticks) = (snapshot.steps, snapshot.ticks);
+        if let (Some(r), Some(p)) = (&mut self.digit_rotor, snapshot.digit) {
+            r.position = p;
+        }
+    }
+
+    /// Через сколько символов алфавита состояние роторов повторится
+    /// (`u128::MAX`, если больше). Считается по размерам блоков, без прогона:
+    /// блоки шагают независимо, период — НОК по блокам.
+    pub fn period(&self) -> u128 {
+        let every = self.bank.every.iter().copied().chain(core::iter::repeat(1));
+        let blocks = self.bank.lens.iter().map(|&r| r as usize).zip(every);
+        rotor_period(self.alphabet.len(), blocks, self.step_interval as usize)
+    }
+
+    /// См. `Reflector::has_fixed_point`
+    pub fn reflector_has_fixed_point(&self) -> bool {
+        self.reflector.has_fixed_point()
+    }
+
+    /// Пара символа на plugboard (для `plugboard_map` — куда ведёт его провод
+    /// на входе); символ без пары и символ вне алфавита возвращаются как есть
+    pub fn plugboard_swap(&self, c: char) -> char {
+        match self.index_map.get(c) {
+            Some(i) => self.alphabet[self.plugboard.swap(i)],
+            None => c,
+        }
+    }
+
+    /// Снимок машины: plugboard и все роторы с текущими позициями
+    pub fn describe(&self) -> MachineDescription {
+        let wires: Vec<_> = if self.plugboard.is_reciprocal() {
+            self.plugboard.pairs().collect()
+        } else {
+            self.plugboard.wires().collect()
+        };
+        let plugboard =
+            wires.into_iter().map(|(i, j)| (self.alphabet[i], self.alphabet[j])).collect();
+        let blocks = self
+            .bank
+            .blocks()
+            .map(|blk| {
+                blk.iter()
+                    .map(|r| RotorDescription {
+                        color: shift_color(r.shift as usize),
+                        shift: r.shift as usize,
+                        position: r.save_position(),
+                        reversed: r.reversed,
+                    })
+                    .collect()
+            })
+            .collect();
+        MachineDescription {
+            alphabet_len: self.alphabet.len(),
+            plugboard,
+            blocks,
+        }
+    }
+
+    /// Ставит роторы блока `block` в `positions` — и сейчас, и как стартовые
+    /// для последующих `reset`. Позиции проверяются, а не берутся по модулю.
+    pub fn set_block_positions(
+        &mut self,
+        block: usize,
+        positions: &[usize],
+    ) -> Result<(), CipherError> {
+        let blocks = self.bank.lens.len();
+        let rotors = *self
+            .bank
+            .lens
+            .get(block)
+            .ok_or(CipherError::BlockOutOfRange { block, blocks })? as usize;
+        if positions.len() != rotors {
+            return Err(CipherError::RotorPositionCountMismatch {
+                block,
+                rotors,
+                positions: positions.len(),
+            });
+        }
+        let alphabet_len = self.alphabet.len();
+        let out_of_range = positions.iter().enumerate().find(|&(_, &p)| p >= alphabet_len);
+        if let Some((rotor, &position)) = out_of_range {
+            return Err(CipherError::PositionOutOfRange { block, rotor, position, alphabet_len });
+        }
+
+        let start: usize = self.bank.lens[..block].iter().map(|&l| l as usize).sum();
+        for (i, &p) in positions.iter().enumerate() {
+            self.bank.rotors[start + i].load_position(p);
+            self.initial_positions[start + i] = p as Idx;
+        }
+        Ok(())
+    }
+
+    /// Текущая позиция ротора `rotor` блока `block`, как в `save_state`;
+    /// `None` — нет такого блока или ротора
+    pub fn get_position(&self, block: usize, rotor: usize) -> Option<usize> {
+        let i = self.rotor_index(block, rotor).ok()?;
+        Some(self.bank.rotors[i].save_position())
+    }
+
+    /// Ставит один ротор в `position`, как `set_block_positions` — целый
+    /// блок: и сейчас, и как стартовую позицию для `reset`
+    pub fn set_position(
+        &mut self,
+        block: usize,
+        rotor: usize,
+        position: usize,
+    ) -> Result<(), CipherError> {
+        let i = self.rotor_index(block, rotor)?;
+        let alphabet_len = self.alphabet.len();
+        if position >= alphabet_len {
+            return Err(CipherError::PositionOutOfRange { block, rotor, position, alphabet_len });
+        }
+        self.bank.rotors[i].load_position(position);
+        self.initial_positions[i] = position as Idx;
+        Ok(())
+    }
+
+    /// Номер ротора `rotor` блока `block` в общем массиве роторов
+    fn rotor_index(&self, block: usize, rotor: usize) -> Result<usize, CipherError> {
+        let blocks = self.bank.lens.len();
+        let rotors = *self
+            .bank
+            .lens
+            .get(block)
+            .ok_or(CipherError::BlockOutOfRange { block, blocks })? as usize;
+        if rotor >= rotors {
+            return Err(CipherError::RotorOutOfRange { block, rotor, rotors });
+        }
+        let start: usize = self.bank.lens[..block].iter().map(|&l| l as usize).sum();
+        Ok(start + rotor)
+    }
+
+    /// Стартовые позиции для `cfg`, при которых `known_plain` шифруется в
+    /// `known_cipher`; только для маленьких машин, см. `attack::crack_positions`
+    #[cfg(feature = "std")]
+    pub fn crack_positions(
+        cfg: &ConfigData,
+        known_plain: &str,
+        known_cipher: &str,
+    ) -> Option<Vec<Vec<usize>>> {
+        attack::crack_positions(cfg, known_plain, known_cipher)
+    }
+
+    /// Текущие позиции в формате `ConfigData::rotor_positions`: записав их
+    /// в конфиг, следующий сеанс продолжит с того же места
+    pub fn export_positions(&self) -> Vec<Vec<usize>> {
+        self.save_state()
+    }
+
+    /// Восстанавливает позиции роторов, снятые через `save_state`
+    pub fn load_state(&mut self, state: &[Vec<usize>]) {
+        self.bank.load_state(state);
+    }
+
+    /// Прогон индекса через всю машину без вращения роторов. Без отражателя
+    /// блоки проходятся один раз: вперёд при шифровании, назад при
+    /// расшифровке — plugboard на выходе обратный, так что это и есть
+    /// обратная подстановка.
+    #[inline]
+    fn encipher(&self, bank: &RotorBank, idx: usize) -> usize {
+        self.route(bank, idx, self.inverse)
+    }
+
+    /// `encipher` с явным направлением: `inverse` — обратный проход без
+    /// отражателя
+    #[inline]
+    fn route(&self, bank: &RotorBank, mut idx: usize, inverse: bool) -> usize {
+        idx = self.plugboard.swap(idx);
+        idx = match (self.use_reflector, inverse) {
+            (true, _) => bank.backward(self.reflector.reflect_index(bank.forward(idx))),
+            (false, false) => bank.forward(idx),
+            (false, true) => bank.backward(idx),
+        };
+        self.plugboard.unswap(idx)
+    }
+
+    /// `f` в режиме расшифровки: без отражателя блоки идут в обратную
+    /// сторону, с отражателем ничего не меняется
+    fn inverted<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.inverse = !self.use_reflector;
+        let out = f(self);
+        self.inverse = false;
+        out
+    }
+
+    /// Подстановка, которую машина делает прямо сейчас: для каждого индекса
+    /// алфавита — plugboard, роторы, отражатель, роторы, plugboard без
+    /// вращения. При фиксированном состоянии это инволюция, если отражатель
+    /// не выключен (`use_reflector`). Омофоны сюда не входят: они выбирают
+    /// вход, а не меняют разводку.
+    pub fn current_permutation(&self) -> Vec<usize> {
+        (0..self.alphabet.len()).map(|i| self.encipher(&self.bank, i)).collect()
+    }
+
+    /// Омофон буквы для шага `step`; без омофонов — сам индекс
+    #[inline]
+    fn spread(&self, idx: usize, step: u64) -> usize {
+        match &self.homophones {
+            Some(h) => h.spread(idx, step),
+            None => idx,
+        }
+    }
+
+    /// Шифрует индекс алфавита (с омофоном) и поворачивает роторы
+    #[inline]
+    fn encrypt_index(&mut self, idx: usize) -> usize {
+        let idx = self.encipher(&self.bank, self.spread(idx, self.steps));
+        self.step();
+        idx
+    }
+
+    /// Шифрует один символ (уже в нижнем регистре) и поворачивает роторы;
+    /// символы вне алфавита возвращаются как есть, кроме цифр при
+    /// включённом цифровом роторе.
+    #[inline]
+    fn encrypt_char(&mut self, ch: char) -> char {
+        match self.index_map.get(ch) {
+            Some(idx) => {
+                let idx = self.encrypt_index(idx);
+                self.alphabet[idx]
+            }
+            None => {
+                let out = self.encrypt_digit(ch).unwrap_or(ch);
+                if self.constant_work {
+                    self.idle_pass();
+                }
+                out
+            }
+        }
+    }
+
+    /// Холостой прогон для `constant_work`: та же подстановка, что у буквы
+    /// алфавита, но результат выбрасывается и роторы не шагают. `black_box`
+    /// не даёт компилятору выкинуть работу, результат которой не нужен.
+    /// Это выравнивает основную часть времени на символ, но не всё: поиск
+    /// в алфавите и шаг роторов по-прежнему есть только у букв.
+    #[inline(never)]
+    fn idle_pass(&self) {
+        let idx = core::hint::black_box(0);
+        core::hint::black_box(self.encipher(&self.bank, self.spread(idx, self.steps)));
+    }
+
+    #[inline]
+    fn encrypt_digit(&mut self, ch: char) -> Option<char> {
+        encrypt_digit(self.digit_rotor.as_mut()?, ch)
+    }
+
+    /// Один проход по сообщению без промежуточных буферов. Символ может
+    /// раскрыться в нижнем регистре в несколько (`'İ'` → `"i̇"`) — тогда
+    /// шифруется каждый из них, как и раньше при `to_lowercase` всей строки.
+    /// В байтовом режиме регистр не трогается.
+    pub fn encrypt(&mut self, msg: &str) -> String {
+        let msg = self.normalized(msg);
+        let mut out = String::with_capacity(msg.len());
+        for ch in msg.chars() {
+            if self.fold_case {
+                for lower in ch.to_lowercase() {
+                    out.push(self.encrypt_char(lower));
+                }
+            } else {
+                out.push(self.encrypt_char(ch));
+            }
+        }
+        out
+    }
+
+    /// Текст в NFC, если это включено в конфиге: составные символы вроде
+    /// «и» + U+0306 становятся одним «й» из алфавита. Длина строки при этом
+    /// может измениться.
+    fn normalized<'a>(&self, msg: &'a str) -> Cow<'a, str> {
+        if self.normalize && !is_nfc(msg) {
+            Cow::Owned(msg.nfc().collect())
+        } else {
+            Cow::Borrowed(msg)
+        }
+    }
+
+    /// Ленивый вариант `encrypt`: символы шифруются по одному по мере
+    /// чтения, роторы шагают вместе с итератором. Недочитанный хвост
+    /// роторы не двигает.
+    pub fn encrypt_iter<I: Iterator<Item = char>>(
+        &mut self,
+        input: I,
+    ) -> impl Iterator<Item = char> {
+        let fold_case = self.fold_case;
+        let input = match self.normalize {
+            true => Either::Left(input.nfc()),
+            false => Either::Right(input),
+        };
+        input
+            .flat_map(move |ch| {
+                // либо все символы нижнего регистра, либо сам символ
+                let lower = ch.to_lowercase().take(if fold_case { usize::MAX } else { 0 });
+                lower.chain((!fold_case).then_some(ch))
+            })
+            .map(move |ch| self.encrypt_char(ch))
+    }
+
+    /// `encrypt` без выделения памяти: шифротекст в UTF-8 пишется в `out`
+    /// (буфер на стеке, `heapless::Vec` и т.п.), возвращается число байт.
+    /// Если следующий символ не помещается — `BufferTooSmall`, а роторы к
+    /// этому моменту уже сдвинуты: перед повтором нужен `reset` или
+    /// `load_state`.
+    pub fn encrypt_into(&mut self, msg: &str, out: &mut [u8]) -> Result<usize, CipherError> {
+        let mut written = 0;
+        for ch in self.encrypt_iter(msg.chars()) {
+            let end = written + ch.len_utf8();
+            let Some(slot) = out.get_mut(written..end) else {
+                return Err(CipherError::BufferTooSmall { written });
+            };
+            ch.encode_utf8(slot);
+            written = end;
+        }
+        Ok(written)
+    }
+
+    /// Расшифровка. С отражателем машина обратна сама себе и это тот же
+    /// проход, что `encrypt`, но снаружи расшифровывать надо именно через
+    /// этот метод: без отражателя (`use_reflector`) блоки здесь идут в
+    /// обратную сторону, омофоны сводятся обратно к буквам, а разбор
+    /// заголовков, проверки отпечатков и индикаторы появятся здесь же.
+    pub fn decrypt(&mut self, msg: &str) -> Result<String, CipherError> {
+        let (plain, ()) = self.inverted(|m| m.without_homophones(|m| (m.encrypt(msg), ())));
+        Ok(plain)
+    }
+
+    /// `f` с выключенными омофонами, результат — со сведёнными к буквам:
+    /// без омофонов проход обратен шифрованию и выдаёт ровно тот символ,
+    /// что стоял после омофонов при шифровании
+    fn without_homophones<T>(&mut self, f: impl FnOnce(&mut Self) -> (String, T)) -> (String, T) {
+        let Some(homophones) = self.homophones.take() else {
+            return f(self);
+        };
+        let (spread, extra) = f(self);
+        let plain = spread
+            .chars()
+            .map(|c| match self.index_map.get(c) {
+                Some(idx) => self.alphabet[homophones.collapse(idx)],
+                None => c,
+            })
+            .collect();
+        self.homophones = Some(homophones);
+        (plain, extra)
+    }
+
+    /// Как `encrypt`, но ещё записывает путь каждого символа алфавита через
+    /// машину; символы вне алфавита в трассу не попадают. Медленно — только
+    /// для отладки коротких сообщений.
+    pub fn encrypt_traced(&mut self, msg: &str) -> (String, Vec<CharTrace>) {
+        let msg = self.normalized(msg);
+        let mut out = String::with_capacity(msg.len());
+        let mut traces = Vec::new();
+        for ch in msg.chars() {
+            let lower: Vec<char> = if self.fold_case {
+                ch.to_lowercase().collect()
+            } else {
+                vec![ch]
+            };
+            for c in lower {
+                match self.index_map.get(c) {
+                    Some(idx) => {
+                        let trace = self.trace_index(c, self.spread(idx, self.steps));
+                        self.step();
+                        out.push(trace.output);
+                        traces.push(trace);
+                    }
+                    None => out.push(self.encrypt_digit(c).unwrap_or(c)),
+                }
+            }
+        }
+        (out, traces)
+    }
+
+    /// Как `encrypt_traced`, но расшифровка: без отражателя трасса идёт
+    /// обратным проходом, омофоны в тексте сводятся к буквам (в трассе —
+    /// нет)
+    pub fn decrypt_traced(&mut self, msg: &str) -> (String, Vec<CharTrace>) {
+        self.inverted(|m| m.without_homophones(|m| m.encrypt_traced(msg)))
+    }
+
+    /// Как `encrypt`, но перед шагом роторов отдаёт каждый символ в `watch`:
+    /// с позициями первых `blocks` блоков до шага и результатом. В отличие
+    /// от `encrypt_traced` ничего не копит и годится для огромных машин.
+    /// Символы вне алфавита тоже проходят через `watch`.
+    pub fn encrypt_watched<F: FnMut(&CharStep)>(
+        &mut self,
+        msg: &str,
+        blocks: usize,
+        mut watch: F,
+    ) -> String {
+        let msg = self.normalized(msg);
+        let mut out = String::with_capacity(msg.len());
+        for ch in msg.chars() {
+            let lower: Vec<char> = if self.fold_case {
+                ch.to_lowercase().collect()
+            } else {
+                vec![ch]
+            };
+            for c in lower {
+                let positions = self
+                    .bank
+                    .blocks()
+                    .take(blocks)
+                    .map(|blk| blk.iter().map(Rotor::save_position).collect())
+                    .collect();
+                let output = self.encrypt_char(c);
+                watch(&CharStep { input: c, positions, output });
+                out.push(output);
+            }
+        }
+        out
+    }
+
+    /// Как `encrypt_watched`, но расшифровка; у омофонов в `watch` приходит
+    /// символ до сведения к букве
+    pub fn decrypt_watched<F: FnMut(&CharStep)>(
+        &mut self,
+        msg: &str,
+        blocks: usize,
+        watch: F,
+    ) -> String {
+        let watched = |m: &mut Self| (m.encrypt_watched(msg, blocks, watch), ());
+        self.inverted(|m| m.without_homophones(watched)).0
+    }
+
+    /// То же, что `encipher`, но с записью промежуточных индексов
+    fn trace_index(&self, input: char, idx: usize) -> CharTrace {
+        let after_plugboard = self.plugboard.swap(idx);
+        let blocks: Vec<&[Rotor]> = self.bank.blocks().collect();
+        let mut forward = Vec::with_capacity(blocks.len());
+        let mut i = after_plugboard;
+        if self.use_reflector || !self.inverse {
+            for blk in &blocks {
+                for r in blk.iter() {
+                    i = r.encode_index(i, false);
+                }
+                forward.push(i);
+            }
+        }
+        let after_reflector = if self.use_reflector { self.reflector.reflect_index(i) } else { i };
+        let mut backward = Vec::with_capacity(blocks.len());
+        i = after_reflector;
+        if self.use_reflector || self.inverse {
+            for blk in blocks.iter().rev() {
+                for r in blk.iter().rev() {
+                    i = r.encode_index(i, true);
+                }
+                backward.push(i);
+            }
+        }
+        let after_plugboard_out = self.plugboard.unswap(i);
+        CharTrace {
+            input,
+            output: self.alphabet[after_plugboard_out],
+            positions: self.save_state(),
+            after_plugboard,
+            forward,
+            after_reflector,
+            backward,
+            after_plugboard_out,
+        }
+    }
+
+    /// Шифрует байты. Байт шифруется, если он совпадает с символом алфавита
+    /// (U+0000..U+00FF), остальные проходят как есть — в режиме `bytes`
+    /// шифруется каждый байт.
+    pub fn encrypt_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        self.encrypt_bytes_in_place(&mut out);
+        out
+    }
+
+    /// Обратное к `encrypt_bytes`; с отражателем — тот же проход
+    pub fn decrypt_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        self.decrypt_bytes_in_place(&mut out);
+        out
+    }
+
+    pub fn decrypt_bytes_in_place(&mut self, data: &mut [u8]) {
+        self.inverted(|m| m.encrypt_bytes_in_place(data))
+    }
+
+    pub fn encrypt_bytes_in_place(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if let Some(idx) = self.index_map.get(char::from(*byte)) {
+                let idx = self.encrypt_index(idx);
+                // алфавит, в котором нашёлся байт, целиком лежит в U+0000..U+00FF
+                *byte = self.alphabet[idx] as u8;
+            } else if let Some(d) = self.encrypt_digit(char::from(*byte)) {
+                *byte = d as u8;
+            }
+        }
+    }
+
+    /// Потоковое шифрование байтов из `reader` в `writer` кусками по
+    /// `STREAM_CHUNK`. Возвращает число обработанных байт.
+    #[cfg(feature = "std")]
+    pub fn encrypt_stream<R: Read, W: Write>(&mut self, reader: R, writer: W) -> io::Result<u64> {
+        self.encrypt_stream_with_progress(reader, writer, None, |_| {})
+    }
+
+    /// То же, что `encrypt_stream`, но после каждого куска зовёт `progress`;
+    /// `total` — ожидаемый размер, если он известен (длина файла)
+    #[cfg(feature = "std")]
+    pub fn encrypt_stream_with_progress<R, W, F>(
+        &mut self,
+        mut reader: R,
+        mut writer: W,
+        total: Option<u64>,
+        mut progress: F,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        F: FnMut(Progress),
+    {
+        let mut buf = vec![0u8; STREAM_CHUNK];
+        let mut done = 0u64;
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.encrypt_bytes_in_place(&mut buf[..n]);
+            writer.write_all(&buf[..n])?;
+            done += n as u64;
+            progress(Progress { done, total });
+        }
+        writer.flush()?;
+        Ok(done)
+    }
+
+    /// Обратное к `encrypt_stream`
+    #[cfg(feature = "std")]
+    pub fn decrypt_stream<R: Read, W: Write>(&mut self, reader: R, writer: W) -> io::Result<u64> {
+        self.decrypt_stream_with_progress(reader, writer, None, |_| {})
+    }
+
+    /// Обратное к `encrypt_stream_with_progress`
+    #[cfg(feature = "std")]
+    pub fn decrypt_stream_with_progress<R, W, F>(
+        &mut self,
+        reader: R,
+        writer: W,
+        total: Option<u64>,
+        progress: F,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        F: FnMut(Progress),
+    {
+        self.inverted(|m| m.encrypt_stream_with_progress(reader, writer, total, progress))
+    }
+
+    /// Шифрует и добавляет тег целостности шифротекста
+    pub fn encrypt_authenticated(&mut self, msg: &str, key: &MacKey) -> String {
+        key.seal(&self.encrypt(msg))
+    }
+
+    /// Проверяет тег и только потом расшифровывает; при несовпадении
+    /// роторы не двигаются
+    pub fn decrypt_authenticated(
+        &mut self,
+        sealed: &str,
+        key: &MacKey,
+    ) -> Result<String, CipherError> {
+        let ciphertext = key.open(sealed)?;
+        self.decrypt(ciphertext)
+    }
+
+    /// Шифрует текст и дописывает к нему зашифрованную контрольную сумму
+    /// из `CHECKSUM_LEN` символов алфавита. Это не MAC: сумма ловит случайную
+    /// порчу шифротекста, а не подделку (для неё есть `encrypt_authenticated`).
+    pub fn encrypt_with_tag(&mut self, msg: &str) -> String {
+        let msg = self.normalized(msg);
+        let plain = if self.fold_case {
+            msg.chars().flat_map(char::to_lowercase).collect()
+        } else {
+            msg.to_string()
+        };
+        let mut out = self.encrypt(&plain);
+        for c in self.checksum(&plain) {
+            out.push(self.encrypt_char(c));
+        }
+        out
+    }
+
+    /// Расшифровывает результат `encrypt_with_tag` и сверяет контрольную сумму
+    pub fn decrypt_with_tag(&mut self, cipher: &str) -> Result<String, CipherError> {
+        let mut plain = self.decrypt(cipher)?;
+        let split = plain
+            .char_indices()
+            .rev()
+            .nth(CHECKSUM_LEN - 1)
+            .ok_or(CipherError::IntegrityFailure)?
+            .0;
+        let tag = plain.split_off(split);
+        if tag.chars().ne(self.checksum(&plain)) {
+            return Err(CipherError::IntegrityFailure);
+        }
+        Ok(plain)
+    }
+
+    /// SHA-256 от ключа машины (алфавит, plugboard, роторы, стартовые
+    /// позиции) и текста, первые байты — в символы алфавита
+    fn checksum(&self, plain: &str) -> Vec<char> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"esd-checksum-v1");
+        for &c in &self.alphabet {
+            hasher.update(u32::from(c).to_le_bytes());
+        }
+        for i in 0..self.plugboard.alphabet_len() {
+            hasher.update((self.plugboard.swap(i) as Idx).to_le_bytes());
+        }
+        hasher.update(&self.bank.lens);
+        for rotor in &self.bank.rotors {
+            hasher.update(rotor.shift.to_le_bytes());
+        }
+        // номера перевёрнутых роторов; без них сумма та же, что и раньше
+        for (i, _) in self.bank.rotors.iter().enumerate().filter(|(_, r)| r.reversed) {
+            hasher.update((i as u64).to_le_bytes());
+        }
+        // так же и с выключенным отражателем
+        if !self.use_reflector {
+            hasher.update(b"no-reflector");
+        }
+        for &p in &self.initial_positions {
+            hasher.update(p.to_le_bytes());
+        }
+        hasher.update(plain.as_bytes());
+        let digest = hasher.finalize();
+        // только буквы: омофоны после расшифровки превращаются в буквы
+        let letters = self.homophones.as_ref().map_or(self.alphabet.len(), Homophones::letters);
+        digest[..CHECKSUM_LEN]
+            .iter()
+            .map(|&b| self.alphabet[b as usize % letters])
+            .collect()
+    }
+
+    /// Параллельное шифрование: сообщение режется на `threads` диапазонов
+    /// (но не больше, чем ядер и символов), состояние роторов на начале
+    /// каждого диапазона считается через `advance_by`, а диапазоны
+    /// шифруются одновременно в глобальном пуле rayon — так что потоков
+    /// занято не больше `threads`, и свой пул на каждый вызов не строится.
+    ///
+    /// Результат побайтно совпадает с `encrypt`, но состояние самой машины
+    /// не меняется.
+    #[cfg(feature = "std")]
+    pub fn encrypt_parallel(&self, msg: &str, threads: usize) -> String {
+        let msg = self.normalized(msg);
+        let lower: Vec<char> = if self.fold_case {
+            msg.chars().flat_map(char::to_lowercase).collect()
+        } else {
+            msg.chars().collect()
+        };
+        // больше кусков, чем символов или ядер, не бывает: `threads` — от
+        // вызывающего и может быть любым, вплоть до `usize::MAX`
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let threads = threads.min(cores).min(lower.len()).max(1);
+        let chunk_len = lower.len().div_ceil(threads).max(1);
+
+        // (кусок, сколько символов алфавита и цифр для цифрового ротора
+        // было до него)
+        let is_digit = |ch: char| self.digit_rotor.is_some() && ch.is_ascii_digit();
+        let mut ranges = Vec::with_capacity(threads);
+        let (mut offset, mut digits) = (0u64, 0u64);
+        for part in lower.chunks(chunk_len) {
+            ranges.push((part, offset, digits));
+            for &ch in part {
+                if self.contains(ch) {
+                    offset += 1;
+                } else if is_digit(ch) {
+                    digits += 1;
+                }
+            }
+        }
+
+        let parts: Vec<String> = ranges
+            .par_iter()
+            .map(|&(part, offset, digits)| {
+                let mut bank = self.bank.clone();
+                bank.advance_by(rotations(self.steps, offset, self.step_interval));
+                let mut digit_rotor = self.digit_rotor.clone();
+                if let Some(r) = &mut digit_rotor {
+                    r.advance_by(digits);
+                }
+                let mut out = String::with_capacity(part.len());
+                let mut step = self.steps.wrapping_add(offset);
+                for &ch in part {
+                    if let Some(idx) = self.index_map.get(ch) {
+                        let idx = self.encipher(&bank, self.spread(idx, step));
+                        step = step.wrapping_add(1);
+                        if step.is_multiple_of(self.step_interval) {
+                            bank.rotate();
+                        }
+                        out.push(self.alphabet[idx]);
+                    } else if let Some(d) =
+                        digit_rotor.as_mut().and_then(|r| encrypt_digit(r, ch))
+                    {
+                        out.push(d);
+                    } else {
+                        out.push(ch);
+                    }
+                }
+                out
+            })
+            .collect();
+
+        parts.concat()
+    }
+
+    /// Конфиг из файла: `*.bin` — двоичный формат, остальное — JSON
+    #[cfg(feature = "fs")]
+    pub fn load_config(filename: &str) -> io::Result<ConfigData> {
+        if filename.ends_with(BIN_CONFIG_EXT) {
+            return Self::load_config_bin(filename);
+        }
+        let s = fs::read_to_string(filename)?;
+        let cfg = serde_json::from_str(&s)?;
+        Ok(cfg)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_config_bin(filename: &str) -> io::Result<ConfigData> {
+        config_bin::decode(&fs::read(filename)?)
+    }
+
+    /// Записывает конфиг атомарно: во временный `<файл>.tmp`, fsync и
+    /// переименование, так что обрыв посреди записи не портит старый файл.
+    /// С `keep_backup` прежняя версия остаётся в `<файл>.bak`.
+    /// Формат выбирается по расширению, как в `load_config`.
+    #[cfg(feature = "fs")]
+    pub fn save_config(cfg: &ConfigData, filename: &str, keep_backup: bool) -> io::Result<()> {
+        if filename.ends_with(BIN_CONFIG_EXT) {
+            return Self::save_config_bin(cfg, filename, keep_backup);
+        }
+        write_atomically(filename, &serde_json::to_vec_pretty(cfg)?, keep_backup)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn save_config_bin(cfg: &ConfigData, filename: &str, keep_backup: bool) -> io::Result<()> {
+        write_atomically(filename, &config_bin::encode(cfg)?, keep_backup)
+    }
+}
+
+/// Пишет во временный файл рядом и переименовывает его поверх: при сбое
+/// остаётся либо старый файл, либо новый целиком. Временный файл при ошибке
+/// удаляется, а каталог после переименования сбрасывается на диск — иначе
+/// само переименование могло бы не пережить отключения питания.
+#[cfg(feature = "fs")]
+fn write_atomically(filename: &str, data: &[u8], keep_backup: bool) -> io::Result<()> {
+    let tmp = format!("{}.tmp", filename);
+    let replace = || {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+
+        if keep_backup && fs::metadata(filename).is_ok() {
+            fs::copy(filename, format!("{}.bak", filename))?;
+        }
+        fs::rename(&tmp, filename)
+    };
+    if let Err(e) = replace() {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    sync_parent_dir(Path::new(filename))
+}
+
+/// Каталог открывается как файл только в Unix; в остальных системах шаг
+/// пропускается
+#[cfg(feature = "fs")]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+        fs::File::open(parent.unwrap_or(Path::new(".")))?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Файл цепочки по умолчанию, см. `MultiEnigma::load_cascade`
+pub const MULTI_CONFIG_FILE: &str = "multi_config.json";
+
+/// Элемент файла каскада: путь к конфигу (относительно самого файла) или
+/// конфиг целиком
+#[cfg(feature = "fs")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CascadeEntry {
+    Path(String),
+    Inline(Box<ConfigData>),
+}
+
+/// Файл каскада: `{"cascade": [...], "strict": false}` или просто массив
+#[cfg(feature = "fs")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CascadeFile {
+    Chain(Vec<CascadeEntry>),
+    Cascade {
+        cascade: Vec<CascadeEntry>,
+        #[serde(default = "strict_by_default")]
+        strict: bool,
+    },
+}
+
+#[cfg(feature = "fs")]
+fn strict_by_default() -> bool {
+    true
+}
+
+/// Несколько независимых машин подряд (каскад): шифротекст первой —
+/// открытый текст второй и так далее. Расшифровка идёт в обратном порядке.
+pub struct MultiEnigma {
+    machines: Vec<EnigmaSudnogoDnya>,
+}
+
+impl MultiEnigma {
+    /// Строгий каскад из конфигов, см. `from_machines`
+    pub fn new(cfgs: &[ConfigData]) -> Result<Self, CipherError> {
+        if cfgs.is_empty() {
+            return Err(CipherError::EmptyChain);
+        }
+        let machines = cfgs
+            .iter()
+            .map(EnigmaSudnogoDnya::new)
+            .collect::<Result<_, _>>()?;
+        Self::from_machines(machines, true)
+    }
+
+    /// Каскад из готовых машин. Со `strict` соседние машины обязаны иметь
+    /// один алфавит: иначе символы, которых нет у следующей машины, прошли
+    /// бы её без шифрования. Без `strict` так и происходит, а регистр
+    /// теряется, как и у одной машины: расшифровка вернёт строчные буквы.
+    /// Но машина, приводящая текст к нижнему регистру, не может стоять за
+    /// байтовой: заглавные в её шифротексте свернулись бы в строчные и
+    /// испортили его, — это `CascadeCaseMismatch` в любом режиме.
+    pub fn from_machines(
+        machines: Vec<EnigmaSudnogoDnya>,
+        strict: bool,
+    ) -> Result<Self, CipherError> {
+        if machines.is_empty() {
+            return Err(CipherError::EmptyChain);
+        }
+        let mismatch = machines.windows(2).position(|w| w[0].alphabet != w[1].alphabet);
+        if let (true, Some(i)) = (strict, mismatch) {
+            return Err(CipherError::CascadeAlphabetMismatch { machine: i + 1 });
+        }
+        let folds = |w: &[EnigmaSudnogoDnya]| {
+            w[1].fold_case && w[0].alphabet.iter().any(|c| c.is_uppercase())
+        };
+        if let Some(i) = machines.windows(2).position(folds) {
+            return Err(CipherError::CascadeCaseMismatch { machine: i + 1 });
+        }
+        Ok(MultiEnigma { machines })
+    }
+
+    pub fn from_cascade(cascade: &CascadeConfig) -> Result<Self, CipherError> {
+        if cascade.configs.is_empty() {
+            return Err(CipherError::EmptyChain);
+        }
+        let machines = cascade
+            .configs
+            .iter()
+            .map(EnigmaSudnogoDnya::new)
+            .collect::<Result<_, _>>()?;
+        Self::from_machines(machines, cascade.strict)
+    }
+
+    pub fn machines(&self) -> &[EnigmaSudnogoDnya] {
+        &self.machines
+    }
+
+    /// Все машины — в стартовые позиции
+    pub fn reset(&mut self) {
+        for m in &mut self.machines {
+            m.reset();
+        }
+    }
+
+    pub fn encrypt(&mut self, msg: &str) -> String {
+        let mut text = msg.to_string();
+        for m in &mut self.machines {
+            text = m.encrypt(&text);
+        }
+        text
+    }
+
+    pub fn decrypt(&mut self, msg: &str) -> Result<String, CipherError> {
+        let mut text = msg.to_string();
+        for m in self.machines.iter_mut().rev() {
+            text = m.decrypt(&text)?;
+        }
+        Ok(text)
+    }
+
+    /// Байты через все машины по порядку; обратно — `decrypt_bytes`
+    pub fn encrypt_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        for m in &mut self.machines {
+            m.encrypt_bytes_in_place(&mut out);
+        }
+        out
+    }
+
+    /// Обратное к `encrypt_bytes`: машины расшифровывают с конца
+    pub fn decrypt_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        for m in self.machines.iter_mut().rev() {
+            m.decrypt_bytes_in_place(&mut out);
+        }
+        out
+    }
+
+    /// Конфиги цепочки без флага `strict`, см. `load_cascade`
+    #[cfg(feature = "fs")]
+    pub fn load_config(filename: &str) -> io::Result<Vec<ConfigData>> {
+        Ok(Self::load_cascade(filename)?.configs)
+    }
+
+    /// Файл каскада: JSON-массив или `{"cascade": [...], "strict": false}`.
+    /// Элемент — конфиг целиком или путь к нему (`*.bin` тоже), отсчитанный
+    /// от папки файла каскада. Без `strict` каскад строгий.
+    #[cfg(feature = "fs")]
+    pub fn load_cascade(filename: &str) -> io::Result<CascadeConfig> {
+        let s = fs::read_to_string(filename)?;
+        let (entries, strict) = match serde_json::from_str(&s)? {
+            CascadeFile::Chain(entries) => (entries, true),
+            CascadeFile::Cascade { cascade, strict } => (cascade, strict),
+        };
+        let dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+        let configs = entries
+            .into_iter()
+            .map(|entry| match entry {
+                CascadeEntry::Inline(cfg) => Ok(*cfg),
+                CascadeEntry::Path(path) => {
+                    EnigmaSudnogoDnya::load_config(&dir.join(path).to_string_lossy())
+                }
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(CascadeConfig { configs, strict })
+    }
+}
+
+/// Позиций у цифрового ротора
+const DIGITS: usize = 10;
+
+/// Цифра через цифровой ротор: `k - d mod 10`, где `k` — сдвиг плюс
+/// позиция. Как и основная машина, это отражение, обратное само себе.
+/// Ротор шагает на каждой цифре; основные роторы цифры не двигают.
+#[inline]
+fn encrypt_digit(rotor: &mut Rotor, ch: char) -> Option<char> {
+    let d = ch.to_digit(DIGITS as u32)? as usize;
+    let k = rotor.encode_index(0, false);
+    rotor.rotate();
+    char::from_digit(((k + DIGITS - d) % DIGITS) as u32, DIGITS as u32)
+}
+
+/// Период короче этого — практическая слабость: поток сдвигов повторяется
+/// уже внутри обычного письма
+pub const SHORT_PERIOD: u128 = 10_000;
+
+/// Период для вывода; `u128::MAX` означает «не меньше 2^128»
+pub fn format_period(period: u128) -> String {
+    if period == u128::MAX {
+        "≥ 2^128".to_string()
+    } else {
+        period.to_string()
+    }
+}
+
+/// Блок — одометр с периодом `len^роторов` шагов; шагая раз в `every`
+/// тактов, он повторяется через `every · len^роторов` тактов. Блоки шагают
+/// независимо, так что период машины — НОК по блокам, умноженный на
+/// `step_interval` символов в такте. `u128::MAX` — «не меньше 2^128».
+pub(crate) fn rotor_period(
+    len: usize,
+    blocks: impl Iterator<Item = (usize, u64)>,
+    step_interval: usize,
+) -> u128 {
+    let len = len as u128;
+    let mut period: u128 = 1;
+    for (rotors, every) in blocks {
+        let block = u32::try_from(rotors)
+            .ok()
+            .and_then(|r| len.checked_pow(r))
+            .and_then(|p| p.checked_mul(every as u128));
+        let Some(block) = block else {
+            return u128::MAX;
+        };
+        let Some(lcm) = (period / gcd(period, block)).checked_mul(block) else {
+            return u128::MAX;
+        };
+        period = lcm;
+    }
+    period.saturating_mul(step_interval as u128)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
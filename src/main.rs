@@ -1,610 +1,1690 @@
-use rand::Rng;
-use rand::rng;
-use rand::seq::SliceRandom;
-use serde::{Deserialize, Serialize};
+mod cli;
+mod serve;
+
+use cli::{Options, OutputFormat, take_alphabet, take_flag, take_lang, take_options, take_value};
+use cli::{is_quiet, iv, progress_bar, read_line, set_iv, set_quiet, show_progress};
+use enigma_sd::console::{self, Console, Menu};
+use enigma_sd::dir::{self, DirOptions};
+use enigma_sd::armor::{self, Encoding};
+use enigma_sd::benchmark::{self, BenchmarkOptions};
+use enigma_sd::clipboard::{self, Clipboard};
+use enigma_sd::file_header::{FILE_MAGIC, FileHeader};
+use enigma_sd::passphrase::DerivationParams;
+use enigma_sd::plugboard;
+use enigma_sd::progress::Progress;
+use enigma_sd::resume;
+use enigma_sd::wizard::{self, Params};
+use enigma_sd::{analysis, attack, wrap};
+use enigma_sd::messages::{self, Lang, Msg};
+use enigma_sd::{
+    BENCH_SEED, bench_corpus, config_bitness, format_period,
+    CharStep, CharTrace, CipherError, ConfigData, EnigmaSudnogoDnya, MacKey,
+    MachineDescription, MultiEnigma, SHORT_PERIOD, vectors,
+};
+use serde::Serialize;
 use std::fs;
-use std::io::{self, Write};
-use std::time::Instant;
-
-/// Конфиг для (де)сериализации через JSON
-#[derive(Serialize, Deserialize, Debug)]
-struct ConfigData {
-    alphabet: String,                 // "latin" или "cyrillic"
-    plugboard: Vec<(char, char)>,     // пары замен
-    blocks: Vec<String>,              // строки цветовых меток, напр. "КБЧ"
-    rotor_positions: Vec<Vec<usize>>, // для каждого блока — вектор стартовых позиций роторов
-}
-
-/// Таблица: символ → индекс в алфавите
-struct AlphabetIndex {
-    min: u32,
-    indices: Vec<Option<usize>>,
-}
-
-impl AlphabetIndex {
-    fn new(alphabet: &[char]) -> Self {
-        let codes: Vec<u32> = alphabet.iter().map(|&c| c as u32).collect();
-        let &min = codes.iter().min().unwrap();
-        let &max = codes.iter().max().unwrap();
-        let size = (max - min + 1) as usize;
-        let mut indices = vec![None; size];
-        for (i, &c) in alphabet.iter().enumerate() {
-            indices[(c as u32 - min) as usize] = Some(i);
-        }
-        AlphabetIndex { min, indices }
-    }
-
-    #[inline]
-    fn get(&self, c: char) -> Option<usize> {
-        let code = c as u32;
-        if code < self.min || code > self.min + (self.indices.len() - 1) as u32 {
-            None
-        } else {
-            self.indices[(code - self.min) as usize]
+use std::ops::ControlFlow;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
+
+/// С какого числа блоков показывать прогресс построения машины
+const PROGRESS_THRESHOLD_BLOCKS: usize = 100_000;
+
+/// На тексте какого размера `benchmark --freq` считает частоты
+const BENCH_FREQ_SIZE: usize = 10_000;
+
+/// Контрольная точка `--resume` по умолчанию, МиБ
+const DEFAULT_CHECKPOINT_MB: u64 = 64;
+
+/// Заголовок `benchmark --csv`; не переводится — его читают программы
+const BENCH_CSV_HEADER: &str = "size,construct,encrypt,decrypt,kat,bitness";
+
+/// Выше этого объёма памяти спрашиваем подтверждение перед построением машины
+const MEMORY_WARN_BYTES: usize = 1 << 30;
+
+fn exit_config_error(e: CipherError) -> ! {
+    eprintln!("{}", Msg::ConfigError.fill(&[&e]));
+    std::process::exit(1);
+}
+
+/// Построение машины; для огромных конфигов печатает проценты, а если у
+/// отражателя есть неподвижные точки — предупреждение. С `--iv` машина
+/// сразу сдвинута на вектор инициализации.
+fn build_machine(cfg: &ConfigData) -> EnigmaSudnogoDnya {
+    let mut machine = if cfg.block_count() <= PROGRESS_THRESHOLD_BLOCKS || !show_progress() {
+        EnigmaSudnogoDnya::new(cfg).unwrap_or_else(|e| exit_config_error(e))
+    } else {
+        let mut last_percent = usize::MAX;
+        let machine = EnigmaSudnogoDnya::new_with_progress(cfg, |done, total| {
+            let percent = done * 100 / total;
+            if percent != last_percent {
+                last_percent = percent;
+                // в stderr, чтобы не смешивать с результатом в режиме фильтра
+                eprint!("\r{}", Msg::BuildProgress.fill(&[&percent]));
+            }
+        })
+        .unwrap_or_else(|e| exit_config_error(e));
+        eprintln!();
+        machine
+    };
+    if let Some(steps) = iv() {
+        machine.set_offset(steps);
+    }
+    if machine.reflector_has_fixed_point() && !is_quiet() {
+        let len = cfg.alphabet_chars().len();
+        eprintln!("{}", Msg::ReflectorFixedPoint.fill(&[&cfg.reflector_kind, &len]));
+    }
+    machine
+}
+
+/// Результат для `--output-format json`: по `final_state` можно продолжить
+/// с того же места через `load_state`
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    ciphertext: &'a str,
+    final_state: Vec<Vec<usize>>,
+    bitness: f64,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    trace: &'a [CharTrace],
+}
+
+/// Период и предупреждение, если он короче `SHORT_PERIOD`
+fn print_period(out: &mut dyn Write, period: u128) -> io::Result<()> {
+    writeln!(out, "{}", Msg::Period.fill(&[&format_period(period)]))?;
+    if period < SHORT_PERIOD {
+        writeln!(out, "{}", Msg::ShortPeriod.fill(&[&SHORT_PERIOD]))?;
+    }
+    Ok(())
+}
+
+/// Сколько блоков показывать в `state`
+const STATE_MAX_BLOCKS: usize = 50;
+
+/// Команда `state`: блок за блоком `цвет+сдвиг@позиция`
+fn print_state(out: &mut dyn Write, desc: &MachineDescription) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        Msg::StateHeader.fill(&[&desc.alphabet_len, &desc.plugboard.len(), &desc.blocks.len()])
+    )?;
+    for (i, blk) in desc.blocks.iter().take(STATE_MAX_BLOCKS).enumerate() {
+        let rotors: Vec<String> = blk
+            .iter()
+            .map(|r| {
+                let sign = if r.reversed { '-' } else { '+' };
+                format!("{}{}{}@{}", r.color.unwrap_or('?'), sign, r.shift, r.position)
+            })
+            .collect();
+        writeln!(out, "{:>5}: {}", i, rotors.join(" "))?;
+    }
+    if desc.blocks.len() > STATE_MAX_BLOCKS {
+        writeln!(out, "{}", Msg::MoreBlocks.fill(&[&(desc.blocks.len() - STATE_MAX_BLOCKS)]))?;
+    }
+    Ok(())
+}
+
+fn print_result(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    machine: &EnigmaSudnogoDnya,
+    bitness: f64,
+    result: &str,
+    trace: &[CharTrace],
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            writeln!(out, "{}", Msg::Result.fill(&[&result]))?;
+            print_trace(trace, out);
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let json = JsonOutput {
+                ciphertext: result,
+                final_state: machine.save_state(),
+                bitness,
+                trace,
+            };
+            writeln!(out, "{}", serde_json::to_string(&json).unwrap())
         }
     }
 }
 
-/// Ротор (работает с индексами)
-struct Rotor {
-    shift: usize,
-    position: usize,
-    size: usize,
+/// `--trace` пишет трассу не больше чем для стольких символов
+const TRACE_MAX_CHARS: usize = 200;
+
+/// Шифрует (или расшифровывает, `decrypt`) с трассой первых
+/// `TRACE_MAX_CHARS` символов, остальное — как обычно
+fn traced_prefix(
+    machine: &mut EnigmaSudnogoDnya,
+    msg: &str,
+    decrypt: bool,
+) -> Result<(String, Vec<CharTrace>), CipherError> {
+    let split = msg
+        .char_indices()
+        .nth(TRACE_MAX_CHARS)
+        .map_or(msg.len(), |(i, _)| i);
+    let (head, tail) = msg.split_at(split);
+    let (mut out, trace) = if decrypt {
+        machine.decrypt_traced(head)
+    } else {
+        machine.encrypt_traced(head)
+    };
+    if !tail.is_empty() {
+        eprintln!("{}", Msg::TraceTruncated.fill(&[&TRACE_MAX_CHARS]));
+        out.push_str(&if decrypt { machine.decrypt(tail)? } else { machine.encrypt(tail) });
+    }
+    Ok((out, trace))
+}
+
+/// Трасса построчно: символ, позиции и индексы на каждом этапе
+fn print_trace(trace: &[CharTrace], out: &mut dyn Write) {
+    for (i, t) in trace.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{:>4} {:?}→{:?} pb={} fwd={:?} refl={} bwd={:?} pb={} pos={:?}",
+            i,
+            t.input,
+            t.output,
+            t.after_plugboard,
+            t.forward,
+            t.after_reflector,
+            t.backward,
+            t.after_plugboard_out,
+            t.positions
+        );
+    }
 }
 
-impl Rotor {
-    fn new(shift: usize, alphabet_len: usize) -> Self {
-        Rotor {
-            shift,
-            position: 0,
-            size: alphabet_len,
+/// `encrypt --trace` в меню: символ, позиции показанных блоков до шага
+/// через ` | ` и результат
+fn print_step(step: &CharStep, out: &mut dyn Write) {
+    let blocks: Vec<String> = step
+        .positions
+        .iter()
+        .map(|blk| blk.iter().map(usize::to_string).collect::<Vec<_>>().join(","))
+        .collect();
+    let (input, output) = (step.input.escape_debug(), step.output.escape_debug());
+    let _ = writeln!(out, "{:>4}  {}  → {}", input.to_string(), blocks.join(" | "), output);
+}
+
+/// Сообщение с `print_step` на каждом символе, по ходу шифрования. Для
+/// `--output-format json` шаги идут в stderr, чтобы не ломать JSON.
+fn run_watched(
+    out: &mut dyn Write,
+    machine: &mut EnigmaSudnogoDnya,
+    msg: &str,
+    decrypt: bool,
+    key: Option<&MacKey>,
+    blocks: usize,
+    format: OutputFormat,
+) -> Result<String, CipherError> {
+    let input = match key {
+        Some(key) if decrypt => key.open(msg)?,
+        _ => msg,
+    };
+    let watch = |step: &CharStep| match format {
+        OutputFormat::Text => print_step(step, out),
+        OutputFormat::Json => print_step(step, &mut io::stderr()),
+    };
+    let result = if decrypt {
+        machine.decrypt_watched(input, blocks, watch)
+    } else {
+        machine.encrypt_watched(input, blocks, watch)
+    };
+    Ok(match key {
+        Some(key) if !decrypt => key.seal(&result),
+        _ => result,
+    })
+}
+
+/// Одно сообщение интерактивного режима: с тегом целостности сначала
+/// проверяется тег (`decrypt`) или он добавляется после шифрования (`encrypt`)
+fn run_message(
+    machine: &mut EnigmaSudnogoDnya,
+    msg: &str,
+    decrypt: bool,
+    key: Option<&MacKey>,
+    trace: bool,
+) -> Result<(String, Vec<CharTrace>), CipherError> {
+    if decrypt && !trace {
+        let out = match key {
+            Some(key) => machine.decrypt_authenticated(msg, key)?,
+            None => machine.decrypt(msg)?,
+        };
+        return Ok((out, Vec::new()));
+    }
+    let input = match key {
+        Some(key) if decrypt => key.open(msg)?,
+        _ => msg,
+    };
+    let (out, trace) = if trace {
+        traced_prefix(machine, input, decrypt)?
+    } else {
+        (machine.encrypt(input), Vec::new())
+    };
+    let out = match key {
+        Some(key) if !decrypt => key.seal(&out),
+        _ => out,
+    };
+    Ok((out, trace))
+}
+
+/// `encrypt-file|decrypt-file <вход> <выход> [--binary] [--force]` — без
+/// меню. Машина обратима, поэтому без тега целостности обе команды
+/// отличаются только омофонами (`homophonic`). Не-UTF-8 файлы (или любые с
+/// `--binary`) шифруются в байтовом режиме тем же ключом. Перед шифротекстом
+/// идёт заголовок `FileHeader`; существующий выход перезаписывается только
+/// с `--force`. С `--checkpoint`/`--resume` — см. `run_resumable`.
+fn run_file_mode(args: &[String], decrypt: bool, opts: &Options) {
+    let mut args = args.to_vec();
+    let checkpoint = take_value(&mut args, "--checkpoint");
+    let resume = take_flag(&mut args, "--resume");
+    let (input, output, binary, force) = file_args(&args);
+    if checkpoint.is_some() || resume {
+        return run_resumable(input, output, decrypt, checkpoint, resume, force, opts);
+    }
+    refuse_overwrite(output, force);
+    let cfg = load_config_or_exit(opts);
+    let data = fs::read(input).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::ReadFailed.fill(&[input, &e]));
+        std::process::exit(1);
+    });
+    let (data, positions) = if decrypt {
+        decrypt_file(&cfg, opts, &data, binary).unwrap_or_else(|e| {
+            eprintln!("{}: {}", input, e);
+            std::process::exit(1);
+        })
+    } else {
+        encrypt_file(&cfg, opts, &data, binary)
+    };
+    if let Err(e) = fs::write(output, data) {
+        eprintln!("{}", Msg::WriteFailed.fill(&[output, &e]));
+        std::process::exit(1);
+    }
+    if opts.advance_config {
+        advance_config(&cfg, positions, &opts.config);
+    }
+}
+
+/// `--checkpoint N` (МиБ) и `--resume`: файл побайтно с контрольными
+/// точками, см. `enigma_sd::resume`. Тег целостности считается по всему
+/// файлу сразу, поэтому с ним контрольных точек нет; брони тоже нет —
+/// файл пишется побайтно.
+fn run_resumable(
+    input: &str,
+    output: &str,
+    decrypt: bool,
+    checkpoint: Option<String>,
+    resume: bool,
+    force: bool,
+    opts: &Options,
+) {
+    let every = match checkpoint.map(|mb| mb.parse::<u64>()) {
+        None => DEFAULT_CHECKPOINT_MB,
+        Some(Ok(mb)) if mb > 0 => mb,
+        Some(_) => {
+            eprintln!("{}", Msg::FileUsage.text());
+            std::process::exit(2);
         }
+    };
+    // продолжение дописывает существующий выход
+    if !resume {
+        refuse_overwrite(output, force);
+    }
+    let cfg = load_config_or_exit(opts);
+    if opts.mac_key(&cfg).is_some() {
+        eprintln!("{}", Msg::CheckpointNeedsNoTag.text());
+        std::process::exit(2);
+    }
+    if opts.encode != Encoding::None || opts.decode.is_some_and(|e| e != Encoding::None) {
+        eprintln!("{}", Msg::CheckpointNoArmor.text());
+        std::process::exit(2);
+    }
+    if opts.iv.is_some() {
+        eprintln!("{}", Msg::IvUnsupported.text());
+        std::process::exit(2);
     }
+    let total = fs::metadata(input).map(|m| m.len()).ok();
+    let label = if decrypt { Msg::ProgressDecrypting } else { Msg::ProgressEncrypting };
+    let bar = progress_bar(label, total, true);
+    let result = resume::process_file(
+        &cfg,
+        Path::new(input),
+        Path::new(output),
+        decrypt,
+        every << 20,
+        resume,
+        |checkpoint| {
+            bar.set_position(checkpoint.offset);
+            ControlFlow::Continue(())
+        },
+    );
+    bar.finish_and_clear();
+    let positions = result.unwrap_or_else(|e| {
+        eprintln!("{}: {}", input, e);
+        std::process::exit(1);
+    });
+    if let (true, Some(positions)) = (opts.advance_config, positions) {
+        advance_config(&cfg, positions, &opts.config);
+    }
+}
 
-    #[inline]
-    fn encode_index(&self, idx: usize, reverse: bool) -> usize {
-        if reverse {
-            (idx + self.size - ((self.shift + self.position) % self.size)) % self.size
-        } else {
-            (idx + self.shift + self.position) % self.size
+/// Заголовок и шифротекст; `--wrap` касается только текста
+fn encrypt_file(cfg: &ConfigData, opts: &Options, data: &[u8], binary: bool) -> Processed {
+    let binary = binary || std::str::from_utf8(data).is_err();
+    let key = opts.mac_key(cfg);
+    let (body, positions) = match &key {
+        None => encrypt_data(cfg, data, binary),
+        Some(key) => seal_data(cfg, key, data, binary),
+    };
+    // ширина в заголовке — u32; шире всё равно ничего не переносится
+    let cols = opts.wrap.filter(|_| !binary).map(|cols| cols.min(u32::MAX as usize));
+    let header = FileHeader::for_config(cfg, binary, key.is_some(), cols.map(|c| c as u32));
+    let mut out = header.to_bytes();
+    out.extend(apply_wrap(body, cols, wrap::wrap));
+    // броня — на весь файл с заголовком: `decrypt_file` узнаёт её по `ESD1`
+    (apply_armor(out, opts.encode), positions)
+}
+
+/// Обратное к `encrypt_file`: режим, тег и переносы берутся из заголовка,
+/// броня — из `--decode` или по заголовку под ней. Файл без заголовка (из
+/// сборок до `ESD1`) читается по флагам, как раньше.
+fn decrypt_file(
+    cfg: &ConfigData,
+    opts: &Options,
+    data: &[u8],
+    binary: bool,
+) -> Result<Processed, CipherError> {
+    let detected = || std::str::from_utf8(data).ok().and_then(|t| armor::detect(t, FILE_MAGIC));
+    let encoding = opts.decode.or_else(detected);
+    let data = &remove_armor(data.to_vec(), encoding)?;
+    let Some((header, body)) = FileHeader::parse(data)? else {
+        let cols = opts.wrap.filter(|_| !binary && std::str::from_utf8(data).is_ok());
+        let data = apply_wrap(data.to_vec(), cols, wrap::unwrap);
+        return match opts.mac_key(cfg) {
+            None => Ok(decrypt_data(cfg, &data, binary)),
+            Some(key) => decrypt_sealed(cfg, &key, &data, binary),
+        };
+    };
+    header.check(cfg)?;
+    // тег требуют флаг или конфиг, а в файле его нет — не расшифровываем молча
+    if !header.authenticated && opts.mac_key(cfg).is_some() {
+        return Err(CipherError::IntegrityFailure);
+    }
+    let cols = header.wrap.map(|cols| cols as usize);
+    let body = apply_wrap(body.to_vec(), cols, wrap::unwrap);
+    if header.authenticated {
+        decrypt_sealed(cfg, &cfg.mac_key(&opts.passphrase), &body, header.binary)
+    } else {
+        Ok(decrypt_data(cfg, &body, header.binary))
+    }
+}
+
+/// `encrypt-dir|decrypt-dir <каталог> --out <каталог> [--encrypt-names]
+/// [--jobs N] [--force]`: весь каталог по файлам, см. `enigma_sd::dir`.
+/// Пропущенные ссылки и не обычные файлы — предупреждением в stderr.
+fn run_dir_mode(args: &[String], decrypt: bool, opts: &Options) {
+    if opts.iv.is_some() {
+        eprintln!("{}", Msg::IvUnsupported.text());
+        std::process::exit(2);
+    }
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::DirUsage.text());
+        std::process::exit(2);
+    };
+    let encrypt_names = take_flag(&mut args, "--encrypt-names");
+    let force = take_flag(&mut args, "--force");
+    let jobs = match take_value(&mut args, "--jobs") {
+        None => 1,
+        Some(n) => n.parse().ok().filter(|&n| n > 0).unwrap_or_else(|| usage()),
+    };
+    let out = take_value(&mut args, "--out").unwrap_or_else(|| usage());
+    // имена расшифровываются по манифесту, флаг там не нужен
+    if decrypt && encrypt_names {
+        usage();
+    }
+    let [src] = &args[..] else { usage() };
+    refuse_overwrite(&out, force);
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    let (src, out) = (Path::new(src), Path::new(&out));
+    let report = if decrypt {
+        dir::decrypt_dir(&cfg, src, out, jobs)
+    } else {
+        dir::encrypt_dir(&cfg, src, out, &DirOptions { encrypt_names, jobs })
+    };
+    let report = report.unwrap_or_else(|e| {
+        eprintln!("{}: {}", src.display(), e);
+        std::process::exit(1);
+    });
+    if !is_quiet() {
+        for path in &report.skipped {
+            eprintln!("{}", Msg::DirSkipped.fill(&[&path.display()]));
         }
     }
+    println!("{}", Msg::DirDone.fill(&[&report.files, &out.display()]));
+}
+
+/// `<вход> <выход> [--binary] [--force]` для `encrypt-file` и `decrypt-file`
+fn file_args(args: &[String]) -> (&String, &String, bool, bool) {
+    let binary = args.iter().any(|a| a == "--binary");
+    let force = args.iter().any(|a| a == "--force");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [input, output] = paths[..] else {
+        eprintln!("{}", Msg::FileUsage.text());
+        std::process::exit(2);
+    };
+    (input, output, binary, force)
+}
 
-    #[inline]
-    fn rotate(&mut self) -> bool {
-        self.position = (self.position + 1) % self.size;
-        self.position == 0
+/// Без `--force` существующий файл не перезаписывается
+fn refuse_overwrite(output: &str, force: bool) {
+    if !force && Path::new(output).exists() {
+        eprintln!("{}", Msg::OutputExists.fill(&[&output]));
+        std::process::exit(1);
     }
+}
+
+/// Несколько `--config`: `encrypt-file` и `decrypt-file` через каскад машин
+/// (`MultiEnigma`) в порядке флагов. Тег целостности, трасса, JSON и
+/// `--advance-config` рассчитаны на одну машину и здесь не работают.
+fn run_cascade(args: &[String], opts: &Options) {
+    let cfgs: Vec<ConfigData> =
+        opts.cascade.iter().map(|path| load_config_from(path, opts)).collect();
+    let single_machine_only = opts.format == OutputFormat::Json
+        || opts.trace
+        || opts.advance_config
+        || opts.authenticate
+        || cfgs.iter().any(|cfg| cfg.authenticate);
+    let decrypt = match args.first().map(String::as_str) {
+        Some("encrypt-file") if !single_machine_only => false,
+        Some("decrypt-file") if !single_machine_only => true,
+        _ => {
+            eprintln!("{}", Msg::CascadeFilesOnly.text());
+            std::process::exit(2);
+        }
+    };
+    let (input, output, binary, force) = file_args(&args[1..]);
+    refuse_overwrite(output, force);
+    let data = fs::read(input).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::ReadFailed.fill(&[input, &e]));
+        std::process::exit(1);
+    });
+    let data = remove_armor_or_exit(data, opts.decode.filter(|_| decrypt));
+    let text = !binary && std::str::from_utf8(&data).is_ok();
+    let cols = opts.wrap.filter(|_| text);
+    let data = if decrypt { apply_wrap(data, cols, wrap::unwrap) } else { data };
 
-    #[inline]
-    fn save_position(&self) -> usize {
-        self.position
+    let machines = cfgs
+        .iter()
+        .map(|cfg| build_machine(&if text { cfg.clone() } else { cfg.to_byte_mode() }))
+        .collect();
+    let mut cascade =
+        MultiEnigma::from_machines(machines, opts.strict).unwrap_or_else(|e| exit_config_error(e));
+    let data = match std::str::from_utf8(&data) {
+        Ok(msg) if text && decrypt => {
+            cascade.decrypt(msg).unwrap_or_else(|e| exit_config_error(e)).into_bytes()
+        }
+        Ok(msg) if text => apply_wrap(cascade.encrypt(msg).into_bytes(), cols, wrap::wrap),
+        _ if decrypt => cascade.decrypt_bytes(&data),
+        _ => cascade.encrypt_bytes(&data),
+    };
+    let data = if decrypt { data } else { apply_armor(data, opts.encode) };
+    if let Err(e) = fs::write(output, data) {
+        eprintln!("{}", Msg::WriteFailed.fill(&[output, &e]));
+        std::process::exit(1);
     }
+}
 
-    #[inline]
-    fn load_position(&mut self, pos: usize) {
-        self.position = pos % self.size;
+/// `--wrap` для текста: `wrap::wrap` или `wrap::unwrap` с шириной `cols`;
+/// без неё или для не-UTF-8 данные остаются как есть
+fn apply_wrap(data: Vec<u8>, cols: Option<usize>, f: fn(&str, usize) -> String) -> Vec<u8> {
+    match (cols, std::str::from_utf8(&data)) {
+        (Some(cols), Ok(text)) => f(text, cols).into_bytes(),
+        _ => data,
     }
 }
 
-/// Блок роторов
-struct Block {
-    rotors: Vec<Rotor>,
+/// `--encode`: вывод в броне; без флага — как есть
+fn apply_armor(data: Vec<u8>, encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::None => data,
+        encoding => armor::encode_bytes(&data, encoding).into_bytes(),
+    }
 }
 
-impl Block {
-    fn new(colors: &str, alphabet_len: usize) -> Self {
-        let rotors = colors
-            .chars()
-            .map(|col| {
-                let shift = match col {
-                    'К' => 1, 'Б' => 2, 'Ч' => 3, 'З' => 5, 'Р' => 4,
-                    'О' => 6, 'Ф' => 7, 'С' => 8, 'Г' => 9, 'Л' => 10,
-                    _ => panic!("Неизвестный цвет"),
-                };
-                Rotor::new(shift, alphabet_len)
-            })
-            .collect();
-        Block { rotors }
+/// Снимает броню `encoding` со входа; без неё данные остаются как есть
+fn remove_armor(data: Vec<u8>, encoding: Option<Encoding>) -> Result<Vec<u8>, CipherError> {
+    match encoding {
+        None | Some(Encoding::None) => Ok(data),
+        Some(encoding) => {
+            let bad = CipherError::BadArmor(encoding.name());
+            let text = std::str::from_utf8(&data).map_err(|_| bad)?;
+            armor::decode_bytes(text, encoding)
+        }
     }
+}
 
-    #[inline]
-    fn process_index(&self, mut idx: usize, reverse: bool) -> usize {
-        if !reverse {
-            for r in &self.rotors {
-                idx = r.encode_index(idx, false);
-            }
+/// `remove_armor` для stdin и файлов каскада: битая броня — выход с ошибкой
+fn remove_armor_or_exit(data: Vec<u8>, encoding: Option<Encoding>) -> Vec<u8> {
+    remove_armor(data, encoding).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Неинтерактивный фильтр: stdin не терминал — читаем его целиком,
+/// шифруем сохранённым конфигом и пишем в stdout. Фильтр симметричен, так
+/// что с `--wrap` переносы снимаются со входа и расставляются в выводе;
+/// JSON не переносится. Броню `--decode` фильтр снимает со входа, а
+/// `--encode` надевает на вывод. Без отражателя (`use_reflector`) фильтр только
+/// шифрует — расшифровка через `decrypt-file`.
+fn run_filter_mode(opts: &Options) {
+    let cfg = load_config_or_exit(opts);
+    if opts.mac_key(&cfg).is_some() {
+        eprintln!("{}", Msg::FilterNeedsFiles.text());
+        std::process::exit(2);
+    }
+    let mut data = Vec::new();
+    if let Err(e) = io::stdin().lock().read_to_end(&mut data) {
+        eprintln!("{}", Msg::ReadFailed.fill(&[&"stdin", &e]));
+        std::process::exit(1);
+    }
+    let data = remove_armor_or_exit(data, opts.decode);
+    let data = apply_wrap(data, opts.wrap, wrap::unwrap);
+    if opts.format == OutputFormat::Json || opts.trace {
+        let Ok(text) = std::str::from_utf8(&data) else {
+            eprintln!("{}", Msg::JsonNeedsUtf8.text());
+            std::process::exit(1);
+        };
+        let mut machine = build_machine(&cfg);
+        let (cipher, trace) = run_message(&mut machine, text, false, None, opts.trace)
+            .expect("без тега целостности ошибок нет");
+        if opts.format == OutputFormat::Json {
+            let cipher = armor::encode(&cipher, opts.encode);
+            let bitness = config_bitness(&cfg);
+            let mut out = io::stdout();
+            print_result(&mut out, opts.format, &machine, bitness, &cipher, &trace).unwrap();
         } else {
-            for r in self.rotors.iter().rev() {
-                idx = r.encode_index(idx, true);
-            }
+            // stdout — только результат, трасса уходит в stderr
+            let cipher = match opts.wrap {
+                Some(cols) => wrap::wrap(&cipher, cols),
+                None => cipher,
+            };
+            print!("{}", armor::encode(&cipher, opts.encode));
+            io::stdout().flush().unwrap();
+            print_trace(&trace, &mut io::stderr());
+        }
+        if opts.advance_config {
+            advance_config(&cfg, machine.export_positions(), &opts.config);
+        }
+        return;
+    }
+    let (result, positions) = encrypt_data(&cfg, &data, false);
+    let result = apply_armor(apply_wrap(result, opts.wrap, wrap::wrap), opts.encode);
+    let mut out = io::stdout().lock();
+    if let Err(e) = out.write_all(&result).and_then(|_| out.flush()) {
+        eprintln!("{}", Msg::WriteResultFailed.fill(&[&e]));
+        std::process::exit(1);
+    }
+    if opts.advance_config {
+        advance_config(&cfg, positions, &opts.config);
+    }
+}
+
+/// `--input a.txt b.txt ...`: файлы по порядку через одну машину без
+/// `reset` между ними, как один поток — шифротекст в stdout тот же, что у
+/// склеенного входа. Если хоть один файл не UTF-8, все идут побайтно.
+fn run_input_files(opts: &Options) {
+    let cfg = load_config_or_exit(opts);
+    if opts.mac_key(&cfg).is_some() {
+        eprintln!("{}", Msg::FilterNeedsFiles.text());
+        std::process::exit(2);
+    }
+    if opts.format == OutputFormat::Json || opts.trace {
+        eprintln!("{}", Msg::InputPlainOnly.text());
+        std::process::exit(2);
+    }
+    let files: Vec<Vec<u8>> = opts
+        .inputs
+        .iter()
+        .map(|path| {
+            fs::read(path).unwrap_or_else(|e| {
+                eprintln!("{}", Msg::ReadFailed.fill(&[path, &e]));
+                std::process::exit(1);
+            })
+        })
+        .collect();
+    let text = files.iter().all(|data| std::str::from_utf8(data).is_ok());
+    let mut machine = build_machine(&if text { cfg.clone() } else { cfg.to_byte_mode() });
+    let mut result = Vec::new();
+    for data in &files {
+        match std::str::from_utf8(data) {
+            Ok(chunk) if text => result.extend(machine.encrypt(chunk).into_bytes()),
+            _ => result.extend(machine.encrypt_bytes(data)),
         }
-        idx
     }
+    // переносы считаются по всему потоку, а не по каждому файлу
+    let result = apply_wrap(result, opts.wrap.filter(|_| text), wrap::wrap);
+    let result = apply_armor(result, opts.encode);
+    let mut out = io::stdout().lock();
+    if let Err(e) = out.write_all(&result).and_then(|_| out.flush()) {
+        eprintln!("{}", Msg::WriteResultFailed.fill(&[&e]));
+        std::process::exit(1);
+    }
+    if opts.advance_config {
+        advance_config(&cfg, machine.export_positions(), &opts.config);
+    }
+}
 
-    fn rotate(&mut self) {
-        let mut carry = true;
-        for r in &mut self.rotors {
-            if carry {
-                carry = r.rotate();
-            } else {
-                break;
-            }
+/// `--advance-config`: записывает в конфиг позиции роторов после сообщения,
+/// чтобы следующий запуск продолжил поток с того же места
+fn advance_config(cfg: &ConfigData, positions: Vec<Vec<usize>>, path: &str) -> ConfigData {
+    let advanced = ConfigData {
+        rotor_positions: positions,
+        ..cfg.clone()
+    };
+    if let Err(e) = EnigmaSudnogoDnya::save_config(&advanced, path, true) {
+        eprintln!("{}", Msg::WriteFailed.fill(&[&path, &e]));
+        std::process::exit(1);
+    }
+    advanced
+}
+
+fn load_config_or_exit(opts: &Options) -> ConfigData {
+    load_config_from(&opts.config, opts)
+}
+
+fn load_config_from(path: &str, opts: &Options) -> ConfigData {
+    let mut cfg = EnigmaSudnogoDnya::load_config(path).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::LoadFailed.fill(&[&path, &e]));
+        std::process::exit(1);
+    });
+    if let (None, Some(lang)) = (opts.lang, cfg.lang) {
+        messages::set_lang(lang);
+    }
+    cfg.allow_degenerate = opts.allow_degenerate;
+    if cfg.allow_degenerate && cfg.block_count() == 0 && !is_quiet() {
+        eprintln!("{}", Msg::DegenerateConfig.fill(&[&path]));
+    }
+    cfg
+}
+
+/// Результат и позиции роторов после него
+type Processed = (Vec<u8>, Vec<Vec<usize>>);
+
+/// UTF-8 шифруется как текст, всё остальное (или `binary`) — побайтно
+fn encrypt_data(cfg: &ConfigData, data: &[u8], binary: bool) -> Processed {
+    match std::str::from_utf8(data) {
+        Ok(text) if !binary => {
+            let mut machine = build_machine(cfg);
+            let out = machine.encrypt(text).into_bytes();
+            (out, machine.export_positions())
         }
+        _ => process_bytes(cfg, data, false),
     }
+}
 
-    fn save_positions(&self) -> Vec<usize> {
-        self.rotors.iter().map(Rotor::save_position).collect()
+/// Побайтно в байтовом режиме конфига — потоком, чтобы на больших файлах
+/// был виден ход
+fn process_bytes(cfg: &ConfigData, data: &[u8], decrypt: bool) -> Processed {
+    let mut machine = build_machine(&cfg.to_byte_mode());
+    let total = data.len() as u64;
+    let label = if decrypt { Msg::ProgressDecrypting } else { Msg::ProgressEncrypting };
+    let bar = progress_bar(label, Some(total), true);
+    let mut out = Vec::with_capacity(data.len());
+    let progress = |p: Progress| bar.set_position(p.done);
+    if decrypt {
+        machine.decrypt_stream_with_progress(data, &mut out, Some(total), progress)
+    } else {
+        machine.encrypt_stream_with_progress(data, &mut out, Some(total), progress)
     }
+    .expect("запись в Vec не падает");
+    bar.finish_and_clear();
+    (out, machine.export_positions())
+}
 
-    fn load_positions(&mut self, pos: &[usize]) {
-        for (r, &p) in self.rotors.iter_mut().zip(pos.iter()) {
-            r.load_position(p);
+/// Обратное к `encrypt_data`: текст — через `decrypt`, чтобы омофоны
+/// вернулись в буквы
+fn decrypt_data(cfg: &ConfigData, data: &[u8], binary: bool) -> Processed {
+    match std::str::from_utf8(data) {
+        Ok(text) if !binary => {
+            let mut machine = build_machine(cfg);
+            let out = machine.decrypt(text).unwrap_or_else(|e| exit_config_error(e));
+            (out.into_bytes(), machine.export_positions())
         }
+        _ => process_bytes(cfg, data, true),
     }
 }
 
-/// Рефлектор (работает с индексами)
-struct Reflector {
-    map_idx: Vec<usize>,
+/// Как `encrypt_data`, но с тегом целостности: текст — `шифротекст#тег`,
+/// байты — сырой тег в конце
+fn seal_data(cfg: &ConfigData, key: &MacKey, data: &[u8], binary: bool) -> Processed {
+    match std::str::from_utf8(data) {
+        Ok(text) if !binary => {
+            let mut machine = build_machine(cfg);
+            let out = machine.encrypt_authenticated(text, key).into_bytes();
+            (out, machine.export_positions())
+        }
+        _ => {
+            let (cipher, positions) = encrypt_data(cfg, data, true);
+            (key.seal_bytes(&cipher), positions)
+        }
+    }
 }
 
-impl Reflector {
-    fn new(alphabet: &[char]) -> Self {
-        let len = alphabet.len();
-        let mut map_idx = vec![0; len];
-        for (i, slot) in map_idx.iter_mut().enumerate() {
-            *slot = len - 1 - i;
+/// Обратное к `seal_data`: сначала проверка тега, потом расшифровка
+fn decrypt_sealed(
+    cfg: &ConfigData,
+    key: &MacKey,
+    data: &[u8],
+    binary: bool,
+) -> Result<Processed, CipherError> {
+    match std::str::from_utf8(data) {
+        Ok(text) if !binary => {
+            let mut machine = build_machine(cfg);
+            let out = machine.decrypt_authenticated(text, key)?.into_bytes();
+            Ok((out, machine.export_positions()))
         }
-        Reflector { map_idx }
+        _ => Ok(process_bytes(cfg, key.open_bytes(data)?, true)),
     }
+}
 
-    #[inline]
-    fn reflect_index(&self, idx: usize) -> usize {
-        self.map_idx[idx]
+/// `analyze <файл> [--json] [--alphabet <имя>]`: частоты букв,
+/// хи-квадрат, индекс совпадений и биграммы. Алфавит по умолчанию — из конфига.
+fn run_analyze(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let json = take_flag(&mut args, "--json") || matches!(opts.format, OutputFormat::Json);
+    let alphabet = take_alphabet(&mut args)
+        .or_else(|| EnigmaSudnogoDnya::load_config(&opts.config).ok().map(|c| c.alphabet))
+        .unwrap_or_else(|| ConfigData::default().alphabet);
+    let [input] = &args[..] else {
+        eprintln!("{}", Msg::AnalyzeUsage.text());
+        std::process::exit(2);
+    };
+    let text = fs::read_to_string(input).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::ReadFailed.fill(&[input, &e]));
+        std::process::exit(1);
+    });
+    let chars = ConfigData { alphabet, ..Default::default() }.alphabet_chars();
+    let report = analysis::frequency_profile(&text, &chars);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
     }
+
+    println!("{}", Msg::AnalyzeTotal.fill(&[&report.total]));
+    let widths = [6, 10, 10];
+    let header: Vec<String> = Msg::AnalyzeHeader
+        .text()
+        .split('|')
+        .zip(widths)
+        .map(|(col, w)| format!("{:>w$}", col))
+        .collect();
+    println!("{}", header.join(" "));
+    for &(c, count) in &report.counts {
+        let share = 100.0 * count as f64 / report.total.max(1) as f64;
+        let letter = analysis::readable(c);
+        println!("{:>6} {:>10} {:>10.2}", letter, count, share);
+    }
+    println!("{}", Msg::AnalyzeChiSquare.fill(&[&format!("{:.2}", report.chi_square)]));
+    let uniform = format!("{:.4}", 1.0 / chars.len() as f64);
+    let ioc = format!("{:.4}", report.index_of_coincidence);
+    println!("{}", Msg::AnalyzeIoc.fill(&[&ioc, &uniform]));
+    let bigrams: Vec<String> = report
+        .top_bigrams
+        .iter()
+        .map(|(pair, count)| {
+            let pair: String = pair.chars().map(analysis::readable).collect();
+            format!("{} ({})", pair, count)
+        })
+        .collect();
+    println!("{}", Msg::AnalyzeBigrams.fill(&[&bigrams.join(", ")]));
 }
 
-/// Машина ЭСД
-struct EnigmaSudnogoDnya {
-    alphabet: Vec<char>,
-    index_map: AlphabetIndex,
-    plugboard_map: Vec<usize>,
-    blocks: Vec<Block>,
-    reflector: Reflector,
+/// `benchmark --freq`: частоты букв открытого текста и шифра рядом и
+/// хи-квадрат обоих против равномерного. Состояние машины не меняется.
+fn print_bench_frequencies(
+    out: &mut dyn Write,
+    machine: &mut EnigmaSudnogoDnya,
+    text: &str,
+) -> io::Result<()> {
+    let state = machine.save_state();
+    let cipher = machine.encrypt(text);
+    machine.load_state(&state);
+    let alphabet = machine.alphabet().to_vec();
+    let plain = analysis::frequency_profile(text, &alphabet);
+    let enciphered = analysis::frequency_profile(&cipher, &alphabet);
+
+    writeln!(out, "{}", Msg::BenchFreqTitle.fill(&[&plain.total]))?;
+    let widths = [6, 10, 10];
+    let header: Vec<String> = Msg::BenchFreqHeader
+        .text()
+        .split('|')
+        .zip(widths)
+        .map(|(col, w)| format!("{:>w$}", col))
+        .collect();
+    writeln!(out, "{}", header.join(" "))?;
+    for (&(c, p), &(_, e)) in plain.counts.iter().zip(&enciphered.counts) {
+        writeln!(out, "{:>6} {:>10} {:>10}", c.escape_debug().to_string(), p, e)?;
+    }
+    let chi = |r: &analysis::FreqReport| format!("{:.2}", r.chi_square);
+    // степеней свободы на одну меньше, чем букв
+    let df = alphabet.len() - 1;
+    writeln!(out, "{}", Msg::BenchFreqChiSquare.fill(&[&chi(&plain), &chi(&enciphered), &df]))?;
+    Ok(())
 }
 
-impl EnigmaSudnogoDnya {
-    fn new(cfg: &ConfigData) -> Self {
-        let alph_str = if cfg.alphabet == "latin" {
-            "abcdefghijklmnopqrstuvwxyz"
-        } else {
-            "абвгдеёжзийклмнопрстуфхцчшщъыьэюя"
+/// Флаги `benchmark`: `--csv` — только таблица замеров, без стойкости и
+/// потоков, для графиков; `--json` — отчёт `benchmark::run` целиком;
+/// `--freq` — частоты открытого текста и шифра на одном размере;
+/// `--sizes 1k,100k,10m` и `--max-seconds N` — размеры и бюджет
+struct BenchFlags {
+    csv: bool,
+    json: bool,
+    freq: bool,
+    sizes: Vec<usize>,
+    max_time: Option<Duration>,
+}
+
+impl BenchFlags {
+    /// `None` — непонятный флаг или значение
+    fn parse(args: &[&str]) -> Option<Self> {
+        let mut flags = BenchFlags {
+            csv: false,
+            json: false,
+            freq: false,
+            sizes: benchmark::DEFAULT_SIZES.to_vec(),
+            max_time: None,
         };
-        let alphabet: Vec<char> = alph_str.chars().collect();
-        let alphabet_len = alphabet.len();
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg, None),
+            };
+            let mut value = || inline.or_else(|| args.next().copied());
+            match name {
+                "--csv" if inline.is_none() => flags.csv = true,
+                "--json" if inline.is_none() => flags.json = true,
+                "--freq" if inline.is_none() => flags.freq = true,
+                "--sizes" => {
+                    let sizes: Option<Vec<usize>> =
+                        value()?.split(',').map(benchmark::parse_size).collect();
+                    flags.sizes = sizes.filter(|s| !s.is_empty())?;
+                }
+                "--max-seconds" => {
+                    let secs = value()?.parse::<f64>().ok().filter(|&s| s > 0.0)?;
+                    flags.max_time = Some(Duration::try_from_secs_f64(secs).ok()?);
+                }
+                _ => return None,
+            }
+        }
+        Some(flags)
+    }
+}
 
-        let index_map = AlphabetIndex::new(&alphabet);
+/// `benchmark [флаги]`: то же, что команда меню, над конфигом из `--config`
+fn run_benchmark_command(args: &[String], opts: &Options) {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let Some(flags) = BenchFlags::parse(&args) else {
+        eprintln!("{}", Msg::BenchUsage.text());
+        std::process::exit(2);
+    };
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    run_benchmark(&mut io::stdout(), &cfg, &flags, opts).unwrap();
+}
 
-        let mut plugboard_map = (0..alphabet_len).collect::<Vec<usize>>();
-        for &(a, b) in cfg.plugboard.iter() {
-            let ia = index_map.get(a).expect("Символ вне алфавита");
-            let ib = index_map.get(b).expect("Символ вне алфавита");
-            plugboard_map[ia] = ib;
-            plugboard_map[ib] = ia;
+/// Замер `benchmark::run_with` и вывод: отчёт JSON, CSV или таблица со
+/// стойкостью, частотами и ускорением по потокам
+fn run_benchmark(
+    out: &mut dyn Write,
+    cfg: &ConfigData,
+    flags: &BenchFlags,
+    opts: &Options,
+) -> io::Result<()> {
+    let start = Instant::now();
+    // одни и те же тексты при каждом запуске, чтобы замеры до и после
+    // изменения были сравнимы
+    let seed = opts.seed.unwrap_or(BENCH_SEED);
+    let options = BenchmarkOptions { seed, max_time: flags.max_time };
+    let report = match benchmark::run_with(cfg, &flags.sizes, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Ok(());
         }
+    };
+    for s in report.sizes.iter().filter(|s| !s.kat_ok) {
+        eprintln!("{}", Msg::KatFailed.fill(&[&s.size]));
+    }
+    if flags.json || opts.format == OutputFormat::Json {
+        return writeln!(out, "{}", serde_json::to_string_pretty(&report).unwrap());
+    }
 
-        let mut blocks: Vec<Block> = cfg
-            .blocks
-            .iter()
-            .map(|s| Block::new(s, alphabet_len))
-            .collect();
+    // construct — только `new`, один раз на весь замер, encrypt/decrypt —
+    // только проход по тексту. Колонка construct в CSV осталась на месте,
+    // чтобы не ломать разбор, и одна на все строки.
+    if flags.csv {
+        writeln!(out, "{}", BENCH_CSV_HEADER)?;
+        for s in &report.sizes {
+            let kat = if s.kat_ok { "pass" } else { "fail" };
+            let (c, e, d) = (report.construct_secs, s.encrypt_secs, s.decrypt_secs);
+            writeln!(out, "{},{:.6},{:.6},{:.6},{},{:.2}", s.size, c, e, d, kat, report.bitness)?;
+        }
+        return Ok(());
+    }
+    print_strength(out, cfg)?;
+    let construct = format!("{:.6}", report.construct_secs);
+    writeln!(out, "{}", Msg::BenchConstruct.fill(&[&construct]))?;
+    let widths = [9, 12, 12, 15, 15, 4];
+    let header: Vec<String> = Msg::BenchHeader
+        .text()
+        .split('|')
+        .zip(widths)
+        .map(|(col, w)| format!("{:>w$}", col))
+        .collect();
+    writeln!(out, "{}", header.join(" "))?;
+    for s in &report.sizes {
+        writeln!(
+            out,
+            "{:>9} {:>12.6} {:>12.6} {:>15.0} {:>15.0} {:>4}",
+            s.size,
+            s.encrypt_secs,
+            s.decrypt_secs,
+            s.encrypt_chars_per_sec,
+            s.decrypt_chars_per_sec,
+            if s.kat_ok { "pass" } else { "FAIL" }
+        )?;
+    }
+    if !report.skipped.is_empty() {
+        let skipped: Vec<String> = report.skipped.iter().map(usize::to_string).collect();
+        writeln!(out, "{}", Msg::BenchSkipped.fill(&[&skipped.join(", ")]))?;
+        return Ok(());
+    }
 
-        if cfg.rotor_positions.len() == blocks.len() {
-            for (i, block) in blocks.iter_mut().enumerate() {
-                let pos_vec = &cfg.rotor_positions[i];
-                block.load_positions(pos_vec);
-            }
-        } else if !cfg.rotor_positions.is_empty() {
-            panic!(
-                "Ошибка: rotor_positions.len() ({}) != blocks.len() ({})",
-                cfg.rotor_positions.len(),
-                blocks.len()
-            );
+    let mut machine = build_machine(cfg);
+    let alphabet = machine.alphabet().to_vec();
+    if flags.freq {
+        let text = bench_corpus(seed, &alphabet, &[BENCH_FREQ_SIZE]).remove(0);
+        print_bench_frequencies(out, &mut machine, &text)?;
+    }
+    if flags.max_time.is_some_and(|budget| start.elapsed() >= budget) {
+        return Ok(());
+    }
+    // Масштабирование параллельного шифрования по числу потоков на самом
+    // длинном из размеров
+    let Some(&longest) = flags.sizes.iter().max() else { return Ok(()) };
+    let text = bench_corpus(seed, &alphabet, &[longest]).remove(0);
+    let max_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let mut base_t = 0.0;
+    let mut threads = 1;
+    while threads <= max_threads {
+        let t = Instant::now();
+        let _ = machine.encrypt_parallel(&text, threads);
+        let par_t = t.elapsed().as_secs_f64();
+        if threads == 1 {
+            base_t = par_t;
         }
+        writeln!(
+            out,
+            "{}",
+            Msg::ThreadTiming.fill(&[
+                &threads,
+                &format!("{:.6}", par_t),
+                &format!("{:.2}", base_t / par_t),
+            ])
+        )?;
+        threads *= 2;
+    }
+    Ok(())
+}
 
-        let reflector = Reflector::new(&alphabet);
+/// Наивная и эффективная битность конфига
+fn print_strength(out: &mut dyn Write, cfg: &ConfigData) -> io::Result<()> {
+    let estimate = analysis::estimate_keyspace(cfg);
+    let alphabet_len = cfg.alphabet_chars().len();
+    writeln!(
+        out,
+        "{}",
+        Msg::Bitness.fill(&[
+            &format!("{:.3}", estimate.naive_bits),
+            &alphabet_len,
+            &cfg.rotor_count(),
+            &cfg.plugboard.len()
+        ])
+    )?;
+    let effective = format!("{:.3}", estimate.effective_bits);
+    let period = format!("{:.1}", estimate.period_bits);
+    writeln!(out, "{}", Msg::EffectiveBitness.fill(&[&effective, &period]))?;
+    print_period(out, cfg.period())
+}
 
-        EnigmaSudnogoDnya {
-            alphabet,
-            index_map,
-            plugboard_map,
-            blocks,
-            reflector,
+/// `strength`: оценка стойкости текущего конфига без построения машины
+fn run_strength(opts: &Options) {
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    match opts.format {
+        OutputFormat::Json => {
+            let estimate = analysis::estimate_keyspace(&cfg);
+            println!("{}", serde_json::to_string_pretty(&estimate).unwrap());
         }
+        OutputFormat::Text => print_strength(&mut io::stdout(), &cfg).unwrap(),
     }
+}
+
+/// Сколько кандидатов `crack` печатает в текстовом виде
+const CRACK_SHOW: usize = 10;
 
-    fn encrypt(&mut self, msg: &str) -> String {
-        let lower = msg.to_lowercase();
-        let mut input_indices: Vec<Option<usize>> = Vec::with_capacity(lower.len());
-        for ch in lower.chars() {
-            input_indices.push(self.index_map.get(ch));
+/// `crack --crib <текст> [--alphabet A] [--max-blocks N] [--max-rotors N] [файл]`:
+/// учебный перебор маленьких машин без plugboard. Шифротекст — из файла
+/// или со stdin.
+fn run_crack(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::CrackUsage.text());
+        std::process::exit(2);
+    };
+    let Some(crib) = take_value(&mut args, "--crib") else { usage() };
+    let mut space = attack::SearchSpace::default();
+    if let Some(alphabet) = take_alphabet(&mut args) {
+        space.alphabet = alphabet;
+    }
+    for (flag, slot) in [
+        ("--max-blocks", &mut space.max_blocks),
+        ("--max-rotors", &mut space.max_rotors),
+    ] {
+        if let Some(v) = take_value(&mut args, flag) {
+            *slot = v.parse().unwrap_or_else(|_| usage());
         }
+    }
+    let cipher = match &args[..] {
+        [] => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s).unwrap_or_else(|e| {
+                eprintln!("{}", Msg::ReadFailed.fill(&[&"stdin", &e]));
+                std::process::exit(1);
+            });
+            s
+        }
+        [path] => fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("{}", Msg::ReadFailed.fill(&[path, &e]));
+            std::process::exit(1);
+        }),
+        _ => usage(),
+    };
+    let candidates = attack::brute_force(&cipher, &crib, &space)
+        .unwrap_or_else(|e| exit_config_error(e));
+    if let OutputFormat::Json = opts.format {
+        println!("{}", serde_json::to_string_pretty(&candidates).unwrap());
+        return;
+    }
+    println!("{}", Msg::CrackFound.fill(&[&candidates.len()]));
+    for c in candidates.iter().take(CRACK_SHOW) {
+        let preview: String = c.plaintext.chars().take(60).collect();
+        let blocks = format!("{:?}", c.config.blocks);
+        let positions = format!("{:?}", c.config.rotor_positions);
+        println!("{}", Msg::CrackCandidate.fill(&[&blocks, &positions, &preview.trim_end()]));
+    }
+    if candidates.len() > CRACK_SHOW {
+        println!("{}", Msg::CrackMore.fill(&[&(candidates.len() - CRACK_SHOW)]));
+    }
+}
 
-        let mut output_indices: Vec<Option<usize>> =
-            Vec::with_capacity(input_indices.len());
+/// Числа блоков `survey` по умолчанию
+const SURVEY_BLOCKS: &[usize] = &[4, 6, 8];
+/// Конфигов `survey` на каждое число блоков по умолчанию
+const SURVEY_COUNT: usize = 5;
 
-        for &maybe_idx in input_indices.iter() {
-            if let Some(mut idx) = maybe_idx {
-                idx = self.plugboard_map[idx];
-                for blk in &self.blocks {
-                    idx = blk.process_index(idx, false);
-                }
-                idx = self.reflector.reflect_index(idx);
-                for blk in self.blocks.iter().rev() {
-                    idx = blk.process_index(idx, true);
-                }
-                idx = self.plugboard_map[idx];
-                for blk in &mut self.blocks {
-                    blk.rotate();
-                }
-                output_indices.push(Some(idx));
-            } else {
-                output_indices.push(None);
-            }
+/// `survey [--alphabet A] [--blocks N,N,...] [--count N] [--pairs N]`:
+/// битность случайных конфигов для каждого числа блоков, чтобы подобрать
+/// его под нужную стойкость. Ничего не сохраняет; `--seed` повторяет обзор.
+fn run_survey(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::SurveyUsage.text());
+        std::process::exit(2);
+    };
+    let alphabet = take_alphabet(&mut args)
+        .unwrap_or_else(|| DerivationParams::default().alphabet);
+    let block_counts: Vec<usize> = match take_value(&mut args, "--blocks") {
+        None => SURVEY_BLOCKS.to_vec(),
+        Some(list) => list
+            .split(',')
+            .map(|n| n.trim().parse().ok().filter(|&n| n > 0))
+            .collect::<Option<_>>()
+            .unwrap_or_else(|| usage()),
+    };
+    let mut number = |name: &str, default: usize| match take_value(&mut args, name) {
+        None => default,
+        Some(n) => n.parse().unwrap_or_else(|_| usage()),
+    };
+    let count = number("--count", SURVEY_COUNT);
+    let pairs = number("--pairs", plugboard::DEFAULT_PAIRS);
+    if !args.is_empty() {
+        usage();
+    }
+    let mut rng = opts.config_rng();
+    let entries = analysis::survey(&mut rng, &alphabet, &block_counts, count, pairs)
+        .unwrap_or_else(|e| exit_config_error(e));
+    if let OutputFormat::Json = opts.format {
+        println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        return;
+    }
+    let widths = [7, 8, 9];
+    let header: Vec<String> = Msg::SurveyHeader
+        .text()
+        .split('|')
+        .zip(widths)
+        .map(|(col, w)| format!("{:>w$}", col))
+        .collect();
+    println!("{}", header.join(" "));
+    for group in entries.chunk_by(|a, b| a.blocks == b.blocks) {
+        for e in group {
+            println!("{:>7} {:>8} {:>9.3}", e.blocks, e.rotors, e.bitness);
         }
+        let average = group.iter().map(|e| e.bitness).sum::<f64>() / group.len() as f64;
+        let average = format!("{:.3}", average);
+        println!("{}", Msg::SurveyAverage.fill(&[&group[0].blocks, &average]));
+    }
+}
 
-        let mut out = String::with_capacity(output_indices.len());
-        let mut it_lower = lower.chars();
-        for maybe_idx in output_indices.into_iter() {
-            if let Some(idx) = maybe_idx {
-                out.push(self.alphabet[idx]);
-                it_lower.next().unwrap();
-            } else {
-                let ch = it_lower.next().unwrap();
-                out.push(ch);
-            }
+/// `serve [--listen адрес:порт] [--max-body байт]`: HTTP-сервис поверх
+/// конфига из `--config`, см. модуль `serve`
+fn run_serve(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::ServeUsage.text());
+        std::process::exit(2);
+    };
+    let listen = take_value(&mut args, "--listen").unwrap_or_else(|| serve::DEFAULT_LISTEN.into());
+    let max_body = match take_value(&mut args, "--max-body") {
+        None => serve::DEFAULT_MAX_BODY,
+        Some(n) => n.parse().unwrap_or_else(|_| usage()),
+    };
+    if !args.is_empty() {
+        usage();
+    }
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    let mac_key = opts.mac_key(&cfg);
+    if let Err(e) = serve::run(&cfg, mac_key.as_ref(), &listen, max_body) {
+        eprintln!("{}: {}", listen, e);
+        std::process::exit(1);
+    }
+}
+
+/// `export`: конфиг одной строкой для пересылки, см. `ConfigData::export_code`
+fn run_export(opts: &Options) {
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    // проверенный конфиг без неизвестных цветов кодируется всегда
+    println!("{}", cfg.export_code().expect("цвета уже проверены"));
+}
+
+/// `import <код>`: конфиг из строки `export` записывается в `--config`;
+/// прежний файл остаётся рядом как `.bak`
+fn run_import(args: &[String], opts: &Options) {
+    let [code] = args else {
+        eprintln!("{}", Msg::ImportUsage.text());
+        std::process::exit(2);
+    };
+    let cfg = ConfigData::import_code(code).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::ImportFailed.fill(&[&e]));
+        std::process::exit(1);
+    });
+    save_imported(&cfg, opts);
+}
+
+/// `encrypt|decrypt --clipboard`: текст из буфера обмена, результат туда
+/// же, в stderr — только подтверждение с числом символов. Шифрует со
+/// стартовых позиций конфига, как фильтр; `--advance-config` их сдвигает.
+fn run_clipboard(args: &[String], decrypt: bool, opts: &Options) {
+    let mut args = args.to_vec();
+    if !take_flag(&mut args, "--clipboard") || !args.is_empty() {
+        eprintln!("{}", Msg::ClipboardUsage.text());
+        std::process::exit(2);
+    }
+    let cfg = load_config_or_exit(opts);
+    let mut machine = build_machine(&cfg);
+    let key = opts.mac_key(&cfg);
+    let count = open_clipboard().and_then(|mut board| {
+        clipboard::process(board.as_mut(), &mut machine, decrypt, key.as_ref())
+    });
+    match count {
+        Ok(count) => eprintln!("{}", Msg::ClipboardDone.fill(&[&count])),
+        // не расшифровалось: текст не от этого ключа или тег не сошёлся
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
+        Err(e) => {
+            eprintln!("{}", Msg::ClipboardUnavailable.fill(&[&e]));
+            std::process::exit(1);
+        }
+    }
+    if opts.advance_config {
+        advance_config(&cfg, machine.export_positions(), &opts.config);
+    }
+}
 
-        out
+#[cfg(feature = "clipboard")]
+fn open_clipboard() -> io::Result<Box<dyn Clipboard>> {
+    Ok(Box::new(clipboard::SystemClipboard::open()?))
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn open_clipboard() -> io::Result<Box<dyn Clipboard>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, Msg::ClipboardNotBuilt.text()))
+}
+
+/// `export-mnemonic`: конфиг словами, см. `ConfigData::to_mnemonic`
+fn run_export_mnemonic(opts: &Options) {
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    match cfg.to_mnemonic() {
+        Ok(words) => println!("{}", words),
+        Err(e) => {
+            eprintln!("{}", Msg::MnemonicExportFailed.fill(&[&e]));
+            std::process::exit(1);
+        }
     }
+}
 
-    fn load_config(filename: &str) -> io::Result<ConfigData> {
-        let s = fs::read_to_string(filename)?;
-        let cfg = serde_json::from_str(&s)?;
-        Ok(cfg)
+/// `import-mnemonic <слова...>`: как `import`, но из слов `export-mnemonic`.
+/// Слова можно передать одним аргументом в кавычках или по одному.
+fn run_import_mnemonic(args: &[String], opts: &Options) {
+    if args.is_empty() {
+        eprintln!("{}", Msg::ImportMnemonicUsage.text());
+        std::process::exit(2);
     }
+    let cfg = ConfigData::from_mnemonic(&args.join(" ")).unwrap_or_else(|e| {
+        eprintln!("{}", Msg::MnemonicImportFailed.fill(&[&e]));
+        std::process::exit(1);
+    });
+    save_imported(&cfg, opts);
 }
 
-fn read_line() -> String {
-    let mut s = String::new();
-    io::stdin().read_line(&mut s).unwrap();
-    s.trim().to_string()
+/// Проверяет принятый конфиг и пишет его в `--config` с `.bak`
+fn save_imported(cfg: &ConfigData, opts: &Options) {
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    if let Err(e) = EnigmaSudnogoDnya::save_config(cfg, &opts.config, true) {
+        eprintln!("{}", Msg::WriteFailed.fill(&[&opts.config, &e]));
+        std::process::exit(1);
+    }
+    println!("{}", Msg::ConfigImported.fill(&[&opts.config]));
 }
 
-/// Вычисляет log2(n!)
-fn log2_factorial(n: usize) -> f64 {
-    let mut sum = 0.0;
-    for i in 1..=n {
-        sum += (i as f64).log2();
+/// `gen-config [--from-passphrase] [--alphabet ...] [--blocks N] [--pairs N]`:
+/// новый конфиг в `--config`. С `--from-passphrase` фраза читается первой
+/// строкой stdin и конфиг выводится из неё, иначе — случайный (`--seed`).
+fn run_gen_config(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::GenConfigUsage.text());
+        std::process::exit(2);
+    };
+    let from_passphrase = take_flag(&mut args, "--from-passphrase");
+    let defaults = DerivationParams::default();
+    let alphabet = take_alphabet(&mut args).unwrap_or(defaults.alphabet.clone());
+    let mut number = |name: &str, default: usize| match take_value(&mut args, name) {
+        None => default,
+        Some(n) => n.parse().unwrap_or_else(|_| usage()),
+    };
+    let (blocks, pairs) = (number("--blocks", defaults.blocks), number("--pairs", defaults.pairs));
+    if !args.is_empty() {
+        usage();
+    }
+    let cfg = if from_passphrase {
+        let line = if io::stdin().is_terminal() {
+            Console::new(io::stdin().lock(), io::stdout()).prompt(Msg::PassphrasePrompt.text())
+        } else {
+            read_line()
+        };
+        let mut phrase = match line {
+            Ok(Some(phrase)) if !phrase.is_empty() => phrase,
+            Ok(_) => {
+                eprintln!("{}", Msg::EmptyPassphrase.text());
+                std::process::exit(2);
+            }
+            Err(e) => {
+                eprintln!("{}", Msg::ReadFailed.fill(&[&"stdin", &e]));
+                std::process::exit(1);
+            }
+        };
+        let params = DerivationParams { alphabet, blocks, pairs, ..defaults };
+        let cfg = ConfigData::from_passphrase(&phrase, params);
+        phrase.zeroize();
+        cfg
+    } else {
+        ConfigData::generate_with_pairs(&mut opts.config_rng(), &alphabet, blocks, pairs)
     }
-    sum
+    .unwrap_or_else(|e| exit_config_error(e));
+    if let Err(e) = EnigmaSudnogoDnya::save_config(&cfg, &opts.config, true) {
+        eprintln!("{}", Msg::WriteFailed.fill(&[&opts.config, &e]));
+        std::process::exit(1);
+    }
+    println!("{}", Msg::ConfigImported.fill(&[&opts.config]));
 }
 
-/// Для меню: пресет
-#[derive(Clone)]
-struct Preset {
-    name: &'static str,
-    description: &'static str,
-    blocks: usize,
-    speed_idx: u8,
-}
-
-const ROTOR_COLORS: &[char] = &['К','Б','Ч','З','Р','О','Ф','С','Г','Л'];
-
-const PRESETS: &[Preset] = &[
-    Preset {
-        name: "минимально безопасный",
-        description: "3 блока, короткие роторы — быстро, но слабее.",
-        blocks: 3,
-        speed_idx: 8,
-    },
-    Preset {
-        name: "безопасный",
-        description: "4 блока, средние роторы — хороший баланс.",
-        blocks: 4,
-        speed_idx: 7,
-    },
-    Preset {
-        name: "паранойя",
-        description: "12 блоков, длинные роторы — медленней, но максимум стойкости.",
-        blocks: 12,
-        speed_idx: 4,
-    },
-    Preset {
-        name: "Бладислав Ворон",
-        description: "О нём мало чего известно, ведь от него получали больше пиздюлей, чем информации, но что известно, так это то что пока одной рукой он делал тихий океан ещё тише, а другой рукой создавал эту бездарную планету и существовать с ним на одной планете это та ещё задача со звёздочкой, награда за которую не предусмотрена",
-        blocks: 8_388_608,
-        speed_idx: 1,
-    },
-    Preset {
-        name: "Боронислав Владон",
-        description: "Пока Бладислав Ворон был занят со своим братом делами галактического масштаба, а мы не знали что делать и чем защищаться, с нами на связь вышел старший двоюрный брат Бладислава и его брата - Боронислав Владон.\nХотите верьте, хотите нет, но пытаясь хоть что либо хоть где либо узнать о Борониславе мы ничего не нашли, даже спрашивая напрямую у Бладислава - данные попросту засекречены всеми возможными грифами секретности, а те кто пытались что-то рассекретить, ну, они получали больше пиздюлей чем информации.\nЗа его работу он потребовал лишь 60 гигиабайт ОЗУ и побольше вычислительных мощностей, ведь его услуги не из дешёвых.",
-        blocks: 134_217_728,
-        speed_idx: 0,
-    },
-    Preset {
-        name: "Александр \"42\"",
-        description: "Уважаемая личность на районе, так именуемый \"42\" в честь количества блоков внутри него.",
-        blocks: 42,
-        speed_idx: 5,
-    },
-    Preset {
-        name: "Анаколий",
-        description: "В любой компании есть самый младший, тут тоже он есть.\n Он самый шустрый и самый малой в компании этих гигантов, но это не мешает ему быть хоть немного грозным, ведь внутри него целых 81.337 бит и хоть 81.337 бит это практически смешно для серьезной защиты, Анаколий предпочитает домашние посиделки за чаем, нежели защиту всего с грифом Top Secret как его старшие братья - а там 81 это вполне достаточно.",
-        blocks: 1,
-        speed_idx: 10,
-    },
-];
-
-fn random_blocks<R: Rng>(rng: &mut R, blocks: usize) -> Vec<String> {
-    (0..blocks)
-        .map(|_| {
-            let k = rng.random_range(3..=9);
-            (0..k)
-                .map(|_| {
-                    let idx = rng.random_range(0..ROTOR_COLORS.len());
-                    ROTOR_COLORS[idx]
-                })
-                .collect()
-        })
-        .collect()
+/// `crack-positions --plain <текст> --cipher <текст>`: подбирает стартовые
+/// позиции роторов текущего конфига по известному началу сообщения
+fn run_crack_positions(args: &[String], opts: &Options) {
+    let mut args = args.to_vec();
+    let usage = || -> ! {
+        eprintln!("{}", Msg::CrackPositionsUsage.text());
+        std::process::exit(2);
+    };
+    let (Some(plain), Some(cipher)) =
+        (take_value(&mut args, "--plain"), take_value(&mut args, "--cipher"))
+    else {
+        usage()
+    };
+    if !args.is_empty() {
+        usage();
+    }
+    let cfg = load_config_or_exit(opts);
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
+    let found = EnigmaSudnogoDnya::crack_positions(&cfg, &plain, &cipher);
+    if let OutputFormat::Json = opts.format {
+        println!("{}", serde_json::to_string_pretty(&found).unwrap());
+    } else if let Some(positions) = &found {
+        println!("{}", Msg::CrackPositionsFound.fill(&[&format!("{:?}", positions)]));
+    } else {
+        eprintln!("{}", Msg::CrackPositionsNotFound.text());
+    }
+    if found.is_none() {
+        std::process::exit(1);
+    }
 }
 
-fn random_plugboard_pairs<R: Rng>(rng: &mut R, alphabet: &[char]) -> Vec<(char, char)> {
-    let mut pool: Vec<char> = alphabet.to_vec();
-    pool.shuffle(rng);
-    pool.chunks(2)
-        .take(8)
-        .map(|chunk| (chunk[0], chunk[1]))
-        .collect()
+/// `verify-vectors [каталог]`: сверяет сборку с эталонными векторами —
+/// вшитыми или из указанного каталога. Код выхода 1 при расхождении.
+fn verify_vectors(dir: Option<&str>) {
+    let loaded = match dir {
+        None => vectors::builtin(),
+        Some(dir) => match vectors::load_dir(Path::new(dir)) {
+            Ok(loaded) => loaded.into_iter().map(|(_, v)| v).collect(),
+            Err(e) => {
+                eprintln!("{}", Msg::ReadFailed.fill(&[&dir, &e]));
+                std::process::exit(1);
+            }
+        },
+    };
+    let mut passed = 0;
+    for vector in &loaded {
+        match vector.verify() {
+            Ok(true) => {
+                println!("{}", Msg::VectorOk.fill(&[&vector.name]));
+                passed += 1;
+            }
+            Ok(false) => {
+                let actual = vector.compute().unwrap_or_default();
+                let msg = Msg::VectorMismatch.fill(&[&vector.name, &vector.ciphertext, &actual]);
+                eprintln!("{}", msg);
+            }
+            Err(e) => eprintln!("{}", Msg::VectorFailed.fill(&[&vector.name, &e])),
+        }
+    }
+    println!("{}", Msg::VectorsSummary.fill(&[&passed, &loaded.len()]));
+    if passed != loaded.len() {
+        std::process::exit(1);
+    }
 }
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let lang = take_lang(&mut args);
+    messages::set_lang(lang.or_else(messages::lang_from_env).unwrap_or(Lang::Ru));
+    let opts = take_options(&mut args, lang);
+    set_quiet(opts.quiet);
+    set_iv(opts.iv);
+    if opts.iv.is_some() && opts.advance_config {
+        eprintln!("{}", Msg::IvUnsupported.text());
+        std::process::exit(2);
+    }
+    if !opts.cascade.is_empty() {
+        return run_cascade(&args, &opts);
+    }
+    if let Some(cmd) = args.first() {
+        match cmd.as_str() {
+            "encrypt" => return run_clipboard(&args[1..], false, &opts),
+            "decrypt" => return run_clipboard(&args[1..], true, &opts),
+            "encrypt-file" => return run_file_mode(&args[1..], false, &opts),
+            "decrypt-file" => return run_file_mode(&args[1..], true, &opts),
+            "encrypt-dir" => return run_dir_mode(&args[1..], false, &opts),
+            "decrypt-dir" => return run_dir_mode(&args[1..], true, &opts),
+            "analyze" => return run_analyze(&args[1..], &opts),
+            "strength" => return run_strength(&opts),
+            "crack" => return run_crack(&args[1..], &opts),
+            "crack-positions" => return run_crack_positions(&args[1..], &opts),
+            "serve" => return run_serve(&args[1..], &opts),
+            "export" => return run_export(&opts),
+            "import" => return run_import(&args[1..], &opts),
+            "export-mnemonic" => return run_export_mnemonic(&opts),
+            "import-mnemonic" => return run_import_mnemonic(&args[1..], &opts),
+            "gen-config" => return run_gen_config(&args[1..], &opts),
+            "survey" => return run_survey(&args[1..], &opts),
+            "benchmark" => return run_benchmark_command(&args[1..], &opts),
+            "verify-vectors" => return verify_vectors(args.get(1).map(String::as_str)),
+            _ => {
+                eprintln!("{}", Msg::UnknownArgument.fill(&[cmd]));
+                std::process::exit(2);
+            }
+        }
+    }
+    if !opts.inputs.is_empty() {
+        return run_input_files(&opts);
+    }
+    if !opts.interactive && !io::stdin().is_terminal() {
+        return run_filter_mode(&opts);
+    }
+    let mut console = Console::new(io::stdin().lock(), io::stdout());
+    if io::stdin().is_terminal() {
+        console.enable_editor(opts.history_messages);
+    }
+    if let Err(e) = run_interactive(&mut console, &opts) {
+        eprintln!("{}", Msg::ReadFailed.fill(&[&"stdin", &e]));
+        std::process::exit(1);
+    }
+}
+
+/// Меню: загрузка или генерация конфига, затем цикл команд. Конец ввода
+/// в любом вопросе — обычный выход.
+fn run_interactive<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    opts: &Options,
+) -> io::Result<()> {
     // A) Загрузка или генерация конфига
-    let cfg = if fs::metadata("esd_config.json").is_ok() {
-        print!("Найден конфиг, загрузить? (да/нет): ");
-        io::stdout().flush().unwrap();
-        if read_line().to_lowercase() == "да" {
-            EnigmaSudnogoDnya::load_config("esd_config.json")
-                .expect("Не удалось загрузить")
+    let cfg = if fs::metadata(&opts.config).is_ok() {
+        if console.confirm(Msg::ConfigFound.text())? {
+            load_config_or_exit(opts)
         } else {
-            fs::remove_file("esd_config.json").ok();
-            ConfigData {
-                alphabet: "latin".into(),
-                plugboard: Vec::new(),
-                blocks: Vec::new(),
-                rotor_positions: Vec::new(),
+            // старый ключ удаляем только по явному согласию; иначе он уйдёт
+            // в .bak при сохранении нового
+            if console.confirm(&Msg::DeleteConfig.fill(&[&opts.config]))? {
+                fs::remove_file(&opts.config).ok();
             }
+            ConfigData::default()
         }
     } else {
-        ConfigData {
-            alphabet: "latin".into(),
-            plugboard: Vec::new(),
-            blocks: Vec::new(),
-            rotor_positions: Vec::new(),
-        }
+        ConfigData::default()
     };
 
     let mut cfg = cfg;
 
-    // B) Генерация нового, если cfg.blocks пустой
-    if cfg.blocks.is_empty() {
-        // 1) Выбор алфавита
-        println!("Выберите алфавит:\n1) Латиница\n2) Кириллица");
-        print!("> ");
-        io::stdout().flush().unwrap();
-        cfg.alphabet = if read_line() == "1" {
-            "latin".into()
-        } else {
-            "cyrillic".into()
+    // B) Мастер нового конфига, если в конфиге нет блоков
+    if cfg.block_count() == 0 {
+        let mut rng = opts.config_rng();
+        let generate = |params: &Params| {
+            let bar = progress_bar(Msg::ProgressGenerating, Some(2 * params.blocks as u64), false);
+            let cfg = params.generate(&mut rng, |p: Progress| bar.set_position(p.done));
+            bar.finish_and_clear();
+            cfg
         };
+        let Some(outcome) = wizard::setup(console, generate)? else { return Ok(()) };
+        cfg = outcome.cfg;
+        if outcome.save && let Err(e) = EnigmaSudnogoDnya::save_config(&cfg, &opts.config, true) {
+            eprintln!("{}", Msg::WriteFailed.fill(&[&opts.config, &e]));
+        }
+    }
 
-        let alphabet_str = if cfg.alphabet == "latin" {
-            "abcdefghijklmnopqrstuvwxyz"
-        } else {
-            "абвгдеёжзийклмнопрстуфхцчшщъыьэюя"
-        };
-        let alphabet_chars: Vec<char> = alphabet_str.chars().collect();
-
-        // 2) Меню пресетов
-        println!("\nНастройка конфигурации:");
-        println!("0) Я сам всё настрою");
-        for (i, p) in PRESETS.iter().enumerate() {
-            println!(
-                "{:>2}) {} — {} (блоков: {}, скорость: {}/10)",
-                i + 1,
-                p.name,
-                p.description,
-                p.blocks,
-                p.speed_idx
-            );
-        }
-        print!("Выбор: ");
-        io::stdout().flush().unwrap();
-        let choice: usize = read_line().parse().unwrap_or(0);
-
-        if choice == 0 {
-            // === Ручная настройка (без изменений) ===
-            println!("Настройка plugboard (взаимозамен):");
-            println!("1) Ввести вручную");
-            println!("2) Сгенерировать случайно");
-            print!("> ");
-            io::stdout().flush().unwrap();
-            let pb_choice = read_line();
-            let mut plugboard_pairs: Vec<(char, char)> = Vec::new();
-            if pb_choice == "1" {
-                println!("Вводите пары 'a b'. Пустая строка — выход.");
-                loop {
-                    print!("Добавить пару: ");
-                    io::stdout().flush().unwrap();
-                    let line = read_line();
-                    if line.trim().is_empty() {
-                        break;
-                    }
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() != 2 {
-                        eprintln!("Нужно ровно два символа через пробел.");
-                        continue;
-                    }
-                    let a = parts[0].chars().next().unwrap();
-                    let b = parts[1].chars().next().unwrap();
-                    plugboard_pairs.push((a, b));
-                }
-            } else {
-                let mut rng = rng();
-                plugboard_pairs = random_plugboard_pairs(&mut rng, &alphabet_chars);
-                println!("Случайно сгенерированные пары plugboard: {:?}", plugboard_pairs);
-            }
-            cfg.plugboard = plugboard_pairs;
+    if let Err(e) = cfg.validate() {
+        exit_config_error(e);
+    }
 
-            print!("Сколько блоков? ");
-            io::stdout().flush().unwrap();
-            let n: usize = read_line().parse().unwrap_or(4);
-            let mut rng = rng();
-            cfg.blocks = random_blocks(&mut rng, n);
-            cfg.rotor_positions = cfg
-                .blocks
-                .iter()
-                .map(|b| {
-                    (0..b.len())
-                        .map(|_| rng.random_range(0..alphabet_chars.len()))
-                        .collect()
-                })
-                .collect();
+    let bitness = config_bitness(&cfg);
+    let mac_key = opts.mac_key(&cfg);
+    let memory = cfg.estimated_memory_bytes();
+    if memory > MEMORY_WARN_BYTES {
+        let gib = format!("{:.2}", memory as f64 / (1u64 << 30) as f64);
+        if !console.confirm(&Msg::MemoryConfirm.fill(&[&gib]))? {
+            return Ok(());
+        }
+    }
 
-        } else {
-            // === Генерация по пресету ===
-            let preset = &PRESETS[choice - 1];
-            let mut rng = rng();
-
-            cfg.blocks = random_blocks(&mut rng, preset.blocks);
-            cfg.rotor_positions = cfg
-                .blocks
-                .iter()
-                .map(|b| {
-                    (0..b.len())
-                        .map(|_| rng.random_range(0..alphabet_chars.len()))
-                        .collect()
-                })
-                .collect();
-            cfg.plugboard = random_plugboard_pairs(&mut rng, &alphabet_chars);
-
-            //println!(
-            //    "\nСгенерировано по пресету «{}»:\n  блоки = {:?}\n  пары plugboard = {:?}",
-            //    preset.name, cfg.blocks, cfg.plugboard
-            //);
-        }
-
-        // 3) Сохранить конфиг?
-        print!("Сохранить конфиг? (да/нет): ");
-        io::stdout().flush().unwrap();
-        if read_line().to_lowercase() == "да" {
-            serde_json::to_writer_pretty(
-                fs::File::create("esd_config.json").unwrap(),
-                &cfg,
-            )
-            .unwrap();
-        }
-    }
-
-    // C) Основной цикл
-    loop {
-        print!("Команда (encrypt/decrypt/benchmark/exit): ");
-        io::stdout().flush().unwrap();
-        match read_line().as_str() {
-            "exit" => break,
-
-            "encrypt" => {
-                let mut enigma_enc = EnigmaSudnogoDnya::new(&cfg);
-                print!("Сообщение: ");
-                io::stdout().flush().unwrap();
-                let msg = read_line();
-                println!("Результат: {}", enigma_enc.encrypt(&msg));
-            }
+    // C) Основной цикл: машина строится один раз и сбрасывается перед сообщением
+    let mut session = Session { opts, cfg, machine: None, mac_key, bitness };
+    console::run_menu(console, &mut session)
+}
 
-            "decrypt" => {
-                let mut enigma_dec = EnigmaSudnogoDnya::new(&cfg);
-                print!("Сообщение: ");
-                io::stdout().flush().unwrap();
-                let msg = read_line();
-                println!("Результат: {}", enigma_dec.encrypt(&msg));
-            }
+/// Состояние основного цикла меню: конфиг, машина (строится по первой
+/// команде, которой нужна) и ключ тега целостности
+struct Session<'a> {
+    opts: &'a Options,
+    cfg: ConfigData,
+    machine: Option<EnigmaSudnogoDnya>,
+    mac_key: Option<MacKey>,
+    bitness: f64,
+}
 
-            "benchmark" => {
-                let mut rng = rng();
-                {
-                    let alphabet_len = EnigmaSudnogoDnya::new(&cfg).alphabet.len();
-                    let total_rotors: usize =
-                        cfg.blocks.iter().map(|blk| blk.len()).sum();
-                    let plugboard_pairs = cfg.plugboard.len();
-
-                    let log2_positions =
-                        (total_rotors as f64) * (alphabet_len as f64).log2();
-                    let log2_plugboard = log2_factorial(alphabet_len)
-                        - log2_factorial(alphabet_len.saturating_sub(2 * plugboard_pairs))
-                        - (plugboard_pairs as f64)
-                        - log2_factorial(plugboard_pairs);
-                    let total_bitness = log2_positions + log2_plugboard;
-
-                    println!(
-                        "\nБитность конфигурации: {:.3} бит (A = {}, R = {}, P = {})",
-                        total_bitness, alphabet_len, total_rotors, plugboard_pairs
-                    );
-                }
+impl Session<'_> {
+    fn machine(&mut self) -> &mut EnigmaSudnogoDnya {
+        self.machine.get_or_insert_with(|| build_machine(&self.cfg))
+    }
 
-                for &size in &[10, 100, 1_000, 10_000, 50_000, 100 * 100 * 100] {
-                    let mut text = String::with_capacity(size);
-                    let alphabet = EnigmaSudnogoDnya::new(&cfg).alphabet;
-                    let a_len = alphabet.len();
-                    for _ in 0..size {
-                        let idx = rng.random_range(0..a_len);
-                        text.push(alphabet[idx]);
-                    }
-
-                    let t3 = Instant::now();
-                    let mut enc = EnigmaSudnogoDnya::new(&cfg);
-                    let cipher = enc.encrypt(&text);
-                    let mut dec = EnigmaSudnogoDnya::new(&cfg);
-                    let recovered = dec.encrypt(&cipher);
-                    let kat_time = t3.elapsed().as_secs_f32();
-                    if recovered != text {
-                        eprintln!(
-                            "KAT FAILED на size = {}: decrypt(encrypt(text)) != text",
-                            size
-                        );
-                    } else {
-                        println!("KAT: pass");
-                    }
-
-                    let t0 = Instant::now();
-                    let mut e1 = EnigmaSudnogoDnya::new(&cfg);
-                    let _ = e1.encrypt(&text);
-                    let enc_t = t0.elapsed().as_secs_f32();
-
-                    let t1 = Instant::now();
-                    let mut e2 = EnigmaSudnogoDnya::new(&cfg);
-                    let _ = e2.encrypt(&cipher);
-                    let dec_t = t1.elapsed().as_secs_f32();
-
-                    println!(
-                        "{} → encrypt: {:.6}, decrypt: {:.6}, KAT: {:.6}",
-                        size, enc_t, dec_t, kat_time
-                    );
-                }
+    /// `set-positions <блок> <p1,p2,...> [save]`
+    fn set_positions(&mut self, out: &mut dyn Write, args: &[&str]) -> io::Result<()> {
+        let mut words = args.iter().copied();
+        let (Some(block), Some(list)) = (words.next(), words.next()) else {
+            return writeln!(out, "{}", Msg::SetPositionsUsage.text());
+        };
+        let save = words.next() == Some("save");
+        let block: Option<usize> = block.parse().ok();
+        let positions: Option<Vec<usize>> =
+            list.split(',').map(|p| p.trim().parse().ok()).collect();
+        let (Some(block), Some(positions)) = (block, positions) else {
+            return writeln!(out, "{}", Msg::SetPositionsUsage.text());
+        };
+        if let Err(e) = self.machine().set_block_positions(block, &positions) {
+            eprintln!("{}", e);
+            return Ok(());
+        }
+        // машина сбрасывается к стартовым позициям из конфига, так что
+        // держим их в согласии
+        if self.cfg.rotor_positions.len() != self.cfg.block_count() {
+            self.cfg.rotor_positions = self.machine().export_positions();
+        }
+        self.cfg.rotor_positions[block] = positions;
+        self.mac_key = self.opts.mac_key(&self.cfg);
+        let path = &self.opts.config;
+        if save && let Err(e) = EnigmaSudnogoDnya::save_config(&self.cfg, path, true) {
+            eprintln!("{}", Msg::WriteFailed.fill(&[path, &e]));
+            return Ok(());
+        }
+        writeln!(out, "{}", Msg::PositionsSet.fill(&[&block]))
+    }
+}
+
+impl Menu for Session<'_> {
+    fn message(
+        &mut self,
+        out: &mut dyn Write,
+        decrypt: bool,
+        watch: Option<usize>,
+        msg: &str,
+    ) -> io::Result<()> {
+        let Session { opts, cfg, machine, mac_key, bitness } = self;
+        let live = machine.get_or_insert_with(|| build_machine(cfg));
+        if !opts.advance_config {
+            live.reset();
+            if let Some(steps) = opts.iv {
+                live.set_offset(steps);
             }
+        }
+        let msg = match opts.wrap {
+            Some(cols) if decrypt => wrap::unwrap(msg, cols),
+            _ => msg.to_string(),
+        };
+        let done = match watch {
+            Some(blocks) => {
+                let shown = blocks.min(cfg.block_count());
+                writeln!(out, "{}", Msg::StepsHeader.fill(&[&shown, &cfg.block_count()]))?;
+                let key = mac_key.as_ref();
+                run_watched(out, live, &msg, decrypt, key, blocks, opts.format)
+                    .map(|result| (result, Vec::new()))
+            }
+            None => run_message(live, &msg, decrypt, mac_key.as_ref(), opts.trace),
+        };
+        let (result, trace) = match done {
+            Ok(done) => done,
+            Err(e) => {
+                eprintln!("{}", e);
+                return Ok(());
+            }
+        };
+        let result = match opts.wrap {
+            Some(cols) if !decrypt && opts.format == OutputFormat::Text => {
+                wrap::wrap(&result, cols)
+            }
+            _ => result,
+        };
+        print_result(out, opts.format, live, *bitness, &result, &trace)?;
+        if opts.advance_config {
+            // тег целостности зависит от позиций — ключ тоже продвигается
+            *cfg = advance_config(cfg, live.export_positions(), &opts.config);
+            *mac_key = opts.mac_key(cfg);
+        }
+        Ok(())
+    }
 
-            _ => println!("Неизвестная команда."),
+    fn command(&mut self, out: &mut dyn Write, name: &str, args: &[&str]) -> io::Result<bool> {
+        match name {
+            "state" => {
+                let live = self.machine();
+                let (desc, period) = (live.describe(), live.period());
+                print_state(out, &desc)?;
+                print_period(out, period)?;
+            }
+            "set-positions" => self.set_positions(out, args)?,
+            "benchmark" => match BenchFlags::parse(args) {
+                Some(flags) => run_benchmark(out, &self.cfg, &flags, self.opts)?,
+                None => writeln!(out, "{}", Msg::BenchUsage.text())?,
+            },
+            _ => return Ok(false),
         }
+        Ok(true)
     }
 }
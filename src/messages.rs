@@ -0,0 +1,837 @@
+//! Таблица сообщений интерфейса на русском и английском.
+//!
+//! Язык выбирается один раз при старте (`set_lang`) и дальше читается
+//! отовсюду, в том числе из `Display` для `CipherError`.
+
+use crate::prelude::*;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Язык сообщений; в конфиге — `"ru"` или `"en"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
+pub enum Lang {
+    Ru,
+    En,
+}
+
+impl Lang {
+    /// `ru`/`en`, регистр и хвост вида `_US.UTF-8` не важны
+    pub fn parse(s: &str) -> Option<Lang> {
+        let code = s.get(..2)?.to_ascii_lowercase();
+        match code.as_str() {
+            "ru" => Some(Lang::Ru),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+}
+
+/// Язык из окружения: сначала `GIORDANO_LANG`, потом системный `LANG`
+#[cfg(feature = "std")]
+pub fn lang_from_env() -> Option<Lang> {
+    ["GIORDANO_LANG", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().and_then(|code| Lang::parse(&code)))
+}
+
+/// Ответ на вопрос «да/нет» на любом из языков, полностью или первой
+/// буквой, без учёта регистра и пробелов; `None` — ответ не распознан
+pub fn parse_yes_no(answer: &str) -> Option<bool> {
+    let answer = answer.trim().to_lowercase();
+    if answer.is_empty() {
+        return None;
+    }
+    let matches = |msg: Msg| {
+        [Lang::Ru, Lang::En].iter().any(|&lang| {
+            let word = msg.in_lang(lang);
+            answer == word || answer.chars().eq(word.chars().take(1))
+        })
+    };
+    if matches(Msg::Yes) {
+        Some(true)
+    } else if matches(Msg::No) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::Ru as u8);
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    if LANG.load(Ordering::Relaxed) == Lang::En as u8 {
+        Lang::En
+    } else {
+        Lang::Ru
+    }
+}
+
+/// Ключи сообщений. `{}` в тексте заполняются по порядку через `Msg::fill`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    EmptyBlock,
+    NoBlocks,
+    TooManyRotors,
+    UnknownColor,
+    IntegrityFailure,
+    BlockOutOfRange,
+    RotorOutOfRange,
+    RotorPositionCountMismatch,
+    ShiftOutOfRange,
+    PositionOutOfRange,
+    PlugboardUnknownChar,
+    PlugboardCharReused,
+    PlugboardSelfPair,
+    EmptyChain,
+    PositionBlockCountMismatch,
+    UnknownReflector,
+    UnknownAlphabet,
+    ReflectorAlphabetMismatch,
+    TooManyPlugboardPairs,
+    HomophonicUnsupported,
+    ReversedBlockCountMismatch,
+    ReversedRotorCountMismatch,
+    BufferTooSmall,
+    ZeroStepInterval,
+    CascadeAlphabetMismatch,
+    CascadeCaseMismatch,
+    StepEveryBlockCountMismatch,
+    ZeroStepEvery,
+    PlugboardPairsAndMap,
+    PlugboardMapOpen,
+    UnsupportedDerivationVersion,
+    BadDerivationParams,
+    BadFileHeader,
+    UnsupportedFileVersion,
+    FileFingerprintMismatch,
+    BadArmor,
+    SearchSpaceTooLarge,
+    OutputExists,
+    DirUsage,
+    CheckpointNeedsNoTag,
+    CheckpointNoArmor,
+    DirSkipped,
+    DirDone,
+    BuildProgress,
+    InputPlainOnly,
+    ProgressEncrypting,
+    ProgressDecrypting,
+    ProgressGenerating,
+    MissingValue,
+    BadOutputFormat,
+    BadLang,
+    BadSeed,
+    BadIv,
+    IvUnsupported,
+    BadWrap,
+    BadEncoding,
+    Result,
+    FileUsage,
+    CascadeFilesOnly,
+    ReadFailed,
+    WriteFailed,
+    WriteResultFailed,
+    LoadFailed,
+    FilterNeedsFiles,
+    JsonNeedsUtf8,
+    TraceTruncated,
+    StepsHeader,
+    UnknownArgument,
+    UnknownPreset,
+    Yes,
+    No,
+    AnswerYesNo,
+    ConfigFound,
+    DeleteConfig,
+    ChooseAlphabet,
+    ConfigSetup,
+    ManualSetup,
+    PresetLine,
+    Choice,
+    PlugboardSetup,
+    EnterPairs,
+    AddPair,
+    NeedTwoChars,
+    PairSelf,
+    PairNotInAlphabet,
+    PairSymbolTaken,
+    PairNotFound,
+    PairsEmpty,
+    HowManyBlocks,
+    HowManyPairs,
+    SaveConfig,
+    WizardStrength,
+    WizardRotors,
+    WizardSpeed,
+    WizardUnknownAnswer,
+    ConfigError,
+    MemoryConfirm,
+    PresetEstimate,
+    ContinuePrompt,
+    CommandPrompt,
+    MessagePrompt,
+    StateHeader,
+    MoreBlocks,
+    SetPositionsUsage,
+    PositionsSet,
+    Bitness,
+    Period,
+    ShortPeriod,
+    ReflectorFixedPoint,
+    DegenerateConfig,
+    EffectiveBitness,
+    KatFailed,
+    BenchUsage,
+    BenchSkipped,
+    BenchHeader,
+    BenchConstruct,
+    BenchFreqTitle,
+    BenchFreqHeader,
+    BenchFreqChiSquare,
+    ThreadTiming,
+    UnknownCommand,
+    VectorOk,
+    VectorMismatch,
+    VectorFailed,
+    VectorBlessed,
+    VectorsSummary,
+    VectorsBlessHint,
+    AnalyzeUsage,
+    AnalyzeTotal,
+    AnalyzeHeader,
+    AnalyzeChiSquare,
+    AnalyzeIoc,
+    AnalyzeBigrams,
+    CrackUsage,
+    CrackFound,
+    CrackCandidate,
+    CrackMore,
+    CrackPositionsUsage,
+    CrackPositionsFound,
+    CrackPositionsNotFound,
+    ServeUsage,
+    ServeListening,
+    ServeNotFound,
+    ServeMethodNotAllowed,
+    ServeBodyTooLarge,
+    ServeBadJson,
+    ImportUsage,
+    ImportFailed,
+    ConfigImported,
+    ImportMnemonicUsage,
+    MnemonicExportFailed,
+    MnemonicImportFailed,
+    GenConfigUsage,
+    SurveyUsage,
+    SurveyHeader,
+    SurveyAverage,
+    PassphrasePrompt,
+    EmptyPassphrase,
+    ClipboardUsage,
+    ClipboardUnavailable,
+    ClipboardNotBuilt,
+    ClipboardDone,
+}
+
+impl Msg {
+    /// Все ключи — для проверки, что каталоги совпадают
+    pub const ALL: &[Msg] = &[
+        Msg::EmptyBlock, Msg::NoBlocks, Msg::TooManyRotors, Msg::UnknownColor,
+        Msg::IntegrityFailure, Msg::BlockOutOfRange, Msg::RotorOutOfRange,
+        Msg::RotorPositionCountMismatch, Msg::ShiftOutOfRange, Msg::PositionOutOfRange,
+        Msg::PlugboardUnknownChar, Msg::PlugboardCharReused, Msg::PlugboardSelfPair,
+        Msg::EmptyChain, Msg::PositionBlockCountMismatch, Msg::UnknownReflector,
+        Msg::UnknownAlphabet, Msg::ReflectorAlphabetMismatch, Msg::TooManyPlugboardPairs,
+        Msg::HomophonicUnsupported, Msg::ReversedBlockCountMismatch,
+        Msg::ReversedRotorCountMismatch, Msg::BufferTooSmall, Msg::ZeroStepInterval,
+        Msg::CascadeAlphabetMismatch, Msg::CascadeCaseMismatch, Msg::StepEveryBlockCountMismatch,
+        Msg::ZeroStepEvery, Msg::PlugboardPairsAndMap, Msg::PlugboardMapOpen,
+        Msg::UnsupportedDerivationVersion, Msg::BadDerivationParams, Msg::BadFileHeader,
+        Msg::UnsupportedFileVersion, Msg::FileFingerprintMismatch, Msg::BadArmor,
+        Msg::SearchSpaceTooLarge, Msg::OutputExists, Msg::DirUsage, Msg::CheckpointNeedsNoTag,
+        Msg::CheckpointNoArmor, Msg::DirSkipped, Msg::DirDone, Msg::BuildProgress,
+        Msg::InputPlainOnly, Msg::ProgressEncrypting, Msg::ProgressDecrypting,
+        Msg::ProgressGenerating, Msg::MissingValue, Msg::BadOutputFormat, Msg::BadLang,
+        Msg::BadSeed, Msg::BadIv, Msg::IvUnsupported, Msg::BadWrap, Msg::BadEncoding, Msg::Result,
+        Msg::FileUsage, Msg::CascadeFilesOnly, Msg::ReadFailed, Msg::WriteFailed,
+        Msg::WriteResultFailed, Msg::LoadFailed, Msg::FilterNeedsFiles, Msg::JsonNeedsUtf8,
+        Msg::TraceTruncated, Msg::StepsHeader, Msg::UnknownArgument, Msg::UnknownPreset, Msg::Yes,
+        Msg::No, Msg::AnswerYesNo, Msg::ConfigFound, Msg::DeleteConfig, Msg::ChooseAlphabet,
+        Msg::ConfigSetup, Msg::ManualSetup, Msg::PresetLine, Msg::Choice, Msg::PlugboardSetup,
+        Msg::EnterPairs, Msg::AddPair, Msg::NeedTwoChars, Msg::PairSelf, Msg::PairNotInAlphabet,
+        Msg::PairSymbolTaken, Msg::PairNotFound, Msg::PairsEmpty, Msg::HowManyBlocks,
+        Msg::HowManyPairs, Msg::SaveConfig, Msg::WizardStrength, Msg::WizardRotors,
+        Msg::WizardSpeed, Msg::WizardUnknownAnswer, Msg::ConfigError, Msg::MemoryConfirm,
+        Msg::PresetEstimate, Msg::ContinuePrompt, Msg::CommandPrompt, Msg::MessagePrompt,
+        Msg::StateHeader, Msg::MoreBlocks, Msg::SetPositionsUsage, Msg::PositionsSet, Msg::Bitness,
+        Msg::Period, Msg::ShortPeriod, Msg::ReflectorFixedPoint, Msg::DegenerateConfig,
+        Msg::EffectiveBitness, Msg::KatFailed, Msg::BenchUsage, Msg::BenchSkipped, Msg::BenchHeader,
+        Msg::BenchConstruct, Msg::BenchFreqTitle, Msg::BenchFreqHeader, Msg::BenchFreqChiSquare,
+        Msg::ThreadTiming, Msg::UnknownCommand, Msg::VectorOk, Msg::VectorMismatch,
+        Msg::VectorFailed, Msg::VectorBlessed, Msg::VectorsSummary, Msg::VectorsBlessHint,
+        Msg::AnalyzeUsage, Msg::AnalyzeTotal, Msg::AnalyzeHeader, Msg::AnalyzeChiSquare,
+        Msg::AnalyzeIoc, Msg::AnalyzeBigrams, Msg::CrackUsage, Msg::CrackFound, Msg::CrackCandidate,
+        Msg::CrackMore, Msg::CrackPositionsUsage, Msg::CrackPositionsFound,
+        Msg::CrackPositionsNotFound, Msg::ServeUsage, Msg::ServeListening, Msg::ServeNotFound,
+        Msg::ServeMethodNotAllowed, Msg::ServeBodyTooLarge, Msg::ServeBadJson, Msg::ImportUsage,
+        Msg::ImportFailed, Msg::ConfigImported, Msg::ImportMnemonicUsage, Msg::MnemonicExportFailed,
+        Msg::MnemonicImportFailed, Msg::GenConfigUsage, Msg::SurveyUsage, Msg::SurveyHeader,
+        Msg::SurveyAverage, Msg::PassphrasePrompt, Msg::EmptyPassphrase, Msg::ClipboardUsage,
+        Msg::ClipboardUnavailable, Msg::ClipboardNotBuilt, Msg::ClipboardDone,
+    ];
+
+    /// Текст на текущем языке
+    pub fn text(self) -> &'static str {
+        self.in_lang(lang())
+    }
+
+    /// Текст на текущем языке с подставленными аргументами
+    pub fn fill(self, args: &[&dyn fmt::Display]) -> String {
+        let mut out = String::new();
+        let mut args = args.iter();
+        let mut parts = self.text().split("{}");
+        out.push_str(parts.next().unwrap_or_default());
+        for part in parts {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+            out.push_str(part);
+        }
+        out
+    }
+
+    pub fn in_lang(self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ru => self.ru(),
+            Lang::En => self.en(),
+        }
+    }
+
+    fn ru(self) -> &'static str {
+        match self {
+            Msg::EmptyBlock => "Пустой блок: нужен хотя бы один ротор",
+            Msg::NoBlocks => {
+                "В конфиге нет ни одного блока: без роторов шифр — неизменная простая замена. \
+                 Если это и нужно, запустите с --allow-degenerate"
+            }
+            Msg::TooManyRotors => "Блок {} содержит {} роторов (максимум {})",
+            Msg::UnknownColor => "Неизвестный цвет: {}",
+            Msg::IntegrityFailure => {
+                "Проверка целостности не пройдена: шифротекст изменён или ключ не тот"
+            }
+            Msg::BlockOutOfRange => "Нет блока {} (всего блоков: {})",
+            Msg::RotorOutOfRange => "Блок {}: нет ротора {} (всего роторов: {})",
+            Msg::RotorPositionCountMismatch => "Блок {}: {} позиций на {} роторов",
+            Msg::ShiftOutOfRange => "Сдвиг {} вне алфавита из {} символов",
+            Msg::PositionOutOfRange => "Блок {}, ротор {}: позиция {} вне алфавита из {} символов",
+            Msg::PlugboardUnknownChar => "Символа {} из plugboard нет в алфавите",
+            Msg::PlugboardCharReused => "Символ {} встречается в plugboard больше одного раза",
+            Msg::PlugboardSelfPair => "Символ {} соединён в plugboard сам с собой",
+            Msg::EmptyChain => "В цепочке нет ни одного конфига",
+            Msg::PositionBlockCountMismatch => "rotor_positions: {} векторов на {} блоков",
+            Msg::UnknownReflector => {
+                "Неизвестный отражатель: {} (есть reverse, identity-pairs, ukw-b, ukw-c)"
+            }
+            Msg::UnknownAlphabet => "Неизвестный алфавит: {} (есть {})",
+            Msg::ReflectorAlphabetMismatch => {
+                "Отражатель {} не подходит к алфавиту из {} символов"
+            }
+            Msg::TooManyPlugboardPairs => "{} пар plugboard не уместить в алфавит из {} символов",
+            Msg::HomophonicUnsupported => {
+                "Омофоны работают только с буквенным алфавитом и без цифрового ротора"
+            }
+            Msg::ReversedBlockCountMismatch => "reversed: {} векторов на {} блоков",
+            Msg::ReversedRotorCountMismatch => "Блок {}: {} флагов reversed на {} роторов",
+            Msg::BufferTooSmall => "Шифротекст не поместился в буфер: записано {} байт",
+            Msg::ZeroStepInterval => "step_interval должен быть не меньше 1",
+            Msg::CascadeAlphabetMismatch => {
+                "Машина {} каскада: алфавит не тот, что у предыдущей, и её символы прошли бы \
+                 без шифрования (разрешить — strict: false или --no-strict)"
+            }
+            Msg::CascadeCaseMismatch => {
+                "Машина {} каскада приводит текст к нижнему регистру, а шифротекст \
+                 предыдущей — байты с заглавными буквами: он бы испортился"
+            }
+            Msg::StepEveryBlockCountMismatch => "step_every: {} делителей на {} блоков",
+            Msg::ZeroStepEvery => "step_every: у блока {} нулевой делитель, он бы не шагал",
+            Msg::PlugboardPairsAndMap => "Заданы и plugboard, и plugboard_map: нужно что-то одно",
+            Msg::PlugboardMapOpen => {
+                "plugboard_map: в {} ведёт провод, но из него провода нет — это не перестановка"
+            }
+            Msg::UnsupportedDerivationVersion => {
+                "Версия {} вывода конфига из фразы не поддерживается"
+            }
+            Msg::BadDerivationParams => "Недопустимые параметры argon2id для вывода конфига",
+            Msg::BadFileHeader => "Заголовок файла ESD1 обрезан или испорчен",
+            Msg::UnsupportedFileVersion => {
+                "Файл версии {}: эта сборка её не знает, обновите программу"
+            }
+            Msg::FileFingerprintMismatch => {
+                "Файл зашифрован другим конфигом (или с другими стартовыми позициями)"
+            }
+            Msg::BadArmor => "Текст не разбирается как {}: броня испорчена или обрезана",
+            Msg::SearchSpaceTooLarge => {
+                "Перебор больше {} вариантов; уменьшите --max-blocks или --max-rotors"
+            }
+            Msg::OutputExists => "{} уже существует; перезаписать — --force",
+            Msg::DirUsage => {
+                "Использование: encrypt-dir|decrypt-dir <каталог> --out <каталог> \
+                 [--encrypt-names] [--jobs N] [--force]"
+            }
+            Msg::CheckpointNeedsNoTag => {
+                "--checkpoint и --resume не работают с тегом целостности (--authenticate)"
+            }
+            Msg::CheckpointNoArmor => {
+                "--checkpoint и --resume пишут двоичный файл: без --encode и --decode"
+            }
+            Msg::DirSkipped => "Предупреждение: {} — ссылка или не обычный файл, пропущен",
+            Msg::DirDone => "Файлов: {} → {}",
+            Msg::BuildProgress => "Построение машины: {}%",
+            Msg::InputPlainOnly => "--input: JSON-вывод и трасса не поддерживаются",
+            Msg::ProgressEncrypting => "Шифрование",
+            Msg::ProgressDecrypting => "Расшифровка",
+            Msg::ProgressGenerating => "Генерация конфига",
+            Msg::MissingValue => "{}: не указано значение",
+            Msg::BadOutputFormat => "--output-format: ожидается text или json",
+            Msg::BadLang => "--lang: ожидается ru или en",
+            Msg::BadSeed => "--seed: ожидается целое число от 0 до 2^64-1",
+            Msg::BadIv => "--iv: ожидается целое число от 0 до 2^64-1",
+            Msg::IvUnsupported => {
+                "--iv сдвигает каждое сообщение от начала машины: без --advance-config, \
+                 --checkpoint, --resume и encrypt-dir/decrypt-dir"
+            }
+            Msg::BadWrap => "--wrap: ожидается ширина строки — целое число больше нуля",
+            Msg::BadEncoding => "--encode и --decode: ожидается hex, base64 или none",
+            Msg::Result => "Результат: {}",
+            Msg::FileUsage => {
+                "Использование: encrypt-file|decrypt-file <вход> <выход> [--binary] [--force] \
+                 [--checkpoint МиБ] [--resume]"
+            }
+            Msg::CascadeFilesOnly => {
+                "Несколько --config — каскад машин: только encrypt-file и decrypt-file, без тега \
+                 целостности, --trace, JSON и --advance-config"
+            }
+            Msg::ReadFailed => "Не удалось прочитать {}: {}",
+            Msg::WriteFailed => "Не удалось записать {}: {}",
+            Msg::WriteResultFailed => "Не удалось записать результат: {}",
+            Msg::LoadFailed => "Не удалось загрузить {}: {}",
+            Msg::FilterNeedsFiles => "С тегом целостности используйте encrypt-file/decrypt-file",
+            Msg::JsonNeedsUtf8 => "JSON-вывод и трасса поддерживаются только для текста в UTF-8",
+            Msg::TraceTruncated => "Трасса показана только для первых {} символов",
+            Msg::StepsHeader => "Позиции роторов до шага, блоков показано: {} из {}",
+            Msg::UnknownArgument => "Неизвестная команда: {}",
+            Msg::UnknownPreset => "Неизвестный пресет: {}",
+            Msg::Yes => "да",
+            Msg::No => "нет",
+            Msg::AnswerYesNo => "Ответьте «да» или «нет» (можно «д»/«н», «y»/«n»)",
+            Msg::ConfigFound => "Найден конфиг, загрузить? (да/нет): ",
+            Msg::DeleteConfig => "Удалить {} со старым ключом? (да/нет): ",
+            Msg::ChooseAlphabet => "Выберите алфавит:",
+            Msg::ConfigSetup => "\nНастройка конфигурации:",
+            Msg::ManualSetup => "0) Я сам всё настрою",
+            Msg::PresetLine => "{}) {} — {} (блоков: {}, скорость: {}/10)",
+            Msg::Choice => "Выбор: ",
+            Msg::PlugboardSetup => {
+                "Настройка plugboard (взаимозамен):\n1) Ввести вручную\n2) Сгенерировать случайно"
+            }
+            Msg::EnterPairs => {
+                "Вводите пары 'a b', пробел — '_'. 'remove a' — убрать пару с 'a', 'list' — \
+                 показать пары, 'clear' — убрать все. Пустая строка — выход."
+            }
+            Msg::AddPair => "Добавить пару: ",
+            Msg::NeedTwoChars => "Нужно ровно два символа через пробел.",
+            Msg::PairSelf => "Символ нельзя соединить сам с собой.",
+            Msg::PairNotInAlphabet => "Символа '{}' нет в алфавите.",
+            Msg::PairSymbolTaken => "Символ '{}' уже в паре, сначала 'remove {}'.",
+            Msg::PairNotFound => "Пары с символом '{}' нет.",
+            Msg::PairsEmpty => "Пар пока нет.",
+            Msg::HowManyBlocks => "Сколько блоков? ",
+            Msg::HowManyPairs => "Сколько пар? (Enter — {}): ",
+            Msg::SaveConfig => {
+                "Сохранить конфиг? (да/нет; regen — сгенерировать заново, blocks N или \
+                 pairs N — другое число блоков или пар plugboard): "
+            }
+            Msg::WizardStrength => "\nСтойкость: {} бит наивно, {} бит эффективно",
+            Msg::WizardRotors => "Роторов: {} в {} блоках",
+            Msg::WizardSpeed => "Скорость: ~{} символов/с, {} символов — ~{} с",
+            Msg::WizardUnknownAnswer => "Ответ не распознан.",
+            Msg::ConfigError => "Ошибка в конфиге: {}",
+            Msg::MemoryConfirm => {
+                "Машине понадобится примерно {} ГиБ ОЗУ. Продолжить? (да/нет): "
+            }
+            Msg::PresetEstimate => {
+                "Пресет «{}»: {} блоков, примерно {} ГиБ ОЗУ и {} с на генерацию."
+            }
+            Msg::ContinuePrompt => "Продолжить? (да/нет): ",
+            Msg::CommandPrompt => {
+                "Команда (encrypt [текст]/decrypt [текст]/state/set-positions/benchmark/exit): "
+            }
+            Msg::MessagePrompt => "Сообщение (<< — несколько строк до строки «.»): ",
+            Msg::StateHeader => "Алфавит: {} симв., пар plugboard: {}, блоков: {}",
+            Msg::MoreBlocks => "… и ещё блоков: {}",
+            Msg::SetPositionsUsage => "Использование: set-positions <блок> <p1,p2,...> [save]",
+            Msg::PositionsSet => "Позиции блока {} обновлены",
+            Msg::Bitness => "\nБитность конфигурации: {} бит (A = {}, R = {}, P = {})",
+            Msg::Period => "Период: {} символов",
+            Msg::ShortPeriod => {
+                "Внимание: период короче {} символов — сдвиги повторятся внутри \
+                 одного сообщения. Добавьте роторов в самый длинный блок."
+            }
+            Msg::DegenerateConfig => {
+                "Внимание: в {} нет ни одного блока — шифр сведён к неизменной простой замене \
+                 (--allow-degenerate)."
+            }
+            Msg::ReflectorFixedPoint => {
+                "Внимание: отражатель {} оставляет позиции алфавита из {} символов \
+                 на месте — иногда символ будет шифроваться сам в себя."
+            }
+            Msg::EffectiveBitness => {
+                "Эффективно: {} бит — роторы сводятся к одному сдвигу на символ, \
+                 период 2^{} символов"
+            }
+            Msg::KatFailed => "KAT FAILED на size = {}: decrypt(encrypt(text)) != text",
+            Msg::BenchUsage => {
+                "Использование: benchmark [--sizes <1k,100k,10m>] [--max-seconds <N>] [--json | \
+                 --csv] [--freq]"
+            }
+            Msg::BenchSkipped => "Не уложились в --max-seconds, пропущены размеры: {}",
+            Msg::BenchHeader => {
+                "размер|encrypt, с|decrypt, с|encrypt, симв/с|decrypt, симв/с|KAT"
+            }
+            Msg::BenchConstruct => "Построение машины (один раз на все размеры): {} с",
+            Msg::BenchFreqTitle => "Частоты букв на тексте из {} символов:",
+            Msg::BenchFreqHeader => "буква|открытый|шифр",
+            Msg::BenchFreqChiSquare => {
+                "Хи-квадрат от равномерного: открытый {}, шифр {} (степеней свободы: {})"
+            }
+            Msg::ThreadTiming => "{} потоков → {} с (ускорение x{})",
+            Msg::UnknownCommand => "Неизвестная команда.",
+            Msg::VectorOk => "{}: ок",
+            Msg::VectorMismatch => "{}: расхождение\n  ожидалось: {}\n  получено:  {}",
+            Msg::VectorFailed => "{}: {}",
+            Msg::VectorBlessed => "{}: шифротекст перезаписан",
+            Msg::VectorsSummary => "Совпало векторов: {} из {}",
+            Msg::VectorsBlessHint => {
+                "Если алгоритм изменён намеренно, обновите векторы: \
+                 cargo run --bin gen_vectors -- --bless"
+            }
+            Msg::AnalyzeUsage => "Использование: analyze <файл> [--json] [--alphabet <алфавит>]",
+            Msg::AnalyzeTotal => "Символов алфавита: {}",
+            Msg::AnalyzeHeader => "буква|кол-во|доля, %",
+            Msg::AnalyzeChiSquare => "Хи-квадрат от равномерного: {}",
+            Msg::AnalyzeIoc => "Индекс совпадений: {} (у равномерного текста {})",
+            Msg::AnalyzeBigrams => "Частые биграммы: {}",
+            Msg::CrackUsage => {
+                "Использование: crack --crib <начало текста> [--alphabet <алфавит>] \
+                 [--max-blocks N] [--max-rotors N] [файл]"
+            }
+            Msg::CrackFound => "Найдено кандидатов: {}",
+            Msg::CrackCandidate => "  блоки {}, позиции {}: {}",
+            Msg::CrackMore => "  … и ещё {}",
+            Msg::CrackPositionsUsage => {
+                "Использование: crack-positions --plain <открытый текст> --cipher <шифротекст>"
+            }
+            Msg::CrackPositionsFound => "Стартовые позиции: {}",
+            Msg::ImportUsage => "Использование: import <код из export>",
+            Msg::ImportFailed => "Не удалось разобрать код конфига: {}",
+            Msg::ConfigImported => "Конфиг записан в {}",
+            Msg::ImportMnemonicUsage => "Использование: import-mnemonic <слова из export-mnemonic>",
+            Msg::MnemonicExportFailed => "Конфиг не записать словами: {}",
+            Msg::MnemonicImportFailed => "Не удалось разобрать слова конфига: {}",
+            Msg::GenConfigUsage => {
+                "Использование: gen-config [--from-passphrase] [--alphabet latin|cyrillic|bytes] \
+                 [--blocks <N>] [--pairs <N>]"
+            }
+            Msg::SurveyUsage => {
+                "Использование: survey [--alphabet <алфавит>] [--blocks <N,N,...>] \
+                 [--count <N>] [--pairs <N>]"
+            }
+            Msg::SurveyHeader => "блоков|роторов|бит",
+            Msg::SurveyAverage => "в среднем для {} блоков: {} бит",
+            Msg::PassphrasePrompt => "Парольная фраза: ",
+            Msg::EmptyPassphrase => "Парольная фраза пуста",
+            Msg::ClipboardUsage => "Использование: encrypt|decrypt --clipboard",
+            Msg::ClipboardUnavailable => "Буфер обмена недоступен: {}",
+            Msg::ClipboardNotBuilt => "программа собрана без фичи clipboard",
+            Msg::ClipboardDone => "Результат в буфере обмена, символов: {}",
+            Msg::CrackPositionsNotFound => {
+                "Позиции не найдены: нет совпадений или машина слишком велика для перебора"
+            }
+            Msg::ServeUsage => "Использование: serve [--listen <адрес:порт>] [--max-body <байт>]",
+            Msg::ServeListening => "Слушаю http://{}",
+            Msg::ServeNotFound => "Нет такого пути: есть только /encrypt и /decrypt",
+            Msg::ServeMethodNotAllowed => "Нужен POST",
+            Msg::ServeBodyTooLarge => "Тело запроса больше {} байт",
+            Msg::ServeBadJson => "Некорректный JSON: {}",
+        }
+    }
+
+    fn en(self) -> &'static str {
+        match self {
+            Msg::EmptyBlock => "Empty block: at least one rotor is required",
+            Msg::NoBlocks => {
+                "The config has no blocks: without rotors the cipher is a fixed simple \
+                 substitution. If that is what you want, run with --allow-degenerate"
+            }
+            Msg::TooManyRotors => "Block {} has {} rotors (maximum {})",
+            Msg::UnknownColor => "Unknown color: {}",
+            Msg::IntegrityFailure => {
+                "Integrity check failed: the ciphertext was modified or the key is wrong"
+            }
+            Msg::BlockOutOfRange => "No block {} (the machine has {})",
+            Msg::RotorOutOfRange => "Block {}: no rotor {} (the block has {})",
+            Msg::RotorPositionCountMismatch => "Block {}: {} positions for {} rotors",
+            Msg::ShiftOutOfRange => "Shift {} is outside the {}-character alphabet",
+            Msg::PositionOutOfRange => {
+                "Block {}, rotor {}: position {} is outside the {}-character alphabet"
+            }
+            Msg::PlugboardUnknownChar => "Plugboard character {} is not in the alphabet",
+            Msg::PlugboardCharReused => "Plugboard character {} is used more than once",
+            Msg::PlugboardSelfPair => "Plugboard character {} is paired with itself",
+            Msg::EmptyChain => "The chain has no configs",
+            Msg::PositionBlockCountMismatch => "rotor_positions: {} vectors for {} blocks",
+            Msg::UnknownReflector => {
+                "Unknown reflector: {} (available: reverse, identity-pairs, ukw-b, ukw-c)"
+            }
+            Msg::UnknownAlphabet => "Unknown alphabet: {} (available: {})",
+            Msg::ReflectorAlphabetMismatch => {
+                "Reflector {} does not fit an alphabet of {} characters"
+            }
+            Msg::TooManyPlugboardPairs => {
+                "{} plugboard pairs do not fit an alphabet of {} characters"
+            }
+            Msg::HomophonicUnsupported => {
+                "Homophones need a letter alphabet and no digit rotor"
+            }
+            Msg::ReversedBlockCountMismatch => "reversed: {} vectors for {} blocks",
+            Msg::ReversedRotorCountMismatch => "Block {}: {} reversed flags for {} rotors",
+            Msg::BufferTooSmall => "Ciphertext does not fit the buffer: {} bytes written",
+            Msg::ZeroStepInterval => "step_interval must be at least 1",
+            Msg::CascadeAlphabetMismatch => {
+                "Cascade machine {}: alphabet differs from the previous one, so its symbols \
+                 would pass unencrypted (allow with strict: false or --no-strict)"
+            }
+            Msg::CascadeCaseMismatch => {
+                "Cascade machine {} lowercases its input, but the previous one outputs \
+                 bytes with uppercase letters: the ciphertext would be corrupted"
+            }
+            Msg::StepEveryBlockCountMismatch => "step_every: {} divisors for {} blocks",
+            Msg::ZeroStepEvery => "step_every: block {} has a zero divisor and would never step",
+            Msg::PlugboardPairsAndMap => "Both plugboard and plugboard_map are set: use only one",
+            Msg::PlugboardMapOpen => {
+                "plugboard_map: a wire leads into {} but none leads out, so it is not a permutation"
+            }
+            Msg::UnsupportedDerivationVersion => {
+                "Passphrase derivation version {} is not supported"
+            }
+            Msg::BadDerivationParams => "Invalid argon2id parameters for config derivation",
+            Msg::BadFileHeader => "The ESD1 file header is truncated or corrupt",
+            Msg::UnsupportedFileVersion => {
+                "File format version {} is unknown to this build; please upgrade"
+            }
+            Msg::FileFingerprintMismatch => {
+                "The file was encrypted with a different config (or other start positions)"
+            }
+            Msg::BadArmor => "The text does not parse as {}: the armor is corrupt or truncated",
+            Msg::SearchSpaceTooLarge => {
+                "The search exceeds {} candidates; lower --max-blocks or --max-rotors"
+            }
+            Msg::OutputExists => "{} already exists; pass --force to overwrite",
+            Msg::DirUsage => {
+                "Usage: encrypt-dir|decrypt-dir <directory> --out <directory> \
+                 [--encrypt-names] [--jobs N] [--force]"
+            }
+            Msg::CheckpointNeedsNoTag => {
+                "--checkpoint and --resume do not work with an integrity tag (--authenticate)"
+            }
+            Msg::CheckpointNoArmor => {
+                "--checkpoint and --resume write a binary file: no --encode or --decode"
+            }
+            Msg::DirSkipped => "Warning: {} is a symlink or not a regular file, skipped",
+            Msg::DirDone => "Files: {} → {}",
+            Msg::BuildProgress => "Building machine: {}%",
+            Msg::InputPlainOnly => "--input: JSON output and tracing are not supported",
+            Msg::ProgressEncrypting => "Encrypting",
+            Msg::ProgressDecrypting => "Decrypting",
+            Msg::ProgressGenerating => "Generating config",
+            Msg::MissingValue => "{}: missing value",
+            Msg::BadOutputFormat => "--output-format: expected text or json",
+            Msg::BadLang => "--lang: expected ru or en",
+            Msg::BadSeed => "--seed: expected an integer from 0 to 2^64-1",
+            Msg::BadIv => "--iv: expected an integer from 0 to 2^64-1",
+            Msg::IvUnsupported => {
+                "--iv offsets every message from the start of the machine: no --advance-config, \
+                 --checkpoint, --resume or encrypt-dir/decrypt-dir"
+            }
+            Msg::BadWrap => "--wrap: expected a line width, an integer greater than zero",
+            Msg::BadEncoding => "--encode and --decode: expected hex, base64 or none",
+            Msg::Result => "Result: {}",
+            Msg::FileUsage => {
+                "Usage: encrypt-file|decrypt-file <input> <output> [--binary] [--force] \
+                 [--checkpoint MiB] [--resume]"
+            }
+            Msg::CascadeFilesOnly => {
+                "Several --config make a cascade of machines: encrypt-file and decrypt-file \
+                 only, without integrity tags, --trace, JSON or --advance-config"
+            }
+            Msg::ReadFailed => "Failed to read {}: {}",
+            Msg::WriteFailed => "Failed to write {}: {}",
+            Msg::WriteResultFailed => "Failed to write the result: {}",
+            Msg::LoadFailed => "Failed to load {}: {}",
+            Msg::FilterNeedsFiles => "Use encrypt-file/decrypt-file with the integrity tag",
+            Msg::JsonNeedsUtf8 => "JSON output and tracing are only supported for UTF-8 text",
+            Msg::TraceTruncated => "The trace only covers the first {} characters",
+            Msg::StepsHeader => "Rotor positions before stepping, blocks shown: {} of {}",
+            Msg::UnknownArgument => "Unknown command: {}",
+            Msg::UnknownPreset => "Unknown preset: {}",
+            Msg::Yes => "yes",
+            Msg::No => "no",
+            Msg::AnswerYesNo => "Please answer \"yes\" or \"no\" (also y/n, д/н)",
+            Msg::ConfigFound => "Config found, load it? (yes/no): ",
+            Msg::DeleteConfig => "Delete {} with the old key? (yes/no): ",
+            Msg::ChooseAlphabet => "Choose an alphabet:",
+            Msg::ConfigSetup => "\nConfiguration:",
+            Msg::ManualSetup => "0) I'll set everything up myself",
+            Msg::PresetLine => "{}) {} — {} (blocks: {}, speed: {}/10)",
+            Msg::Choice => "Choice: ",
+            Msg::PlugboardSetup => {
+                "Plugboard setup (swaps):\n1) Enter manually\n2) Generate randomly"
+            }
+            Msg::EnterPairs => {
+                "Enter pairs as 'a b', '_' for a space. 'remove a' drops the pair with 'a', \
+                 'list' shows pairs, 'clear' drops all. An empty line finishes."
+            }
+            Msg::AddPair => "Add pair: ",
+            Msg::NeedTwoChars => "Exactly two characters separated by a space are required.",
+            Msg::PairSelf => "A character cannot be paired with itself.",
+            Msg::PairNotInAlphabet => "Character '{}' is not in the alphabet.",
+            Msg::PairSymbolTaken => "Character '{}' is already paired, use 'remove {}' first.",
+            Msg::PairNotFound => "No pair contains '{}'.",
+            Msg::PairsEmpty => "No pairs yet.",
+            Msg::HowManyBlocks => "How many blocks? ",
+            Msg::HowManyPairs => "How many pairs? (Enter for {}): ",
+            Msg::SaveConfig => {
+                "Save the config? (yes/no; regen to generate again, blocks N or pairs N for \
+                 another number of blocks or plugboard pairs): "
+            }
+            Msg::WizardStrength => "\nStrength: {} bits naive, {} bits effective",
+            Msg::WizardRotors => "Rotors: {} in {} blocks",
+            Msg::WizardSpeed => "Speed: ~{} characters/s, {} characters in ~{} s",
+            Msg::WizardUnknownAnswer => "Answer not recognised.",
+            Msg::ConfigError => "Config error: {}",
+            Msg::MemoryConfirm => "The machine needs about {} GiB of RAM. Continue? (yes/no): ",
+            Msg::PresetEstimate => {
+                "Preset \"{}\": {} blocks, about {} GiB of RAM and {} s to generate."
+            }
+            Msg::ContinuePrompt => "Continue? (yes/no): ",
+            Msg::CommandPrompt => {
+                "Command (encrypt [text]/decrypt [text]/state/set-positions/benchmark/exit): "
+            }
+            Msg::MessagePrompt => "Message (<< for several lines up to a \".\" line): ",
+            Msg::StateHeader => "Alphabet: {} chars, plugboard pairs: {}, blocks: {}",
+            Msg::MoreBlocks => "… and {} more blocks",
+            Msg::SetPositionsUsage => "Usage: set-positions <block> <p1,p2,...> [save]",
+            Msg::PositionsSet => "Block {} positions updated",
+            Msg::Bitness => "\nConfiguration bitness: {} bits (A = {}, R = {}, P = {})",
+            Msg::Period => "Period: {} characters",
+            Msg::ShortPeriod => {
+                "Warning: period is shorter than {} characters — shifts will repeat \
+                 within a single message. Add rotors to the longest block."
+            }
+            Msg::DegenerateConfig => {
+                "Warning: {} has no blocks — the cipher is reduced to a fixed simple substitution \
+                 (--allow-degenerate)."
+            }
+            Msg::ReflectorFixedPoint => {
+                "Warning: reflector {} leaves positions of an alphabet of {} characters \
+                 in place — a character will sometimes encrypt to itself."
+            }
+            Msg::EffectiveBitness => {
+                "Effective: {} bits — the rotors reduce to one shift per character, \
+                 period 2^{} characters"
+            }
+            Msg::KatFailed => "KAT FAILED at size = {}: decrypt(encrypt(text)) != text",
+            Msg::BenchUsage => {
+                "Usage: benchmark [--sizes <1k,100k,10m>] [--max-seconds <N>] [--json | --csv] \
+                 [--freq]"
+            }
+            Msg::BenchSkipped => "Did not fit in --max-seconds, skipped sizes: {}",
+            Msg::BenchHeader => {
+                "size|encrypt, s|decrypt, s|encrypt, chars/s|decrypt, chars/s|KAT"
+            }
+            Msg::BenchConstruct => "Machine construction (once for all sizes): {} s",
+            Msg::BenchFreqTitle => "Letter frequencies on a {}-character text:",
+            Msg::BenchFreqHeader => "letter|plain|cipher",
+            Msg::BenchFreqChiSquare => {
+                "Chi-square from uniform: plaintext {}, ciphertext {} (degrees of freedom: {})"
+            }
+            Msg::ThreadTiming => "{} threads → {} s (speedup x{})",
+            Msg::UnknownCommand => "Unknown command.",
+            Msg::VectorOk => "{}: ok",
+            Msg::VectorMismatch => "{}: mismatch\n  expected: {}\n  got:      {}",
+            Msg::VectorFailed => "{}: {}",
+            Msg::VectorBlessed => "{}: ciphertext rewritten",
+            Msg::VectorsSummary => "Vectors matched: {} of {}",
+            Msg::VectorsBlessHint => {
+                "If the algorithm was changed on purpose, regenerate the vectors: \
+                 cargo run --bin gen_vectors -- --bless"
+            }
+            Msg::AnalyzeUsage => "Usage: analyze <file> [--json] [--alphabet <alphabet>]",
+            Msg::AnalyzeTotal => "Alphabet characters: {}",
+            Msg::AnalyzeHeader => "letter|count|share, %",
+            Msg::AnalyzeChiSquare => "Chi-square from uniform: {}",
+            Msg::AnalyzeIoc => "Index of coincidence: {} (uniform text: {})",
+            Msg::AnalyzeBigrams => "Most common bigrams: {}",
+            Msg::CrackUsage => {
+                "Usage: crack --crib <plaintext start> [--alphabet <alphabet>] \
+                 [--max-blocks N] [--max-rotors N] [file]"
+            }
+            Msg::CrackFound => "Candidates found: {}",
+            Msg::CrackCandidate => "  blocks {}, positions {}: {}",
+            Msg::CrackMore => "  … and {} more",
+            Msg::CrackPositionsUsage => {
+                "Usage: crack-positions --plain <plaintext> --cipher <ciphertext>"
+            }
+            Msg::CrackPositionsFound => "Start positions: {}",
+            Msg::ImportUsage => "Usage: import <code from export>",
+            Msg::ImportFailed => "Could not parse the config code: {}",
+            Msg::ConfigImported => "Config written to {}",
+            Msg::ImportMnemonicUsage => "Usage: import-mnemonic <words from export-mnemonic>",
+            Msg::MnemonicExportFailed => "Cannot write the config as words: {}",
+            Msg::MnemonicImportFailed => "Could not parse the config words: {}",
+            Msg::GenConfigUsage => {
+                "Usage: gen-config [--from-passphrase] [--alphabet latin|cyrillic|bytes] \
+                 [--blocks <N>] [--pairs <N>]"
+            }
+            Msg::SurveyUsage => {
+                "Usage: survey [--alphabet <alphabet>] [--blocks <N,N,...>] \
+                 [--count <N>] [--pairs <N>]"
+            }
+            Msg::SurveyHeader => "blocks|rotors|bits",
+            Msg::SurveyAverage => "average for {} blocks: {} bits",
+            Msg::PassphrasePrompt => "Passphrase: ",
+            Msg::EmptyPassphrase => "The passphrase is empty",
+            Msg::ClipboardUsage => "Usage: encrypt|decrypt --clipboard",
+            Msg::ClipboardUnavailable => "Clipboard unavailable: {}",
+            Msg::ClipboardNotBuilt => "the program was built without the clipboard feature",
+            Msg::ClipboardDone => "Result copied to the clipboard: {} characters",
+            Msg::CrackPositionsNotFound => {
+                "No positions found: nothing matches or the machine is too large to search"
+            }
+            Msg::ServeUsage => "Usage: serve [--listen <address:port>] [--max-body <bytes>]",
+            Msg::ServeListening => "Listening on http://{}",
+            Msg::ServeNotFound => "No such path: only /encrypt and /decrypt exist",
+            Msg::ServeMethodNotAllowed => "POST required",
+            Msg::ServeBodyTooLarge => "Request body exceeds {} bytes",
+            Msg::ServeBadJson => "Malformed JSON: {}",
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Конфиг словами: то же, что `export_code`, но его можно продиктовать или
+//! переписать с бумаги.
+//!
+//! Двоичный формат без `ESDB` режется на куски по 11 бит, каждый кусок —
+//! слово английского списка BIP39 (2048 слов, первые четыре буквы
+//! различают любые два). Хвост последнего куска добит нулями. За данными
+//! идёт слово проверки: 10 старших бит SHA-256 данных и младший бит —
+//! чётность их длины. По числу слов длина известна с точностью до байта,
+//! чётность снимает неоднозначность.
+
+use crate::ConfigData;
+use crate::config_bin::{self, MAGIC};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::sync::OnceLock;
+
+/// Больше слов не выдаём: четыре фразы BIP39 по 24 слова. Конфиг из
+/// `gen-config` с четырьмя блоками помещается, сотни блоков — нет.
+pub const MAX_MNEMONIC_WORDS: usize = 96;
+
+const WORD_BITS: u32 = 11;
+
+fn words() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| include_str!("mnemonic_words.txt").lines().collect())
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Слово проверки для данных `payload`
+fn check_word(payload: &[u8]) -> usize {
+    let hash = Sha256::digest(payload);
+    let top = (hash[0] as usize) << 2 | (hash[1] >> 6) as usize;
+    (top << 1) | (payload.len() % 2)
+}
+
+/// Конфиг в слова через пробел. Конфиг длиннее `MAX_MNEMONIC_WORDS` слов —
+/// ошибка `InvalidInput` с пределом в тексте.
+pub fn encode(cfg: &ConfigData) -> io::Result<String> {
+    let data = config_bin::encode(cfg)?;
+    let payload = &data[MAGIC.len()..];
+    let count = (payload.len() * 8).div_ceil(WORD_BITS as usize) + 1;
+    if count > MAX_MNEMONIC_WORDS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "config takes {} words, a mnemonic holds at most {} \
+                 ({} bytes of binary config)",
+                count,
+                MAX_MNEMONIC_WORDS,
+                max_payload() + MAGIC.len(),
+            ),
+        ));
+    }
+    let list = words();
+    let mut out = Vec::with_capacity(count);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &b in payload {
+        acc = acc << 8 | b as u32;
+        bits += 8;
+        while bits >= WORD_BITS {
+            bits -= WORD_BITS;
+            out.push(list[(acc >> bits) as usize & 0x7ff]);
+        }
+    }
+    if bits > 0 {
+        out.push(list[(acc << (WORD_BITS - bits)) as usize & 0x7ff]);
+    }
+    out.push(list[check_word(payload)]);
+    Ok(out.join(" "))
+}
+
+/// Сколько байт данных помещается в `MAX_MNEMONIC_WORDS` слов
+fn max_payload() -> usize {
+    (MAX_MNEMONIC_WORDS - 1) * WORD_BITS as usize / 8
+}
+
+/// Слова `encode` обратно в конфиг. Регистр и лишние пробелы не важны;
+/// неизвестное слово, неверное слово проверки или число слов — ошибка
+/// `InvalidData`.
+pub fn decode(mnemonic: &str) -> io::Result<ConfigData> {
+    let list = words();
+    let indices = mnemonic
+        .split_whitespace()
+        .map(|w| {
+            let w = w.to_lowercase();
+            list.binary_search(&w.as_str()).map_err(|_| invalid(format!("unknown word {:?}", w)))
+        })
+        .collect::<io::Result<Vec<usize>>>()?;
+    let Some((&check, data_words)) = indices.split_last() else {
+        return Err(invalid("empty mnemonic".into()));
+    };
+    if indices.len() > MAX_MNEMONIC_WORDS {
+        return Err(invalid(format!("more than {} words", MAX_MNEMONIC_WORDS)));
+    }
+    let total_bits = data_words.len() * WORD_BITS as usize;
+    // длин, дающих это число слов, одна или две подряд; чётность выбирает
+    let mut len = total_bits / 8;
+    if len % 2 != check & 1 {
+        len = len.saturating_sub(1);
+    }
+    if len == 0 || (len * 8).div_ceil(WORD_BITS as usize) != data_words.len() {
+        return Err(invalid("wrong number of words".into()));
+    }
+    let mut payload = Vec::with_capacity(len + 1);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &w in data_words {
+        acc = acc << WORD_BITS | w as u32;
+        bits += WORD_BITS;
+        while bits >= 8 {
+            bits -= 8;
+            payload.push((acc >> bits) as u8);
+        }
+    }
+    let padding = payload.split_off(len);
+    // ненулевой добивочный хвост — тоже искажённое слово
+    let dirty_tail = padding.iter().any(|&b| b != 0) || acc & ((1 << bits) - 1) != 0;
+    if dirty_tail || check_word(&payload) != check {
+        return Err(invalid("mnemonic checksum mismatch".into()));
+    }
+    let mut data = MAGIC.to_vec();
+    data.extend_from_slice(&payload);
+    config_bin::decode(&data)
+}
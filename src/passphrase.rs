@@ -0,0 +1,89 @@
+//! Конфиг из общей парольной фразы: обе стороны получают одну и ту же
+//! машину, не пересылая JSON.
+//!
+//! Фраза идёт через argon2id, солью служат метка, версия формата и все
+//! параметры вывода — изменённый параметр даёт другой конфиг, а не тот же
+//! молча. 32 байта на выходе — зерно ChaCha20, и из него `generate` строит
+//! блоки, позиции и plugboard так же, как из любого другого ГПСЧ. ChaCha20
+//! взят явно: алгоритм `StdRng` в новых версиях `rand` может смениться.
+
+use crate::prelude::*;
+use crate::{CipherError, ConfigData, plugboard};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use zeroize::Zeroize;
+
+/// Текущая версия вывода; старые версии продолжают поддерживаться
+pub const DERIVATION_VERSION: u32 = 1;
+
+/// Параметры вывода конфига из фразы. Обе стороны должны взять одинаковые:
+/// все поля входят в соль.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationParams {
+    /// Версия формата вывода, см. `DERIVATION_VERSION`
+    pub version: u32,
+    /// Память argon2id, КиБ
+    pub memory_kib: u32,
+    /// Число проходов argon2id
+    pub iterations: u32,
+    /// Число дорожек argon2id
+    pub parallelism: u32,
+    pub alphabet: String,
+    pub blocks: usize,
+    /// Сколько пар plugboard ставить
+    pub pairs: usize,
+}
+
+/// Рекомендация OWASP для argon2id (19 МиБ, 2 прохода) и машина пресета
+/// «безопасный»
+impl Default for DerivationParams {
+    fn default() -> Self {
+        DerivationParams {
+            version: DERIVATION_VERSION,
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+            alphabet: "latin".into(),
+            blocks: 4,
+            pairs: plugboard::DEFAULT_PAIRS,
+        }
+    }
+}
+
+impl DerivationParams {
+    /// Соль argon2id: метка, затем все поля фиксированной ширины в LE
+    fn salt(&self) -> Vec<u8> {
+        let mut salt = b"esd-passphrase".to_vec();
+        for v in [self.version, self.memory_kib, self.iterations, self.parallelism] {
+            salt.extend_from_slice(&v.to_le_bytes());
+        }
+        salt.extend_from_slice(&(self.alphabet.len() as u64).to_le_bytes());
+        salt.extend_from_slice(self.alphabet.as_bytes());
+        salt.extend_from_slice(&(self.blocks as u64).to_le_bytes());
+        salt.extend_from_slice(&(self.pairs as u64).to_le_bytes());
+        salt
+    }
+}
+
+impl ConfigData {
+    /// Детерминированный конфиг из парольной фразы: одна фраза и одни
+    /// параметры — один и тот же конфиг на любой машине и в любой версии,
+    /// пока поддерживается `params.version`. Дорого по памяти и времени
+    /// намеренно — так перебор фраз тоже дорог.
+    pub fn from_passphrase(phrase: &str, params: DerivationParams) -> Result<Self, CipherError> {
+        if params.version != DERIVATION_VERSION {
+            return Err(CipherError::UnsupportedDerivationVersion(params.version));
+        }
+        let kdf_params =
+            Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+                .map_err(|_| CipherError::BadDerivationParams)?;
+        let mut seed = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, kdf_params)
+            .hash_password_into(phrase.as_bytes(), &params.salt(), &mut seed)
+            .map_err(|_| CipherError::BadDerivationParams)?;
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        seed.zeroize();
+        Self::generate_with_pairs(&mut rng, &params.alphabet, params.blocks, params.pairs)
+    }
+}
@@ -0,0 +1,186 @@
+//! Коммутационная панель (plugboard): пары взаимозамен символов алфавита
+//! или, как в «Uhr», произвольная перестановка с отдельной обратной таблицей.
+
+use crate::prelude::*;
+use crate::{AlphabetIndex, CipherError, Idx, Redacted};
+#[cfg(feature = "rand")]
+use rand::{Rng, seq::SliceRandom};
+use core::fmt;
+use zeroize::Zeroize;
+
+/// Сколько пар ставят пресеты и генератор по умолчанию
+pub const DEFAULT_PAIRS: usize = 8;
+
+/// `count` случайных непересекающихся пар из алфавита. Больше половины
+/// алфавита пар не бывает — это ошибка `TooManyPlugboardPairs`.
+#[cfg(feature = "rand")]
+pub fn random_pairs<R: Rng>(
+    alphabet: &[char],
+    count: usize,
+    rng: &mut R,
+) -> Result<Vec<(char, char)>, CipherError> {
+    if count > alphabet.len() / 2 {
+        let alphabet_len = alphabet.len();
+        return Err(CipherError::TooManyPlugboardPairs { pairs: count, alphabet_len });
+    }
+    let mut pool: Vec<char> = alphabet.to_vec();
+    pool.shuffle(rng);
+    Ok(pool.chunks(2).take(count).map(|chunk| (chunk[0], chunk[1])).collect())
+}
+
+/// Случайная перестановка всего алфавита для `plugboard_map`: только
+/// символы, которые она сдвигает, в порядке алфавита
+#[cfg(feature = "rand")]
+pub fn random_map<R: Rng>(alphabet: &[char], rng: &mut R) -> Vec<(char, char)> {
+    let mut images: Vec<char> = alphabet.to_vec();
+    images.shuffle(rng);
+    alphabet.iter().copied().zip(images).filter(|(a, b)| a != b).collect()
+}
+
+/// Собранная панель: прямая таблица на входе в роторы и обратная на выходе.
+/// Для пар они совпадают; для `plugboard_map` и стопки панелей машина всё
+/// равно остаётся сама себе обратной: σ⁻¹·R·σ — инволюция, раз R — инволюция.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Plugboard {
+    map_fwd: Vec<Idx>,
+    map_rev: Vec<Idx>,
+}
+
+/// Пары — часть ключа: в `Debug` только длина алфавита
+impl fmt::Debug for Plugboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugboard")
+            .field("alphabet_len", &self.alphabet_len())
+            .field("map", &Redacted)
+            .finish()
+    }
+}
+
+impl Drop for Plugboard {
+    fn drop(&mut self) {
+        self.map_fwd.zeroize();
+        self.map_rev.zeroize();
+    }
+}
+
+impl Plugboard {
+    /// Панель из конфига: пары или `plugboard_map`, но не то и другое сразу,
+    /// а за ней по порядку панели пар `boards` (см. `then`)
+    pub fn new(
+        pairs: &[(char, char)],
+        map: &[(char, char)],
+        boards: &[Vec<(char, char)>],
+        index: &AlphabetIndex,
+    ) -> Result<Self, CipherError> {
+        let first = match (pairs.is_empty(), map.is_empty()) {
+            (false, false) => return Err(CipherError::PlugboardPairsAndMap),
+            (true, false) => Self::from_map(map, index)?,
+            _ => Self::from_pairs(pairs, index)?,
+        };
+        boards.iter().try_fold(first, |acc, board| Ok(acc.then(&Self::from_pairs(board, index)?)))
+    }
+
+    /// Единственное место, где проверяются пары: оба символа из алфавита,
+    /// символ не соединён сам с собой и не стоит в двух парах сразу
+    pub fn from_pairs(pairs: &[(char, char)], index: &AlphabetIndex) -> Result<Self, CipherError> {
+        let mut map: Vec<Idx> = (0..index.alphabet_len() as Idx).collect();
+        for &(a, b) in pairs {
+            let (ia, ib) = match (index.get(a), index.get(b)) {
+                (Some(ia), Some(ib)) => (ia, ib),
+                (None, _) => return Err(CipherError::PlugboardUnknownChar(a)),
+                (_, None) => return Err(CipherError::PlugboardUnknownChar(b)),
+            };
+            if ia == ib {
+                return Err(CipherError::PlugboardSelfPair(a));
+            }
+            for (c, i) in [(a, ia), (b, ib)] {
+                if map[i] as usize != i {
+                    return Err(CipherError::PlugboardCharReused(c));
+                }
+            }
+            map[ia] = ib as Idx;
+            map[ib] = ia as Idx;
+        }
+        Ok(Plugboard { map_rev: map.clone(), map_fwd: map })
+    }
+
+    /// Панель-перестановка: каждая пара `(a, b)` — провод из `a` в `b`.
+    /// Источники и цели не повторяются, и каждая цель сама куда-то ведёт,
+    /// иначе таблица не перестановка; символ без провода остаётся на месте.
+    pub fn from_map(map: &[(char, char)], index: &AlphabetIndex) -> Result<Self, CipherError> {
+        let n = index.alphabet_len();
+        let mut map_fwd: Vec<Idx> = (0..n as Idx).collect();
+        let mut map_rev = map_fwd.clone();
+        let (mut is_source, mut is_target) = (vec![false; n], vec![false; n]);
+        let mut wires = Vec::with_capacity(map.len());
+        for &(a, b) in map {
+            let (ia, ib) = match (index.get(a), index.get(b)) {
+                (Some(ia), Some(ib)) => (ia, ib),
+                (None, _) => return Err(CipherError::PlugboardUnknownChar(a)),
+                (_, None) => return Err(CipherError::PlugboardUnknownChar(b)),
+            };
+            if ia == ib {
+                return Err(CipherError::PlugboardSelfPair(a));
+            }
+            for (c, seen) in [(a, &mut is_source[ia]), (b, &mut is_target[ib])] {
+                if *seen {
+                    return Err(CipherError::PlugboardCharReused(c));
+                }
+                *seen = true;
+            }
+            wires.push((ib, b));
+            map_fwd[ia] = ib as Idx;
+            map_rev[ib] = ia as Idx;
+        }
+        if let Some(&(_, b)) = wires.iter().find(|&&(ib, _)| !is_source[ib]) {
+            return Err(CipherError::PlugboardMapOpen(b));
+        }
+        Ok(Plugboard { map_fwd, map_rev })
+    }
+
+    /// Две панели подряд: сигнал идёт через `self`, потом через `next`, а на
+    /// выходе обратно через `next` и `self`. Стопка собирается в одну
+    /// таблицу, так что символ стоит одного поиска при любом числе панелей.
+    pub fn then(&self, next: &Plugboard) -> Plugboard {
+        let map_fwd: Vec<Idx> = self.map_fwd.iter().map(|&i| next.map_fwd[i as usize]).collect();
+        let mut map_rev = map_fwd.clone();
+        for (i, &j) in map_fwd.iter().enumerate() {
+            map_rev[j as usize] = i as Idx;
+        }
+        Plugboard { map_fwd, map_rev }
+    }
+
+    /// Вход в панель: индекс пары или образ по прямой таблице; символ без
+    /// провода остаётся на месте
+    #[inline]
+    pub fn swap(&self, idx: usize) -> usize {
+        self.map_fwd[idx] as usize
+    }
+
+    /// Выход из панели: обратная таблица, для пар то же, что `swap`
+    #[inline]
+    pub fn unswap(&self, idx: usize) -> usize {
+        self.map_rev[idx] as usize
+    }
+
+    /// Панель из пар: прямая и обратная таблицы совпадают
+    pub fn is_reciprocal(&self) -> bool {
+        self.map_fwd == self.map_rev
+    }
+
+    /// Пары индексов `(i, j)` с `i < j` в порядке алфавита; для панели не
+    /// из пар — только взаимные провода, см. `wires`
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.wires().filter(|&(i, j)| i < j && self.swap(j) == i)
+    }
+
+    /// Провода `(i, σ(i))` для всех сдвинутых индексов в порядке алфавита
+    pub fn wires(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.map_fwd.iter().enumerate().map(|(i, &j)| (i, j as usize)).filter(|&(i, j)| i != j)
+    }
+
+    /// Длина алфавита, под который собрана панель
+    pub fn alphabet_len(&self) -> usize {
+        self.map_fwd.len()
+    }
+}
@@ -0,0 +1,16 @@
+//! Ход долгих операций: генерации огромных конфигов и потокового
+//! шифрования. Колбэк зовётся пачками, а не на каждый символ или блок —
+//! иначе отчёт стоил бы дороже самой работы.
+
+/// Сколько сделано и сколько всего; `total` неизвестен, например, для stdin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: Option<u64>,
+}
+
+/// Через сколько блоков `ConfigData::generate_with_progress` сообщает о ходе
+pub const GENERATE_BATCH: usize = 4096;
+
+/// Кусок `encrypt_stream`: о ходе сообщается после каждого
+pub const STREAM_CHUNK: usize = 64 * 1024;
@@ -0,0 +1,89 @@
+//! Разбор строк интерактивного меню: команда, сообщение в той же строке и
+//! что из строки можно записать в историю.
+//!
+//! `encrypt <сообщение>` шифрует сразу, без вопроса «Сообщение:». Вместо
+//! сообщения можно написать `<<`: тогда читаются строки до строки из одной
+//! точки, как heredoc в оболочке. `encrypt --trace[=K]` показывает, как
+//! шагают роторы первых K блоков на каждом символе.
+
+use crate::prelude::*;
+
+/// Начало многострочного сообщения
+pub const HEREDOC_START: &str = "<<";
+/// Строка, которой многострочное сообщение заканчивается
+pub const HEREDOC_END: &str = ".";
+/// Сколько блоков показывает `--trace` без `=K`
+pub const TRACE_BLOCKS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command<'a> {
+    /// Пустая строка
+    Empty,
+    /// `encrypt` или `decrypt`; `trace` — сколько блоков показывать с
+    /// `--trace`, сообщение — если оно в той же строке
+    Message { decrypt: bool, trace: Option<usize>, text: Option<&'a str> },
+    /// Любая другая команда: имя и слова после него
+    Other { name: &'a str, args: Vec<&'a str> },
+}
+
+/// Строка меню в команду. Сообщение после `encrypt`/`decrypt` берётся как
+/// есть, без пробелов по краям: пробелы внутри него — часть текста. Первое
+/// слово `--trace` или `--trace=K` с K > 0 — флаг, а не начало сообщения.
+pub fn parse_command(line: &str) -> Command<'_> {
+    let line = line.trim();
+    let (name, rest) = match line.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim_start()),
+        None => (line, ""),
+    };
+    match name {
+        "" => Command::Empty,
+        "encrypt" | "decrypt" => {
+            let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let trace = parse_trace(word);
+            let text = if trace.is_some() { after.trim_start() } else { rest };
+            Command::Message {
+                decrypt: name == "decrypt",
+                trace,
+                text: (!text.is_empty()).then_some(text),
+            }
+        }
+        _ => Command::Other { name, args: rest.split_whitespace().collect() },
+    }
+}
+
+/// `--trace` или `--trace=K` в число блоков
+fn parse_trace(word: &str) -> Option<usize> {
+    match word.strip_prefix("--trace")? {
+        "" => Some(TRACE_BLOCKS),
+        blocks => blocks.strip_prefix('=')?.parse().ok().filter(|&k| k > 0),
+    }
+}
+
+impl Command<'_> {
+    /// Что записать в историю за строку `line`. Без `with_messages` текст
+    /// сообщения туда не попадает: от `encrypt <текст>` остаётся `encrypt`.
+    pub fn history_entry(&self, line: &str, with_messages: bool) -> Option<String> {
+        match self {
+            Command::Empty => None,
+            Command::Message { decrypt, trace, text: Some(_) } if !with_messages => {
+                let name = if *decrypt { "decrypt" } else { "encrypt" };
+                Some(match trace {
+                    Some(blocks) => format!("{} --trace={}", name, blocks),
+                    None => name.into(),
+                })
+            }
+            _ => Some(line.trim().into()),
+        }
+    }
+}
+
+/// Сообщение начинается с `<<`: дальше строки до `HEREDOC_END`
+pub fn is_heredoc_start(text: &str) -> bool {
+    text.trim() == HEREDOC_START
+}
+
+/// Строка `line` закрывает многострочное сообщение. Пробелы вокруг точки
+/// допускаются: их не видно, а сообщение без конца зависло бы.
+pub fn is_heredoc_end(line: &str) -> bool {
+    line.trim() == HEREDOC_END
+}
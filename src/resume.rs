@@ -0,0 +1,188 @@
+//! Шифрование больших файлов с контрольными точками: после сбоя работа
+//! продолжается с последней точки, а не с начала.
+//!
+//! Файл идёт побайтно, как `encrypt-file` для двоичных данных, и с тем же
+//! заголовком `FileHeader`. Каждые `every` байт выход сбрасывается на диск,
+//! и рядом пишется `<выход>.esd.progress`: смещение, позиции роторов и
+//! отпечаток конфига. При продолжении машина не грузит позиции, а
+//! перематывается `advance_by` на смещение — так верна и фаза
+//! `step_interval`, — и записанные позиции лишь сверяются с полученными.
+//! Оба файла встают на смещение, хвост выхода после точки отрезается.
+//! После успешного конца файл прогресса удаляется.
+
+use crate::file_header::{FILE_HEADER_LEN, FileHeader};
+use crate::progress::STREAM_CHUNK;
+use crate::{ConfigData, EnigmaSudnogoDnya};
+use core::ops::ControlFlow;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Суффикс файла прогресса рядом с выходом
+pub const PROGRESS_SUFFIX: &str = ".esd.progress";
+
+/// Содержимое файла прогресса
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Расшифровка, а не шифрование
+    pub decrypt: bool,
+    /// Сколько байт данных (без заголовка) уже обработано и лежит на диске
+    pub offset: u64,
+    /// Интервал контрольных точек, байт
+    pub every: u64,
+    /// Позиции роторов после `offset` байт
+    pub state: Vec<Vec<usize>>,
+    /// Начало отпечатка конфига, как в заголовке файла
+    pub fingerprint: [u8; 8],
+    /// Размер входа: другой вход — продолжать нечего
+    pub input_len: u64,
+}
+
+/// Где лежит файл прогресса для выхода `output`
+pub fn progress_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(PROGRESS_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Пишет файл прогресса через временный и переименование, чтобы сбой
+/// посреди записи не оставил половину JSON
+fn save_checkpoint(path: &Path, checkpoint: &Checkpoint) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let json = serde_json::to_vec(checkpoint).expect("контрольная точка всегда сериализуется");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+/// Шифрует (или с `decrypt` расшифровывает) `input` в `output` с контрольной
+/// точкой каждые `every` байт. С `resume` продолжает по файлу прогресса,
+/// иначе начинает заново. После каждой точки зовёт `on_checkpoint`; `Break`
+/// останавливает работу, как сбой, — файл прогресса остаётся.
+///
+/// `Some(позиции)` — файл готов, позиции роторов после него; `None` —
+/// остановлено из `on_checkpoint`.
+pub fn process_file<F>(
+    cfg: &ConfigData,
+    input: &Path,
+    output: &Path,
+    decrypt: bool,
+    every: u64,
+    resume: bool,
+    mut on_checkpoint: F,
+) -> io::Result<Option<Vec<Vec<usize>>>>
+where
+    F: FnMut(&Checkpoint) -> ControlFlow<()>,
+{
+    let mut machine = EnigmaSudnogoDnya::new(&cfg.to_byte_mode())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let header = FileHeader::for_config(cfg, true, false, None);
+    let check_header = |bytes: &[u8]| -> io::Result<()> {
+        let (found, _) = FileHeader::parse(bytes)
+            .map_err(|e| invalid(&e.to_string()))?
+            .ok_or_else(|| invalid("no ESD1 header"))?;
+        found.check(cfg).map_err(|e| invalid(&e.to_string()))?;
+        if !found.binary || found.authenticated {
+            return Err(invalid("only byte-mode files without a tag can be resumed"));
+        }
+        Ok(())
+    };
+
+    let mut reader = File::open(input)?;
+    let input_len = reader.metadata()?.len();
+    // заголовок у шифротекста: на входе при расшифровке, на выходе при шифровании
+    let (in_base, out_base) = if decrypt {
+        let mut bytes = [0; FILE_HEADER_LEN];
+        reader.read_exact(&mut bytes)?;
+        check_header(&bytes)?;
+        (FILE_HEADER_LEN as u64, 0)
+    } else {
+        (0, FILE_HEADER_LEN as u64)
+    };
+    let sidecar = progress_path(output);
+    let (mut writer, mut offset, every) = if resume {
+        let checkpoint: Checkpoint = serde_json::from_slice(&fs::read(&sidecar)?)
+            .map_err(|e| invalid(&e.to_string()))?;
+        let same_job = checkpoint.decrypt == decrypt
+            && checkpoint.fingerprint == header.fingerprint
+            && checkpoint.input_len == input_len
+            && checkpoint.offset <= input_len - in_base;
+        if !same_job {
+            return Err(invalid("the progress file belongs to another job"));
+        }
+        // с нулём кусок чтения пуст и цикл не кончился бы никогда
+        if checkpoint.every == 0 {
+            return Err(invalid("the progress file has a zero checkpoint interval"));
+        }
+        machine.advance_by(checkpoint.offset);
+        if machine.save_state() != checkpoint.state {
+            return Err(invalid("the progress file does not match the config"));
+        }
+        let mut writer = OpenOptions::new().read(true).write(true).open(output)?;
+        if !decrypt {
+            let mut bytes = [0; FILE_HEADER_LEN];
+            writer.read_exact(&mut bytes)?;
+            check_header(&bytes)?;
+        }
+        // записанное после точки могло не дойти до диска целиком
+        writer.set_len(out_base + checkpoint.offset)?;
+        writer.seek(SeekFrom::Start(out_base + checkpoint.offset))?;
+        reader.seek(SeekFrom::Start(in_base + checkpoint.offset))?;
+        (writer, checkpoint.offset, checkpoint.every)
+    } else {
+        let mut writer = File::create(output)?;
+        if !decrypt {
+            writer.write_all(&header.to_bytes())?;
+        }
+        (writer, 0, every.max(1))
+    };
+
+    let mut buf = vec![0u8; STREAM_CHUNK.min(every as usize)];
+    let mut since = 0;
+    loop {
+        // кусок не переходит через точку: она ровно через `every` байт
+        let want = buf.len().min((every - since) as usize);
+        let n = match reader.read(&mut buf[..want]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if decrypt {
+            machine.decrypt_bytes_in_place(&mut buf[..n]);
+        } else {
+            machine.encrypt_bytes_in_place(&mut buf[..n]);
+        }
+        writer.write_all(&buf[..n])?;
+        offset += n as u64;
+        since += n as u64;
+        if since == every {
+            since = 0;
+            // сначала данные на диск, потом запись о них
+            writer.sync_data()?;
+            let checkpoint = Checkpoint {
+                decrypt,
+                offset,
+                every,
+                state: machine.save_state(),
+                fingerprint: header.fingerprint,
+                input_len,
+            };
+            save_checkpoint(&sidecar, &checkpoint)?;
+            if on_checkpoint(&checkpoint).is_break() {
+                return Ok(None);
+            }
+        }
+    }
+    writer.sync_data()?;
+    match fs::remove_file(&sidecar) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    Ok(Some(machine.export_positions()))
+}
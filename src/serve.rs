@@ -0,0 +1,130 @@
+//! `serve`: машина как небольшой внутренний HTTP-сервис.
+//!
+//! `POST /encrypt` и `POST /decrypt` принимают `{"text": "...", "state": ...}`
+//! и отвечают `{"text": "...", "state": ...}`, где `state` — позиции роторов
+//! после сообщения в формате `rotor_positions`. Клиент без своего состояния
+//! передаёт его в следующий запрос и продолжает поток; без `state` сообщение
+//! шифруется со стартовых позиций конфига. Тексты запросов и ответов нигде
+//! не пишутся в лог.
+
+use enigma_sd::messages::Msg;
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya, MacKey};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Адрес по умолчанию — только локальная машина
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8080";
+
+/// Лимит тела запроса по умолчанию, байт
+pub const DEFAULT_MAX_BODY: usize = 1 << 20;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MessageRequest {
+    text: String,
+    #[serde(default)]
+    state: Option<Vec<Vec<usize>>>,
+}
+
+#[derive(Serialize)]
+struct MessageResponse<'a> {
+    text: &'a str,
+    state: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Код ответа и JSON-тело
+type Reply = (u16, String);
+
+fn error(status: u16, message: String) -> Reply {
+    let body = serde_json::to_string(&ErrorResponse { error: message }).expect("строка в JSON");
+    (status, body)
+}
+
+/// Обслуживает запросы по одному, пока процесс не остановят. Машина
+/// строится один раз и перед каждым сообщением встаёт в нужное состояние.
+pub fn run(
+    cfg: &ConfigData,
+    mac_key: Option<&MacKey>,
+    listen: &str,
+    max_body: usize,
+) -> io::Result<()> {
+    let server = Server::http(listen).map_err(io::Error::other)?;
+    let mut machine = crate::build_machine(cfg);
+    // реальный адрес: с портом 0 его выбирает система
+    let addr = server.server_addr().to_ip().map_or(listen.to_string(), |a| a.to_string());
+    println!("{}", Msg::ServeListening.fill(&[&addr]));
+    io::stdout().flush()?;
+
+    let json = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("заголовок корректен");
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(&mut machine, cfg, mac_key, &mut request, max_body);
+        let response =
+            Response::from_string(body).with_status_code(status).with_header(json.clone());
+        // клиент мог уйти, не дождавшись ответа — это не повод падать
+        request.respond(response).ok();
+    }
+    Ok(())
+}
+
+fn handle(
+    machine: &mut EnigmaSudnogoDnya,
+    cfg: &ConfigData,
+    mac_key: Option<&MacKey>,
+    request: &mut Request,
+    max_body: usize,
+) -> Reply {
+    let decrypt = match request.url() {
+        "/encrypt" => false,
+        "/decrypt" => true,
+        _ => return error(404, Msg::ServeNotFound.text().to_string()),
+    };
+    if *request.method() != Method::Post {
+        return error(405, Msg::ServeMethodNotAllowed.text().to_string());
+    }
+    let too_large = || error(413, Msg::ServeBodyTooLarge.fill(&[&max_body]));
+    if request.body_length().is_some_and(|n| n > max_body) {
+        return too_large();
+    }
+    let mut body = Vec::new();
+    let limit = max_body as u64 + 1;
+    if let Err(e) = request.as_reader().take(limit).read_to_end(&mut body) {
+        return error(400, e.to_string());
+    }
+    if body.len() > max_body {
+        return too_large();
+    }
+    let message: MessageRequest = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => return error(400, Msg::ServeBadJson.fill(&[&e])),
+    };
+
+    machine.reset();
+    if let Some(state) = message.state {
+        // те же проверки, что у `rotor_positions` в конфиге
+        let positioned = ConfigData { rotor_positions: state, ..cfg.clone() };
+        if let Err(e) = positioned.validate() {
+            return error(400, e.to_string());
+        }
+        machine.load_state(&positioned.rotor_positions);
+    }
+    let result: Result<String, CipherError> = match (decrypt, mac_key) {
+        (false, None) => Ok(machine.encrypt(&message.text)),
+        (false, Some(key)) => Ok(machine.encrypt_authenticated(&message.text, key)),
+        (true, None) => machine.decrypt(&message.text),
+        (true, Some(key)) => machine.decrypt_authenticated(&message.text, key),
+    };
+    match result {
+        Ok(text) => {
+            let response = MessageResponse { text: &text, state: machine.export_positions() };
+            (200, serde_json::to_string(&response).expect("ответ сериализуется"))
+        }
+        Err(e) => error(400, e.to_string()),
+    }
+}
@@ -0,0 +1,82 @@
+//! Эталонные векторы: фиксированный конфиг, открытый текст и шифротекст.
+//! Лежат в `tests/vectors/*.json` и вшиты в бинарник, чтобы любая сборка
+//! могла проверить, что шифр не поменялся незаметно.
+
+use crate::prelude::*;
+use crate::{CipherError, ConfigData, EnigmaSudnogoDnya};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Каталог с векторами относительно корня репозитория
+pub const VECTORS_DIR: &str = "tests/vectors";
+
+const BUILTIN: &[&str] = &[
+    include_str!("../tests/vectors/latin_single_block.json"),
+    include_str!("../tests/vectors/latin_plugboard.json"),
+    include_str!("../tests/vectors/latin_block_shifts.json"),
+    include_str!("../tests/vectors/cyrillic_plugboard.json"),
+    include_str!("../tests/vectors/cyrillic_many_blocks.json"),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestVector {
+    pub name: String,
+    pub config: ConfigData,
+    pub plaintext: String,
+    pub ciphertext: String,
+}
+
+impl TestVector {
+    /// Шифротекст, который даёт текущая сборка
+    pub fn compute(&self) -> Result<String, CipherError> {
+        Ok(EnigmaSudnogoDnya::new(&self.config)?.encrypt(&self.plaintext))
+    }
+
+    /// Совпадает ли шифротекст и расшифровывается ли он обратно.
+    /// Открытый текст сравнивается после приведения к нижнему регистру,
+    /// как его видит машина.
+    pub fn verify(&self) -> Result<bool, CipherError> {
+        if self.compute()? != self.ciphertext {
+            return Ok(false);
+        }
+        let plain = EnigmaSudnogoDnya::new(&self.config)?.decrypt(&self.ciphertext)?;
+        Ok(plain == self.plaintext.to_lowercase())
+    }
+}
+
+/// Векторы, вшитые в сборку
+pub fn builtin() -> Vec<TestVector> {
+    BUILTIN
+        .iter()
+        .map(|s| serde_json::from_str(s).expect("битый встроенный вектор"))
+        .collect()
+}
+
+/// Все `*.json` из каталога, по имени файла
+#[cfg(feature = "fs")]
+pub fn load_dir(dir: &Path) -> io::Result<Vec<(PathBuf, TestVector)>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|p| p.extension().is_some_and(|e| e == "json"));
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|p| {
+            let vector = serde_json::from_str(&fs::read_to_string(&p)?)?;
+            Ok((p, vector))
+        })
+        .collect()
+}
+
+/// Перезаписывает файл вектора (для `--bless` в генераторе)
+#[cfg(feature = "fs")]
+pub fn save(path: &Path, vector: &TestVector) -> io::Result<()> {
+    let mut json = serde_json::to_string_pretty(vector)?;
+    json.push('\n');
+    fs::write(path, json)
+}
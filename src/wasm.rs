@@ -0,0 +1,60 @@
+//! Привязки для браузера через wasm-bindgen. Сборка без нативной части:
+//! `wasm-pack build -- --no-default-features --features wasm`.
+//!
+//! Конфиг передаётся и возвращается тем же JSON, что лежит в
+//! `esd_config.json`, ошибки становятся исключениями JS.
+
+use crate::messages::Msg;
+use crate::{ConfigData, EnigmaSudnogoDnya, PRESETS};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use wasm_bindgen::prelude::*;
+
+/// Машина для JS: `new EsdMachine(configJson)`
+#[wasm_bindgen]
+pub struct EsdMachine {
+    inner: EnigmaSudnogoDnya,
+}
+
+#[wasm_bindgen]
+impl EsdMachine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<EsdMachine, JsError> {
+        let cfg: ConfigData = serde_json::from_str(config_json)?;
+        Ok(EsdMachine { inner: EnigmaSudnogoDnya::new(&cfg)? })
+    }
+
+    /// Шифрует с текущих позиций; перед новым сообщением — `reset`
+    pub fn encrypt(&mut self, text: &str) -> String {
+        self.inner.encrypt(text)
+    }
+
+    /// Обратное к `encrypt` (с омофонами — не то же самое)
+    pub fn decrypt(&mut self, text: &str) -> Result<String, JsError> {
+        Ok(self.inner.decrypt(text)?)
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// JSON случайного конфига по пресету (русское или английское имя) и
+/// зерну ГПСЧ: одно зерно — один конфиг, как `--seed` в программе.
+/// `seed` в JS — `BigInt`.
+#[wasm_bindgen(js_name = generateConfig)]
+pub fn generate_config(preset_name: &str, seed: u64) -> Result<String, JsError> {
+    let preset = PRESETS
+        .iter()
+        .find(|p| p.name == preset_name || p.name_en.eq_ignore_ascii_case(preset_name))
+        .ok_or_else(|| JsError::new(&Msg::UnknownPreset.fill(&[&preset_name])))?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let alphabet = ConfigData::default().alphabet;
+    let cfg = ConfigData::generate_with_pairs(
+        &mut rng,
+        &alphabet,
+        preset.blocks,
+        preset.plugboard_pairs,
+    )?;
+    Ok(serde_json::to_string(&cfg)?)
+}
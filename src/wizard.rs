@@ -0,0 +1,406 @@
+//! Мастер нового конфига: алфавит, ручная настройка или пресет, а после
+//! генерации — стойкость, период, число роторов и скорость шифрования и
+//! вопрос, что дальше: сохранить, сгенерировать заново или поменять число
+//! блоков и пар plugboard.
+//!
+//! Вопросы идут через `Console`: меню строит его над stdin и stdout, тесты —
+//! над строками в памяти.
+
+use crate::analysis::{self, KeyspaceEstimate};
+use crate::console::Console;
+use crate::messages::{self, Msg};
+use crate::plugboard::{self, Plugboard};
+use crate::progress::Progress;
+use crate::{Alphabet, AlphabetIndex, BENCH_SEED, BYTES_ALPHABET, CipherError, ConfigData};
+use crate::{EnigmaSudnogoDnya, PRESETS};
+use crate::{SHORT_PERIOD, alphabet, bench_corpus, format_period};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// Сколько символов шифрует замер скорости
+pub const THROUGHPUT_SAMPLE: usize = 10_000;
+/// Замер не дольше этого: огромный конфиг меряется на начале образца
+const THROUGHPUT_BUDGET: Duration = Duration::from_millis(500);
+/// Символов между проверками времени
+const THROUGHPUT_CHUNK: usize = 100;
+
+/// Пресеты больше этого числа блоков генерируются только после подтверждения
+pub const PRESET_CONFIRM_BLOCKS: usize = 1_000_000;
+/// Образец для замера скорости генерации пресета
+const PRESET_SAMPLE_BLOCKS: usize = 10_000;
+/// Блоков при ручной настройке, если ответ не число
+const DEFAULT_BLOCKS: usize = 4;
+
+/// Что генерировать
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub alphabet: String,
+    pub blocks: usize,
+    /// Сколько случайных пар plugboard ставить
+    pub pairs: usize,
+    /// Пары, введённые вручную: новая генерация их не трогает, пока не
+    /// попросили другое число пар
+    pub fixed_pairs: Option<Vec<(char, char)>>,
+}
+
+impl Params {
+    /// Конфиг по параметрам; `progress` — как у
+    /// `ConfigData::generate_with_progress`
+    pub fn generate<R: Rng, F: FnMut(Progress)>(
+        &self,
+        rng: &mut R,
+        progress: F,
+    ) -> Result<ConfigData, CipherError> {
+        let pairs = if self.fixed_pairs.is_some() { 0 } else { self.pairs };
+        let mut cfg =
+            ConfigData::generate_with_progress(rng, &self.alphabet, self.blocks, pairs, progress)?;
+        if let Some(fixed) = &self.fixed_pairs {
+            cfg.plugboard = fixed.clone();
+        }
+        Ok(cfg)
+    }
+}
+
+/// Что мастер показывает о конфиге
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub keyspace: KeyspaceEstimate,
+    pub period: u128,
+    pub rotors: usize,
+    pub blocks: usize,
+    /// Скорость шифрования, символов в секунду
+    pub chars_per_sec: f64,
+}
+
+impl Summary {
+    /// Оценки по конфигу и замер скорости на построенной из него машине
+    pub fn measure(cfg: &ConfigData) -> Result<Self, CipherError> {
+        let mut machine = EnigmaSudnogoDnya::new(cfg)?;
+        Ok(Summary {
+            keyspace: analysis::estimate_keyspace(cfg),
+            period: cfg.period(),
+            rotors: cfg.rotor_count(),
+            blocks: cfg.block_count(),
+            chars_per_sec: throughput(&mut machine),
+        })
+    }
+
+    /// Сколько секунд уйдёт на `THROUGHPUT_SAMPLE` символов
+    pub fn sample_secs(&self) -> f64 {
+        THROUGHPUT_SAMPLE as f64 / self.chars_per_sec
+    }
+
+    fn write(&self, output: &mut impl Write) -> io::Result<()> {
+        let naive = format!("{:.1}", self.keyspace.naive_bits);
+        let effective = format!("{:.1}", self.keyspace.effective_bits);
+        writeln!(output, "{}", Msg::WizardStrength.fill(&[&naive, &effective]))?;
+        writeln!(output, "{}", Msg::Period.fill(&[&format_period(self.period)]))?;
+        if self.period < SHORT_PERIOD {
+            writeln!(output, "{}", Msg::ShortPeriod.fill(&[&SHORT_PERIOD]))?;
+        }
+        writeln!(output, "{}", Msg::WizardRotors.fill(&[&self.rotors, &self.blocks]))?;
+        let speed = format!("{:.0}", self.chars_per_sec);
+        let secs = format!("{:.3}", self.sample_secs());
+        writeln!(output, "{}", Msg::WizardSpeed.fill(&[&speed, &THROUGHPUT_SAMPLE, &secs]))
+    }
+}
+
+/// Символов в секунду на шифровании `THROUGHPUT_SAMPLE` случайных символов
+/// алфавита. Долгий замер обрывается через `THROUGHPUT_BUDGET` и считается
+/// по сделанному. Состояние машины после замера прежнее.
+pub fn throughput(machine: &mut EnigmaSudnogoDnya) -> f64 {
+    let state = machine.save_state();
+    let text = bench_corpus(BENCH_SEED, machine.alphabet(), &[THROUGHPUT_SAMPLE]).remove(0);
+    let chars: Vec<char> = text.chars().collect();
+    let chunks: Vec<String> = chars.chunks(THROUGHPUT_CHUNK).map(|c| c.iter().collect()).collect();
+
+    let t = Instant::now();
+    let mut done = 0;
+    for chunk in &chunks {
+        black_box(machine.encrypt(chunk));
+        done += chunk.chars().count();
+        if t.elapsed() >= THROUGHPUT_BUDGET {
+            break;
+        }
+    }
+    let secs = t.elapsed().as_secs_f64();
+    machine.load_state(&state);
+    done as f64 / secs.max(f64::MIN_POSITIVE)
+}
+
+/// Ответ на вопрос мастера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    /// «да» или `save`
+    Save,
+    /// «нет»: работать с конфигом, не сохраняя
+    Keep,
+    /// `regen`: те же параметры, новые случайные блоки и пары
+    Regenerate,
+    /// `blocks N`, N > 0
+    Blocks(usize),
+    /// `pairs N`
+    Pairs(usize),
+}
+
+pub fn parse_answer(line: &str) -> Option<Answer> {
+    let mut words = line.split_whitespace();
+    let (word, arg) = (words.next()?, words.next());
+    if words.next().is_some() {
+        return None;
+    }
+    let count = arg.and_then(|n| n.parse::<usize>().ok());
+    match (word, arg) {
+        ("save", None) => Some(Answer::Save),
+        ("regen", None) => Some(Answer::Regenerate),
+        ("blocks", Some(_)) => count.filter(|&n| n > 0).map(Answer::Blocks),
+        ("pairs", Some(_)) => count.map(Answer::Pairs),
+        (_, None) => {
+            messages::parse_yes_no(word).map(|yes| if yes { Answer::Save } else { Answer::Keep })
+        }
+        _ => None,
+    }
+}
+
+/// Чем кончился мастер
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub cfg: ConfigData,
+    /// Сохранить ли конфиг; конец ввода — не сохранять
+    pub save: bool,
+}
+
+enum State {
+    /// Сгенерировать по параметрам; при ошибке вернуться к прежнему конфигу
+    Generate(Params, Option<ConfigData>),
+    /// Показать сводку по свежему конфигу
+    Review(ConfigData),
+    /// Спросить, что дальше
+    Ask(ConfigData),
+}
+
+/// Генерирует конфиг через `generate` и крутится, пока его не сохранят или
+/// не оставят. Параметры, с которыми генерация не удалась, не принимаются:
+/// ошибка печатается, и вопрос повторяется для прежнего конфига.
+pub fn run<R, W, G>(
+    mut params: Params,
+    mut generate: G,
+    console: &mut Console<R, W>,
+) -> io::Result<Outcome>
+where
+    R: BufRead,
+    W: Write,
+    G: FnMut(&Params) -> Result<ConfigData, CipherError>,
+{
+    let mut state = State::Generate(params.clone(), None);
+    loop {
+        state = match state {
+            State::Generate(next, fallback) => match (generate(&next), fallback) {
+                (Ok(cfg), _) => {
+                    params = next;
+                    State::Review(cfg)
+                }
+                (Err(e), Some(cfg)) => {
+                    writeln!(console, "{}", e)?;
+                    State::Ask(cfg)
+                }
+                (Err(e), None) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()));
+                }
+            },
+            State::Review(cfg) => {
+                Summary::measure(&cfg)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?
+                    .write(console)?;
+                State::Ask(cfg)
+            }
+            State::Ask(cfg) => {
+                let Some(line) = console.prompt(Msg::SaveConfig.text())? else {
+                    return Ok(Outcome { cfg, save: false });
+                };
+                let mut next = params.clone();
+                match parse_answer(&line) {
+                    Some(Answer::Save) => return Ok(Outcome { cfg, save: true }),
+                    Some(Answer::Keep) => return Ok(Outcome { cfg, save: false }),
+                    Some(Answer::Regenerate) => State::Generate(next, Some(cfg)),
+                    Some(Answer::Blocks(blocks)) => {
+                        next.blocks = blocks;
+                        State::Generate(next, Some(cfg))
+                    }
+                    Some(Answer::Pairs(pairs)) => {
+                        next.pairs = pairs;
+                        next.fixed_pairs = None;
+                        State::Generate(next, Some(cfg))
+                    }
+                    None => {
+                        writeln!(console, "{}", Msg::WizardUnknownAnswer.text())?;
+                        State::Ask(cfg)
+                    }
+                }
+            }
+        };
+    }
+}
+
+/// Весь мастер: алфавит, затем ручная настройка или пресет, затем `run`.
+/// `None` — ввод кончился раньше генерации или тяжёлый пресет не
+/// подтвердили.
+pub fn setup<R, W, G>(console: &mut Console<R, W>, generate: G) -> io::Result<Option<Outcome>>
+where
+    R: BufRead,
+    W: Write,
+    G: FnMut(&Params) -> Result<ConfigData, CipherError>,
+{
+    writeln!(console, "{}", Msg::ChooseAlphabet.text())?;
+    let menu: Vec<&Alphabet> = Alphabet::ALL.iter().filter(|a| a.name != BYTES_ALPHABET).collect();
+    for (i, a) in menu.iter().enumerate() {
+        writeln!(console, "{}) {}", i + 1, a.localized_title())?;
+    }
+    let Some(answer) = console.prompt("> ")? else { return Ok(None) };
+    // номер из меню или имя алфавита; иначе кириллица, как было всегда
+    let chosen = match answer.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).and_then(|i| menu.get(i).copied()),
+        Err(_) => menu.iter().copied().find(|a| a.name == answer),
+    };
+    let alphabet = chosen.map_or("cyrillic", |a| a.name);
+
+    writeln!(console, "{}", Msg::ConfigSetup.text())?;
+    writeln!(console, "{}", Msg::ManualSetup.text())?;
+    for (i, p) in PRESETS.iter().enumerate() {
+        let number = format!("{:>2}", i + 1);
+        let (name, description) = (p.localized_name(), p.localized_description());
+        let line = Msg::PresetLine.fill(&[&number, &name, &description, &p.blocks, &p.speed_idx]);
+        writeln!(console, "{}", line)?;
+    }
+    let Some(answer) = console.prompt(Msg::Choice.text())? else { return Ok(None) };
+    // не число или нет такого пресета — ручная настройка
+    let preset = answer.parse::<usize>().ok().and_then(|n| PRESETS.get(n.checked_sub(1)?));
+    let params = match preset {
+        None => manual_params(console, alphabet)?,
+        Some(preset) => {
+            if preset.blocks > PRESET_CONFIRM_BLOCKS {
+                // скорость генерации меряем на небольшом образце отдельным
+                // ГПСЧ, чтобы не сдвинуть поток `generate`
+                let t = Instant::now();
+                let mut rng = StdRng::seed_from_u64(BENCH_SEED);
+                ConfigData::generate(&mut rng, alphabet, PRESET_SAMPLE_BLOCKS);
+                let secs = t.elapsed().as_secs_f64() * preset.blocks as f64
+                    / PRESET_SAMPLE_BLOCKS as f64;
+                let gib = preset.estimated_memory_bytes() as f64 / (1u64 << 30) as f64;
+                let estimate = Msg::PresetEstimate.fill(&[
+                    &preset.localized_name(),
+                    &preset.blocks,
+                    &format!("{:.2}", gib),
+                    &format!("{:.0}", secs.ceil()),
+                ]);
+                writeln!(console, "{}", estimate)?;
+                if !console.confirm(Msg::ContinuePrompt.text())? {
+                    return Ok(None);
+                }
+            }
+            let (blocks, pairs) = (preset.blocks, preset.plugboard_pairs);
+            Some(Params { alphabet: alphabet.into(), blocks, pairs, fixed_pairs: None })
+        }
+    };
+    match params {
+        Some(params) => run(params, generate, console).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Так при вводе пар plugboard пишется пробел. Ни в одном буквенном
+/// алфавите `_` нет, так что спутать не с чем.
+pub const SPACE_KEY: char = '_';
+
+/// Ручная настройка: пары plugboard вручную или их число, затем число
+/// блоков. `None` — конец ввода.
+fn manual_params<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    alphabet: &str,
+) -> io::Result<Option<Params>> {
+    let alphabet_chars = alphabet::chars(alphabet);
+    writeln!(console, "{}", Msg::PlugboardSetup.text())?;
+    let Some(choice) = console.prompt("> ")? else { return Ok(None) };
+    let (mut pairs, mut fixed_pairs) = (0, None);
+    if choice == "1" {
+        let mut entered: Vec<(char, char)> = Vec::new();
+        let index = AlphabetIndex::new(&alphabet_chars);
+        writeln!(console, "{}", Msg::EnterPairs.text())?;
+        while let Some(line) = console.prompt(Msg::AddPair.text())? {
+            if line.is_empty() {
+                break;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            // пробел в паре не напечатать — вместо него `SPACE_KEY`
+            let symbol = |s: &str| match s.chars().next().unwrap() {
+                SPACE_KEY => ' ',
+                c => c,
+            };
+            match parts.as_slice() {
+                ["list"] if entered.is_empty() => writeln!(console, "{}", Msg::PairsEmpty.text())?,
+                ["list"] => {
+                    for (a, b) in &entered {
+                        let shown = |c: char| if c == ' ' { SPACE_KEY } else { c };
+                        writeln!(console, "  {} ↔ {}", shown(*a), shown(*b))?;
+                    }
+                }
+                ["clear"] => entered.clear(),
+                ["remove", sym] => {
+                    let c = symbol(sym);
+                    let before = entered.len();
+                    entered.retain(|&(a, b)| a != c && b != c);
+                    if entered.len() == before {
+                        writeln!(console, "{}", Msg::PairNotFound.fill(&[&c]))?;
+                    }
+                }
+                [a, b] => {
+                    let (a, b) = (symbol(a), symbol(b));
+                    match check_pair(&entered, &index, a, b) {
+                        Some(err) => writeln!(console, "{}", err)?,
+                        None => entered.push((a, b)),
+                    }
+                }
+                _ => writeln!(console, "{}", Msg::NeedTwoChars.text())?,
+            }
+        }
+        fixed_pairs = Some(entered);
+    } else {
+        pairs = loop {
+            let text = Msg::HowManyPairs.fill(&[&plugboard::DEFAULT_PAIRS]);
+            let Some(answer) = console.prompt(&text)? else { return Ok(None) };
+            let count = match answer.as_str() {
+                "" => plugboard::DEFAULT_PAIRS,
+                n => match n.parse() {
+                    Ok(count) => count,
+                    Err(_) => continue,
+                },
+            };
+            let alphabet_len = alphabet_chars.len();
+            if count <= alphabet_len / 2 {
+                break count;
+            }
+            let e = CipherError::TooManyPlugboardPairs { pairs: count, alphabet_len };
+            writeln!(console, "{}", e)?;
+        };
+    }
+
+    let Some(answer) = console.prompt(Msg::HowManyBlocks.text())? else { return Ok(None) };
+    let blocks = answer.parse().ok().filter(|&n| n > 0).unwrap_or(DEFAULT_BLOCKS);
+    Ok(Some(Params { alphabet: alphabet.into(), blocks, pairs, fixed_pairs }))
+}
+
+/// Проверка новой пары plugboard теми же правилами, что и у машины
+/// (`Plugboard::from_pairs`), но с подсказками для меню. `None` — пару
+/// можно добавлять.
+fn check_pair(pairs: &[(char, char)], index: &AlphabetIndex, a: char, b: char) -> Option<String> {
+    let candidate: Vec<(char, char)> = pairs.iter().copied().chain([(a, b)]).collect();
+    Some(match Plugboard::from_pairs(&candidate, index).err()? {
+        CipherError::PlugboardSelfPair(_) => Msg::PairSelf.text().to_string(),
+        CipherError::PlugboardUnknownChar(c) => Msg::PairNotInAlphabet.fill(&[&c]),
+        CipherError::PlugboardCharReused(c) => Msg::PairSymbolTaken.fill(&[&c, &c]),
+        e => e.to_string(),
+    })
+}
@@ -0,0 +1,45 @@
+//! Перенос длинного шифротекста по строкам фиксированной ширины, чтобы его
+//! было удобно копировать из терминала или письма.
+//!
+//! Считаются все символы, включая переводы строк из самого текста: перенос
+//! стоит после каждых `cols` символов исходника независимо от содержимого.
+//! Поэтому `unwrap` с той же шириной — точное обратное к `wrap` для любого
+//! текста, даже многострочного. Символы вне алфавита машина не шифрует и
+//! роторы на них не шагают, но переносы всё равно нужно снять перед
+//! расшифровкой: иначе они попадут в открытый текст и сломают тег целостности.
+
+
+use crate::prelude::*;
+
+/// Вставляет `'\n'` после каждых `cols` символов; в конце текста — нет
+pub fn wrap(text: &str, cols: usize) -> String {
+    assert!(cols > 0, "ширина строки больше нуля");
+    let mut out = String::with_capacity(text.len() + text.len() / cols);
+    for (i, ch) in text.chars().enumerate() {
+        if i > 0 && i % cols == 0 {
+            out.push('\n');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Убирает переносы, вставленные `wrap` с той же шириной. Текст без
+/// переносов не меняется, если в нём нет `'\n'` ровно на месте переноса.
+pub fn unwrap(text: &str, cols: usize) -> String {
+    assert!(cols > 0, "ширина строки больше нуля");
+    let mut out = String::with_capacity(text.len());
+    let mut taken = 0;
+    for ch in text.chars() {
+        if taken == cols && ch == '\n' {
+            taken = 0;
+            continue;
+        }
+        if taken == cols {
+            taken = 0;
+        }
+        out.push(ch);
+        taken += 1;
+    }
+    out
+}
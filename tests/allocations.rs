@@ -0,0 +1,48 @@
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Аллокатор, считающий выделения памяти в текущем потоке
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn encrypt_allocates_only_the_output() {
+    let cfg = ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'b')],
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        rotor_positions: Vec::new(),
+        ..Default::default()
+    };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    // заглавные и символы вне алфавита: раньше здесь появлялись копия
+    // в нижнем регистре и два вектора индексов
+    for phrase in ["the quick brown fox jumps over the lazy dog. ", "The Quick, Brown FOX! 42 "] {
+        let msg = phrase.repeat(40_000);
+
+        let before = ALLOCATIONS.with(Cell::get);
+        let cipher = machine.encrypt(&msg);
+        let allocations = ALLOCATIONS.with(Cell::get) - before;
+
+        assert_eq!(cipher.chars().count(), msg.chars().count());
+        assert_eq!(allocations, 1, "{phrase:?}");
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use enigma_sd::{Alphabet, BYTES_ALPHABET, CipherError, ConfigData, EnigmaSudnogoDnya, wrap};
+use unicode_normalization::UnicodeNormalization;
+
+fn round_trip(cfg: &ConfigData, text: &str) -> (String, String) {
+    let cipher = EnigmaSudnogoDnya::new(cfg).unwrap().encrypt(text);
+    let plain = EnigmaSudnogoDnya::new(cfg).unwrap().decrypt(&cipher).unwrap();
+    (cipher, plain)
+}
+
+#[test]
+fn registry_knows_every_alphabet() {
+    for (name, len) in [
+        ("latin", 26),
+        ("cyrillic", 33),
+        ("ukrainian", 33),
+        ("belarusian", 32),
+        ("greek", 36),
+        ("latin-ext", 39),
+        (BYTES_ALPHABET, 256),
+    ] {
+        let letters = Alphabet::by_name(name).unwrap();
+        assert_eq!(letters.len(), len, "{name}");
+        assert_eq!(common::config(name, &[]).alphabet_chars(), letters);
+    }
+    assert_eq!(Alphabet::by_name("Latin"), None);
+    assert_eq!(Alphabet::by_name("klingon"), None);
+}
+
+#[test]
+fn letters_are_distinct_lowercase_and_nfc() {
+    for a in Alphabet::ALL.iter().filter(|a| a.name != BYTES_ALPHABET) {
+        let mut sorted = a.letters.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), a.letters.len(), "{}", a.name);
+        for &c in a.letters {
+            // NFC не должна менять букву, иначе при `normalize` она была бы
+            // недостижима; заглавная сворачивается в неё же, кроме греческих
+            // букв без своей заглавной
+            assert!(c.to_lowercase().eq([c]), "{} {c}", a.name);
+            assert!(c.to_string().nfc().eq([c]), "{} {c}", a.name);
+            let folded: String = c.to_uppercase().flat_map(char::to_lowercase).collect();
+            match c {
+                'ς' => assert_eq!(folded, "σ"),
+                'ΐ' | 'ΰ' => assert_eq!(folded.nfc().collect::<String>(), c.to_string()),
+                _ => assert_eq!(folded, c.to_string(), "{}", a.name),
+            }
+        }
+    }
+}
+
+#[test]
+fn ukrainian_and_belarusian_letters_are_enciphered() {
+    for (name, text, foreign) in [
+        ("ukrainian", "Ґанок, Їжак і Єнот п'ють чай", 'ы'),
+        ("belarusian", "Ўсё, што Іван і Ёсік бачылі", 'щ'),
+    ] {
+        let cfg = common::config(name, &[]);
+        let (cipher, plain) = round_trip(&cfg, text);
+        assert_eq!(plain, text.to_lowercase());
+        let letters = Alphabet::by_name(name).unwrap();
+        assert!(cipher.chars().filter(|c| c.is_alphabetic()).all(|c| letters.contains(&c)));
+        // буква соседнего алфавита идёт как есть
+        let (cipher, _) = round_trip(&cfg, &foreign.to_string());
+        assert_eq!(cipher, foreign.to_string());
+    }
+}
+
+#[test]
+fn greek_keeps_final_sigma_and_accents() {
+    let cfg = common::config("greek", &[]);
+    let text = "καλημέρα κόσμος, πώς είσαι; ϊ ΰ";
+    let (cipher, plain) = round_trip(&cfg, text);
+    assert_eq!(plain, text);
+    let letters = Alphabet::by_name("greek").unwrap();
+    assert!(cipher.chars().filter(|c| c.is_alphabetic()).all(|c| letters.contains(&c)));
+    // заглавные сворачиваются посимвольно: Σ в конце слова — σ, а не ς
+    let (_, plain) = round_trip(&cfg, "ΟΔΟΣ Άρης");
+    assert_eq!(plain, "οδοσ άρης");
+    // ς шифруется, а не выдаёт конец слова
+    let (cipher, _) = round_trip(&cfg, "ςςςς");
+    assert_ne!(cipher, "ςςςς");
+    assert!(cipher.chars().all(|c| letters.contains(&c)));
+}
+
+#[test]
+fn new_alphabets_support_homophones() {
+    for (name, text) in [
+        ("ukrainian", "Ой у лузі червона калина похилилася"),
+        ("belarusian", "Пагоня ляціць на ўсход і на захад"),
+        ("greek", "Ο ήλιος ανατέλλει πάνω από το Αιγαίο"),
+    ] {
+        let cfg = ConfigData { homophonic: true, ..common::config(name, &[]) };
+        let (cipher, plain) = round_trip(&cfg, text);
+        assert!(cipher.chars().any(|c| c.is_ascii_digit()), "{name}");
+        assert_eq!(plain, text.to_lowercase(), "{name}");
+    }
+}
+
+#[test]
+fn unknown_alphabet_is_rejected() {
+    let cfg = common::config("klingon", &[]);
+    let err = EnigmaSudnogoDnya::new(&cfg).unwrap_err();
+    assert_eq!(err, CipherError::UnknownAlphabet("klingon".into()));
+    assert!(err.to_string().contains("greek"));
+    assert_eq!(cfg.validate(), Err(err));
+}
+
+#[test]
+fn latin_ext_hides_spaces_and_punctuation() {
+    let cfg = common::config("latin-ext", &[(' ', 'e'), ('.', '7')]);
+    let text = "Meet me at 10.30, by the old mill. Come alone.";
+    let (cipher, plain) = round_trip(&cfg, text);
+    assert_eq!(plain, text.to_lowercase());
+    // каждый символ — буква алфавита, так что ничего не прошло как есть:
+    // ни длины слов, ни места знаков
+    let letters = Alphabet::by_name("latin-ext").unwrap();
+    assert!(plain.chars().all(|c| letters.contains(&c)));
+    assert!(cipher.chars().all(|c| letters.contains(&c)));
+    let words = |s: &str| s.split(' ').map(str::len).collect::<Vec<_>>();
+    assert_ne!(words(&cipher), words(&plain));
+    let marks = |s: &str| s.match_indices([',', '.']).map(|(i, _)| i).collect::<Vec<_>>();
+    assert_ne!(marks(&cipher), marks(&plain));
+
+    // перенос строк считает позиции, а не пробелы: пробел-буква ему не мешает
+    let wrapped = wrap::wrap(&cipher, 7);
+    let unwrapped = wrap::unwrap(&wrapped, 7);
+    assert_eq!(unwrapped, cipher);
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&unwrapped).unwrap(), plain);
+}
+
+#[test]
+fn latin_ext_has_no_free_digits_for_homophones() {
+    let cfg = ConfigData { homophonic: true, ..common::config("latin-ext", &[]) };
+    assert_eq!(cfg.validate(), Err(CipherError::HomophonicUnsupported));
+}
@@ -0,0 +1,154 @@
+#![cfg(all(feature = "std", feature = "rand"))]
+
+use enigma_sd::analysis::{estimate_keyspace, frequency_profile, readable, survey};
+use enigma_sd::{BENCH_SEED, ConfigData, bench_corpus, config_bitness};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn latin() -> Vec<char> {
+    ConfigData::default().alphabet_chars()
+}
+
+#[test]
+fn uniform_text_has_zero_chi_square() {
+    let text = "abcdefghijklmnopqrstuvwxyz".repeat(40);
+    let report = frequency_profile(&text, &latin());
+    assert_eq!(report.total, 26 * 40);
+    assert!(report.counts.iter().all(|&(_, c)| c == 40));
+    assert!(report.chi_square.abs() < 1e-9);
+    // 26 · 40 · 39 / (1040 · 1039)
+    assert!((report.index_of_coincidence - 39.0 / 1039.0).abs() < 1e-12);
+}
+
+#[test]
+fn single_letter_text_is_maximally_skewed() {
+    let report = frequency_profile(&"e".repeat(100), &latin());
+    assert_eq!(report.index_of_coincidence, 1.0);
+    // ожидаемое 100/26 на букву: (100 - E)²/E + 25 · E
+    let e = 100.0 / 26.0;
+    let chi = (100.0 - e) * (100.0 - e) / e + 25.0 * e;
+    assert!((report.chi_square - chi).abs() < 1e-9);
+    assert_eq!(report.top_bigrams, vec![("ee".to_string(), 99)]);
+}
+
+#[test]
+fn foreign_characters_and_case_are_ignored() {
+    let report = frequency_profile("Ab, AB! ab 42 — ж", &latin());
+    assert_eq!(report.total, 6);
+    assert_eq!(report.counts[0], ('a', 3));
+    assert_eq!(report.counts[1], ('b', 3));
+    assert_eq!(
+        report.top_bigrams,
+        vec![("ab".to_string(), 3), ("ba".to_string(), 2)]
+    );
+}
+
+#[test]
+fn empty_text_reports_zeros() {
+    let report = frequency_profile("", &latin());
+    assert_eq!(report.total, 0);
+    assert_eq!(report.chi_square, 0.0);
+    assert_eq!(report.index_of_coincidence, 0.0);
+    assert!(report.top_bigrams.is_empty());
+}
+
+fn blocks(alphabet: &str, blocks: &[&str]) -> ConfigData {
+    ConfigData {
+        alphabet: alphabet.into(),
+        blocks: blocks.iter().map(|b| b.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn assert_bits(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+#[test]
+fn single_rotor_is_one_doubled_shift() {
+    let est = estimate_keyspace(&blocks("latin", &["К"]));
+    assert_bits(est.naive_bits, 26f64.log2());
+    // 2S mod 26 принимает 13 значений
+    assert_bits(est.effective_bits, 13f64.log2());
+    assert_bits(est.period_bits, 26f64.log2());
+}
+
+#[test]
+fn equal_blocks_commute() {
+    let est = estimate_keyspace(&blocks("latin", &["КБ", "ЧЗ"]));
+    assert_bits(est.naive_bits, 4.0 * 26f64.log2());
+    // 13 сдвигов × мультимножества из двух младших позиций: C(27, 2) = 351.
+    // Перебор всех 26^4 стартовых позиций даёт ровно 4563 разных шифротекста.
+    assert_bits(est.effective_bits, (13.0 * 351.0f64).log2());
+    assert_bits(est.period_bits, 2.0 * 26f64.log2());
+}
+
+#[test]
+fn colors_and_rotor_order_do_not_matter() {
+    let base = estimate_keyspace(&blocks("cyrillic", &["КБ", "Ч"]));
+    for other in [&["БК", "Ч"][..], &["ЛЛ", "Г"], &["Ч", "КБ"]] {
+        assert_eq!(estimate_keyspace(&blocks("cyrillic", other)), base);
+    }
+    // нечётный алфавит: сдвиг 33 значения, младшая позиция ещё 33
+    assert_bits(base.effective_bits, 2.0 * 33f64.log2());
+    assert_bits(base.naive_bits, 3.0 * 33f64.log2());
+}
+
+#[test]
+fn plugboard_counts_in_both_estimates() {
+    let mut cfg = blocks("latin", &["К"]);
+    cfg.plugboard = vec![('a', 'b')];
+    let est = estimate_keyspace(&cfg);
+    assert_bits(est.plugboard_bits, 325f64.log2());
+    assert_bits(est.naive_bits, 26f64.log2() + 325f64.log2());
+    assert_bits(est.effective_bits, 13f64.log2() + 325f64.log2());
+    assert_eq!(config_bitness(&cfg), est.naive_bits);
+}
+
+#[test]
+fn enciphered_benchmark_text_has_a_chi_square() {
+    let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(827), "latin", 2);
+    let mut machine = enigma_sd::EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let alphabet = machine.alphabet().to_vec();
+    let corpus = bench_corpus(BENCH_SEED, &alphabet, &[0, 1, 10_000]);
+    for text in &corpus {
+        let cipher = machine.encrypt(text);
+        let plain = frequency_profile(text, &alphabet);
+        let enciphered = frequency_profile(&cipher, &alphabet);
+        assert_eq!(enciphered.total, plain.total);
+        assert!(enciphered.chi_square.is_finite() && enciphered.chi_square >= 0.0);
+    }
+}
+
+#[test]
+fn surveyed_bitness_grows_with_blocks() {
+    let counts = [1, 2, 4, 8, 16];
+    let entries = survey(&mut StdRng::seed_from_u64(3), "latin", &counts, 20, 8).unwrap();
+    assert_eq!(entries.len(), counts.len() * 20);
+    let averages: Vec<f64> = entries
+        .chunks(20)
+        .zip(counts)
+        .map(|(group, blocks)| {
+            assert!(group.iter().all(|e| e.blocks == blocks));
+            group.iter().map(|e| e.bitness).sum::<f64>() / 20.0
+        })
+        .collect();
+    assert!(averages.windows(2).all(|w| w[0] < w[1]), "{averages:?}");
+
+    // битность та же, что у конфига из того же потока ГПСЧ
+    let mut rng = StdRng::seed_from_u64(3);
+    let first = ConfigData::generate_with_pairs(&mut rng, "latin", 1, 8).unwrap();
+    assert_eq!(entries[0].bitness, config_bitness(&first));
+    assert_eq!(entries[0].rotors, first.rotor_count());
+}
+
+#[test]
+fn space_as_a_letter_is_counted_and_labelled() {
+    let alphabet: Vec<char> = "abc ".chars().collect();
+    let report = frequency_profile("a b  c", &alphabet);
+    assert_eq!(report.total, 6);
+    assert_eq!(report.counts[3], (' ', 3));
+    assert_eq!(readable(' '), "␣");
+    assert_eq!(readable('\n'), "\\n");
+    assert_eq!(readable('a'), "a");
+}
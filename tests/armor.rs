@@ -0,0 +1,73 @@
+#![cfg(feature = "rand")]
+
+use enigma_sd::armor::{self, ARMOR_COLS, Encoding};
+use enigma_sd::file_header::FILE_MAGIC;
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+const MESSAGE: &str = "атака на рассвете, держать мост до подхода своих";
+
+fn cyrillic_cipher() -> String {
+    let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(11), "cyrillic", 3);
+    EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(MESSAGE)
+}
+
+#[test]
+fn cyrillic_ciphertext_survives_every_encoding() {
+    let cipher = cyrillic_cipher();
+    assert!(!cipher.is_ascii());
+    for encoding in [Encoding::Hex, Encoding::Base64] {
+        let armored = armor::encode(&cipher, encoding);
+        assert!(armored.is_ascii(), "{}", encoding.name());
+        assert!(armored.lines().all(|line| line.len() <= ARMOR_COLS));
+        assert!(armored.lines().count() > 1);
+        assert_eq!(armor::decode(&armored, encoding).unwrap(), cipher);
+        // письмо из Windows: `\r\n`, пробелы в конце строк и перевод в конце
+        let mangled = armored.replace('\n', " \r\n") + "\r\n";
+        assert_eq!(armor::decode(&mangled, encoding).unwrap(), cipher);
+    }
+    assert_eq!(armor::encode(&cipher, Encoding::None), cipher);
+    assert_eq!(armor::decode(&cipher, Encoding::None).unwrap(), cipher);
+}
+
+#[test]
+fn encodings_match_the_usual_forms() {
+    assert_eq!(armor::encode("Я", Encoding::Hex), "d0af");
+    assert_eq!(armor::decode("D0AF", Encoding::Hex).unwrap(), "Я");
+    for (text, base64) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v")] {
+        assert_eq!(armor::encode(text, Encoding::Base64), base64);
+        assert_eq!(armor::decode(base64, Encoding::Base64).unwrap(), text);
+    }
+    for name in ["hex", "base64", "none"] {
+        assert_eq!(Encoding::parse(name).unwrap().name(), name);
+    }
+    assert_eq!(Encoding::parse("base32"), None);
+}
+
+#[test]
+fn broken_armor_is_an_error() {
+    let bad = |encoding: Encoding| Err(CipherError::BadArmor(encoding.name()));
+    for broken in ["abc", "zz", "+f"] {
+        assert_eq!(armor::decode(broken, Encoding::Hex), bad(Encoding::Hex), "{broken}");
+    }
+    for broken in ["Zg=", "Z===", "Zg==Zg==", "Zm8*"] {
+        assert_eq!(armor::decode(broken, Encoding::Base64), bad(Encoding::Base64), "{broken}");
+    }
+    // внутри не UTF-8
+    assert_eq!(armor::decode("ff", Encoding::Hex), bad(Encoding::Hex));
+    assert_eq!(armor::decode_bytes("ff", Encoding::Hex).unwrap(), [0xff]);
+}
+
+#[test]
+fn detect_finds_the_armor_around_a_header() {
+    let mut file = FILE_MAGIC.to_vec();
+    file.extend_from_slice(&[0, 1, 2, 0xff]);
+    for encoding in [Encoding::Hex, Encoding::Base64] {
+        let armored = armor::encode_bytes(&file, encoding).replace('\n', "\r\n");
+        assert_eq!(armor::detect(&armored, FILE_MAGIC), Some(encoding));
+        assert_eq!(armor::decode_bytes(&armored, encoding).unwrap(), file);
+    }
+    assert_eq!(armor::detect("not armor at all", FILE_MAGIC), None);
+    assert_eq!(armor::detect(&armor::encode("ESD0", Encoding::Hex), FILE_MAGIC), None);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+
+use enigma_sd::attack::{MAX_SEARCH_CANDIDATES, SearchSpace, brute_force};
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+
+const MESSAGE: &str = "attack at dawn, the quick brown fox jumps over the lazy dog";
+
+fn planted() -> ConfigData {
+    ConfigData {
+        blocks: vec!["БЧ".into()],
+        rotor_positions: vec![vec![3, 7]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn recovers_planted_config_from_crib() {
+    let cipher = EnigmaSudnogoDnya::new(&planted()).unwrap().encrypt(MESSAGE);
+    let space = SearchSpace { max_blocks: 1, ..Default::default() };
+    let found = brute_force(&cipher, "attack at dawn", &space).unwrap();
+    assert!(found.iter().any(|c| c.plaintext == MESSAGE));
+    // каждый кандидат действительно даёт crib
+    assert!(found.iter().all(|c| c.plaintext.starts_with("attack at dawn")));
+}
+
+#[test]
+fn full_crib_leaves_only_equivalent_keys() {
+    let cipher = EnigmaSudnogoDnya::new(&planted()).unwrap().encrypt(MESSAGE);
+    let space = SearchSpace { max_blocks: 1, ..Default::default() };
+    let found = brute_force(&cipher, MESSAGE, &space).unwrap();
+    assert!(found.iter().all(|c| c.plaintext == MESSAGE));
+    // двухроторный блок: важна младшая позиция и сумма mod n/2 — остальное
+    // даёт 26 · 26 / (26 · 13) = 2 эквивалентных ключа
+    let two_rotor: Vec<_> = found.iter().filter(|c| c.config.blocks == ["КК"]).collect();
+    assert_eq!(two_rotor.len(), 2);
+    for c in two_rotor {
+        let mut machine = EnigmaSudnogoDnya::new(&c.config).unwrap();
+        assert_eq!(machine.encrypt(MESSAGE), cipher);
+    }
+}
+
+#[test]
+fn cracks_start_positions_of_a_two_rotor_block() {
+    let cipher = EnigmaSudnogoDnya::new(&planted()).unwrap().encrypt(MESSAGE);
+    let unknown = ConfigData { rotor_positions: Vec::new(), ..planted() };
+    // фрагмент короче оборота младшего ротора не различает расписание переносов
+    let short = EnigmaSudnogoDnya::crack_positions(&unknown, "attack at dawn", &cipher[..14]);
+    assert!(short.is_some());
+
+    let positions = EnigmaSudnogoDnya::crack_positions(&unknown, MESSAGE, &cipher)
+        .expect("26² вариантов перебираются целиком");
+    // может найтись эквивалентный ключ, но расшифровка та же
+    let cracked = ConfigData { rotor_positions: positions, ..planted() };
+    assert_eq!(EnigmaSudnogoDnya::new(&cracked).unwrap().decrypt(&cipher).unwrap(), MESSAGE);
+
+    assert_eq!(EnigmaSudnogoDnya::crack_positions(&unknown, "attack", "zzzzzz"), None);
+}
+
+#[test]
+fn large_machines_are_not_searched() {
+    let big = ConfigData { blocks: vec!["КБЧ".into(), "КБ".into()], ..Default::default() };
+    assert_eq!(EnigmaSudnogoDnya::crack_positions(&big, "", ""), None);
+}
+
+#[test]
+fn oversized_search_is_refused() {
+    let space = SearchSpace { max_blocks: 3, ..Default::default() };
+    assert_eq!(
+        brute_force("abc", "abc", &space).err(),
+        Some(CipherError::SearchSpaceTooLarge { max: MAX_SEARCH_CANDIDATES })
+    );
+    // переполнение u64 — тот же отказ, а не тихий пропуск формы
+    let huge = SearchSpace { max_blocks: 20, max_rotors: 20, ..Default::default() };
+    let refused = brute_force("abc", "abc", &huge);
+    assert!(matches!(refused, Err(CipherError::SearchSpaceTooLarge { .. })));
+}
@@ -0,0 +1,69 @@
+#![cfg(all(feature = "std", feature = "json", feature = "rand"))]
+
+mod common;
+
+use enigma_sd::benchmark::{self, BenchmarkOptions, parse_size};
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, config_bitness};
+use std::time::Duration;
+
+fn config() -> ConfigData {
+    common::config("latin", &[('a', 'q')])
+}
+
+#[test]
+fn sizes_take_decimal_suffixes() {
+    assert_eq!(parse_size("500"), Some(500));
+    assert_eq!(parse_size("1k"), Some(1_000));
+    assert_eq!(parse_size(" 100K "), Some(100_000));
+    assert_eq!(parse_size("10m"), Some(10_000_000));
+    assert_eq!(parse_size("2G"), Some(2_000_000_000));
+    for bad in ["", "k", "0", "0k", "1.5k", "1kb", "-1", "99999999999999999999g"] {
+        assert_eq!(parse_size(bad), None, "{bad:?}");
+    }
+}
+
+#[test]
+fn report_covers_every_size() {
+    let cfg = config();
+    let report = benchmark::run(&cfg, &[100, 10, 1_000]).unwrap();
+    let sizes: Vec<usize> = report.sizes.iter().map(|s| s.size).collect();
+    assert_eq!(sizes, [100, 10, 1_000]);
+    assert!(report.skipped.is_empty());
+    assert!(report.kat_ok());
+    assert_eq!(report.bitness, config_bitness(&cfg));
+    assert_eq!(report.period, EnigmaSudnogoDnya::new(&cfg).unwrap().period());
+    assert_eq!(report.fingerprint.len(), 64);
+    for s in &report.sizes {
+        assert!(s.encrypt_chars_per_sec > 0.0 && s.decrypt_chars_per_sec > 0.0);
+    }
+
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["sizes"][2]["size"], 1_000);
+    assert_eq!(json["sizes"][0]["kat_ok"], true);
+    assert!(json["sizes"][1]["encrypt_secs"].is_f64());
+    // машина строится один раз, и время построения — одно на отчёт
+    assert!(json["construct_secs"].is_f64());
+    assert!(json["sizes"][0].get("construct_secs").is_none());
+}
+
+#[test]
+fn one_machine_serves_every_size() {
+    // `reset` между размерами возвращает и фазу шагания
+    let cfg = ConfigData { step_interval: 3, step_every: vec![1, 2], ..config() };
+    let report = benchmark::run(&cfg, &[7, 10, 1_000]).unwrap();
+    assert!(report.kat_ok());
+}
+
+#[test]
+fn budget_skips_sizes_that_would_not_fit() {
+    let budget = Some(Duration::from_nanos(1));
+    let options = BenchmarkOptions { max_time: budget, ..Default::default() };
+    let report = benchmark::run_with(&config(), &[10, 1_000, 100], &options).unwrap();
+    // первый размер меряется всегда
+    assert_eq!(report.sizes.len(), 1);
+    assert_eq!(report.skipped, [1_000, 100]);
+
+    let options = BenchmarkOptions { max_time: Some(Duration::from_secs(3600)), ..options };
+    let report = benchmark::run_with(&config(), &[10, 1_000, 100], &options).unwrap();
+    assert_eq!(report.sizes.len(), 3);
+}
@@ -0,0 +1,69 @@
+#![cfg(all(feature = "std", feature = "rand"))]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, BYTES_ALPHABET};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn byte_config() -> ConfigData {
+    let mut rng = StdRng::seed_from_u64(791);
+    ConfigData::generate(&mut rng, BYTES_ALPHABET, 5)
+}
+
+fn sample_blob() -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut data: Vec<u8> = (0..=u8::MAX).collect();
+    data.extend((0..100_000).map(|_| rng.random::<u8>()));
+    data
+}
+
+#[test]
+fn binary_blob_roundtrips() {
+    let cfg = byte_config();
+    let data = sample_blob();
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(&data);
+    assert_eq!(cipher.len(), data.len());
+    assert_ne!(cipher, data);
+    let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(&cipher);
+    assert_eq!(plain, data);
+}
+
+#[test]
+fn stream_matches_encrypt_bytes() {
+    let cfg = byte_config();
+    let data = sample_blob();
+    let expected = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(&data);
+    let mut out = Vec::new();
+    let n = EnigmaSudnogoDnya::new(&cfg)
+        .unwrap()
+        .encrypt_stream(&data[..], &mut out)
+        .unwrap();
+    assert_eq!(n, data.len() as u64);
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn text_key_works_in_byte_mode() {
+    let cfg = ConfigData {
+        alphabet: "cyrillic".into(),
+        plugboard: vec![('а', 'я'), ('ё', 'к')],
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        rotor_positions: vec![vec![1, 32, 5], vec![0, 17]],
+        ..Default::default()
+    };
+    let bytes_cfg = cfg.to_byte_mode();
+    assert_eq!(bytes_cfg.alphabet, BYTES_ALPHABET);
+    assert_eq!(bytes_cfg.plugboard, vec![('\u{0}', '\u{20}'), ('\u{6}', '\u{b}')]);
+
+    let data = sample_blob();
+    let cipher = EnigmaSudnogoDnya::new(&bytes_cfg).unwrap().encrypt_bytes(&data);
+    let plain = EnigmaSudnogoDnya::new(&bytes_cfg).unwrap().encrypt_bytes(&cipher);
+    assert_eq!(plain, data);
+}
+
+#[test]
+fn byte_mode_keeps_case() {
+    let cfg = byte_config();
+    let text = "Hello, World";
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&cipher), text);
+}
@@ -0,0 +1,709 @@
+#![cfg(feature = "cli")]
+
+use enigma_sd::file_header::FILE_HEADER_LEN;
+use enigma_sd::resume;
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use std::fs;
+use std::io::Write;
+use std::ops::ControlFlow;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Временный каталог с конфигом: программа ищет `esd_config.json` в текущем каталоге
+fn workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("esd-cli-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("esd_config.json"),
+        r#"{"alphabet":"latin","plugboard":[["a","q"]],"blocks":["КБЧ","З"],"rotor_positions":[[1,2,3],[4]]}"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn run_piped(dir: &PathBuf, args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enigma_SD"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("LANG")
+        .env_remove("GIORDANO_LANG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn piped_stdin_is_filtered_without_menu() {
+    let dir = workdir("pipe");
+    let cipher = run_piped(&dir, &[], b"hello world\n");
+    assert!(cipher.status.success());
+    assert_ne!(cipher.stdout, b"hello world\n");
+    let plain = run_piped(&dir, &[], &cipher.stdout);
+    assert_eq!(plain.stdout, b"hello world\n");
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn file_commands_roundtrip_binary() {
+    let dir = workdir("file");
+    let data: Vec<u8> = (0..=u8::MAX).cycle().take(5000).collect();
+    fs::write(dir.join("in.bin"), &data).unwrap();
+    let encrypted = run_piped(&dir, &["--quiet", "encrypt-file", "in.bin", "c.bin"], b"");
+    assert!(encrypted.status.success());
+    // stdout не терминал — индикатора хода нет и без `--quiet`
+    let decrypted = run_piped(&dir, &["decrypt-file", "c.bin", "out.bin"], b"");
+    assert!(decrypted.status.success());
+    assert!(encrypted.stderr.is_empty() && decrypted.stderr.is_empty());
+    assert_ne!(fs::read(dir.join("c.bin")).unwrap(), data);
+    assert_eq!(fs::read(dir.join("out.bin")).unwrap(), data);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn armored_output_roundtrips() {
+    let dir = workdir("armor");
+    let cyrillic = ConfigData::generate(&mut StdRng::seed_from_u64(12), "cyrillic", 2);
+    let config = serde_json::to_string(&cyrillic).unwrap();
+    fs::write(dir.join("ru.json"), config).unwrap();
+    let text = "атака на рассвете\r\nдержать мост\r\n";
+    fs::write(dir.join("in.txt"), text).unwrap();
+    for encoding in ["hex", "base64"] {
+        let cipher = format!("c-{encoding}.txt");
+        let args = ["--config", "ru.json", "--encode", encoding, "encrypt-file", "in.txt", &cipher];
+        assert!(run_piped(&dir, &args, b"").status.success());
+        let armored = fs::read(dir.join(&cipher)).unwrap();
+        assert!(armored.is_ascii() && armored.split(|&b| b == b'\n').all(|l| l.len() <= 64));
+        // броня узнаётся по заголовку и после правки переводов строк
+        let crlf = String::from_utf8(armored).unwrap().replace('\n', "\r\n");
+        fs::write(dir.join(&cipher), crlf).unwrap();
+        let args = ["--config", "ru.json", "decrypt-file", &cipher, "out.txt", "--force"];
+        let out = run_piped(&dir, &args, b"");
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), text);
+    }
+
+    // фильтр: `--encode` на выходе, `--decode` на входе
+    let args = ["--config", "ru.json", "--encode", "base64"];
+    let cipher = run_piped(&dir, &args, text.as_bytes());
+    assert!(cipher.status.success() && cipher.stdout.is_ascii());
+    let args = ["--config", "ru.json", "--decode", "base64"];
+    assert_eq!(run_piped(&dir, &args, &cipher.stdout).stdout, text.as_bytes());
+    let bad = run_piped(&dir, &["--lang", "en", "--encode", "base32"], b"");
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("hex, base64"));
+    fs::remove_dir_all(dir).ok();
+}
+
+/// Без дисплея (или без фичи `clipboard`) — понятная ошибка, а не паника
+#[test]
+#[cfg(any(not(feature = "clipboard"), target_os = "linux"))]
+fn clipboard_without_display_fails_cleanly() {
+    let dir = workdir("clipboard");
+    let out = Command::new(env!("CARGO_BIN_EXE_enigma_SD"))
+        .args(["--lang", "en", "encrypt", "--clipboard"])
+        .current_dir(&dir)
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("Clipboard unavailable"));
+    let usage = run_piped(&dir, &["--lang", "en", "decrypt"], b"");
+    assert_eq!(usage.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&usage.stderr).contains("--clipboard"));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn file_commands_roundtrip_without_reflector() {
+    let dir = workdir("one-way");
+    fs::write(
+        dir.join("esd_config.json"),
+        r#"{"alphabet":"latin","plugboard":[],"blocks":["КБЧ"],"rotor_positions":[[1,2,3]],
+            "use_reflector":false}"#,
+    )
+    .unwrap();
+    let bytes: Vec<u8> = (0..=u8::MAX).collect();
+    for (name, data) in [("txt", &b"one way only"[..]), ("bin", &bytes[..])] {
+        let (input, cipher, output) =
+            (format!("in.{name}"), format!("c.{name}"), format!("out.{name}"));
+        fs::write(dir.join(&input), data).unwrap();
+        assert!(run_piped(&dir, &["encrypt-file", &input, &cipher], b"").status.success());
+        assert!(run_piped(&dir, &["decrypt-file", &cipher, &output], b"").status.success());
+        assert_ne!(fs::read(dir.join(&cipher)).unwrap(), data);
+        assert_eq!(fs::read(dir.join(&output)).unwrap(), data);
+    }
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn encrypted_files_carry_a_header() {
+    let dir = workdir("header");
+    // только символы вне алфавита, пустые строки и перевод строки в конце
+    for (name, text) in [("in.txt", "attack at dawn!\n\n"), ("odd.txt", "123 ?!\n")] {
+        fs::write(dir.join(name), text).unwrap();
+        let cipher = format!("c-{name}");
+        assert!(run_piped(&dir, &["encrypt-file", name, &cipher], b"").status.success());
+        assert!(fs::read(dir.join(&cipher)).unwrap().starts_with(b"ESD1"));
+        let out = run_piped(&dir, &["decrypt-file", &cipher, "out.txt", "--force"], b"");
+        assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+        assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), text);
+    }
+
+    // без `--force` существующий выход не трогается
+    let again = run_piped(&dir, &["--lang", "en", "encrypt-file", "in.txt", "c-in.txt"], b"");
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("--force"));
+    let forced = run_piped(&dir, &["encrypt-file", "in.txt", "c-in.txt", "--force"], b"");
+    assert!(forced.status.success());
+
+    // другой конфиг — ошибка, а не мусор на выходе
+    fs::write(
+        dir.join("other.json"),
+        r#"{"alphabet":"latin","plugboard":[],"blocks":["КБЧ"],"rotor_positions":[[1,2,3]]}"#,
+    )
+    .unwrap();
+    let args = ["--config", "other.json", "decrypt-file", "c-in.txt", "wrong.txt"];
+    let wrong = run_piped(&dir, &args, b"");
+    assert!(!wrong.status.success() && !dir.join("wrong.txt").exists());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn directory_commands_roundtrip() {
+    let dir = workdir("dir");
+    fs::create_dir_all(dir.join("notes/deep")).unwrap();
+    fs::write(dir.join("notes/deep/a.txt"), "attack at dawn\n").unwrap();
+    fs::write(dir.join("notes/empty.txt"), "").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("deep", dir.join("notes/link")).unwrap();
+
+    let args = ["--lang", "en", "encrypt-dir", "notes", "--out", "enc", "--jobs", "2"];
+    let out = run_piped(&dir, &args, b"");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    #[cfg(unix)]
+    assert!(String::from_utf8_lossy(&out.stderr).contains("link"));
+    // файл каталога — обычный файл `encrypt-file`
+    let one = run_piped(&dir, &["decrypt-file", "enc/deep/a.txt", "a.txt"], b"");
+    assert!(one.status.success());
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "attack at dawn\n");
+
+    assert!(!run_piped(&dir, &["encrypt-dir", "notes", "--out", "enc"], b"").status.success());
+    assert!(run_piped(&dir, &["decrypt-dir", "enc", "--out", "dec"], b"").status.success());
+    assert_eq!(fs::read_to_string(dir.join("dec/deep/a.txt")).unwrap(), "attack at dawn\n");
+    assert_eq!(fs::read(dir.join("dec/empty.txt")).unwrap(), b"");
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn interrupted_encryption_resumes() {
+    let dir = workdir("resume");
+    let data: Vec<u8> = (0..3_000_000u32).map(|i| (i % 253) as u8).collect();
+    fs::write(dir.join("in.bin"), &data).unwrap();
+    let full = run_piped(&dir, &["encrypt-file", "in.bin", "full.esd", "--binary"], b"");
+    assert!(full.status.success());
+
+    // первая точка через мегабайт — и «сбой» сразу после неё
+    let cfg_path = dir.join("esd_config.json");
+    let cfg = EnigmaSudnogoDnya::load_config(cfg_path.to_str().unwrap()).unwrap();
+    let (input, part) = (dir.join("in.bin"), dir.join("part.esd"));
+    let stop = |_: &_| ControlFlow::Break(());
+    let done = resume::process_file(&cfg, &input, &part, false, 1 << 20, false, stop).unwrap();
+    assert!(done.is_none());
+
+    let resumed = run_piped(&dir, &["encrypt-file", "in.bin", "part.esd", "--resume"], b"");
+    assert!(resumed.status.success(), "{}", String::from_utf8_lossy(&resumed.stderr));
+    assert_eq!(fs::read(dir.join("part.esd")).unwrap(), fs::read(dir.join("full.esd")).unwrap());
+    assert!(!resume::progress_path(&part).exists());
+
+    let args = ["decrypt-file", "part.esd", "out.bin", "--checkpoint", "1"];
+    assert!(run_piped(&dir, &args, b"").status.success());
+    assert_eq!(fs::read(dir.join("out.bin")).unwrap(), data);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn json_output_matches_plain_output() {
+    let dir = workdir("json");
+    let plain = run_piped(&dir, &[], b"attack at dawn");
+    let json = run_piped(&dir, &["--output-format", "json"], b"attack at dawn");
+    assert!(json.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(value["ciphertext"].as_str().unwrap().as_bytes(), plain.stdout);
+    assert_eq!(value["final_state"].as_array().unwrap().len(), 2);
+    assert!(value["bitness"].as_f64().unwrap() > 0.0);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn authenticated_files_reject_tampering() {
+    let dir = workdir("auth");
+    fs::write(dir.join("in.txt"), "attack at dawn").unwrap();
+    let auth = ["--authenticate", "--passphrase", "secret"];
+    let run = |cmd: &str, from: &str, to: &str| {
+        let args: Vec<&str> = [cmd, from, to].iter().chain(&auth).copied().collect();
+        run_piped(&dir, &args, b"")
+    };
+    assert!(run("encrypt-file", "in.txt", "c.txt").status.success());
+    assert!(run("decrypt-file", "c.txt", "out.txt").status.success());
+    assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), "attack at dawn");
+
+    let mut sealed = fs::read(dir.join("c.txt")).unwrap();
+    let i = FILE_HEADER_LEN;
+    sealed[i] = if sealed[i] == b'z' { b'y' } else { b'z' };
+    fs::write(dir.join("c.txt"), sealed).unwrap();
+    assert!(!run("decrypt-file", "c.txt", "bad.txt").status.success());
+    assert!(!dir.join("bad.txt").exists());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn lang_flag_switches_messages() {
+    let dir = workdir("lang");
+    let out = run_piped(&dir, &["--lang", "en", "frobnicate"], b"");
+    assert_eq!(String::from_utf8_lossy(&out.stderr).trim(), "Unknown command: frobnicate");
+    let out = run_piped(&dir, &["frobnicate"], b"");
+    assert_eq!(String::from_utf8_lossy(&out.stderr).trim(), "Неизвестная команда: frobnicate");
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn advance_config_continues_the_stream() {
+    let dir = workdir("advance");
+    let initial = fs::read(dir.join("esd_config.json")).unwrap();
+    let c1 = run_piped(&dir, &["--advance-config"], b"first message").stdout;
+    let c2 = run_piped(&dir, &["--advance-config"], b"second message").stdout;
+    assert_ne!(fs::read(dir.join("esd_config.json")).unwrap(), initial);
+    assert!(!dir.join("esd_config.json.tmp").exists());
+
+    // без продвинутых позиций второе сообщение не читается
+    fs::write(dir.join("esd_config.json"), &initial).unwrap();
+    assert_ne!(run_piped(&dir, &[], &c2).stdout, b"second message");
+
+    assert_eq!(run_piped(&dir, &["--advance-config"], &c1).stdout, b"first message");
+    assert_eq!(run_piped(&dir, &[], &c2).stdout, b"second message");
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn iv_must_match_for_decrypt() {
+    let dir = workdir("iv");
+    let c1 = run_piped(&dir, &["--iv", "1"], b"same message").stdout;
+    let c2 = run_piped(&dir, &["--iv", "2"], b"same message").stdout;
+    assert_ne!(c1, c2);
+    assert_eq!(run_piped(&dir, &["--iv", "1"], &c1).stdout, b"same message");
+    assert_ne!(run_piped(&dir, &[], &c1).stdout, b"same message");
+
+    let bad = run_piped(&dir, &["--lang", "en", "--iv", "x"], b"");
+    assert_eq!(bad.status.code(), Some(2));
+    let both = run_piped(&dir, &["--iv", "1", "--advance-config"], b"");
+    assert_eq!(both.status.code(), Some(2));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn survey_prints_configs_without_saving() {
+    let dir = workdir("survey");
+    let before = fs::read(dir.join("esd_config.json")).unwrap();
+    let args = ["--lang", "en", "--seed", "1", "survey", "--blocks", "2,5", "--count", "3"];
+    let out = run_piped(&dir, &args, b"");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    // заголовок, по три строки и среднее на каждое число блоков
+    assert_eq!(stdout.lines().count(), 1 + 2 * (3 + 1));
+    assert!(stdout.contains("average for 5 blocks:"));
+    assert_eq!(run_piped(&dir, &args, b"").stdout, out.stdout, "--seed повторяет обзор");
+    assert_eq!(fs::read(dir.join("esd_config.json")).unwrap(), before);
+    assert_eq!(run_piped(&dir, &["survey", "--blocks", "0"], b"").status.code(), Some(2));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn config_without_blocks_needs_allow_degenerate() {
+    let dir = workdir("degenerate");
+    let empty = r#"{"alphabet":"latin","plugboard":[["a","q"]],"blocks":[],"rotor_positions":[]}"#;
+    fs::write(dir.join("esd_config.json"), empty).unwrap();
+    let out = run_piped(&dir, &["--lang", "en"], b"");
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--allow-degenerate"));
+
+    let out = run_piped(&dir, &["--lang", "en", "--allow-degenerate"], b"aaa");
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("Warning:"));
+    let cipher = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(cipher.trim().len(), 3);
+    assert!(cipher.trim().chars().all(|c| cipher.starts_with(c)));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn verify_vectors_passes_on_this_build() {
+    let dir = workdir("vectors");
+    let out = run_piped(&dir, &["verify-vectors"], b"");
+    assert!(out.status.success());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn analyze_reports_json() {
+    let dir = workdir("analyze");
+    fs::write(dir.join("plain.txt"), "aaaa bbbb").unwrap();
+    let out = run_piped(&dir, &["analyze", "plain.txt", "--json"], b"");
+    assert!(out.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(value["total"], 8);
+    assert_eq!(value["top_bigrams"][0], serde_json::json!(["aa", 3]));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn config_flag_reads_binary_configs() {
+    let dir = workdir("binconfig");
+    let cfg = enigma_sd::EnigmaSudnogoDnya::load_config(
+        dir.join("esd_config.json").to_str().unwrap(),
+    )
+    .unwrap();
+    let bin = dir.join("key.bin");
+    enigma_sd::EnigmaSudnogoDnya::save_config(&cfg, bin.to_str().unwrap(), false).unwrap();
+    let from_json = run_piped(&dir, &[], b"hello world");
+    let from_bin = run_piped(&dir, &["--config", "key.bin"], b"hello world");
+    assert!(from_bin.status.success());
+    assert_eq!(from_bin.stdout, from_json.stdout);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn language_comes_from_env_and_config() {
+    let dir = workdir("lang");
+    let out = Command::new(env!("CARGO_BIN_EXE_enigma_SD"))
+        .arg("frobnicate")
+        .env_remove("GIORDANO_LANG")
+        .env("LANG", "en_US.UTF-8")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stderr).trim(), "Unknown command: frobnicate");
+
+    let english = serde_json::json!({
+        "alphabet": "latin", "plugboard": [], "blocks": ["КБ"], "rotor_positions": [[1, 2]],
+        "lang": "en",
+    });
+    fs::write(dir.join("en.json"), english.to_string()).unwrap();
+    let out = run_piped(&dir, &["--config", "en.json", "strength"], b"");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Period: 676 characters"));
+    // явный --lang важнее конфига
+    let out = run_piped(&dir, &["--lang", "ru", "--config", "en.json", "strength"], b"");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Период: 676 символов"));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn interactive_script_ends_cleanly_at_eof() {
+    let dir = workdir("script");
+    let filtered = run_piped(&dir, &[], b"hello world");
+    let cipher = String::from_utf8(filtered.stdout).unwrap();
+
+    let script = b"yes\nfrobnicate\nencrypt\nhello world\n";
+    let out = run_piped(&dir, &["--interactive"], script);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Неизвестная команда"));
+    assert!(stdout.contains(cipher.trim()));
+    // после последней строки ввода — снова приглашение, и на нём конец
+    assert!(stdout.trim_end().ends_with("exit):"));
+
+    // конец ввода посреди вопроса — тоже нормальный выход
+    let out = run_piped(&dir, &["--interactive"], b"");
+    assert!(out.status.success());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn interactive_setup_then_encrypt_and_decrypt() {
+    let dir = workdir("setup");
+    fs::remove_file(dir.join("esd_config.json")).unwrap();
+    // латиница, первый пресет, сохранить; затем сообщение туда и обратно
+    let script = b"1\n1\nyes\nencrypt hello world\n";
+    let out = run_piped(&dir, &["--interactive", "--lang", "en", "--seed", "5"], script);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Choose an alphabet"));
+    assert!(stdout.contains("Save the config?"));
+    let saved = EnigmaSudnogoDnya::load_config(dir.join("esd_config.json").to_str().unwrap());
+    assert_eq!(saved.unwrap().alphabet, "latin");
+
+    let cipher = String::from_utf8(run_piped(&dir, &[], b"hello world").stdout).unwrap();
+    assert!(stdout.contains(cipher.trim()));
+    let script = format!("yes\ndecrypt {}\nexit\nencrypt never read\n", cipher.trim());
+    let out = run_piped(&dir, &["--interactive", "--lang", "en"], script.as_bytes());
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("hello world"));
+    // exit завершает меню, оставшиеся строки не читаются
+    assert!(stdout.trim_end().ends_with("exit):"));
+    assert!(!stdout.contains("never"));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn interactive_message_inline_and_multiline() {
+    let dir = workdir("heredoc");
+    let cipher = |text: &[u8]| String::from_utf8(run_piped(&dir, &[], text).stdout).unwrap();
+    let (one, two) = (cipher(b"hello world"), cipher(b"first line\n  second"));
+
+    let script = b"yes\nencrypt hello world\nencrypt <<\nfirst line\n  second\n.\n";
+    let out = run_piped(&dir, &["--interactive"], script);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains(one.trim()));
+    // строки сообщения склеены через \n, пробелы в начале строки сохранены
+    assert!(stdout.contains(two.trim()));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn interactive_trace_prints_a_line_per_character() {
+    let dir = workdir("steps");
+    let cipher = String::from_utf8(run_piped(&dir, &[], b"hi").stdout).unwrap();
+    let script = b"yes\nencrypt --trace=1 hi\nencrypt hi\n";
+    let out = run_piped(&dir, &["--interactive", "--lang", "en"], script);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("blocks shown: 1 of 2"));
+    let steps: Vec<&str> = stdout.lines().filter(|l| l.contains(" → ")).collect();
+    assert_eq!(steps.len(), 2);
+    assert!(steps.iter().all(|l| !l.contains(" | ")), "только первый блок");
+    // шифротекст тот же, что и без трассы
+    assert_eq!(stdout.matches(cipher.trim()).count(), 2);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn crack_positions_recovers_an_equivalent_key() {
+    let dir = workdir("crackpos");
+    let out = run_piped(&dir, &[], b"attack at dawn");
+    let cipher = String::from_utf8(out.stdout).unwrap();
+    let args = ["crack-positions", "--plain", "attack at dawn", "--cipher", cipher.trim_end()];
+    let out = run_piped(&dir, &[&args[..], &["--output-format", "json"]].concat(), b"");
+    assert!(out.status.success());
+    let positions: Vec<Vec<usize>> = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(positions.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1]);
+
+    let out = run_piped(&dir, &["crack-positions", "--plain", "attack", "--cipher", "zzzzzz"], b"");
+    assert_eq!(out.status.code(), Some(1));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn input_files_continue_one_stream() {
+    let dir = workdir("input");
+    let whole = "first log: meet at noon\nsecond log: bring the key\n";
+    let (head, tail) = whole.split_at(24);
+    fs::write(dir.join("a.txt"), head).unwrap();
+    fs::write(dir.join("b.txt"), tail).unwrap();
+    let split = run_piped(&dir, &["--input", "a.txt", "b.txt", "--quiet"], b"");
+    assert!(split.status.success());
+    // без reset между файлами: ровно как один вход целиком
+    assert_eq!(split.stdout, run_piped(&dir, &[], whole.as_bytes()).stdout);
+    assert_ne!(split.stdout, whole.as_bytes());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn benchmark_csv_has_header_and_rows() {
+    let dir = workdir("bench-csv");
+    let out = run_piped(&dir, &["--interactive"], b"yes\nbenchmark --csv\n");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let header = "size,construct,encrypt,decrypt,kat,bitness";
+    // заголовок печатается сразу после приглашения меню, в той же строке
+    let mut lines = stdout.lines().skip_while(|l| !l.ends_with(header)).skip(1);
+    let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+    assert_eq!(row.len(), 6);
+    assert_eq!(row[0], "10");
+    assert!(row[1..4].iter().all(|t| t.parse::<f64>().is_ok()));
+    assert_eq!(row[4], "pass");
+    // за ней только строки CSV, по одной на размер, без таблицы потоков
+    assert_eq!(lines.take_while(|l| l.split(',').count() == 6).count(), 5);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn benchmark_command_reports_json() {
+    let dir = workdir("bench-json");
+    let out = run_piped(&dir, &["benchmark", "--sizes", "1k,100", "--json"], b"");
+    assert!(out.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let sizes = report["sizes"].as_array().unwrap();
+    let sizes: Vec<u64> = sizes.iter().map(|s| s["size"].as_u64().unwrap()).collect();
+    assert_eq!(sizes, [1_000, 100]);
+    assert!(report["bitness"].as_f64().unwrap() > 0.0);
+    assert_eq!(report["fingerprint"].as_str().unwrap().len(), 64);
+
+    let bad = run_piped(&dir, &["benchmark", "--sizes", "10x"], b"");
+    assert_eq!(bad.status.code(), Some(2));
+    // в меню непонятный флаг — подсказка, а не выход
+    let script = b"yes\nbenchmark --max-seconds 0\n";
+    let out = run_piped(&dir, &["--interactive", "--lang", "en"], script);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Usage: benchmark"));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn wrapped_output_decrypts_like_unwrapped() {
+    let dir = workdir("wrap");
+    let text = "the quick brown fox jumps over the lazy dog ".repeat(5);
+    fs::write(dir.join("in.txt"), &text).unwrap();
+    assert!(run_piped(&dir, &["encrypt-file", "in.txt", "c.txt"], b"").status.success());
+    let args = ["--wrap", "16", "encrypt-file", "in.txt", "w.txt"];
+    assert!(run_piped(&dir, &args, b"").status.success());
+    let body = |name: &str| {
+        let data = fs::read(dir.join(name)).unwrap();
+        String::from_utf8(data[FILE_HEADER_LEN..].to_vec()).unwrap()
+    };
+    let (plain, wrapped) = (body("c.txt"), body("w.txt"));
+    assert!(wrapped.lines().all(|line| line.chars().count() <= 16));
+    assert_eq!(wrapped.replace('\n', ""), plain);
+
+    // ширина записана в заголовке, `--wrap` при расшифровке не нужен
+    let args = ["decrypt-file", "w.txt", "out.txt"];
+    assert!(run_piped(&dir, &args, b"").status.success());
+    assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), text);
+    // фильтр симметричен: переносы снимаются со входа
+    let filtered = run_piped(&dir, &["--wrap", "16"], wrapped.as_bytes());
+    assert_eq!(String::from_utf8(filtered.stdout).unwrap(), enigma_sd::wrap::wrap(&text, 16));
+
+    let bad = run_piped(&dir, &["--lang", "en", "--wrap", "0", "encrypt-file", "in.txt", "x"], b"");
+    assert_eq!(bad.status.code(), Some(2));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn odd_alphabet_warns_unless_quiet() {
+    let dir = workdir("fixed-point");
+    fs::write(
+        dir.join("ru.json"),
+        r#"{"alphabet":"cyrillic","plugboard":[],"blocks":["КБЧ"],"rotor_positions":[[1,2,3]]}"#,
+    )
+    .unwrap();
+    let out = run_piped(&dir, &["--lang", "en", "--config", "ru.json"], "привет".as_bytes());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("alphabet of 33 characters"));
+    let quiet = run_piped(&dir, &["--quiet", "--config", "ru.json"], "привет".as_bytes());
+    assert!(quiet.stderr.is_empty());
+    assert_eq!(quiet.stdout, out.stdout, "предупреждение не попадает в результат");
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn export_then_import_recreates_the_config() {
+    let dir = workdir("export");
+    let out = run_piped(&dir, &["export"], b"");
+    assert!(out.status.success());
+    let code = String::from_utf8(out.stdout).unwrap();
+    let args = ["--config", "copy.json", "import", code.trim()];
+    assert!(run_piped(&dir, &args, b"").status.success());
+    let load = |name: &str| EnigmaSudnogoDnya::load_config(dir.join(name).to_str().unwrap());
+    assert_eq!(load("copy.json").unwrap(), load("esd_config.json").unwrap());
+
+    let bad = run_piped(&dir, &["--config", "copy.json", "import", "не код"], b"");
+    assert_eq!(bad.status.code(), Some(1));
+    fs::remove_dir_all(dir).ok();
+}
+
+/// Один HTTP/1.1-запрос к `serve`: код ответа и тело
+fn post(addr: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    use std::io::Read;
+    let mut stream = std::net::TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        addr,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+    let (_, json) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(json).unwrap())
+}
+
+#[test]
+fn serve_roundtrips_and_rejects_bad_json() {
+    use std::io::{BufRead, BufReader};
+    let dir = workdir("serve");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enigma_SD"))
+        .args(["--lang", "en", "serve", "--listen", "127.0.0.1:0", "--max-body", "256"])
+        .current_dir(&dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+    let addr = line.trim().rsplit("http://").next().unwrap().to_string();
+
+    // второе сообщение продолжает поток с состояния после первого
+    let (status, first) = post(&addr, "/encrypt", r#"{"text":"hello"}"#);
+    assert_eq!(status, 200);
+    let body = serde_json::json!({"text": "world", "state": first["state"]}).to_string();
+    let (_, second) = post(&addr, "/encrypt", &body);
+    assert_ne!(second["text"], "world");
+    let (status, plain) =
+        post(&addr, "/decrypt", &serde_json::json!({"text": first["text"]}).to_string());
+    assert_eq!((status, plain["text"].as_str()), (200, Some("hello")));
+    assert_eq!(plain["state"], first["state"]);
+    let body = serde_json::json!({"text": second["text"], "state": first["state"]}).to_string();
+    assert_eq!(post(&addr, "/decrypt", &body).1["text"], "world");
+
+    assert_eq!(post(&addr, "/encrypt", "{\"text\":").0, 400);
+    assert_eq!(post(&addr, "/encrypt", &format!("{{\"text\":\"{}\"}}", "a".repeat(300))).0, 413);
+    assert_eq!(post(&addr, "/nope", "{}").0, 404);
+    child.kill().ok();
+    child.wait().ok();
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn several_configs_make_a_cascade() {
+    let dir = workdir("cascade");
+    fs::write(
+        dir.join("second.json"),
+        r#"{"alphabet":"latin","plugboard":[["b","x"]],"blocks":["ЛГ"],"rotor_positions":[[7,8]]}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("in.txt"), "belt and braces\n").unwrap();
+    let cascade = ["--config", "esd_config.json", "--config", "second.json"];
+    let run = |extra: &[&str]| run_piped(&dir, &[&cascade[..], extra].concat(), b"");
+
+    assert!(run(&["encrypt-file", "in.txt", "c.txt"]).status.success());
+    let single = run_piped(&dir, &["encrypt-file", "in.txt", "single.txt"], b"");
+    assert!(single.status.success());
+    assert_ne!(fs::read(dir.join("c.txt")).unwrap(), fs::read(dir.join("single.txt")).unwrap());
+    assert!(run(&["decrypt-file", "c.txt", "out.txt"]).status.success());
+    assert_eq!(fs::read_to_string(dir.join("out.txt")).unwrap(), "belt and braces\n");
+
+    let unsupported = run(&["--trace", "encrypt-file", "in.txt", "c.txt"]);
+    assert_eq!(unsupported.status.code(), Some(2));
+
+    fs::write(
+        dir.join("second.json"),
+        r#"{"alphabet":"cyrillic","plugboard":[],"blocks":["ЛГ"],"rotor_positions":[[7,8]]}"#,
+    )
+    .unwrap();
+    let mismatch = run(&["encrypt-file", "in.txt", "c.txt", "--force"]);
+    assert!(!mismatch.status.success());
+    assert!(String::from_utf8_lossy(&mismatch.stderr).contains("--no-strict"));
+    let args = ["--no-strict", "encrypt-file", "in.txt", "c.txt", "--force"];
+    assert!(run(&args).status.success());
+    fs::remove_dir_all(dir).ok();
+}
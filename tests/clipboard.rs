@@ -0,0 +1,70 @@
+#![cfg(all(feature = "std", feature = "json"))]
+
+mod common;
+
+use enigma_sd::clipboard::{self, Clipboard};
+use enigma_sd::EnigmaSudnogoDnya;
+use std::io;
+
+/// Буфер в памяти; `broken` — как системный без дисплея
+#[derive(Default)]
+struct MockClipboard {
+    text: String,
+    broken: bool,
+}
+
+impl Clipboard for MockClipboard {
+    fn get_text(&mut self) -> io::Result<String> {
+        if self.broken {
+            return Err(io::Error::other("no display"));
+        }
+        Ok(self.text.clone())
+    }
+
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        self.text = text.into();
+        Ok(())
+    }
+}
+
+fn machine() -> EnigmaSudnogoDnya {
+    EnigmaSudnogoDnya::new(&common::config("cyrillic", &[('а', 'я')])).unwrap()
+}
+
+#[test]
+fn clipboard_text_roundtrips_in_place() {
+    let message = "встречаемся у моста в семь";
+    let mut board = MockClipboard { text: message.into(), ..Default::default() };
+    let count = clipboard::process(&mut board, &mut machine(), false, None).unwrap();
+    assert_ne!(board.text, message);
+    assert_eq!(count, board.text.chars().count());
+    clipboard::process(&mut board, &mut machine(), true, None).unwrap();
+    assert_eq!(board.text, message);
+}
+
+#[test]
+fn clipboard_keeps_the_text_when_the_tag_fails() {
+    let cfg = common::config("latin", &[('a', 'q')]);
+    let key = cfg.mac_key("");
+    let mut board = MockClipboard { text: "attack at dawn".into(), ..Default::default() };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    clipboard::process(&mut board, &mut machine, false, Some(&key)).unwrap();
+    let sealed = board.text.clone();
+
+    let wrong = cfg.mac_key("another passphrase");
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let err = clipboard::process(&mut board, &mut machine, true, Some(&wrong)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(board.text, sealed);
+
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    clipboard::process(&mut board, &mut machine, true, Some(&key)).unwrap();
+    assert_eq!(board.text, "attack at dawn");
+}
+
+#[test]
+fn unavailable_clipboard_is_an_error() {
+    let mut board = MockClipboard { broken: true, ..Default::default() };
+    let err = clipboard::process(&mut board, &mut machine(), false, None).unwrap_err();
+    assert_eq!(err.to_string(), "no display");
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+
+fn config(blocks: &[&str]) -> ConfigData {
+    ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'q')],
+        blocks: blocks.iter().map(|b| b.to_string()).collect(),
+        rotor_positions: vec![vec![1, 2, 3, 4, 5], vec![6, 7, 8, 9, 0]],
+        ..Default::default()
+    }
+}
+
+fn encrypt(cfg: &ConfigData) -> String {
+    EnigmaSudnogoDnya::new(cfg).unwrap().encrypt("the quick brown fox jumps over the lazy dog")
+}
+
+#[test]
+fn latin_aliases_match_cyrillic_colors() {
+    let cyrillic = config(&["КБЧЗР", "ОФСГЛ"]);
+    let expected = encrypt(&cyrillic);
+    for blocks in [["KBCZR", "OFSGL"], ["kbczr", "ofsgl"], ["кбчзр", "офсгл"], ["КbЧzр", "oФsГl"]] {
+        let aliased = config(&blocks);
+        aliased.validate().unwrap();
+        assert_eq!(encrypt(&aliased), expected, "{blocks:?}");
+        // один и тот же ключ — один отпечаток и MAC
+        assert_eq!(aliased.fingerprint(), cyrillic.fingerprint(), "{blocks:?}");
+    }
+}
+
+#[test]
+fn unknown_colors_are_still_rejected() {
+    for bad in ['X', 'x', 'Ж', 'ß', '1'] {
+        let cfg = config(&[&format!("КБ{bad}ЗР"), "ОФСГЛ"]);
+        assert_eq!(cfg.validate(), Err(CipherError::UnknownColor(bad)));
+        assert_eq!(EnigmaSudnogoDnya::new(&cfg).err(), Some(CipherError::UnknownColor(bad)));
+    }
+}
@@ -0,0 +1,15 @@
+//! Общий конфиг интеграционных тестов
+
+use enigma_sd::ConfigData;
+
+/// Два блока, `КБЧ` и `З`, с позициями `[1, 2, 3]` и `[4]`; тесты
+/// достраивают остальное через `..common::config(..)`
+pub fn config(alphabet: &str, plugboard: &[(char, char)]) -> ConfigData {
+    ConfigData {
+        alphabet: alphabet.into(),
+        plugboard: plugboard.to_vec(),
+        blocks: vec!["КБЧ".into(), "З".into()],
+        rotor_positions: vec![vec![1, 2, 3], vec![4]],
+        ..Default::default()
+    }
+}
@@ -0,0 +1,168 @@
+#![cfg(all(feature = "fs", feature = "rand"))]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("esd-bin-{}-{}", std::process::id(), name))
+}
+
+fn sample() -> ConfigData {
+    let mut cfg = ConfigData::generate(&mut StdRng::seed_from_u64(802), "cyrillic", 200);
+    cfg.block_shifts = vec![vec![11, 32], vec![0]];
+    cfg.rotor_positions.push(vec![5, 31]);
+    cfg.rotor_positions.push(vec![1_000_000]);
+    cfg.authenticate = true;
+    cfg.digit_rotor_shift = Some(7);
+    cfg.lang = Some(enigma_sd::messages::Lang::En);
+    cfg.reflector_kind = "identity-pairs".into();
+    cfg.normalize = true;
+    cfg.step_interval = 3;
+    cfg.step_every = (0..cfg.block_count() as u64).map(|i| i % 4 + 1).collect();
+    cfg.use_reflector = false;
+    cfg.reversed = cfg
+        .rotor_positions
+        .iter()
+        .enumerate()
+        .map(|(i, block)| block.iter().map(|_| i % 3 == 0).collect())
+        .collect();
+    cfg
+}
+
+#[test]
+fn binary_config_matches_json_twin() {
+    let cfg = sample();
+    let json = temp_path("twin.json");
+    let bin = temp_path("twin.bin");
+    EnigmaSudnogoDnya::save_config(&cfg, json.to_str().unwrap(), false).unwrap();
+    EnigmaSudnogoDnya::save_config(&cfg, bin.to_str().unwrap(), false).unwrap();
+
+    let from_json = EnigmaSudnogoDnya::load_config(json.to_str().unwrap()).unwrap();
+    let from_bin = EnigmaSudnogoDnya::load_config(bin.to_str().unwrap()).unwrap();
+    assert_eq!(from_bin, cfg);
+    assert_eq!(from_bin, from_json);
+    assert!(fs::metadata(&bin).unwrap().len() * 4 < fs::metadata(&json).unwrap().len());
+
+    let text = "одна и та же машина из двух файлов, 2024";
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&from_bin).unwrap().encrypt(text),
+        EnigmaSudnogoDnya::new(&from_json).unwrap().encrypt(text)
+    );
+    fs::remove_file(json).ok();
+    fs::remove_file(bin).ok();
+}
+
+#[test]
+fn damaged_binary_config_is_an_error() {
+    let path = temp_path("damaged.bin");
+    EnigmaSudnogoDnya::save_config_bin(&sample(), path.to_str().unwrap(), false).unwrap();
+    let data = fs::read(&path).unwrap();
+    for cut in [0, 3, 5, data.len() / 2, data.len() - 1] {
+        fs::write(&path, &data[..cut]).unwrap();
+        assert!(EnigmaSudnogoDnya::load_config_bin(path.to_str().unwrap()).is_err());
+    }
+    fs::write(&path, b"{\"alphabet\":\"latin\"}").unwrap();
+    assert!(EnigmaSudnogoDnya::load_config_bin(path.to_str().unwrap()).is_err());
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn older_versions_still_load() {
+    let path = temp_path("old.bin");
+    EnigmaSudnogoDnya::save_config_bin(&ConfigData::default(), path.to_str().unwrap(), false)
+        .unwrap();
+    let current = fs::read(&path).unwrap();
+    // ESDB, версия, длина "latin", "latin", authenticate, затем цифровой
+    // ротор (с версии 2), язык (с версии 3), отражатель (с версии 4)
+    // и омофоны (с версии 5); в самом конце — флаги reversed (с версии 6),
+    // normalize (с версии 7), step_interval (с версии 8), step_every (с версии 9),
+    // plugboard_map (с версии 10), use_reflector (с версии 11), plugboards (с версии 12)
+    // и constant_work (с версии 13)
+    assert_eq!(current[12..14], [0, 0]);
+    let tail = b"\x07reverse\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x01\x00\x00";
+    assert_eq!(&current[14..], tail);
+    let versions = [(1, 12..23), (2, 13..23), (3, 14..23), (4, 22..23), (5, 23..23)];
+    let newer = (6..13).map(|version| (version, 23..23));
+    let versions = versions.into_iter().chain(newer);
+    for (version, fields) in versions {
+        let mut data = current.clone();
+        data[4] = version;
+        // поля с версии 6 и новее лежат в хвосте, по байту на поле
+        data.truncate(data.len() - (13 - version.max(5) as usize));
+        data.drain(fields);
+        fs::write(&path, &data).unwrap();
+        let cfg = EnigmaSudnogoDnya::load_config_bin(path.to_str().unwrap()).unwrap();
+        assert_eq!(cfg, ConfigData::default());
+    }
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn export_code_roundtrips_the_config() {
+    let cfg = sample();
+    let code = cfg.export_code().unwrap();
+    assert!(code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    assert_eq!(ConfigData::import_code(&code).unwrap(), cfg);
+    // перенесённый при копировании код читается так же
+    assert_eq!(ConfigData::import_code(&enigma_sd::wrap::wrap(&code, 60)).unwrap(), cfg);
+
+    // все хвосты base64: 0, 1 и 2 байта сверх целых троек
+    let mut tails = std::collections::BTreeSet::new();
+    for seed in 1..=12 {
+        let small = ConfigData::generate(&mut StdRng::seed_from_u64(seed), "latin", 1);
+        let code = small.export_code().unwrap();
+        tails.insert(code.len() % 4);
+        assert_eq!(ConfigData::import_code(&code).unwrap(), small);
+    }
+    assert_eq!(tails.len(), 3, "{tails:?}");
+
+    assert!(ConfigData::import_code(&code[..code.len() - 1]).is_err());
+    assert!(ConfigData::import_code(&format!("{code}+")).is_err());
+    assert!(ConfigData::import_code("").is_err());
+}
+
+#[test]
+fn mnemonic_roundtrips_small_configs() {
+    // разные длины: обе чётности и разные хвосты последнего слова
+    for seed in 1..=12 {
+        let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(seed), "latin", 2);
+        let words = cfg.to_mnemonic().unwrap();
+        let count = words.split(' ').count();
+        assert!(count <= enigma_sd::mnemonic::MAX_MNEMONIC_WORDS);
+        assert_eq!(ConfigData::from_mnemonic(&words).unwrap(), cfg);
+        // регистр и переносы при переписывании не мешают
+        let retyped = words.to_uppercase().replace(' ', "\n  ");
+        assert_eq!(ConfigData::from_mnemonic(&retyped).unwrap(), cfg);
+    }
+    let default = ConfigData::default();
+    assert_eq!(ConfigData::from_mnemonic(&default.to_mnemonic().unwrap()).unwrap(), default);
+}
+
+#[test]
+fn mnemonic_checksum_catches_a_swapped_word() {
+    let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(826), "latin", 2);
+    let words: Vec<String> = cfg.to_mnemonic().unwrap().split(' ').map(String::from).collect();
+    for i in [0, words.len() / 2, words.len() - 2, words.len() - 1] {
+        let mut swapped = words.clone();
+        swapped[i] = if words[i] == "zoo" { "abandon" } else { "zoo" }.into();
+        assert!(ConfigData::from_mnemonic(&swapped.join(" ")).is_err(), "слово {i}");
+    }
+    // соседние слова поменяны местами
+    let mut reordered = words.clone();
+    reordered.swap(1, 2);
+    assert!(ConfigData::from_mnemonic(&reordered.join(" ")).is_err());
+    assert!(ConfigData::from_mnemonic(&words[..words.len() - 1].join(" ")).is_err());
+    assert!(ConfigData::from_mnemonic(&format!("{} enigma", words.join(" "))).is_err());
+    assert!(ConfigData::from_mnemonic("").is_err());
+}
+
+#[test]
+fn mnemonic_refuses_large_configs() {
+    let err = sample().to_mnemonic().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let limit = enigma_sd::mnemonic::MAX_MNEMONIC_WORDS.to_string();
+    assert!(err.to_string().contains(&limit), "{err}");
+}
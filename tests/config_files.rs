@@ -0,0 +1,71 @@
+#![cfg(feature = "fs")]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use std::fs;
+use std::path::PathBuf;
+
+fn tempdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("esd-config-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config(position: usize) -> ConfigData {
+    ConfigData {
+        blocks: vec!["КБ".into()],
+        rotor_positions: vec![vec![position, 0]],
+        ..Default::default()
+    }
+}
+
+fn saved_position(path: &str) -> usize {
+    EnigmaSudnogoDnya::load_config(path).unwrap().rotor_positions[0][0]
+}
+
+#[test]
+fn save_replaces_file_and_keeps_one_backup() {
+    let dir = tempdir("backup");
+    let path = dir.join("esd_config.json");
+    let path = path.to_str().unwrap();
+
+    EnigmaSudnogoDnya::save_config(&config(1), path, true).unwrap();
+    assert_eq!(saved_position(path), 1);
+    assert!(fs::metadata(format!("{}.bak", path)).is_err());
+
+    EnigmaSudnogoDnya::save_config(&config(2), path, true).unwrap();
+    EnigmaSudnogoDnya::save_config(&config(3), path, true).unwrap();
+    assert_eq!(saved_position(path), 3);
+    assert_eq!(saved_position(&format!("{}.bak", path)), 2);
+    assert!(fs::metadata(format!("{}.tmp", path)).is_err());
+
+    EnigmaSudnogoDnya::save_config(&config(4), path, false).unwrap();
+    assert_eq!(saved_position(&format!("{}.bak", path)), 2);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn stale_temp_file_does_not_leak_into_config() {
+    let dir = tempdir("stale");
+    let path = dir.join("esd_config.json");
+    let path = path.to_str().unwrap();
+
+    // остаток прерванной записи: обрезанный JSON
+    fs::write(format!("{}.tmp", path), "{\"alphabet\": \"lat").unwrap();
+    EnigmaSudnogoDnya::save_config(&config(5), path, true).unwrap();
+    assert_eq!(saved_position(path), 5);
+    assert!(fs::metadata(format!("{}.tmp", path)).is_err());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn failed_save_leaves_no_temp_file() {
+    let dir = tempdir("failed");
+    // на месте конфига — непустой каталог: переименование не пройдёт
+    let path = dir.join("esd_config.json");
+    fs::create_dir_all(path.join("inside")).unwrap();
+    let path = path.to_str().unwrap();
+
+    assert!(EnigmaSudnogoDnya::save_config(&config(6), path, false).is_err());
+    assert!(fs::metadata(format!("{}.tmp", path)).is_err());
+    fs::remove_dir_all(dir).ok();
+}
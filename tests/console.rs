@@ -0,0 +1,103 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use enigma_sd::EnigmaSudnogoDnya;
+use enigma_sd::console::{self, Console, HEREDOC_PROMPT, Input, Menu};
+use enigma_sd::messages::Msg;
+use std::io::{self, Write};
+
+/// Ответы на вопросы по сценарию и весь вывод
+fn console<'a>(script: &'a str, output: &'a mut Vec<u8>) -> Console<&'a [u8], &'a mut Vec<u8>> {
+    Console::new(script.as_bytes(), output)
+}
+
+#[test]
+fn prompts_go_to_the_output() {
+    let mut output = Vec::new();
+    let mut c = console("  first \nsecond\r\n", &mut output);
+    assert_eq!(c.prompt("> ").unwrap().as_deref(), Some("first"));
+    assert_eq!(c.read_input("? ").unwrap(), Input::Line("second".into()));
+    assert_eq!(c.prompt("> ").unwrap(), None);
+    // на конце ввода — перевод строки после приглашения
+    assert_eq!(String::from_utf8(output).unwrap(), "> ? > \n");
+}
+
+#[test]
+fn confirm_asks_until_understood() {
+    let mut output = Vec::new();
+    let mut c = console("maybe\nда\n", &mut output);
+    assert!(c.confirm("? ").unwrap());
+    assert!(!c.confirm("? ").unwrap(), "конец ввода — «нет»");
+    let expected = format!("? {}\n? ? \n", Msg::AnswerYesNo.text());
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
+
+#[test]
+fn messages_inline_prompted_and_multiline() {
+    let mut output = Vec::new();
+    let mut c = console(" asked \n  kept  \nline two\n.\n", &mut output);
+    assert_eq!(c.read_message(Some("inline")).unwrap(), Input::Line("inline".into()));
+    assert_eq!(c.read_message(None).unwrap(), Input::Line("asked".into()));
+    // строки после << — как есть, до точки
+    let multiline = c.read_message(Some("<<")).unwrap();
+    assert_eq!(multiline, Input::Line("  kept  \nline two".into()));
+    assert_eq!(c.read_message(Some("<<")).unwrap(), Input::End);
+    let prompts = format!("{}{}", Msg::MessagePrompt.text(), HEREDOC_PROMPT.repeat(4));
+    assert_eq!(String::from_utf8(output).unwrap(), prompts + "\n");
+}
+
+/// Меню как у программы: машина сбрасывается перед сообщением, `state`
+/// печатает позиции
+struct Machine(EnigmaSudnogoDnya);
+
+impl Menu for Machine {
+    fn message(
+        &mut self,
+        out: &mut dyn Write,
+        decrypt: bool,
+        _trace: Option<usize>,
+        msg: &str,
+    ) -> io::Result<()> {
+        self.0.reset();
+        let result = if decrypt { self.0.decrypt(msg).unwrap() } else { self.0.encrypt(msg) };
+        writeln!(out, "= {}", result)
+    }
+
+    fn command(&mut self, out: &mut dyn Write, name: &str, _args: &[&str]) -> io::Result<bool> {
+        if name != "state" {
+            return Ok(false);
+        }
+        writeln!(out, "{:?}", self.0.export_positions())?;
+        Ok(true)
+    }
+}
+
+#[test]
+fn main_loop_runs_a_scripted_session() {
+    let cfg = common::config("latin", &[('a', 'z')]);
+    let mut reference = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let cipher = reference.encrypt("attack at dawn");
+    // `state` после расшифровки: роторы прошли сообщение ещё раз от начала
+    reference.reset();
+    reference.decrypt(&cipher).unwrap();
+    let script = format!(
+        "\nfrobnicate\nencrypt attack at dawn\ndecrypt <<\n{}\n.\nstate\nexit\nencrypt never\n",
+        cipher
+    );
+    let mut output = Vec::new();
+    let mut menu = Machine(EnigmaSudnogoDnya::new(&cfg).unwrap());
+    console::run_menu(&mut console(&script, &mut output), &mut menu).unwrap();
+
+    let prompt = Msg::CommandPrompt.text();
+    let expected = [
+        prompt.to_string(),
+        format!("{}{}\n", prompt, Msg::UnknownCommand.text()),
+        format!("{}= {}\n", prompt, cipher),
+        format!("{}{}= attack at dawn\n", prompt, HEREDOC_PROMPT.repeat(2)),
+        format!("{}{:?}\n", prompt, reference.export_positions()),
+        prompt.to_string(),
+    ];
+    // всё, что печатает цикл, идёт в вывод `Console`; после `exit` ввод не читается
+    assert_eq!(String::from_utf8(output).unwrap(), expected.concat());
+}
@@ -0,0 +1,60 @@
+#![cfg(all(feature = "fs", feature = "rand"))]
+
+use enigma_sd::{BYTES_ALPHABET, ConfigData, EnigmaSudnogoDnya};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// Буквы вперемешку с пробелами, знаками и цифрами: пропускаемые символы
+/// идут и подряд, и поодиночке
+const MESSAGE: &str = "Attack at 0600, pier 4!!  hold... then 12 km north\n";
+
+fn pair(cfg: ConfigData) -> (ConfigData, ConfigData) {
+    (ConfigData { constant_work: true, ..cfg.clone() }, cfg)
+}
+
+#[test]
+fn constant_work_does_not_change_the_ciphertext() {
+    let mut rng = StdRng::seed_from_u64(21);
+    let digits = ConfigData {
+        digit_rotor_shift: Some(3),
+        ..ConfigData::generate(&mut rng, "latin", 3)
+    };
+    let homophonic = ConfigData {
+        homophonic: true,
+        step_interval: 2,
+        ..ConfigData::generate(&mut rng, "cyrillic", 2)
+    };
+    let configs = [ConfigData::generate(&mut rng, "latin", 4), digits, homophonic];
+    for (constant, normal) in configs.into_iter().map(pair) {
+        let mut a = EnigmaSudnogoDnya::new(&constant).unwrap();
+        let mut b = EnigmaSudnogoDnya::new(&normal).unwrap();
+        let cipher = a.encrypt(MESSAGE);
+        assert_eq!(cipher, b.encrypt(MESSAGE));
+        // холостой прогон роторы не двигает
+        assert_eq!(a.save_state(), b.save_state());
+        assert_eq!(a.steps(), b.steps());
+        let streamed: String = a.encrypt_iter(MESSAGE.chars()).collect();
+        assert_eq!(streamed, b.encrypt(MESSAGE));
+        a.reset();
+        b.reset();
+        assert_eq!(a.decrypt(&cipher).unwrap(), b.decrypt(&cipher).unwrap());
+    }
+}
+
+#[test]
+fn constant_work_survives_json_and_binary_configs() {
+    let (cfg, _) = pair(ConfigData::generate(&mut StdRng::seed_from_u64(22), "latin", 2));
+    for name in ["constant.json", "constant.bin"] {
+        let path = std::env::temp_dir().join(format!("esd-cw-{}-{}", std::process::id(), name));
+        let path = path.to_str().unwrap();
+        EnigmaSudnogoDnya::save_config(&cfg, path, false).unwrap();
+        assert_eq!(EnigmaSudnogoDnya::load_config(path).unwrap(), cfg);
+        std::fs::remove_file(path).ok();
+    }
+    assert!(cfg.to_byte_mode().constant_work);
+    assert_eq!(cfg.to_byte_mode().alphabet, BYTES_ALPHABET);
+    // по умолчанию флаг в JSON не пишется; ключа он не меняет
+    let plain = ConfigData { constant_work: false, ..cfg.clone() };
+    assert!(!serde_json::to_string(&plain).unwrap().contains("constant_work"));
+    assert_eq!(plain.fingerprint(), cfg.fingerprint());
+}
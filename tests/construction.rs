@@ -0,0 +1,111 @@
+#![cfg(feature = "rand")]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, plugboard, random_blocks, random_positions};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn progress_reports_every_block() {
+    let cfg = ConfigData {
+        alphabet: "cyrillic".into(),
+        plugboard: Vec::new(),
+        blocks: vec!["КБ".into(); 10],
+        rotor_positions: Vec::new(),
+        ..Default::default()
+    };
+    let mut calls = Vec::new();
+    EnigmaSudnogoDnya::new_with_progress(&cfg, |done, total| calls.push((done, total))).unwrap();
+    assert_eq!(calls.len(), 10);
+    assert_eq!(calls.first(), Some(&(1, 10)));
+    assert_eq!(calls.last(), Some(&(10, 10)));
+}
+
+#[test]
+fn memory_estimate_counts_blocks_and_rotors() {
+    let cfg = ConfigData {
+        alphabet: "latin".into(),
+        plugboard: Vec::new(),
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        rotor_positions: Vec::new(),
+        ..Default::default()
+    };
+    // машина: 2 байта на длины блоков + 5 роторов по 8 байт (три u16 и флаг
+    // направления с выравниванием) и их стартовые позиции по 2;
+    // конфиг: по строке и вектору позиций на блок + 5 цветов по 2 байта и
+    // позиций по 8
+    assert_eq!(cfg.estimated_memory_bytes(), 2 + 5 * (8 + 2) + 2 * 2 * 24 + 5 * (2 + 8));
+}
+
+#[test]
+fn reset_matches_fresh_machine() {
+    let cfg = ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('h', 'x')],
+        blocks: vec!["КБЧ".into(), "Л".into()],
+        rotor_positions: vec![vec![4, 25, 9], vec![13]],
+        ..Default::default()
+    };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    machine.encrypt("some earlier message that moves the rotors");
+    machine.reset();
+    let fresh = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("hello world");
+    assert_eq!(machine.encrypt("hello world"), fresh);
+}
+
+#[test]
+fn same_seed_generates_same_config() {
+    let preset = |seed| ConfigData::generate(&mut StdRng::seed_from_u64(seed), "cyrillic", 4);
+    assert_eq!(preset(2024), preset(2024));
+    assert_ne!(preset(2024), preset(2025));
+
+    let manual = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let alphabet = ConfigData::default().alphabet_chars();
+        let plugboard = plugboard::random_pairs(&alphabet, 8, &mut rng).unwrap();
+        let blocks = random_blocks(&mut rng, 3);
+        let positions = random_positions(&mut rng, &blocks, alphabet.len());
+        (plugboard, blocks, positions)
+    };
+    assert_eq!(manual(7), manual(7));
+}
+
+#[test]
+fn preset_estimate_matches_config_estimate() {
+    for preset in enigma_sd::PRESETS.iter().filter(|p| p.blocks <= 1_000) {
+        // пресет считает по среднему — 6 роторов на блок
+        let cfg = ConfigData {
+            alphabet: "latin".into(),
+            blocks: vec!["КБЧЗРО".into(); preset.blocks],
+            ..Default::default()
+        };
+        let (expected, actual) = (preset.estimated_memory_bytes(), cfg.estimated_memory_bytes());
+        assert_eq!(expected, actual, "{}", preset.name);
+    }
+}
+
+#[test]
+fn involution_self_check_passes_for_valid_configs() {
+    let base = ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'z'), ('q', 'w')],
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        rotor_positions: vec![vec![4, 25, 9], vec![13, 0]],
+        ..Default::default()
+    };
+    let configs = [
+        base.clone(),
+        ConfigData { reflector_kind: "ukw-b".into(), digit_rotor_shift: Some(3), ..base.clone() },
+        ConfigData { reversed: vec![vec![true, false, true], vec![false, true]], ..base.clone() },
+        ConfigData { homophonic: true, ..base.clone() },
+        base.to_byte_mode(),
+    ];
+    for cfg in &configs {
+        // `new` уже прогнал пробу — на копии банка, машина осталась нетронутой
+        let mut machine = EnigmaSudnogoDnya::new(cfg).unwrap();
+        assert_eq!((machine.steps(), machine.steps_taken()), (0, 0));
+        assert_eq!(machine.export_positions(), cfg.rotor_positions);
+        let cipher = machine.encrypt("shifted state");
+        machine.reset();
+        assert_eq!(machine.decrypt(&cipher).unwrap(), "shifted state");
+    }
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya, MachineDescription};
+
+fn config() -> ConfigData {
+    ConfigData {
+        plugboard: vec![('q', 'c')],
+        blocks: vec!["КБЧ".into(), "Л".into()],
+        rotor_positions: vec![vec![4, 25, 9], vec![13]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn describe_reports_colors_shifts_and_positions() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let desc = machine.describe();
+    assert_eq!(desc.alphabet_len, 26);
+    assert_eq!(desc.plugboard, vec![('c', 'q')]);
+    let colors: Vec<Option<char>> = desc.blocks[0].iter().map(|r| r.color).collect();
+    assert_eq!(colors, vec![Some('К'), Some('Б'), Some('Ч')]);
+    assert_eq!(desc.blocks[1][0].shift, 10);
+    assert_eq!(desc.blocks[1][0].position, 13);
+
+    machine.encrypt("ab");
+    let positions: Vec<usize> = machine.describe().blocks[0].iter().map(|r| r.position).collect();
+    assert_eq!(positions, vec![6, 25, 9]);
+
+    let json = serde_json::to_string(&desc).unwrap();
+    assert_eq!(serde_json::from_str::<MachineDescription>(&json).unwrap(), desc);
+}
+
+#[test]
+fn set_block_positions_validates_input() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    assert_eq!(
+        machine.set_block_positions(2, &[0]),
+        Err(CipherError::BlockOutOfRange { block: 2, blocks: 2 })
+    );
+    assert_eq!(
+        machine.set_block_positions(0, &[1, 2]),
+        Err(CipherError::RotorPositionCountMismatch { block: 0, rotors: 3, positions: 2 })
+    );
+    assert_eq!(
+        machine.set_block_positions(0, &[1, 26, 2]),
+        Err(CipherError::PositionOutOfRange { block: 0, rotor: 1, position: 26, alphabet_len: 26 })
+    );
+    assert_eq!(machine.save_state(), vec![vec![4, 25, 9], vec![13]]);
+}
+
+#[test]
+fn set_block_positions_survives_reset() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    machine.set_block_positions(1, &[7]).unwrap();
+    machine.encrypt("some text");
+    machine.reset();
+
+    let mut cfg = config();
+    cfg.rotor_positions[1] = vec![7];
+    let expected = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("hello");
+    assert_eq!(machine.encrypt("hello"), expected);
+}
+
+#[test]
+fn single_rotor_positions() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    assert_eq!(machine.get_position(0, 1), Some(25));
+    assert_eq!(machine.get_position(1, 0), Some(13));
+    machine.set_position(0, 1, 3).unwrap();
+    assert_eq!(machine.save_state(), vec![vec![4, 3, 9], vec![13]]);
+    machine.encrypt("some text");
+    machine.reset();
+    assert_eq!(machine.get_position(0, 1), Some(3));
+
+    let mut cfg = config();
+    cfg.rotor_positions[0][1] = 3;
+    let expected = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("hello");
+    assert_eq!(machine.encrypt("hello"), expected);
+}
+
+#[test]
+fn single_rotor_positions_are_bounds_checked() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    for (block, rotor) in [(2, 0), (1, 1), (0, 3)] {
+        assert_eq!(machine.get_position(block, rotor), None);
+    }
+    assert_eq!(
+        machine.set_position(2, 0, 1),
+        Err(CipherError::BlockOutOfRange { block: 2, blocks: 2 })
+    );
+    assert_eq!(
+        machine.set_position(1, 1, 1),
+        Err(CipherError::RotorOutOfRange { block: 1, rotor: 1, rotors: 1 })
+    );
+    assert_eq!(
+        machine.set_position(0, 2, 26),
+        Err(CipherError::PositionOutOfRange { block: 0, rotor: 2, position: 26, alphabet_len: 26 })
+    );
+    assert_eq!(machine.save_state(), vec![vec![4, 25, 9], vec![13]]);
+}
+
+#[test]
+fn current_permutation_is_the_next_substitution() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    machine.encrypt("shift");
+    let perm = machine.current_permutation();
+    assert_eq!(perm.len(), 26);
+    // при фиксированном состоянии шифрование совпадает с расшифровкой
+    assert!(perm.iter().enumerate().all(|(i, &j)| perm[j] == i));
+    // и именно так будет зашифрован следующий символ
+    let state = machine.save_state();
+    for (i, &c) in machine.alphabet().to_vec().iter().enumerate() {
+        machine.load_state(&state);
+        let out = machine.encrypt(&c.to_string());
+        assert_eq!(out.chars().next(), Some(machine.alphabet()[perm[i]]));
+    }
+    // сама функция роторы не двигает
+    machine.load_state(&state);
+    assert_eq!(machine.current_permutation(), perm);
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+
+fn config(digit_rotor_shift: Option<usize>) -> ConfigData {
+    ConfigData { digit_rotor_shift, ..common::config("latin", &[('a', 'z')]) }
+}
+
+const MIXED: &str = "call 8-800-555-35-35 at 10:00, room 1111";
+
+#[test]
+fn mixed_text_round_trips() {
+    let cfg = config(Some(3));
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(MIXED);
+    assert_ne!(cipher, MIXED);
+    let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap();
+    assert_eq!(plain, MIXED);
+}
+
+#[test]
+fn digits_are_hidden_but_letters_are_unchanged() {
+    let with = EnigmaSudnogoDnya::new(&config(Some(3))).unwrap().encrypt(MIXED);
+    let without = EnigmaSudnogoDnya::new(&config(None)).unwrap().encrypt(MIXED);
+    assert!(without.contains("8-800-555-35-35"));
+    let letters = |s: &str| s.chars().filter(|c| !c.is_ascii_digit()).collect::<String>();
+    assert_eq!(letters(&with), letters(&without));
+    // ротор шагает, так что одинаковые цифры не дают одинаковый шифротекст
+    assert!(!with.ends_with("1111"));
+    let tail: Vec<char> = with.chars().rev().take(4).collect();
+    assert!(tail.windows(2).any(|w| w[0] != w[1]));
+}
+
+#[test]
+fn parallel_and_bytes_agree_with_encrypt() {
+    let cfg = config(Some(8));
+    let text = MIXED.repeat(20);
+    let expected = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&text);
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    for threads in [1, 3, 8] {
+        assert_eq!(machine.encrypt_parallel(&text, threads), expected);
+    }
+    let bytes = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(text.as_bytes());
+    assert_eq!(bytes, expected.as_bytes());
+}
+
+#[test]
+fn reset_rewinds_digit_rotor() {
+    let mut machine = EnigmaSudnogoDnya::new(&config(Some(5))).unwrap();
+    let first = machine.encrypt("0123456789");
+    machine.reset();
+    assert_eq!(machine.encrypt("0123456789"), first);
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "fs")]
+
+mod common;
+
+use enigma_sd::ConfigData;
+use enigma_sd::dir::{DirOptions, MANIFEST_FILE, decrypt_dir, encrypt_dir};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("esd-dir-{}-{}", std::process::id(), name));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config() -> ConfigData {
+    common::config("latin", &[('a', 'q')])
+}
+
+/// Дерево: вложенные каталоги, пустой файл, пустой каталог, двоичные данные
+fn make_tree(root: &Path) {
+    fs::create_dir_all(root.join("notes/2024/march")).unwrap();
+    fs::create_dir_all(root.join("empty")).unwrap();
+    fs::write(root.join("readme.txt"), "top secret\n").unwrap();
+    fs::write(root.join("notes/empty.txt"), "").unwrap();
+    fs::write(root.join("notes/2024/march/plan.txt"), "attack at dawn\n".repeat(500)).unwrap();
+    let bytes: Vec<u8> = (0..=u8::MAX).cycle().take(70_000).collect();
+    fs::write(root.join("notes/2024/blob.bin"), bytes).unwrap();
+}
+
+/// Все файлы дерева: относительный путь → содержимое; каталоги — `None`
+fn snapshot(root: &Path) -> BTreeMap<PathBuf, Option<Vec<u8>>> {
+    let mut out = BTreeMap::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let rel = path.strip_prefix(root).unwrap().to_path_buf();
+            if path.is_dir() {
+                out.insert(rel, None);
+                stack.push(path);
+            } else {
+                out.insert(rel, Some(fs::read(&path).unwrap()));
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn directory_tree_roundtrips() {
+    let root = temp_dir("roundtrip");
+    let (src, enc, dec) = (root.join("src"), root.join("enc"), root.join("dec"));
+    make_tree(&src);
+    let report = encrypt_dir(&config(), &src, &enc, &DirOptions::default()).unwrap();
+    assert_eq!(report.files, 4);
+    assert!(report.skipped.is_empty());
+    // структура повторена, содержимое нет
+    assert!(enc.join("notes/2024/march/plan.txt").is_file() && enc.join("empty").is_dir());
+    assert_ne!(fs::read(enc.join("readme.txt")).unwrap(), b"top secret\n");
+    assert!(enc.join(MANIFEST_FILE).is_file());
+
+    let report = decrypt_dir(&config(), &enc, &dec, 1).unwrap();
+    assert_eq!(report.files, 4);
+    assert_eq!(snapshot(&dec), snapshot(&src));
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn encrypted_names_and_parallel_jobs() {
+    let root = temp_dir("names");
+    let src = root.join("src");
+    make_tree(&src);
+    let names = DirOptions { encrypt_names: true, jobs: 1 };
+    encrypt_dir(&config(), &src, &root.join("one"), &names).unwrap();
+    let parallel = DirOptions { encrypt_names: true, jobs: 4 };
+    encrypt_dir(&config(), &src, &root.join("four"), &parallel).unwrap();
+    // каждый файл — с начальных позиций, так что число потоков не важно
+    let one = snapshot(&root.join("one"));
+    assert_eq!(one, snapshot(&root.join("four")));
+    let plain_names = ["notes", "readme.txt", "plan.txt"];
+    let leaked = one
+        .keys()
+        .flat_map(|p| p.components())
+        .any(|c| plain_names.contains(&c.as_os_str().to_str().unwrap()));
+    assert!(!leaked, "{:?}", one.keys().collect::<Vec<_>>());
+
+    decrypt_dir(&config(), &root.join("four"), &root.join("dec"), 3).unwrap();
+    assert_eq!(snapshot(&root.join("dec")), snapshot(&src));
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn damaged_files_and_wrong_configs_are_rejected() {
+    let root = temp_dir("damaged");
+    let (src, enc) = (root.join("src"), root.join("enc"));
+    make_tree(&src);
+    encrypt_dir(&config(), &src, &enc, &DirOptions::default()).unwrap();
+
+    let other = ConfigData { rotor_positions: vec![vec![0, 0, 0], vec![0]], ..config() };
+    assert!(decrypt_dir(&other, &enc, &root.join("other"), 1).is_err());
+
+    let plan = enc.join("notes/2024/march/plan.txt");
+    let mut data = fs::read(&plan).unwrap();
+    *data.last_mut().unwrap() ^= 1;
+    fs::write(&plan, data).unwrap();
+    let err = decrypt_dir(&config(), &enc, &root.join("bad"), 1).unwrap_err();
+    assert!(err.to_string().contains("plan.txt"), "{err}");
+    fs::remove_dir_all(root).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_skipped() {
+    let root = temp_dir("symlink");
+    let (src, enc) = (root.join("src"), root.join("enc"));
+    make_tree(&src);
+    std::os::unix::fs::symlink("/etc/passwd", src.join("passwd")).unwrap();
+    std::os::unix::fs::symlink("notes", src.join("notes-link")).unwrap();
+    let report = encrypt_dir(&config(), &src, &enc, &DirOptions::default()).unwrap();
+    assert_eq!(report.files, 4);
+    assert_eq!(report.skipped, vec![src.join("notes-link"), src.join("passwd")]);
+    assert!(!enc.join("passwd").exists() && !enc.join("notes-link").exists());
+    fs::remove_dir_all(root).ok();
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::file_header::{FILE_HEADER_LEN, FILE_MAGIC, FILE_VERSION, FileHeader};
+use enigma_sd::{CipherError, ConfigData};
+
+fn header(cfg: &ConfigData) -> FileHeader {
+    FileHeader::for_config(cfg, false, true, Some(64))
+}
+
+#[test]
+fn header_roundtrips() {
+    let cfg = ConfigData::default();
+    for (binary, authenticated, wrap) in [(false, false, None), (true, true, Some(76))] {
+        let header = FileHeader::for_config(&cfg, binary, authenticated, wrap);
+        let mut file = header.to_bytes();
+        assert_eq!(file.len(), FILE_HEADER_LEN);
+        assert!(file.starts_with(FILE_MAGIC));
+        file.extend_from_slice(b"ciphertext\n");
+        let (parsed, body) = FileHeader::parse(&file).unwrap().unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(parsed.alphabet, "latin");
+        assert_eq!(body, b"ciphertext\n");
+        parsed.check(&cfg).unwrap();
+    }
+    // файл без заголовка — не ошибка, а старый формат
+    assert_eq!(FileHeader::parse(b"plain old ciphertext").unwrap(), None);
+}
+
+#[test]
+fn header_remembers_every_alphabet() {
+    for name in ["cyrillic", "bytes", "ukrainian", "belarusian", "greek", "latin-ext"] {
+        let cfg = ConfigData { alphabet: name.into(), ..ConfigData::default() };
+        let (parsed, _) = FileHeader::parse(&header(&cfg).to_bytes()).unwrap().unwrap();
+        assert_eq!(parsed.alphabet, name);
+    }
+}
+
+#[test]
+fn header_of_another_config_is_rejected() {
+    let cfg = ConfigData::default();
+    let moved = ConfigData { rotor_positions: vec![vec![1]], ..cfg.clone() };
+    let header = header(&cfg);
+    assert_eq!(header.check(&moved), Err(CipherError::FileFingerprintMismatch));
+}
+
+#[test]
+fn future_versions_and_damaged_headers_are_errors() {
+    let mut file = header(&ConfigData::default()).to_bytes();
+    file.extend_from_slice(b"body");
+
+    let mut future = file.clone();
+    future[FILE_MAGIC.len()] = FILE_VERSION + 1;
+    assert_eq!(
+        FileHeader::parse(&future),
+        Err(CipherError::UnsupportedFileVersion(FILE_VERSION + 1))
+    );
+
+    for truncated in [&file[..FILE_MAGIC.len()], &file[..FILE_HEADER_LEN - 1]] {
+        assert_eq!(FileHeader::parse(truncated), Err(CipherError::BadFileHeader));
+    }
+    let mut unknown_flag = file.clone();
+    unknown_flag[FILE_MAGIC.len() + 2] |= 0x80;
+    assert_eq!(FileHeader::parse(&unknown_flag), Err(CipherError::BadFileHeader));
+    let mut unknown_alphabet = file;
+    unknown_alphabet[FILE_MAGIC.len() + 1] = 9;
+    assert_eq!(FileHeader::parse(&unknown_alphabet), Err(CipherError::BadFileHeader));
+}
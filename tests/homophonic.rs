@@ -0,0 +1,77 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use enigma_sd::analysis::frequency_profile;
+use enigma_sd::homophonic::Homophones;
+use enigma_sd::{BYTES_ALPHABET, CipherError, ConfigData, EnigmaSudnogoDnya};
+
+const SAMPLE: &str = "It was the best of times, it was the worst of times, it was the age of \
+                      wisdom, it was the age of foolishness, it was the epoch of belief, it was \
+                      the epoch of incredulity, it was the season of Light, it was the season \
+                      of Darkness, it was the spring of hope, it was the winter of despair.";
+
+fn config(alphabet: &str) -> ConfigData {
+    ConfigData { homophonic: true, ..common::config(alphabet, &[('a', '7')]) }
+}
+
+#[test]
+fn homophonic_round_trips() {
+    for (alphabet, text) in [
+        ("latin", SAMPLE.to_string()),
+        ("cyrillic", "Ого, «оборона» обороняется: ёж, щука и объём!".into()),
+    ] {
+        let cfg = ConfigData { plugboard: Vec::new(), ..config(alphabet) };
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&text);
+        assert!(cipher.chars().any(|c| c.is_ascii_digit()));
+        let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap();
+        assert_eq!(plain, text.to_lowercase());
+    }
+}
+
+#[test]
+fn homophonic_works_with_every_encrypt_path() {
+    let cfg = config("latin");
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let cipher = machine.encrypt(SAMPLE);
+    machine.reset();
+    assert_eq!(machine.encrypt_parallel(SAMPLE, 4), cipher);
+    machine.reset();
+    assert_eq!(machine.encrypt_iter(SAMPLE.chars()).collect::<String>(), cipher);
+
+    machine.reset();
+    let tagged = machine.encrypt_with_tag(SAMPLE);
+    machine.reset();
+    assert_eq!(machine.decrypt_with_tag(&tagged).unwrap(), SAMPLE.to_lowercase());
+}
+
+#[test]
+fn spreading_flattens_letter_frequencies() {
+    let cfg = config("latin");
+    let alphabet = cfg.alphabet_chars();
+    let homophones = Homophones::new(&cfg.alphabet, &alphabet);
+    let spread: String = SAMPLE
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| alphabet.iter().position(|&a| a == c))
+        .enumerate()
+        .map(|(step, idx)| alphabet[homophones.spread(idx, step as u64)])
+        .collect();
+    let before = frequency_profile(SAMPLE, &alphabet[..homophones.letters()]);
+    let after = frequency_profile(&spread, &alphabet);
+    assert_eq!(before.total, after.total);
+    // на этом тексте индекс совпадений падает почти вдвое: 0.079 → 0.043
+    assert!(after.index_of_coincidence < before.index_of_coincidence * 0.6);
+    let top = |counts: &[(char, usize)]| counts.iter().map(|&(_, c)| c).max().unwrap();
+    assert!(top(&after.counts) * 3 < top(&before.counts) * 2);
+}
+
+#[test]
+fn homophonic_needs_letters_and_free_digits() {
+    let bytes = ConfigData { plugboard: Vec::new(), ..config(BYTES_ALPHABET) };
+    assert_eq!(bytes.validate(), Err(CipherError::HomophonicUnsupported));
+    let digits = ConfigData { digit_rotor_shift: Some(3), ..config("latin") };
+    assert_eq!(digits.validate(), Err(CipherError::HomophonicUnsupported));
+    // цифры — часть алфавита и годятся для plugboard
+    assert_eq!(config("latin").alphabet_chars().len(), 36);
+}
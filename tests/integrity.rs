@@ -0,0 +1,127 @@
+#![cfg(all(feature = "json", feature = "rand"))]
+
+use enigma_sd::{BYTES_ALPHABET, CHECKSUM_LEN, CipherError, ConfigData, EnigmaSudnogoDnya};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn config() -> ConfigData {
+    let mut rng = StdRng::seed_from_u64(792);
+    let mut cfg = ConfigData::generate(&mut rng, "latin", 4);
+    cfg.authenticate = true;
+    cfg
+}
+
+fn sealed(cfg: &ConfigData, msg: &str) -> String {
+    let key = cfg.mac_key("pass");
+    EnigmaSudnogoDnya::new(cfg).unwrap().encrypt_authenticated(msg, &key)
+}
+
+fn open(cfg: &ConfigData, sealed: &str) -> Result<String, CipherError> {
+    let key = cfg.mac_key("pass");
+    EnigmaSudnogoDnya::new(cfg).unwrap().decrypt_authenticated(sealed, &key)
+}
+
+#[test]
+fn authenticated_roundtrip() {
+    let cfg = config();
+    let msg = "meet me at the old mill # at noon";
+    assert_eq!(open(&cfg, &sealed(&cfg, msg)).unwrap(), msg);
+}
+
+#[test]
+fn flipped_character_is_detected() {
+    let cfg = config();
+    let text = sealed(&cfg, "attack at dawn");
+    for i in 0..text.len() {
+        let mut bytes = text.clone().into_bytes();
+        bytes[i] ^= 1;
+        let tampered = String::from_utf8(bytes).unwrap();
+        assert_eq!(open(&cfg, &tampered), Err(CipherError::IntegrityFailure), "i = {i}");
+    }
+}
+
+#[test]
+fn truncation_is_detected() {
+    let cfg = config();
+    let text = sealed(&cfg, "attack at dawn");
+    for len in 0..text.len() {
+        assert_eq!(open(&cfg, &text[..len]), Err(CipherError::IntegrityFailure), "len = {len}");
+    }
+}
+
+#[test]
+fn missing_tag_is_detected() {
+    let cfg = config();
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("attack at dawn");
+    assert_eq!(open(&cfg, &cipher), Err(CipherError::IntegrityFailure));
+}
+
+#[test]
+fn wrong_passphrase_or_config_is_detected() {
+    let cfg = config();
+    let text = sealed(&cfg, "attack at dawn");
+    let key = cfg.mac_key("other");
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert_eq!(machine.decrypt_authenticated(&text, &key), Err(CipherError::IntegrityFailure));
+
+    let mut other = cfg.clone();
+    other.rotor_positions[0][0] += 1;
+    assert_eq!(open(&other, &text), Err(CipherError::IntegrityFailure));
+}
+
+#[test]
+fn sealed_bytes_detect_tampering() {
+    let cfg = config().to_byte_mode();
+    assert_eq!(cfg.alphabet, BYTES_ALPHABET);
+    let key = cfg.mac_key("");
+    let data: Vec<u8> = (0..=u8::MAX).collect();
+    let sealed = key.seal_bytes(&data);
+    assert_eq!(key.open_bytes(&sealed).unwrap(), &data[..]);
+
+    let mut flipped = sealed.clone();
+    flipped[10] ^= 0x80;
+    assert_eq!(key.open_bytes(&flipped), Err(CipherError::IntegrityFailure));
+    assert_eq!(key.open_bytes(&sealed[..sealed.len() - 1]), Err(CipherError::IntegrityFailure));
+    assert_eq!(key.open_bytes(&data[..16]), Err(CipherError::IntegrityFailure));
+}
+
+#[test]
+fn authenticate_is_off_for_old_configs() {
+    let json = r#"{"alphabet":"latin","plugboard":[],"blocks":["КБ"],"rotor_positions":[[1,2]]}"#;
+    let cfg: ConfigData = serde_json::from_str(json).unwrap();
+    assert!(!cfg.authenticate);
+    assert_eq!(serde_json::to_string(&cfg).unwrap(), json);
+}
+
+#[test]
+fn checksum_roundtrip_folds_case() {
+    let cfg = config();
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_with_tag("Hello, World");
+    assert_eq!(cipher.chars().count(), "hello, world".len() + CHECKSUM_LEN);
+    let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt_with_tag(&cipher);
+    assert_eq!(plain.unwrap(), "hello, world");
+}
+
+#[test]
+fn checksum_catches_any_changed_character() {
+    let cfg = config();
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_with_tag("attack at dawn");
+    let chars: Vec<char> = cipher.chars().collect();
+    for i in 0..chars.len() {
+        let mut mutated = chars.clone();
+        mutated[i] = if chars[i] == 'a' { 'b' } else { 'a' };
+        let mutated: String = mutated.into_iter().collect();
+        let result = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt_with_tag(&mutated);
+        assert_eq!(result, Err(CipherError::IntegrityFailure), "i = {i}");
+    }
+}
+
+#[test]
+fn checksum_rejects_short_or_untagged_input() {
+    let cfg = config();
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert_eq!(machine.decrypt_with_tag("abc"), Err(CipherError::IntegrityFailure));
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("attack at dawn");
+    let result = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt_with_tag(&cipher);
+    assert_eq!(result, Err(CipherError::IntegrityFailure));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use enigma_sd::{BYTES_ALPHABET, ConfigData, EnigmaSudnogoDnya};
+
+fn config(alphabet: &str) -> ConfigData {
+    ConfigData { digit_rotor_shift: Some(2), ..common::config(alphabet, &[]) }
+}
+
+#[test]
+fn iterator_matches_batch_encrypt() {
+    let text = "Hello, Wörld! İstanbul 2024 — Привет, МИР\n\t";
+    for alphabet in ["latin", "cyrillic", BYTES_ALPHABET] {
+        let expected = EnigmaSudnogoDnya::new(&config(alphabet)).unwrap().encrypt(text);
+        let mut machine = EnigmaSudnogoDnya::new(&config(alphabet)).unwrap();
+        let lazy: String = machine.encrypt_iter(text.chars()).collect();
+        assert_eq!(lazy, expected, "{alphabet}");
+    }
+}
+
+#[test]
+fn rotors_advance_only_as_far_as_read() {
+    let mut batch = EnigmaSudnogoDnya::new(&config("latin")).unwrap();
+    batch.encrypt("abc");
+    let mut lazy = EnigmaSudnogoDnya::new(&config("latin")).unwrap();
+    let head: String = lazy.encrypt_iter("abcdefgh".chars()).take(3).collect();
+    assert_eq!(head.chars().count(), 3);
+    assert_eq!(lazy.save_state(), batch.save_state());
+}
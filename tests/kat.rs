@@ -0,0 +1,111 @@
+#![cfg(all(feature = "std", feature = "rand"))]
+
+use enigma_sd::{
+    BENCH_SEED, ConfigData, EnigmaSudnogoDnya, bench_corpus, measure_roundtrip, random_text,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// Набор конфигов: оба алфавита, с plugboard и без, от 1 до 12 блоков
+fn kat_configs() -> Vec<ConfigData> {
+    let mut rng = StdRng::seed_from_u64(0x6a6f7264616e6f);
+    let mut configs = Vec::new();
+    for alphabet in ["latin", "cyrillic"] {
+        for with_plugboard in [false, true] {
+            for blocks in [1, 2, 3, 4, 7, 12] {
+                let mut cfg = ConfigData::generate(&mut rng, alphabet, blocks);
+                if !with_plugboard {
+                    cfg.plugboard.clear();
+                }
+                configs.push(cfg);
+            }
+        }
+    }
+    configs
+}
+
+#[test]
+fn decrypt_inverts_encrypt() {
+    let mut rng = StdRng::seed_from_u64(42);
+    for cfg in kat_configs() {
+        let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        let alphabet = machine.alphabet().to_vec();
+        for size in [1, 10, 1_000, 10_000] {
+            let text = random_text(&mut rng, &alphabet, size);
+            let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&text);
+            let recovered = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&cipher);
+            assert_eq!(recovered, text, "{} / {:?}", cfg.alphabet, cfg.blocks);
+            assert!(measure_roundtrip(&mut machine, &text).ok);
+        }
+    }
+}
+
+#[test]
+fn roundtrip_restores_the_step_interval_phase() {
+    let cfg = ConfigData { step_interval: 3, ..kat_configs()[1].clone() };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert!(measure_roundtrip(&mut machine, "abcde").ok);
+    // и с середины интервала: два символа из трёх уже прошли
+    machine.encrypt("ab");
+    let (positions, steps) = (machine.save_state(), machine.steps());
+    for text in ["abcde", "abcdefghij"] {
+        assert!(measure_roundtrip(&mut machine, text).ok, "{text}");
+    }
+    assert_eq!((machine.save_state(), machine.steps()), (positions, steps));
+}
+
+#[test]
+fn roundtrip_restores_the_step_every_phase() {
+    let cfg = ConfigData { step_every: vec![1, 2], ..kat_configs()[1].clone() };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert!(measure_roundtrip(&mut machine, "abcde").ok);
+    // нечётный такт: второй блок шагнёт на следующем символе
+    machine.encrypt("a");
+    for text in ["abcde", "abcdef"] {
+        assert!(measure_roundtrip(&mut machine, text).ok, "{text}");
+    }
+    // с обоими счётчиками сразу
+    let cfg = ConfigData { step_interval: 2, ..cfg };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    machine.encrypt("abc");
+    assert!(measure_roundtrip(&mut machine, "abcdefg").ok);
+}
+
+#[test]
+fn non_alphabet_characters_survive_roundtrip() {
+    for cfg in kat_configs() {
+        let text = if cfg.alphabet == "latin" {
+            "attack at dawn, 05:30! ünïcødé"
+        } else {
+            "атака на рассвете, 05:30! ёж"
+        };
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+        assert_ne!(cipher, text);
+        assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&cipher), text);
+    }
+}
+
+#[test]
+fn decrypt_entry_point_matches_encrypt_both_ways() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for cfg in kat_configs() {
+        let alphabet = EnigmaSudnogoDnya::new(&cfg).unwrap().alphabet().to_vec();
+        let text = random_text(&mut rng, &alphabet, 500);
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&text);
+        assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap(), text);
+        let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&text).unwrap();
+        assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&plain), text);
+    }
+}
+
+#[test]
+fn bench_corpus_is_the_same_for_the_same_seed() {
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    let sizes = [10, 1_000, 50_000];
+    let first = bench_corpus(BENCH_SEED, &alphabet, &sizes);
+    assert_eq!(first, bench_corpus(BENCH_SEED, &alphabet, &sizes));
+    let lens: Vec<usize> = first.iter().map(|t| t.chars().count()).collect();
+    assert_eq!(lens, sizes);
+    assert!(first.iter().flat_map(|t| t.chars()).all(|c| alphabet.contains(&c)));
+    assert_ne!(bench_corpus(BENCH_SEED + 1, &alphabet, &sizes), first);
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::messages::{self, Lang, Msg};
+use enigma_sd::{CipherError, ConfigData, PRESETS};
+use std::collections::BTreeSet;
+
+/// Имена `Msg::X` из куска исходника `messages.rs`
+fn keys_in(source: &str) -> BTreeSet<&str> {
+    source
+        .split("Msg::")
+        .skip(1)
+        .map(|rest| rest.split(|c: char| !c.is_alphanumeric()).next().unwrap())
+        .collect()
+}
+
+#[test]
+fn catalogs_cover_the_same_keys() {
+    let source = include_str!("../src/messages.rs");
+    let section = |from: &str, to: &str| {
+        let start = source.find(from).unwrap();
+        &source[start..start + source[start..].find(to).unwrap()]
+    };
+    let variants: BTreeSet<&str> = section("pub enum Msg {", "}")
+        .lines()
+        .skip(1)
+        .map(|l| l.trim().trim_end_matches(','))
+        .filter(|l| !l.is_empty())
+        .collect();
+    let all: BTreeSet<String> = Msg::ALL.iter().map(|m| format!("{m:?}")).collect();
+    assert_eq!(Msg::ALL.len(), all.len(), "повторы в Msg::ALL");
+    assert_eq!(all.iter().map(String::as_str).collect::<BTreeSet<_>>(), variants);
+    assert_eq!(keys_in(section("fn ru(self)", "fn en(self)")), variants);
+    assert_eq!(keys_in(&source[source.find("fn en(self)").unwrap()..]), variants);
+}
+
+#[test]
+fn catalogs_have_matching_placeholders() {
+    for &msg in Msg::ALL {
+        let ru = msg.in_lang(Lang::Ru);
+        let en = msg.in_lang(Lang::En);
+        assert!(!ru.is_empty() && !en.is_empty(), "{msg:?}");
+        assert_eq!(ru.matches("{}").count(), en.matches("{}").count(), "{msg:?}");
+    }
+}
+
+#[test]
+fn lang_codes_are_parsed() {
+    assert_eq!(Lang::parse("en"), Some(Lang::En));
+    assert_eq!(Lang::parse("EN"), Some(Lang::En));
+    assert_eq!(Lang::parse("ru_RU.UTF-8"), Some(Lang::Ru));
+    assert_eq!(Lang::parse("de"), None);
+    assert_eq!(Lang::parse(""), None);
+}
+
+#[test]
+fn errors_follow_selected_language() {
+    let err = CipherError::TooManyRotors { block: 1, rotors: 70, max: 64 };
+    messages::set_lang(Lang::En);
+    assert_eq!(err.to_string(), "Block 1 has 70 rotors (maximum 64)");
+    messages::set_lang(Lang::Ru);
+    assert_eq!(err.to_string(), "Блок 1 содержит 70 роторов (максимум 64)");
+}
+
+#[test]
+fn yes_no_answers_are_parsed() {
+    let table = [
+        ("да", Some(true)),
+        ("Да ", Some(true)),
+        ("ДА", Some(true)),
+        ("д", Some(true)),
+        ("yes", Some(true)),
+        (" Y", Some(true)),
+        ("нет", Some(false)),
+        ("Н", Some(false)),
+        ("no", Some(false)),
+        ("N\n", Some(false)),
+        ("", None),
+        ("   ", None),
+        ("yep", None),
+        ("дан", None),
+        ("maybe", None),
+        ("1", None),
+    ];
+    for (answer, expected) in table {
+        assert_eq!(messages::parse_yes_no(answer), expected, "{answer:?}");
+    }
+}
+
+#[test]
+fn presets_are_translated() {
+    for preset in PRESETS {
+        assert!(!preset.name_en.is_empty() && !preset.description_en.is_empty());
+        assert!(preset.name_en.is_ascii(), "{}", preset.name_en);
+    }
+}
+
+#[test]
+fn config_lang_is_not_part_of_the_key() {
+    let plain: ConfigData = serde_json::from_str(
+        r#"{"alphabet":"latin","plugboard":[],"blocks":["КБ"],"rotor_positions":[[1,2]]}"#,
+    )
+    .unwrap();
+    let english = ConfigData { lang: Some(Lang::En), ..plain.clone() };
+    assert!(serde_json::to_string(&english).unwrap().contains(r#""lang":"en""#));
+    assert_eq!(english.fingerprint(), plain.fingerprint());
+}
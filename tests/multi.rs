@@ -0,0 +1,166 @@
+#![cfg(all(feature = "fs", feature = "rand"))]
+
+use enigma_sd::{CascadeConfig, CipherError, ConfigData, EnigmaSudnogoDnya, MultiEnigma};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fs;
+
+fn chain() -> Vec<ConfigData> {
+    let mut rng = StdRng::seed_from_u64(800);
+    vec![
+        ConfigData::generate(&mut rng, "latin", 3),
+        ConfigData::generate(&mut rng, "latin", 2),
+    ]
+}
+
+#[test]
+fn two_machine_chain_roundtrips() {
+    let cfgs = chain();
+    let text = "super-encipherment: one machine after another";
+    let cipher = MultiEnigma::new(&cfgs).unwrap().encrypt(text);
+    assert_eq!(MultiEnigma::new(&cfgs).unwrap().decrypt(&cipher).unwrap(), text);
+
+    for cfg in &cfgs {
+        assert_ne!(cipher, EnigmaSudnogoDnya::new(cfg).unwrap().encrypt(text));
+    }
+}
+
+#[test]
+fn reset_returns_every_machine_to_start() {
+    let mut multi = MultiEnigma::new(&chain()).unwrap();
+    let first = multi.encrypt("hello");
+    assert_ne!(multi.encrypt("hello"), first);
+    multi.reset();
+    assert_eq!(multi.encrypt("hello"), first);
+}
+
+#[test]
+fn empty_chain_is_rejected() {
+    assert_eq!(MultiEnigma::new(&[]).err(), Some(CipherError::EmptyChain));
+}
+
+#[test]
+fn chain_loads_from_json_array() {
+    let path = std::env::temp_dir().join(format!("esd-multi-{}.json", std::process::id()));
+    fs::write(&path, serde_json::to_string(&chain()).unwrap()).unwrap();
+    let loaded = MultiEnigma::load_config(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded, chain());
+    fs::remove_file(path).ok();
+}
+
+fn machines(cfgs: &[ConfigData]) -> Vec<EnigmaSudnogoDnya> {
+    cfgs.iter().map(|cfg| EnigmaSudnogoDnya::new(cfg).unwrap()).collect()
+}
+
+#[test]
+fn three_machine_cascade_roundtrips() {
+    let mut rng = StdRng::seed_from_u64(822);
+    let cfgs: Vec<ConfigData> =
+        (2..5).map(|blocks| ConfigData::generate(&mut rng, "cyrillic", blocks)).collect();
+    let text = "три машины подряд, и каждая со своим ключом";
+    let cipher = MultiEnigma::from_machines(machines(&cfgs), true).unwrap().encrypt(text);
+    let two = MultiEnigma::new(&cfgs[..2]).unwrap().encrypt(text);
+    assert_ne!(cipher, two);
+    let mut cascade = MultiEnigma::from_machines(machines(&cfgs), true).unwrap();
+    assert_eq!(cascade.decrypt(&cipher).unwrap(), text);
+
+    // порядок важен: обратный каскад — это другой шифр
+    let reversed: Vec<ConfigData> = cfgs.iter().rev().cloned().collect();
+    assert_ne!(MultiEnigma::new(&reversed).unwrap().encrypt(text), cipher);
+}
+
+#[test]
+fn cascade_bytes_roundtrip() {
+    let cfgs: Vec<ConfigData> = chain().iter().map(ConfigData::to_byte_mode).collect();
+    let data: Vec<u8> = (0..=u8::MAX).cycle().take(1000).collect();
+    let cipher = MultiEnigma::new(&cfgs).unwrap().encrypt_bytes(&data);
+    assert_ne!(cipher, data);
+    assert_eq!(MultiEnigma::new(&cfgs).unwrap().decrypt_bytes(&cipher), data);
+}
+
+#[test]
+fn cascade_with_a_one_way_machine_roundtrips() {
+    let mut cfgs = chain();
+    cfgs[1].use_reflector = false;
+    let text = "no reflector in the second machine";
+    let cipher = MultiEnigma::new(&cfgs).unwrap().encrypt(text);
+    assert_eq!(MultiEnigma::new(&cfgs).unwrap().decrypt(&cipher).unwrap(), text);
+    let bytes: Vec<ConfigData> = cfgs.iter().map(ConfigData::to_byte_mode).collect();
+    let cipher = MultiEnigma::new(&bytes).unwrap().encrypt_bytes(text.as_bytes());
+    assert_eq!(MultiEnigma::new(&bytes).unwrap().decrypt_bytes(&cipher), text.as_bytes());
+}
+
+#[test]
+fn mismatched_alphabets_need_non_strict_cascade() {
+    let mut rng = StdRng::seed_from_u64(823);
+    let cfgs = vec![
+        ConfigData::generate(&mut rng, "latin", 2),
+        ConfigData::generate(&mut rng, "latin", 2),
+        ConfigData::generate(&mut rng, "cyrillic", 2),
+    ];
+    let mismatch = Some(CipherError::CascadeAlphabetMismatch { machine: 2 });
+    assert_eq!(MultiEnigma::new(&cfgs).err(), mismatch);
+    assert_eq!(MultiEnigma::from_machines(machines(&cfgs), true).err(), mismatch);
+
+    // латиница проходит кириллическую машину как есть, кириллица — латинские
+    let text = "mixed текст";
+    let cascade = CascadeConfig { configs: cfgs, strict: false };
+    let cipher = MultiEnigma::from_cascade(&cascade).unwrap().encrypt(text);
+    assert_ne!(cipher, text);
+    assert_eq!(MultiEnigma::from_cascade(&cascade).unwrap().decrypt(&cipher).unwrap(), text);
+}
+
+#[test]
+fn non_strict_cascade_loses_only_case() {
+    let mut rng = StdRng::seed_from_u64(822);
+    let cfgs = vec![
+        ConfigData::generate(&mut rng, "latin", 2),
+        ConfigData::generate(&mut rng, "cyrillic", 2),
+    ];
+    let cascade = CascadeConfig { configs: cfgs, strict: false };
+    let cipher = MultiEnigma::from_cascade(&cascade).unwrap().encrypt("Hello, Мир!");
+    let plain = MultiEnigma::from_cascade(&cascade).unwrap().decrypt(&cipher).unwrap();
+    assert_eq!(plain, "hello, мир!");
+}
+
+#[test]
+fn case_folding_machine_cannot_follow_a_byte_machine() {
+    let mut rng = StdRng::seed_from_u64(8222);
+    let latin = ConfigData::generate(&mut rng, "latin", 2);
+    let bytes = latin.to_byte_mode();
+    let mismatch = Some(CipherError::CascadeCaseMismatch { machine: 1 });
+    for strict in [false, true] {
+        let cascade = CascadeConfig { configs: vec![bytes.clone(), latin.clone()], strict };
+        let expected = if strict {
+            Some(CipherError::CascadeAlphabetMismatch { machine: 1 })
+        } else {
+            mismatch.clone()
+        };
+        assert_eq!(MultiEnigma::from_cascade(&cascade).err(), expected);
+    }
+    // наоборот можно: строчный шифротекст байтовая машина не портит
+    let cascade = CascadeConfig { configs: vec![latin, bytes], strict: false };
+    let cipher = MultiEnigma::from_cascade(&cascade).unwrap().encrypt("Hello");
+    assert_eq!(MultiEnigma::from_cascade(&cascade).unwrap().decrypt(&cipher).unwrap(), "hello");
+}
+
+#[test]
+fn cascade_file_mixes_paths_and_inline_configs() {
+    let dir = std::env::temp_dir().join(format!("esd-cascade-{}", std::process::id()));
+    fs::create_dir_all(dir.join("keys")).unwrap();
+    let [first, second] = <[ConfigData; 2]>::try_from(chain()).unwrap();
+    EnigmaSudnogoDnya::save_config(&first, dir.join("keys/a.bin").to_str().unwrap(), false)
+        .unwrap();
+    let file = dir.join("cascade.json");
+    let json = serde_json::json!({ "cascade": ["keys/a.bin", second], "strict": false });
+    fs::write(&file, json.to_string()).unwrap();
+
+    let loaded = MultiEnigma::load_cascade(file.to_str().unwrap()).unwrap();
+    assert_eq!(loaded, CascadeConfig { configs: chain(), strict: false });
+
+    // без `strict` каскад строгий; старый массив тоже может ссылаться на файлы
+    fs::write(&file, r#"["keys/a.bin", "keys/a.bin"]"#).unwrap();
+    let loaded = MultiEnigma::load_cascade(file.to_str().unwrap()).unwrap();
+    assert_eq!(loaded, CascadeConfig { configs: vec![first.clone(), first], strict: true });
+    fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,67 @@
+//! Только ядро: должно собираться и проходить и с `--no-default-features`
+//! (`cargo test --no-default-features --test no_default`), без serde и rand.
+//! С `--features json` здесь же проверяются эталонные векторы без std.
+
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+
+fn config() -> ConfigData {
+    ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'q'), ('e', 'x')],
+        blocks: vec!["КБЧ".into(), "ЗО".into()],
+        rotor_positions: vec![vec![1, 2, 3], vec![4, 5]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn core_encrypts_without_optional_features() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let cipher = machine.encrypt("attack at dawn");
+    // тот же шифротекст, что и в сборке со всеми фичами
+    assert_eq!(cipher, "eamlvj zs xndi");
+    machine.reset();
+    assert_eq!(machine.decrypt(&cipher).unwrap(), "attack at dawn");
+
+    let lazy: String = machine.encrypt_iter("attack at dawn".chars()).collect();
+    assert_ne!(lazy, cipher, "роторы уже сдвинуты");
+    let mut bytes = b"attack at dawn".to_vec();
+    machine.reset();
+    machine.encrypt_bytes_in_place(&mut bytes);
+    assert_eq!(bytes, cipher.as_bytes());
+}
+
+#[test]
+fn encrypt_into_fills_a_fixed_buffer() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let mut buf = [0u8; 32];
+    let n = machine.encrypt_into("attack at dawn", &mut buf).unwrap();
+    assert_eq!(core::str::from_utf8(&buf[..n]).unwrap(), "eamlvj zs xndi");
+
+    // кириллица вне алфавита проходит как есть, по два байта на символ
+    machine.reset();
+    let expected = machine.encrypt("attack при dawn");
+    machine.reset();
+    let n = machine.encrypt_into("attack при dawn", &mut buf).unwrap();
+    assert_eq!(&buf[..n], expected.as_bytes());
+}
+
+#[test]
+fn encrypt_into_reports_a_short_buffer() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    // «п» уже не влезает целиком в восьмой байт
+    let mut buf = [0u8; 8];
+    assert_eq!(
+        machine.encrypt_into("attack при", &mut buf),
+        Err(CipherError::BufferTooSmall { written: 7 })
+    );
+    assert_eq!(&buf[..6], b"eamlvj");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn builtin_vectors_verify_without_std() {
+    for vector in enigma_sd::vectors::builtin() {
+        assert_eq!(vector.verify(), Ok(true), "{}", vector.name);
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+
+const PRECOMPOSED: &str = "мой край";
+/// То же, но «й» разложена: «и» + U+0306 (комбинируемая бреве)
+const DECOMPOSED: &str = "мои\u{306} краи\u{306}";
+
+fn config(normalize: bool) -> ConfigData {
+    ConfigData { normalize, ..common::config("cyrillic", &[('й', 'ж')]) }
+}
+
+#[test]
+fn decomposed_letter_is_enciphered_like_precomposed() {
+    let expected = EnigmaSudnogoDnya::new(&config(true)).unwrap().encrypt(PRECOMPOSED);
+    let mut machine = EnigmaSudnogoDnya::new(&config(true)).unwrap();
+    let cipher = machine.encrypt(DECOMPOSED);
+    assert_eq!(cipher, expected);
+    // два символа стали одним — строка короче исходной
+    assert!(cipher.chars().count() < DECOMPOSED.chars().count());
+
+    machine.reset();
+    assert_eq!(machine.decrypt(&cipher).unwrap(), PRECOMPOSED);
+    let lazy: String = machine.encrypt_iter(DECOMPOSED.chars()).collect();
+    assert_ne!(lazy, cipher, "роторы уже сдвинуты");
+    machine.reset();
+    assert_eq!(machine.encrypt_parallel(DECOMPOSED, 2), cipher);
+    assert_eq!(machine.encrypt_iter(DECOMPOSED.chars()).collect::<String>(), cipher);
+}
+
+#[test]
+fn without_the_flag_combining_marks_pass_through() {
+    let cipher = EnigmaSudnogoDnya::new(&config(false)).unwrap().encrypt(DECOMPOSED);
+    assert_eq!(cipher.matches('\u{306}').count(), 2);
+    let tagged = EnigmaSudnogoDnya::new(&config(true)).unwrap().encrypt_with_tag(DECOMPOSED);
+    let plain = EnigmaSudnogoDnya::new(&config(true)).unwrap().decrypt_with_tag(&tagged);
+    assert_eq!(plain.unwrap(), PRECOMPOSED);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "std")]
+
+use enigma_sd::{Block, ConfigData, EnigmaSudnogoDnya};
+
+fn sample_config() -> ConfigData {
+    ConfigData {
+        alphabet: "latin".into(),
+        plugboard: vec![('a', 'z'), ('q', 'm'), ('e', 't')],
+        blocks: vec!["КБЧ".into(), "ЗР".into(), "ОФСГЛ".into()],
+        rotor_positions: vec![vec![3, 25, 0], vec![7, 11], vec![1, 2, 3, 4, 5]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn parallel_matches_sequential_on_million_chars() {
+    let cfg = sample_config();
+    let alphabet = EnigmaSudnogoDnya::new(&cfg).unwrap().alphabet().to_vec();
+    let msg: String = (0..1_000_000usize)
+        .map(|i| {
+            if i % 17 == 0 {
+                ' '
+            } else {
+                alphabet[(i * 7 + i / 26) % alphabet.len()]
+            }
+        })
+        .collect();
+
+    let sequential = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&msg);
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    for threads in [1, 2, 3, 8] {
+        assert_eq!(machine.encrypt_parallel(&msg, threads), sequential);
+    }
+}
+
+#[test]
+fn huge_thread_count_is_clamped() {
+    let cfg = sample_config();
+    let text = "attack at dawn, hold the bridge";
+    let sequential = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    for threads in [0, text.len() + 1, 1 << 40, usize::MAX] {
+        assert_eq!(machine.encrypt_parallel(text, threads), sequential);
+    }
+    assert_eq!(machine.encrypt_parallel("", usize::MAX), "");
+}
+
+#[test]
+fn block_advance_by_matches_repeated_rotate() {
+    for n in [0u64, 1, 25, 26, 27, 675, 676, 17_577, 17_576 * 3 + 5] {
+        let mut stepped = Block::new("КБЧ", 26).unwrap();
+        stepped.load_positions(&[5, 24, 13]);
+        let mut jumped = Block::new("КБЧ", 26).unwrap();
+        jumped.load_positions(&[5, 24, 13]);
+        for _ in 0..n {
+            stepped.rotate();
+        }
+        jumped.advance_by(n);
+        assert_eq!(jumped.save_positions(), stepped.save_positions(), "n = {n}");
+    }
+}
@@ -0,0 +1,113 @@
+#![cfg(all(feature = "passphrase", feature = "json"))]
+
+use enigma_sd::passphrase::{DERIVATION_VERSION, DerivationParams};
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+
+const PHRASE: &str = "correct horse battery staple";
+
+/// Дешёвые параметры argon2id, чтобы тесты на различия шли быстро
+fn cheap() -> DerivationParams {
+    DerivationParams { memory_kib: 64, iterations: 1, ..Default::default() }
+}
+
+#[test]
+fn known_phrase_gives_the_pinned_config() {
+    // смена этого конфига ломает совместимость со всеми выведенными ключами:
+    // так можно только с новой `DERIVATION_VERSION`
+    assert_eq!(DERIVATION_VERSION, 1);
+    let cfg = ConfigData::from_passphrase(PHRASE, DerivationParams::default()).unwrap();
+    let pinned: ConfigData = serde_json::from_str(
+        r#"{"alphabet":"latin",
+            "plugboard":[["h","n"],["a","e"],["o","d"],["q","w"],["m","t"],["g","c"],["j","k"],
+                ["p","l"]],
+            "blocks":["ОЛЧКРБР","ГРЧ","ЛБООРГР","ОООЗФО"],
+            "rotor_positions":[[17,13,9,16,15,13,15],[5,0,20],[7,14,16,0,1,16,18],
+                [1,17,2,23,15,18]]}"#,
+    )
+    .unwrap();
+    assert_eq!(cfg, pinned);
+}
+
+#[test]
+fn same_phrase_same_machine() {
+    let a = ConfigData::from_passphrase(PHRASE, cheap()).unwrap();
+    let b = ConfigData::from_passphrase(PHRASE, cheap()).unwrap();
+    assert_eq!(a, b);
+    let cipher = EnigmaSudnogoDnya::new(&a).unwrap().encrypt("meet at noon");
+    assert_eq!(EnigmaSudnogoDnya::new(&b).unwrap().decrypt(&cipher).unwrap(), "meet at noon");
+}
+
+#[test]
+fn every_input_changes_the_config() {
+    let base = ConfigData::from_passphrase(PHRASE, cheap()).unwrap();
+    let other = ConfigData::from_passphrase("correct horse battery stapler", cheap());
+    assert_ne!(other.unwrap(), base);
+    let variants = [
+        DerivationParams { memory_kib: 128, ..cheap() },
+        DerivationParams { iterations: 2, ..cheap() },
+        DerivationParams { parallelism: 2, ..cheap() },
+        DerivationParams { pairs: 5, ..cheap() },
+    ];
+    for params in variants {
+        let cfg = ConfigData::from_passphrase(PHRASE, params.clone()).unwrap();
+        assert_ne!(cfg, base, "{params:?}");
+    }
+    // число блоков — тоже часть соли, а не только длина вывода
+    let more = ConfigData::from_passphrase(PHRASE, DerivationParams { blocks: 5, ..cheap() });
+    assert_ne!(more.unwrap().blocks[..4], base.blocks[..]);
+    let cyrillic = DerivationParams { alphabet: "cyrillic".into(), ..cheap() };
+    assert_eq!(ConfigData::from_passphrase(PHRASE, cyrillic).unwrap().alphabet, "cyrillic");
+}
+
+#[test]
+fn unknown_version_and_bad_params_are_errors() {
+    let future = DerivationParams { version: DERIVATION_VERSION + 1, ..cheap() };
+    assert_eq!(
+        ConfigData::from_passphrase(PHRASE, future),
+        Err(CipherError::UnsupportedDerivationVersion(DERIVATION_VERSION + 1))
+    );
+    for params in [
+        DerivationParams { memory_kib: 1, ..cheap() },
+        DerivationParams { iterations: 0, ..cheap() },
+        DerivationParams { parallelism: 0, ..cheap() },
+    ] {
+        let err = ConfigData::from_passphrase(PHRASE, params);
+        assert_eq!(err, Err(CipherError::BadDerivationParams));
+    }
+    let pairs = DerivationParams { pairs: 14, ..cheap() };
+    let err = CipherError::TooManyPlugboardPairs { pairs: 14, alphabet_len: 26 };
+    assert_eq!(ConfigData::from_passphrase(PHRASE, pairs), Err(err));
+}
+
+/// `gen-config --from-passphrase` в каталоге `dir`, фраза — на stdin
+#[cfg(feature = "cli")]
+fn gen_config(dir: &std::path::Path, config: &str, phrase: &[u8]) -> std::process::ExitStatus {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let args = ["--config", config, "gen-config", "--from-passphrase", "--blocks", "2"];
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enigma_SD"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(phrase).unwrap();
+    child.wait().unwrap()
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn gen_config_derives_the_config_from_a_passphrase() {
+    let dir = std::env::temp_dir().join(format!("esd-passphrase-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    assert!(gen_config(&dir, "p.json", format!("{PHRASE}\n").as_bytes()).success());
+    let written = EnigmaSudnogoDnya::load_config(dir.join("p.json").to_str().unwrap()).unwrap();
+    let params = DerivationParams { blocks: 2, ..Default::default() };
+    assert_eq!(written, ConfigData::from_passphrase(PHRASE, params).unwrap());
+    // пустая фраза — ошибка, конфиг не пишется
+    assert!(!gen_config(&dir, "e.json", b"\n").success());
+    assert!(!dir.join("e.json").exists());
+    std::fs::remove_dir_all(dir).ok();
+}
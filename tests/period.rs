@@ -0,0 +1,83 @@
+use enigma_sd::{BYTES_ALPHABET, ConfigData, EnigmaSudnogoDnya};
+
+fn machine(alphabet: &str, blocks: &[&str]) -> EnigmaSudnogoDnya {
+    let cfg = ConfigData {
+        alphabet: alphabet.into(),
+        blocks: blocks.iter().map(|b| b.to_string()).collect(),
+        ..Default::default()
+    };
+    EnigmaSudnogoDnya::new(&cfg).unwrap()
+}
+
+#[test]
+fn two_rotor_block_repeats_after_alphabet_squared() {
+    let mut m = machine("latin", &["КБ"]);
+    assert_eq!(m.period(), 26 * 26);
+
+    let start = m.save_state();
+    for step in 1..=26 * 26 {
+        m.encrypt("a");
+        assert_eq!(m.save_state() == start, step == 26 * 26, "step = {step}");
+    }
+}
+
+#[test]
+fn blocks_combine_by_lcm() {
+    assert_eq!(machine("latin", &["КБ", "Ч"]).period(), 676);
+    assert_eq!(machine("cyrillic", &["К", "БЧЗ"]).period(), 33u128.pow(3));
+}
+
+#[test]
+fn huge_period_saturates() {
+    // 256^15 = 2^120 ещё помещается, 256^16 = 2^128 уже нет
+    assert_eq!(machine(BYTES_ALPHABET, &[&"К".repeat(15)]).period(), 1 << 120);
+    assert_eq!(machine(BYTES_ALPHABET, &[&"К".repeat(16)]).period(), u128::MAX);
+}
+
+/// Первый шаг, на котором состояние вернулось к стартовому
+fn simulated_period(m: &mut EnigmaSudnogoDnya) -> u128 {
+    let start = m.save_state();
+    let mut steps = 0;
+    loop {
+        m.encrypt("a");
+        steps += 1;
+        if m.save_state() == start {
+            return steps;
+        }
+    }
+}
+
+#[test]
+fn analytic_period_matches_simulation() {
+    let cases: &[&[&str]] = &[&["К"], &["ЧЗ", "Б"], &["К", "КБ", "З"], &["ГЛФ"]];
+    for blocks in cases {
+        let cfg = ConfigData {
+            blocks: blocks.iter().map(|b| b.to_string()).collect(),
+            block_shifts: vec![vec![4, 11]],
+            rotor_positions: Vec::new(),
+            ..Default::default()
+        };
+        let mut m = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        assert_eq!(simulated_period(&mut m), m.period(), "{blocks:?}");
+        assert_eq!(cfg.period(), m.period());
+    }
+}
+
+#[test]
+fn carries_stay_inside_blocks() {
+    // три двухроторных блока: без переносов между ними период — 26², а не 26⁶
+    let mut m = machine("latin", &["КБ", "ЧЗ", "РО"]);
+    assert_eq!(m.period(), 26 * 26);
+    assert_eq!(simulated_period(&mut m), 26 * 26);
+    // ещё блоки той же длины его не удлиняют, длинный блок — удлиняет
+    assert_eq!(machine("latin", &["КБ"; 50]).period(), 26 * 26);
+    assert_eq!(machine("latin", &["КБ", "ЧЗО"]).period(), 26u128.pow(3));
+
+    // второй ротор первого блока шагает на переносе, а второй блок — на
+    // каждом символе, как и первый, а не на переносе из первого блока
+    let mut m = machine("latin", &["КБ", "Ч"]);
+    m.encrypt(&"a".repeat(26));
+    assert_eq!(m.save_state(), vec![vec![0, 1], vec![0]]);
+    m.encrypt("a");
+    assert_eq!(m.save_state(), vec![vec![1, 1], vec![1]]);
+}
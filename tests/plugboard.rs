@@ -0,0 +1,267 @@
+#![cfg(all(feature = "fs", feature = "rand"))]
+
+use enigma_sd::analysis::estimate_keyspace;
+use enigma_sd::plugboard::{self, Plugboard};
+use enigma_sd::{AlphabetIndex, BYTES_ALPHABET, CipherError, ConfigData, EnigmaSudnogoDnya, PRESETS};
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn chars(alphabet: &str) -> Vec<char> {
+    ConfigData { alphabet: alphabet.into(), ..Default::default() }.alphabet_chars()
+}
+
+#[test]
+fn zero_pairs_is_an_empty_plugboard() {
+    let pairs = plugboard::random_pairs(&chars("latin"), 0, &mut StdRng::seed_from_u64(1));
+    assert_eq!(pairs, Ok(Vec::new()));
+}
+
+#[test]
+fn max_pairs_use_the_whole_alphabet() {
+    for (alphabet, max) in [("latin", 13), ("cyrillic", 16), (BYTES_ALPHABET, 128)] {
+        let mut rng = StdRng::seed_from_u64(2);
+        let cfg = ConfigData::generate_with_pairs(&mut rng, alphabet, 2, max).unwrap();
+        assert_eq!(cfg.plugboard.len(), max);
+        // пары не пересекаются — иначе validate бы не пропустил
+        EnigmaSudnogoDnya::new(&cfg).unwrap();
+    }
+}
+
+#[test]
+fn too_many_pairs_is_an_error() {
+    let err = plugboard::random_pairs(&chars("cyrillic"), 17, &mut StdRng::seed_from_u64(3));
+    assert_eq!(err, Err(CipherError::TooManyPlugboardPairs { pairs: 17, alphabet_len: 33 }));
+}
+
+#[test]
+fn presets_and_keyspace_use_the_configured_count() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for preset in PRESETS.iter().filter(|p| p.blocks <= 100) {
+        let pairs = preset.plugboard_pairs;
+        let cfg = ConfigData::generate_with_pairs(&mut rng, "latin", preset.blocks, pairs).unwrap();
+        assert_eq!(cfg.plugboard.len(), preset.plugboard_pairs);
+    }
+    let with = |pairs| {
+        let cfg = ConfigData::generate_with_pairs(&mut StdRng::seed_from_u64(5), "latin", 3, pairs);
+        estimate_keyspace(&cfg.unwrap()).plugboard_bits
+    };
+    assert_eq!(with(0), 0.0);
+    assert!((with(1) - 325f64.log2()).abs() < 1e-9);
+    // полный набор из 13 пар: 25!! = 7 905 853 580 625 вариантов
+    assert!((with(13) - 7_905_853_580_625f64.log2()).abs() < 1e-6);
+}
+
+fn latin_board(pairs: &[(char, char)]) -> Result<Plugboard, CipherError> {
+    Plugboard::from_pairs(pairs, &AlphabetIndex::new(&chars("latin")))
+}
+
+#[test]
+fn from_pairs_rejects_foreign_characters() {
+    assert_eq!(latin_board(&[('a', 'ж')]), Err(CipherError::PlugboardUnknownChar('ж')));
+    assert_eq!(latin_board(&[('Q', 'b')]), Err(CipherError::PlugboardUnknownChar('Q')));
+}
+
+#[test]
+fn from_pairs_rejects_self_pairs() {
+    assert_eq!(latin_board(&[('q', 'q')]), Err(CipherError::PlugboardSelfPair('q')));
+}
+
+#[test]
+fn from_pairs_rejects_reused_characters() {
+    let err = Err(CipherError::PlugboardCharReused('b'));
+    assert_eq!(latin_board(&[('a', 'b'), ('b', 'c')]), err);
+    assert_eq!(latin_board(&[('a', 'b'), ('c', 'b')]), err);
+    // та же пара дважды — тоже повтор
+    assert_eq!(latin_board(&[('b', 'a'), ('a', 'b')]), Err(CipherError::PlugboardCharReused('a')));
+}
+
+#[test]
+fn from_pairs_lists_pairs_in_alphabet_order() {
+    let board = latin_board(&[('z', 'c'), ('b', 'a')]).unwrap();
+    assert_eq!(board.pairs().collect::<Vec<_>>(), [(0, 1), (2, 25)]);
+    assert_eq!(board.swap(3), 3);
+}
+
+proptest! {
+    #[test]
+    fn from_pairs_is_always_an_involution(seed in any::<u64>(), count in 0usize..=13) {
+        let alphabet = chars("latin");
+        let pairs = plugboard::random_pairs(&alphabet, count, &mut StdRng::seed_from_u64(seed))
+            .unwrap();
+        let board = latin_board(&pairs).unwrap();
+        prop_assert_eq!(board.alphabet_len(), 26);
+        for i in 0..26 {
+            prop_assert_eq!(board.swap(board.swap(i)), i);
+        }
+        prop_assert_eq!(board.pairs().count(), count);
+    }
+}
+
+fn latin_map(map: &[(char, char)]) -> Result<Plugboard, CipherError> {
+    Plugboard::from_map(map, &AlphabetIndex::new(&chars("latin")))
+}
+
+#[test]
+fn from_map_keeps_separate_forward_and_inverse_tables() {
+    // цикл a → b → c → a: не пары, панель не сама себе обратная
+    let board = latin_map(&[('a', 'b'), ('b', 'c'), ('c', 'a')]).unwrap();
+    assert!(!board.is_reciprocal());
+    assert_eq!((board.swap(0), board.swap(1), board.swap(2)), (1, 2, 0));
+    assert_eq!((board.unswap(0), board.unswap(1), board.unswap(2)), (2, 0, 1));
+    assert_eq!(board.swap(25), 25);
+    assert_eq!(board.wires().collect::<Vec<_>>(), [(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(board.pairs().count(), 0);
+    // пары — частный случай: обе таблицы совпадают
+    let pairs = latin_board(&[('a', 'b')]).unwrap();
+    assert!(pairs.is_reciprocal());
+    assert_eq!(latin_map(&[('a', 'b'), ('b', 'a')]).unwrap(), pairs);
+}
+
+#[test]
+fn from_map_rejects_what_is_not_a_permutation() {
+    assert_eq!(latin_map(&[('a', 'ж')]), Err(CipherError::PlugboardUnknownChar('ж')));
+    assert_eq!(latin_map(&[('q', 'q')]), Err(CipherError::PlugboardSelfPair('q')));
+    let reused = |c| Err(CipherError::PlugboardCharReused(c));
+    assert_eq!(latin_map(&[('a', 'b'), ('a', 'c'), ('b', 'a')]), reused('a'));
+    assert_eq!(latin_map(&[('a', 'c'), ('b', 'c'), ('c', 'a')]), reused('c'));
+    // из b провода нет, а b → b уже занят проводом из a
+    assert_eq!(latin_map(&[('a', 'b')]), Err(CipherError::PlugboardMapOpen('b')));
+}
+
+#[test]
+fn pairs_and_map_together_are_an_error() {
+    let cfg = ConfigData {
+        plugboard: vec![('a', 'b')],
+        plugboard_map: vec![('c', 'd'), ('d', 'c')],
+        ..Default::default()
+    };
+    assert_eq!(cfg.validate(), Err(CipherError::PlugboardPairsAndMap));
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).err(), Some(CipherError::PlugboardPairsAndMap));
+}
+
+fn uhr_config(seed: u64) -> ConfigData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cfg = ConfigData::generate_with_pairs(&mut rng, "latin", 3, 0).unwrap();
+    cfg.plugboard_map = plugboard::random_map(&cfg.alphabet_chars(), &mut rng);
+    cfg
+}
+
+#[test]
+fn both_modes_round_trip() {
+    let text = "attack at dawn, pier 4";
+    let pairs = ConfigData::generate(&mut StdRng::seed_from_u64(6), "latin", 3);
+    for cfg in [pairs, uhr_config(7)] {
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+        assert_ne!(cipher, text);
+        assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap(), text);
+        let bytes = cfg.to_byte_mode();
+        let mut machine = EnigmaSudnogoDnya::new(&bytes).unwrap();
+        let scrambled = machine.encrypt_bytes(text.as_bytes());
+        machine.reset();
+        assert_eq!(machine.encrypt_bytes(&scrambled), text.as_bytes());
+    }
+}
+
+#[test]
+fn map_mode_is_not_a_hidden_pair_board() {
+    let cfg = ConfigData {
+        plugboard_map: vec![('a', 'b'), ('b', 'c'), ('c', 'a')],
+        ..ConfigData::generate_with_pairs(&mut StdRng::seed_from_u64(8), "latin", 2, 0).unwrap()
+    };
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert_eq!(machine.plugboard_swap('a'), 'b');
+    assert_eq!(machine.plugboard_swap('b'), 'c');
+    assert_eq!(machine.describe().plugboard, [('a', 'b'), ('b', 'c'), ('c', 'a')]);
+    // вход σ, выход σ⁻¹: машина всё ещё инволюция на каждом шаге
+    let perm = machine.current_permutation();
+    assert!((0..26).all(|i| perm[perm[i]] == i));
+    // и шифрует иначе, чем те же роторы без панели
+    let plain = ConfigData { plugboard_map: Vec::new(), ..cfg.clone() };
+    let text = "abcabcabc";
+    let with_map = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+    assert_ne!(with_map, EnigmaSudnogoDnya::new(&plain).unwrap().encrypt(text));
+}
+
+#[test]
+fn old_pair_configs_still_load() {
+    let json = r#"{"alphabet":"latin","plugboard":[["a","z"]],"blocks":["КБЧ"],
+        "rotor_positions":[[1,2,3]]}"#;
+    let cfg: ConfigData = serde_json::from_str(json).unwrap();
+    assert!(cfg.plugboard_map.is_empty());
+    assert!(!serde_json::to_string(&cfg).unwrap().contains("plugboard_map"));
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().plugboard_swap('z'), 'a');
+}
+
+#[test]
+fn map_survives_json_and_binary_configs() {
+    let cfg = uhr_config(9);
+    assert!(!cfg.plugboard_map.is_empty());
+    for name in ["uhr.json", "uhr.bin"] {
+        let path = std::env::temp_dir().join(format!("esd-pb-{}-{}", std::process::id(), name));
+        let path = path.to_str().unwrap();
+        EnigmaSudnogoDnya::save_config(&cfg, path, false).unwrap();
+        assert_eq!(EnigmaSudnogoDnya::load_config(path).unwrap(), cfg);
+        std::fs::remove_file(path).ok();
+    }
+}
+
+#[test]
+fn stacked_plugboards_round_trip() {
+    let cfg = ConfigData {
+        plugboard: vec![('a', 'b')],
+        plugboards: vec![vec![('b', 'c'), ('x', 'y')], vec![('a', 'c'), ('p', 'q')]],
+        ..ConfigData::generate_with_pairs(&mut StdRng::seed_from_u64(10), "latin", 3, 0).unwrap()
+    };
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    // a → b → c → a, b → a → a → c, c → c → b → b: три пары дают не то,
+    // что каждая по отдельности
+    assert_eq!(machine.plugboard_swap('a'), 'a');
+    assert_eq!(machine.plugboard_swap('b'), 'c');
+    assert_eq!(machine.plugboard_swap('c'), 'b');
+    let perm = machine.current_permutation();
+    assert!((0..26).all(|i| perm[perm[i]] == i));
+
+    let text = "attack at dawn, pier 4";
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(text);
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap(), text);
+    let single = ConfigData { plugboards: Vec::new(), ..cfg.clone() };
+    assert_ne!(EnigmaSudnogoDnya::new(&single).unwrap().encrypt(text), cipher);
+    let mut bytes = EnigmaSudnogoDnya::new(&cfg.to_byte_mode()).unwrap();
+    let scrambled = bytes.encrypt_bytes(text.as_bytes());
+    bytes.reset();
+    assert_eq!(bytes.encrypt_bytes(&scrambled), text.as_bytes());
+
+    for name in ["stack.json", "stack.bin"] {
+        let path = std::env::temp_dir().join(format!("esd-pb-{}-{}", std::process::id(), name));
+        let path = path.to_str().unwrap();
+        EnigmaSudnogoDnya::save_config(&cfg, path, false).unwrap();
+        assert_eq!(EnigmaSudnogoDnya::load_config(path).unwrap(), cfg);
+        std::fs::remove_file(path).ok();
+    }
+}
+
+#[test]
+fn stacked_plugboards_are_checked_like_pairs() {
+    let bad = |board: Vec<(char, char)>| ConfigData {
+        plugboards: vec![vec![('a', 'b')], board],
+        ..Default::default()
+    };
+    assert_eq!(bad(vec![('q', 'q')]).validate(), Err(CipherError::PlugboardSelfPair('q')));
+    let reused = bad(vec![('a', 'c'), ('a', 'd')]);
+    assert_eq!(reused.validate(), Err(CipherError::PlugboardCharReused('a')));
+    assert_eq!(bad(vec![('a', 'ж')]).validate(), Err(CipherError::PlugboardUnknownChar('ж')));
+}
+
+proptest! {
+    #[test]
+    fn random_map_is_a_permutation(seed in any::<u64>()) {
+        let alphabet = chars("cyrillic");
+        let map = plugboard::random_map(&alphabet, &mut StdRng::seed_from_u64(seed));
+        let board = Plugboard::from_map(&map, &AlphabetIndex::new(&alphabet)).unwrap();
+        for i in 0..alphabet.len() {
+            prop_assert_eq!(board.unswap(board.swap(i)), i);
+        }
+        prop_assert_eq!(board.wires().count(), map.len());
+    }
+}
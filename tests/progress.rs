@@ -0,0 +1,42 @@
+#![cfg(all(feature = "std", feature = "rand"))]
+
+use enigma_sd::progress::{GENERATE_BATCH, Progress, STREAM_CHUNK};
+use enigma_sd::{BYTES_ALPHABET, ConfigData, EnigmaSudnogoDnya};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn generation_reports_in_batches() {
+    let blocks = 2 * GENERATE_BATCH + 100;
+    let mut calls = Vec::new();
+    let mut rng = StdRng::seed_from_u64(812);
+    let cfg = ConfigData::generate_with_progress(&mut rng, "latin", blocks, 8, |p| calls.push(p))
+        .unwrap();
+    // три пачки блоков и три пачки позиций, а не по вызову на блок
+    assert_eq!(calls.len(), 6);
+    assert!(calls.windows(2).all(|w| w[0].done < w[1].done));
+    let total = Some(2 * blocks as u64);
+    assert_eq!(calls.last(), Some(&Progress { done: 2 * blocks as u64, total }));
+
+    // колбэк не сдвигает поток ГПСЧ: `--seed` даёт тот же конфиг
+    let mut rng = StdRng::seed_from_u64(812);
+    assert_eq!(ConfigData::generate_with_pairs(&mut rng, "latin", blocks, 8).unwrap(), cfg);
+}
+
+#[test]
+fn stream_reports_once_per_chunk() {
+    let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(7), BYTES_ALPHABET, 3);
+    let data = vec![0x5au8; 3 * STREAM_CHUNK + 10];
+    let total = Some(data.len() as u64);
+    let mut calls = Vec::new();
+    let mut out = Vec::new();
+    let n = EnigmaSudnogoDnya::new(&cfg)
+        .unwrap()
+        .encrypt_stream_with_progress(&data[..], &mut out, total, |p| calls.push(p))
+        .unwrap();
+    assert_eq!(n, data.len() as u64);
+    assert_eq!(calls.len(), 4);
+    assert_eq!(calls[0], Progress { done: STREAM_CHUNK as u64, total });
+    assert_eq!(calls[3].done, n);
+    assert_eq!(out, EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(&data));
+}
@@ -0,0 +1,166 @@
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, Rotor};
+use proptest::prelude::*;
+
+const COLORS: &[char] = &['К', 'Б', 'Ч', 'З', 'Р', 'О', 'Ф', 'С', 'Г', 'Л'];
+
+fn alphabet_chars(alphabet: &str) -> Vec<char> {
+    ConfigData { alphabet: alphabet.into(), ..Default::default() }.alphabet_chars()
+}
+
+/// Строки цветов: от 1 до 6 блоков по 1–5 роторов
+fn blocks() -> impl Strategy<Value = Vec<String>> {
+    let block = prop::collection::vec(prop::sample::select(COLORS), 1..=5)
+        .prop_map(|colors| colors.into_iter().collect::<String>());
+    prop::collection::vec(block, 1..=6)
+}
+
+/// Корректный конфиг: пары без повторов, позиции в пределах алфавита
+fn valid_config() -> impl Strategy<Value = ConfigData> {
+    (prop::sample::select(&["latin", "cyrillic"][..]), blocks()).prop_flat_map(
+        |(alphabet, blocks)| {
+            let chars = alphabet_chars(alphabet);
+            let n = chars.len();
+            let pairs = Just(chars)
+                .prop_shuffle()
+                .prop_flat_map(move |chars| (Just(chars), 0..=n / 2))
+                .prop_map(|(chars, count)| {
+                    chars.chunks(2).take(count).map(|p| (p[0], p[1])).collect::<Vec<_>>()
+                });
+            let positions: Vec<_> = blocks
+                .iter()
+                .map(|b| prop::collection::vec(0..n, b.chars().count()))
+                .collect();
+            (Just(alphabet), Just(blocks), pairs, positions).prop_map(
+                |(alphabet, blocks, plugboard, rotor_positions)| ConfigData {
+                    alphabet: alphabet.into(),
+                    plugboard,
+                    blocks,
+                    rotor_positions,
+                    ..Default::default()
+                },
+            )
+        },
+    )
+}
+
+/// Сообщение из символов алфавита вперемешку с посторонними
+fn message(alphabet: Vec<char>) -> impl Strategy<Value = String> {
+    let inside = prop::sample::select(alphabet);
+    let outside = prop::sample::select(vec![' ', ',', '7', '!', '\n', '€', 'ß']);
+    prop::collection::vec(prop_oneof![3 => inside, 1 => outside], 0..200)
+        .prop_map(|chars| chars.into_iter().collect())
+}
+
+fn config_and_message() -> impl Strategy<Value = (ConfigData, String)> {
+    valid_config().prop_flat_map(|cfg| {
+        let msg = message(cfg.alphabet_chars());
+        (Just(cfg), msg)
+    })
+}
+
+proptest! {
+    #[test]
+    fn decrypt_inverts_encrypt((cfg, msg) in config_and_message()) {
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&msg);
+        let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap();
+        prop_assert_eq!(plain, msg);
+    }
+
+    #[test]
+    fn foreign_characters_pass_through_in_place((cfg, msg) in config_and_message()) {
+        let alphabet = cfg.alphabet_chars();
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&msg);
+        prop_assert_eq!(cipher.chars().count(), msg.chars().count());
+        for (m, c) in msg.chars().zip(cipher.chars()) {
+            if alphabet.contains(&m) {
+                prop_assert!(alphabet.contains(&c));
+            } else {
+                prop_assert_eq!(m, c);
+            }
+        }
+    }
+
+    #[test]
+    fn plugboard_is_an_involution(cfg in valid_config()) {
+        let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        for &c in machine.alphabet() {
+            prop_assert_eq!(machine.plugboard_swap(machine.plugboard_swap(c)), c);
+        }
+        for &(a, b) in &cfg.plugboard {
+            prop_assert_eq!(machine.plugboard_swap(a), b);
+        }
+    }
+
+    #[test]
+    fn current_permutation_is_an_involution(cfg in valid_config()) {
+        let perm = EnigmaSudnogoDnya::new(&cfg).unwrap().current_permutation();
+        for (i, &j) in perm.iter().enumerate() {
+            prop_assert_eq!(perm[j], i);
+        }
+    }
+
+    #[test]
+    fn rotor_reverse_undoes_forward(
+        size in 2usize..=256,
+        shift in any::<usize>(),
+        position in any::<usize>(),
+    ) {
+        let mut rotor = Rotor::new(shift, size);
+        rotor.load_position(position);
+        for i in 0..size {
+            prop_assert_eq!(rotor.encode_index(rotor.encode_index(i, false), true), i);
+        }
+    }
+
+    #[test]
+    fn arbitrary_plugboards_are_rejected_not_panicking(
+        cyrillic in any::<bool>(),
+        pairs in prop::collection::vec((any::<char>(), any::<char>()), 0..6),
+    ) {
+        let cfg = ConfigData {
+            alphabet: if cyrillic { "cyrillic" } else { "latin" }.into(),
+            plugboard: pairs,
+            blocks: vec!["КБ".into()],
+            ..Default::default()
+        };
+        prop_assert_eq!(EnigmaSudnogoDnya::new(&cfg).is_ok(), cfg.validate().is_ok());
+    }
+}
+
+/// Синтаксически верные, но как угодно странные конфиги
+fn wild_config() -> impl Strategy<Value = ConfigData> {
+    let alphabet = prop::sample::select(&["latin", "cyrillic", "bytes", "", "klingon"][..]);
+    let block = prop_oneof![
+        prop::collection::vec(prop::sample::select(COLORS), 0..70)
+            .prop_map(|c| c.into_iter().collect::<String>()),
+        any::<String>(),
+    ];
+    (
+        alphabet,
+        prop::collection::vec((any::<char>(), any::<char>()), 0..4),
+        prop::collection::vec(block, 0..4),
+        prop::collection::vec(prop::collection::vec(any::<usize>(), 0..6), 0..5),
+        prop::collection::vec(prop::collection::vec(any::<usize>(), 0..6), 0..3),
+    )
+        .prop_map(|(alphabet, plugboard, blocks, rotor_positions, block_shifts)| ConfigData {
+            alphabet: alphabet.into(),
+            plugboard,
+            blocks,
+            rotor_positions,
+            block_shifts,
+            ..Default::default()
+        })
+}
+
+proptest! {
+    #[test]
+    fn wild_configs_never_panic(cfg in wild_config()) {
+        let built = EnigmaSudnogoDnya::new(&cfg);
+        prop_assert_eq!(built.is_ok(), cfg.validate().is_ok());
+        if let Ok(mut machine) = built {
+            let cipher = machine.encrypt("wild дикий 123");
+            machine.reset();
+            prop_assert_eq!(machine.decrypt(&cipher).unwrap(), "wild дикий 123");
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use enigma_sd::plugboard::Plugboard;
+use enigma_sd::{AlphabetIndex, ConfigData, EnigmaSudnogoDnya};
+
+fn secret() -> ConfigData {
+    ConfigData {
+        alphabet: "cyrillic".into(),
+        plugboard: vec![('ж', 'щ'), ('ф', 'ю')],
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        rotor_positions: vec![vec![17, 29, 31], vec![23, 19]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn debug_hides_the_key() {
+    let cfg = secret();
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let index = AlphabetIndex::new(&cfg.alphabet_chars());
+    let plugboard = Plugboard::from_pairs(&cfg.plugboard, &index).unwrap();
+    for out in [format!("{:?}", cfg), format!("{:?}", machine), format!("{:?}", plugboard)] {
+        for (a, b) in &cfg.plugboard {
+            assert!(!out.contains(*a) && !out.contains(*b), "{}", out);
+        }
+        assert!(!out.contains("КБЧ") && !out.contains("29"), "{}", out);
+        assert!(out.contains("<redacted>"));
+    }
+}
+
+#[test]
+fn debug_full_shows_everything() {
+    let cfg = secret();
+    let full = cfg.debug_full();
+    assert!(full.contains("('ж', 'щ')") && full.contains("КБЧ") && full.contains("29"));
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap().debug_full();
+    assert!(machine.contains("('ж', 'щ')") && machine.contains("position: 29"));
+}
+
+#[test]
+fn zeroize_wipes_the_key_fields() {
+    use zeroize::Zeroize;
+    let mut cfg = secret();
+    cfg.zeroize();
+    let full = cfg.debug_full();
+    assert!(!full.contains('ж') && !full.contains("КБЧ") && !full.contains("29"), "{}", full);
+    // алфавит — не ключ и остаётся
+    assert_eq!(cfg.alphabet, "cyrillic");
+}
@@ -0,0 +1,190 @@
+#![cfg(all(feature = "std", feature = "json"))]
+
+mod common;
+
+use enigma_sd::analysis::estimate_keyspace;
+use enigma_sd::{
+    BYTES_ALPHABET, CipherError, ConfigData, EnigmaSudnogoDnya, REFLECTOR_KINDS, Reflector,
+};
+use std::collections::BTreeSet;
+
+fn config(kind: &str) -> ConfigData {
+    ConfigData { reflector_kind: kind.into(), ..common::config("latin", &[('a', 'k'), ('e', 'x')]) }
+}
+
+const TEXT: &str = "the quick brown fox jumps over the lazy dog";
+
+#[test]
+fn each_kind_gives_its_own_ciphertext_and_round_trips() {
+    let mut seen = BTreeSet::new();
+    for &kind in REFLECTOR_KINDS {
+        let cipher = EnigmaSudnogoDnya::new(&config(kind)).unwrap().encrypt(TEXT);
+        let plain = EnigmaSudnogoDnya::new(&config(kind)).unwrap().decrypt(&cipher).unwrap();
+        assert_eq!(plain, TEXT, "{kind}");
+        assert!(seen.insert(cipher), "{kind}");
+    }
+}
+
+#[test]
+fn named_reflectors_are_involutions() {
+    for alphabet in ["latin", "cyrillic", BYTES_ALPHABET] {
+        let chars =
+            ConfigData { alphabet: alphabet.into(), ..Default::default() }.alphabet_chars();
+        for &kind in REFLECTOR_KINDS {
+            let Ok(r) = Reflector::new(&chars, kind) else {
+                // исторические разводки есть только для латиницы
+                assert!(kind.starts_with("ukw") && alphabet != "latin", "{kind} {alphabet}");
+                continue;
+            };
+            for i in 0..chars.len() {
+                assert_eq!(r.reflect_index(r.reflect_index(i)), i, "{kind} {alphabet}");
+            }
+        }
+    }
+}
+
+#[test]
+fn ukw_b_matches_historical_wiring() {
+    let latin = ConfigData::default().alphabet_chars();
+    let r = Reflector::new(&latin, "ukw-b").unwrap();
+    let wired: String = (0..26).map(|i| latin[r.reflect_index(i)]).collect();
+    assert_eq!(wired, "yruhqsldpxngokmiebfzcwvjat");
+}
+
+#[test]
+fn bad_reflectors_are_rejected() {
+    let cfg = ConfigData { reflector_kind: "ukw-z".into(), ..config("reverse") };
+    assert_eq!(cfg.validate(), Err(CipherError::UnknownReflector("ukw-z".into())));
+    let cyrillic = ConfigData { alphabet: "cyrillic".into(), plugboard: vec![], ..config("ukw-c") };
+    assert!(matches!(
+        EnigmaSudnogoDnya::new(&cyrillic),
+        Err(CipherError::ReflectorAlphabetMismatch { alphabet_len: 33, .. })
+    ));
+}
+
+#[test]
+fn offsets_depend_on_reflector_symmetry() {
+    // один ротор, все 26 позиций: reverse даёт 13 разных машин,
+    // identity-pairs — 2, исторические разводки — все 26
+    for (&kind, expected) in REFLECTOR_KINDS.iter().zip([13, 2, 26, 26]) {
+        let distinct: BTreeSet<String> = (0..26)
+            .map(|p| {
+                let cfg = ConfigData {
+                    blocks: vec!["К".into()],
+                    rotor_positions: vec![vec![p]],
+                    reflector_kind: kind.into(),
+                    ..Default::default()
+                };
+                EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(TEXT)
+            })
+            .collect();
+        assert_eq!(distinct.len(), expected, "{kind}");
+        let cfg = ConfigData {
+            blocks: vec!["К".into()],
+            reflector_kind: kind.into(),
+            ..Default::default()
+        };
+        let bits = estimate_keyspace(&cfg).effective_bits;
+        assert!((bits - (expected as f64).log2()).abs() < 1e-9, "{kind}");
+    }
+}
+
+#[test]
+fn odd_alphabet_leaves_a_fixed_point() {
+    let chars = |alphabet: &str| {
+        ConfigData { alphabet: alphabet.into(), ..Default::default() }.alphabet_chars()
+    };
+    // кириллица с «ё» — 33 буквы
+    let cyrillic = chars("cyrillic");
+    assert_eq!(cyrillic.len(), 33);
+    for kind in ["reverse", "identity-pairs"] {
+        assert!(Reflector::new(&cyrillic, kind).unwrap().has_fixed_point(), "{kind}");
+    }
+    for alphabet in ["latin", BYTES_ALPHABET] {
+        for &kind in REFLECTOR_KINDS {
+            let r = Reflector::new(&chars(alphabet), kind).unwrap();
+            // UKW связывает только латинские буквы, остальные байты на месте
+            let expected = alphabet == BYTES_ALPHABET && kind.starts_with("ukw");
+            assert_eq!(r.has_fixed_point(), expected, "{kind} {alphabet}");
+        }
+    }
+
+    let cfg = ConfigData { alphabet: "cyrillic".into(), plugboard: vec![], ..config("reverse") };
+    assert!(EnigmaSudnogoDnya::new(&cfg).unwrap().reflector_has_fixed_point());
+    assert!(!EnigmaSudnogoDnya::new(&config("ukw-b")).unwrap().reflector_has_fixed_point());
+}
+
+fn one_way() -> ConfigData {
+    ConfigData { use_reflector: false, ..config("reverse") }
+}
+
+#[test]
+fn without_reflector_decrypt_undoes_encrypt() {
+    let cipher = EnigmaSudnogoDnya::new(&one_way()).unwrap().encrypt(TEXT);
+    assert_ne!(cipher, TEXT);
+    let mut machine = EnigmaSudnogoDnya::new(&one_way()).unwrap();
+    assert_eq!(machine.decrypt(&cipher).unwrap(), TEXT);
+    // шифрование ещё раз текст уже не возвращает
+    machine.reset();
+    assert_ne!(machine.encrypt(&cipher), TEXT);
+    machine.reset();
+    assert_ne!(machine.decrypt(TEXT).unwrap(), cipher);
+    // и это другой шифр, чем с отражателем
+    assert_ne!(EnigmaSudnogoDnya::new(&config("reverse")).unwrap().encrypt(TEXT), cipher);
+}
+
+#[test]
+fn without_reflector_the_permutation_is_not_an_involution() {
+    let machine = EnigmaSudnogoDnya::new(&one_way()).unwrap();
+    let perm = machine.current_permutation();
+    assert!((0..perm.len()).any(|i| perm[perm[i]] != i));
+    let mut sorted = perm.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..perm.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn without_reflector_bytes_streams_and_tags_round_trip() {
+    let cfg = ConfigData { alphabet: BYTES_ALPHABET.into(), ..one_way() };
+    let data: Vec<u8> = (0..=u8::MAX).cycle().take(3000).collect();
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt_bytes(&data);
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert_eq!(machine.decrypt_bytes(&cipher), data);
+    let mut out = Vec::new();
+    machine.reset();
+    machine.decrypt_stream(&cipher[..], &mut out).unwrap();
+    assert_eq!(out, data);
+
+    let mut machine = EnigmaSudnogoDnya::new(&one_way()).unwrap();
+    let tagged = machine.encrypt_with_tag(TEXT);
+    machine.reset();
+    assert_eq!(machine.decrypt_with_tag(&tagged).unwrap(), TEXT);
+    let key = one_way().mac_key("");
+    machine.reset();
+    let sealed = machine.encrypt_authenticated(TEXT, &key);
+    machine.reset();
+    assert_eq!(machine.decrypt_authenticated(&sealed, &key).unwrap(), TEXT);
+}
+
+#[test]
+fn without_reflector_the_trace_is_one_pass() {
+    let mut machine = EnigmaSudnogoDnya::new(&one_way()).unwrap();
+    let (cipher, trace) = machine.encrypt_traced("abc");
+    assert!(trace.iter().all(|t| t.forward.len() == 2 && t.backward.is_empty()));
+    machine.reset();
+    let (plain, trace) = machine.decrypt_traced(&cipher);
+    assert_eq!(plain, "abc");
+    assert!(trace.iter().all(|t| t.forward.is_empty() && t.backward.len() == 2));
+}
+
+#[test]
+fn use_reflector_defaults_to_true_and_changes_the_fingerprint() {
+    let cfg: ConfigData = serde_json::from_str(
+        r#"{"alphabet":"latin","plugboard":[],"blocks":["КБ"],"rotor_positions":[[0,1]]}"#,
+    )
+    .unwrap();
+    assert!(cfg.use_reflector);
+    assert!(!serde_json::to_string(&cfg).unwrap().contains("use_reflector"));
+    let one_way = ConfigData { use_reflector: false, ..cfg.clone() };
+    assert_ne!(one_way.fingerprint(), cfg.fingerprint());
+}
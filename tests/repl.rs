@@ -0,0 +1,73 @@
+use enigma_sd::repl::{Command, TRACE_BLOCKS, is_heredoc_end, is_heredoc_start, parse_command};
+
+#[test]
+fn message_can_follow_the_command() {
+    assert_eq!(
+        parse_command("encrypt attack at  dawn "),
+        Command::Message { decrypt: false, trace: None, text: Some("attack at  dawn") }
+    );
+    assert_eq!(
+        parse_command("  decrypt\tзщк"),
+        Command::Message { decrypt: true, trace: None, text: Some("зщк") }
+    );
+    let bare = |decrypt| Command::Message { decrypt, trace: None, text: None };
+    assert_eq!(parse_command("encrypt"), bare(false));
+    assert_eq!(parse_command("decrypt   "), bare(true));
+}
+
+#[test]
+fn trace_flag_comes_before_the_message() {
+    let message = |trace, text| Command::Message { decrypt: false, trace, text };
+    assert_eq!(parse_command("encrypt --trace"), message(Some(TRACE_BLOCKS), None));
+    assert_eq!(parse_command("encrypt --trace=3 hi there"), message(Some(3), Some("hi there")));
+    assert_eq!(parse_command("encrypt --trace  <<"), message(Some(TRACE_BLOCKS), Some("<<")));
+    // всё прочее — уже текст сообщения
+    assert_eq!(parse_command("encrypt --trace=0 hi"), message(None, Some("--trace=0 hi")));
+    assert_eq!(parse_command("encrypt hi --trace"), message(None, Some("hi --trace")));
+    assert_eq!(parse_command("encrypt --tracer"), message(None, Some("--tracer")));
+    let line = "decrypt --trace=2 зщк";
+    assert_eq!(
+        parse_command(line).history_entry(line, false).as_deref(),
+        Some("decrypt --trace=2")
+    );
+}
+
+#[test]
+fn other_commands_split_into_words() {
+    assert_eq!(parse_command(""), Command::Empty);
+    assert_eq!(parse_command("   "), Command::Empty);
+    assert_eq!(parse_command("exit"), Command::Other { name: "exit", args: vec![] });
+    assert_eq!(
+        parse_command("set-positions 0  1,2,3 save"),
+        Command::Other { name: "set-positions", args: vec!["0", "1,2,3", "save"] }
+    );
+    // имя сравнивается целиком
+    assert_eq!(
+        parse_command("encrypted text"),
+        Command::Other { name: "encrypted", args: vec!["text"] }
+    );
+}
+
+#[test]
+fn history_keeps_messages_only_when_allowed() {
+    let line = " encrypt meet at noon";
+    let command = parse_command(line);
+    assert_eq!(command.history_entry(line, false).as_deref(), Some("encrypt"));
+    assert_eq!(command.history_entry(line, true).as_deref(), Some("encrypt meet at noon"));
+    let line = "decrypt";
+    assert_eq!(parse_command(line).history_entry(line, false).as_deref(), Some("decrypt"));
+    let line = "benchmark --csv";
+    assert_eq!(parse_command(line).history_entry(line, false).as_deref(), Some(line));
+    assert_eq!(parse_command("  ").history_entry("  ", true), None);
+}
+
+#[test]
+fn heredoc_markers() {
+    assert!(is_heredoc_start("<<"));
+    assert!(is_heredoc_start(" << "));
+    assert!(!is_heredoc_start("<<EOF"));
+    assert!(is_heredoc_end("."));
+    assert!(is_heredoc_end(" .\r"));
+    assert!(!is_heredoc_end(".."));
+    assert!(!is_heredoc_end("end."));
+}
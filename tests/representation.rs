@@ -0,0 +1,84 @@
+#![cfg(feature = "rand")]
+
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// Эталон: исходная реализация на usize и Vec<Vec<...>> без оптимизаций
+fn reference_encrypt(cfg: &ConfigData, msg: &str) -> String {
+    let alphabet = cfg.alphabet_chars();
+    let n = alphabet.len();
+    let index = |c: char| alphabet.iter().position(|&a| a == c);
+
+    let mut plugboard: Vec<usize> = (0..n).collect();
+    for &(a, b) in &cfg.plugboard {
+        let (ia, ib) = (index(a).unwrap(), index(b).unwrap());
+        plugboard[ia] = ib;
+        plugboard[ib] = ia;
+    }
+    let shifts: Vec<Vec<usize>> = cfg
+        .blocks
+        .iter()
+        .map(|b| {
+            b.chars()
+                .map(|c| match c {
+                    'К' => 1, 'Б' => 2, 'Ч' => 3, 'З' => 5, 'Р' => 4,
+                    'О' => 6, 'Ф' => 7, 'С' => 8, 'Г' => 9, 'Л' => 10,
+                    _ => unreachable!(),
+                })
+                .collect()
+        })
+        .collect();
+    let mut positions: Vec<Vec<usize>> = cfg.rotor_positions.clone();
+
+    let mut out = String::new();
+    for ch in msg.to_lowercase().chars() {
+        let Some(mut idx) = index(ch) else {
+            out.push(ch);
+            continue;
+        };
+        idx = plugboard[idx];
+        for (b, blk) in shifts.iter().enumerate() {
+            for (r, &shift) in blk.iter().enumerate() {
+                idx = (idx + shift + positions[b][r]) % n;
+            }
+        }
+        idx = n - 1 - idx;
+        for (b, blk) in shifts.iter().enumerate().rev() {
+            for (r, &shift) in blk.iter().enumerate().rev() {
+                idx = (idx + n - (shift + positions[b][r]) % n) % n;
+            }
+        }
+        idx = plugboard[idx];
+        for pos in &mut positions {
+            for p in pos.iter_mut() {
+                *p = (*p + 1) % n;
+                if *p != 0 {
+                    break;
+                }
+            }
+        }
+        out.push(alphabet[idx]);
+    }
+    out
+}
+
+proptest! {
+    #[test]
+    fn compact_tables_do_not_change_ciphertext(
+        seed in any::<u64>(),
+        blocks in 1usize..8,
+        cyrillic in any::<bool>(),
+        picks in prop::collection::vec(0usize..40, 0..300),
+    ) {
+        let alphabet = if cyrillic { "cyrillic" } else { "latin" };
+        let cfg = ConfigData::generate(&mut StdRng::seed_from_u64(seed), alphabet, blocks);
+        let chars = cfg.alphabet_chars();
+        // индексы за пределами алфавита дают пробел — проверяем и пропуск символов
+        let msg: String = picks.iter().map(|&i| chars.get(i).copied().unwrap_or(' ')).collect();
+
+        let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        prop_assert_eq!(machine.encrypt(&msg), reference_encrypt(&cfg, &msg));
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "fs")]
+
+mod common;
+
+use enigma_sd::ConfigData;
+use enigma_sd::resume::{progress_path, process_file};
+use std::fs;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("esd-resume-{}-{}", std::process::id(), name));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// `step_interval` 3 и точка через 1000 байт: фаза интервала посреди точки
+fn config() -> ConfigData {
+    ConfigData { step_interval: 3, ..common::config("latin", &[('a', 'q')]) }
+}
+
+const EVERY: u64 = 1000;
+
+fn data() -> Vec<u8> {
+    (0..25_500u32).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+/// Доводит работу до `stop_after`-й точки и «падает»
+fn interrupted(dir: &Path, decrypt: bool, input: &str, output: &str, stop_after: u64) {
+    let mut seen = 0;
+    let done = process_file(
+        &config(),
+        &dir.join(input),
+        &dir.join(output),
+        decrypt,
+        EVERY,
+        false,
+        |_| {
+            seen += 1;
+            if seen == stop_after { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        },
+    )
+    .unwrap();
+    assert!(done.is_none());
+    assert!(progress_path(&dir.join(output)).exists());
+}
+
+fn resume(dir: &Path, decrypt: bool, input: &str, output: &str) -> std::io::Result<()> {
+    let continue_all = |_: &_| ControlFlow::Continue(());
+    let (input, output) = (dir.join(input), dir.join(output));
+    process_file(&config(), &input, &output, decrypt, EVERY, true, continue_all).map(|done| {
+        assert!(done.is_some());
+    })
+}
+
+#[test]
+fn resumed_run_matches_an_uninterrupted_one() {
+    let dir = temp_dir("roundtrip");
+    fs::write(dir.join("in.bin"), data()).unwrap();
+    let continue_all = |_: &_| ControlFlow::Continue(());
+    let (input, full) = (dir.join("in.bin"), dir.join("full.esd"));
+    process_file(&config(), &input, &full, false, EVERY, false, continue_all).unwrap();
+    assert!(!progress_path(&full).exists());
+
+    interrupted(&dir, false, "in.bin", "part.esd", 7);
+    // после точки успело записаться лишнее — при продолжении оно отрезается
+    let mut tail = fs::OpenOptions::new().append(true).open(dir.join("part.esd")).unwrap();
+    tail.write_all(b"half-written chunk").unwrap();
+    resume(&dir, false, "in.bin", "part.esd").unwrap();
+    assert_eq!(fs::read(dir.join("part.esd")).unwrap(), fs::read(&full).unwrap());
+    assert!(!progress_path(&dir.join("part.esd")).exists());
+
+    // расшифровка продолжается так же
+    interrupted(&dir, true, "part.esd", "out.bin", 11);
+    resume(&dir, true, "part.esd", "out.bin").unwrap();
+    assert_eq!(fs::read(dir.join("out.bin")).unwrap(), data());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn resume_refuses_foreign_progress() {
+    let dir = temp_dir("foreign");
+    fs::write(dir.join("in.bin"), data()).unwrap();
+    // продолжать нечего
+    assert!(resume(&dir, false, "in.bin", "none.esd").is_err());
+
+    let continue_all = |_: &_| ControlFlow::Continue(());
+    let (input, full) = (dir.join("in.bin"), dir.join("full.esd"));
+    process_file(&config(), &input, &full, false, EVERY, false, continue_all).unwrap();
+    interrupted(&dir, false, "in.bin", "part.esd", 2);
+    // точка шифрования не годится для расшифровки в тот же выход
+    assert!(resume(&dir, true, "full.esd", "part.esd").is_err());
+    // другой конфиг
+    let other = ConfigData { rotor_positions: vec![vec![0, 0, 0], vec![0]], ..config() };
+    let part = dir.join("part.esd");
+    assert!(process_file(&other, &input, &part, false, EVERY, true, continue_all).is_err());
+    // испорченный интервал точек
+    let sidecar = progress_path(&part);
+    let saved = fs::read_to_string(&sidecar).unwrap();
+    let mut zero: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    zero["every"] = 0.into();
+    fs::write(&sidecar, zero.to_string()).unwrap();
+    let err = resume(&dir, false, "in.bin", "part.esd").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    fs::write(&sidecar, saved).unwrap();
+    // вход поменялся
+    fs::write(dir.join("in.bin"), &data()[..20_000]).unwrap();
+    assert!(resume(&dir, false, "in.bin", "part.esd").is_err());
+    fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,60 @@
+#![cfg(all(feature = "std", feature = "json"))]
+
+use enigma_sd::{CipherError, ConfigData, EnigmaSudnogoDnya};
+
+const MESSAGE: &str = "Перевёрнутые роторы: туда и обратно, 2024!";
+
+fn config(reversed: Vec<Vec<bool>>) -> ConfigData {
+    ConfigData {
+        alphabet: "cyrillic".into(),
+        plugboard: vec![('а', 'я'), ('ё', 'ж')],
+        blocks: vec!["КБЧ".into(), "ЗР".into()],
+        block_shifts: vec![vec![11, 32, 0]],
+        rotor_positions: vec![vec![1, 2, 32], vec![0, 5], vec![7, 7, 7]],
+        reversed,
+        ..Default::default()
+    }
+}
+
+fn mixed() -> Vec<Vec<bool>> {
+    vec![vec![true, false, true], vec![false, false], vec![false, true, true]]
+}
+
+#[test]
+fn mixed_directions_round_trip() {
+    let cfg = config(mixed());
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(MESSAGE);
+    assert_ne!(cipher, EnigmaSudnogoDnya::new(&config(Vec::new())).unwrap().encrypt(MESSAGE));
+    let plain = EnigmaSudnogoDnya::new(&cfg).unwrap().decrypt(&cipher).unwrap();
+    assert_eq!(plain, MESSAGE.to_lowercase());
+
+    // параллельный проход и describe видят те же флаги
+    let machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    assert_eq!(machine.encrypt_parallel(MESSAGE, 3), cipher);
+    let desc = machine.describe();
+    assert!(desc.blocks[0][0].reversed && !desc.blocks[1][0].reversed);
+}
+
+#[test]
+fn all_false_is_the_default_machine() {
+    let flags = vec![vec![false; 3], vec![false; 2], vec![false; 3]];
+    let explicit = EnigmaSudnogoDnya::new(&config(flags)).unwrap().encrypt(MESSAGE);
+    let default = EnigmaSudnogoDnya::new(&config(Vec::new())).unwrap().encrypt(MESSAGE);
+    assert_eq!(explicit, default);
+    // в JSON пустой вектор не пишется — старые конфиги не меняются
+    assert!(!serde_json::to_string(&config(Vec::new())).unwrap().contains("reversed"));
+}
+
+#[test]
+fn reversed_flags_must_match_blocks() {
+    assert_eq!(
+        config(vec![vec![true; 3]]).validate(),
+        Err(CipherError::ReversedBlockCountMismatch { blocks: 3, reversed: 1 })
+    );
+    let mut flags = mixed();
+    flags[1].push(true);
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&config(flags)).err(),
+        Some(CipherError::ReversedRotorCountMismatch { block: 1, rotors: 2, flags: 3 })
+    );
+}
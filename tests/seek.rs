@@ -0,0 +1,150 @@
+use enigma_sd::{Block, ConfigData, EnigmaSudnogoDnya};
+
+fn config() -> ConfigData {
+    ConfigData {
+        plugboard: vec![('e', 't')],
+        blocks: vec!["КБ".into(), "ЧЗО".into(), "Р".into()],
+        rotor_positions: vec![vec![25, 3], vec![24, 25, 7], vec![9]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn block_advance_matches_single_steps() {
+    // период блока из трёх роторов — 26³; проверяем и за ним
+    let period = 26u64.pow(3);
+    let mut stepped = Block::new("ЧЗО", 26).unwrap();
+    stepped.load_positions(&[24, 25, 7]);
+    let mut done = 0;
+    for n in [0, 1, 2, 25, 26, 27, 675, 676, 677, period - 1, period, period + 1, 2 * period + 13]
+    {
+        while done < n {
+            stepped.rotate();
+            done += 1;
+        }
+        let mut jumped = Block::new("ЧЗО", 26).unwrap();
+        jumped.load_positions(&[24, 25, 7]);
+        jumped.advance_by(n);
+        assert_eq!(jumped.save_positions(), stepped.save_positions(), "n = {n}");
+    }
+}
+
+#[test]
+fn machine_advance_matches_encrypting() {
+    let period = EnigmaSudnogoDnya::new(&config()).unwrap().period() as u64;
+    for n in [0, 1, 26, 677, period - 1, period + 5, 3 * period + 100] {
+        let mut encrypted = EnigmaSudnogoDnya::new(&config()).unwrap();
+        encrypted.encrypt(&"a".repeat(n as usize));
+        let mut jumped = EnigmaSudnogoDnya::new(&config()).unwrap();
+        jumped.advance_by(n);
+        assert_eq!(jumped.save_state(), encrypted.save_state(), "n = {n}");
+        assert_eq!(jumped.steps(), n);
+        assert_eq!(encrypted.steps(), n);
+    }
+    // огромный сдвиг не переполняет арифметику позиций
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    machine.advance_by(u64::MAX);
+    let mut reduced = EnigmaSudnogoDnya::new(&config()).unwrap();
+    reduced.advance_by(u64::MAX % period);
+    assert_eq!(machine.save_state(), reduced.save_state());
+}
+
+#[test]
+fn seek_into_the_middle_of_a_message() {
+    let plain: String = "the quick brown fox jumps over the lazy dog "
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .cycle()
+        .take(5_000)
+        .collect();
+    let cipher = EnigmaSudnogoDnya::new(&config()).unwrap().encrypt(&plain);
+
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    machine.advance_by(1_000);
+    let middle = machine.decrypt(&cipher[1_000..1_100]).unwrap();
+    assert_eq!(middle, plain[1_000..1_100]);
+    assert_eq!(machine.steps(), 1_100);
+}
+
+#[test]
+fn steps_count_only_alphabet_characters() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    machine.encrypt("Hello, World! 123");
+    assert_eq!(machine.steps(), 10);
+    machine.reset();
+    assert_eq!(machine.steps(), 0);
+}
+
+#[test]
+fn steps_taken_counts_rotating_characters_since_construction() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    // 12 букв алфавита вперемешку с пробелами, знаками и цифрами
+    machine.encrypt("Attack, at 05:30 — dawn!");
+    assert_eq!(machine.steps_taken(), 12);
+    // `reset` и `advance_by` счётчик не трогают, расшифровка — тоже расход
+    machine.reset();
+    machine.advance_by(100);
+    assert_eq!(machine.steps_taken(), 12);
+    machine.decrypt("abc").unwrap();
+    assert_eq!(machine.steps_taken(), 15);
+
+    // при step_interval = 3 роторы поворачивает каждый третий символ
+    let cfg = ConfigData { step_interval: 3, ..config() };
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    machine.encrypt("Attack, at 05:30 — dawn!");
+    assert_eq!((machine.steps(), machine.steps_taken()), (12, 4));
+    machine.encrypt("xy");
+    assert_eq!(machine.steps_taken(), 4);
+    machine.encrypt("z");
+    assert_eq!(machine.steps_taken(), 5);
+}
+
+#[test]
+fn steps_give_the_next_message_offset() {
+    // цифры идут через цифровой ротор и основные роторы не двигают
+    let cfg = ConfigData { digit_rotor_shift: Some(3), ..config() };
+    let mut sender = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    sender.encrypt("Meet at 0600, pier 4.");
+    assert_eq!(sender.steps(), 10);
+    let second = sender.encrypt("bring the maps");
+
+    let mut receiver = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    receiver.advance_by(10);
+    assert_eq!(receiver.decrypt(&second).unwrap(), "bring the maps");
+    assert_eq!(receiver.steps(), sender.steps());
+}
+
+#[test]
+fn offset_changes_ciphertext_and_decrypts_with_the_same_iv() {
+    let msg = "the same plaintext every time";
+    let with_iv = |iv| {
+        let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+        machine.set_offset(iv);
+        machine
+    };
+    let c1 = with_iv(1).encrypt(msg);
+    let c2 = with_iv(2).encrypt(msg);
+    assert_ne!(c1, c2);
+    assert_ne!(c1, EnigmaSudnogoDnya::new(&config()).unwrap().encrypt(msg));
+    assert_eq!(with_iv(1).decrypt(&c1).unwrap(), msg);
+    assert_eq!(with_iv(2).decrypt(&c2).unwrap(), msg);
+    assert_ne!(with_iv(2).decrypt(&c1).unwrap(), msg);
+
+    // IV 0 — машина как есть; после reset IV ставится заново
+    assert_eq!(with_iv(0).save_state(), EnigmaSudnogoDnya::new(&config()).unwrap().save_state());
+    let mut machine = with_iv(1);
+    machine.encrypt(msg);
+    machine.reset();
+    machine.set_offset(1);
+    assert_eq!(machine.encrypt(msg), c1);
+}
+
+#[test]
+fn offset_steps_blocks_not_characters() {
+    let cfg = ConfigData { step_interval: 3, ..config() };
+    let mut offset = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    offset.set_offset(5);
+    let mut advanced = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    advanced.advance_by(15);
+    assert_eq!(offset.save_state(), advanced.save_state());
+}
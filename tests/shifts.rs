@@ -0,0 +1,57 @@
+use enigma_sd::{Block, CipherError, ConfigData, EnigmaSudnogoDnya};
+
+#[test]
+fn numeric_block_matches_color_block() {
+    let colored = ConfigData {
+        plugboard: vec![('e', 'x')],
+        blocks: vec!["КБЧ".into(), "ЛГ".into()],
+        rotor_positions: vec![vec![3, 0, 17], vec![25, 4]],
+        ..Default::default()
+    };
+    let numeric = ConfigData {
+        blocks: Vec::new(),
+        block_shifts: vec![vec![1, 2, 3], vec![10, 9]],
+        ..colored.clone()
+    };
+    let mixed = ConfigData {
+        blocks: vec!["КБЧ".into()],
+        block_shifts: vec![vec![10, 9]],
+        ..colored.clone()
+    };
+    let msg = "the quick brown fox jumps over the lazy dog";
+    let expected = EnigmaSudnogoDnya::new(&colored).unwrap().encrypt(msg);
+    assert_eq!(EnigmaSudnogoDnya::new(&numeric).unwrap().encrypt(msg), expected);
+    assert_eq!(EnigmaSudnogoDnya::new(&mixed).unwrap().encrypt(msg), expected);
+    assert_eq!(mixed.block_count(), 2);
+    assert_eq!(mixed.rotor_count(), 5);
+}
+
+#[test]
+fn arbitrary_shifts_roundtrip() {
+    let cfg = ConfigData {
+        block_shifts: vec![vec![0, 13, 25], vec![11]],
+        ..Default::default()
+    };
+    assert_eq!(cfg.validate(), Ok(()));
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("hello world");
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&cipher), "hello world");
+}
+
+#[test]
+fn shifts_are_validated() {
+    let err = CipherError::ShiftOutOfRange { shift: 26, alphabet_len: 26 };
+    assert_eq!(Block::from_shifts(&[1, 26], 26).err(), Some(err.clone()));
+    assert_eq!(Block::from_shifts(&[], 26).err(), Some(CipherError::EmptyBlock));
+
+    let cfg = ConfigData {
+        blocks: vec!["К".into()],
+        block_shifts: vec![vec![5], vec![1, 26]],
+        ..Default::default()
+    };
+    assert_eq!(cfg.validate(), Err(err.clone()));
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).err(), Some(err));
+    assert_eq!(
+        cfg.validate_with_max_rotors(1),
+        Err(CipherError::TooManyRotors { block: 2, rotors: 2, max: 1 })
+    );
+}
@@ -0,0 +1,208 @@
+#![cfg(all(feature = "std", feature = "json"))]
+
+use enigma_sd::{Block, CipherError, ConfigData, EnigmaSudnogoDnya};
+
+fn config(step_interval: usize) -> ConfigData {
+    ConfigData {
+        alphabet: "cyrillic".into(),
+        plugboard: vec![('а', 'я'), ('о', 'ё')],
+        blocks: vec!["КБЧ".into(), "ЗРОФ".into()],
+        rotor_positions: vec![vec![3, 1, 4], vec![1, 5, 9, 2]],
+        step_interval,
+        ..Default::default()
+    }
+}
+
+const TEXT: &str = "съешь же ещё этих мягких французских булок, да выпей чаю";
+
+#[test]
+fn step_interval_roundtrips() {
+    let mut machine = EnigmaSudnogoDnya::new(&config(3)).unwrap();
+    let cipher = machine.encrypt(TEXT);
+    assert_ne!(cipher, EnigmaSudnogoDnya::new(&config(1)).unwrap().encrypt(TEXT));
+    machine.reset();
+    assert_eq!(machine.decrypt(&cipher).unwrap(), TEXT);
+}
+
+#[test]
+fn rotors_hold_still_within_an_interval() {
+    let mut machine = EnigmaSudnogoDnya::new(&config(3)).unwrap();
+    let cipher: Vec<char> = machine.encrypt("аааааа").chars().collect();
+    // одна подстановка на три символа подряд, потом — другая
+    assert_eq!(cipher[0], cipher[1]);
+    assert_eq!(cipher[1], cipher[2]);
+    assert_eq!(cipher[3], cipher[4]);
+    assert_eq!(cipher[4], cipher[5]);
+    assert_eq!(machine.steps(), 6);
+    assert_eq!(machine.save_state(), vec![vec![5, 1, 4], vec![3, 5, 9, 2]]);
+}
+
+#[test]
+fn seek_and_parallel_follow_the_interval() {
+    let whole = EnigmaSudnogoDnya::new(&config(3)).unwrap().encrypt(TEXT);
+    // 7 — не кратно интервалу: перемотка должна сохранить фазу
+    let split = TEXT.char_indices().nth(7).unwrap().0;
+    let (head, tail) = TEXT.split_at(split);
+
+    let mut machine = EnigmaSudnogoDnya::new(&config(3)).unwrap();
+    let head_cipher = machine.encrypt(head);
+    let mut seeked = EnigmaSudnogoDnya::new(&config(3)).unwrap();
+    seeked.advance_by(machine.steps());
+    assert_eq!(seeked.save_state(), machine.save_state());
+    assert_eq!(seeked.encrypt_parallel(tail, 4), machine.clone().encrypt(tail));
+    assert_eq!(format!("{head_cipher}{}", seeked.encrypt(tail)), whole);
+
+    machine.reset();
+    for threads in [1, 2, 5] {
+        assert_eq!(machine.encrypt_parallel(TEXT, threads), whole, "{threads} потоков");
+    }
+}
+
+#[test]
+fn period_counts_characters() {
+    let one = EnigmaSudnogoDnya::new(&config(1)).unwrap().period();
+    assert_eq!(EnigmaSudnogoDnya::new(&config(3)).unwrap().period(), 3 * one);
+}
+
+#[test]
+fn zero_interval_is_rejected() {
+    assert_eq!(config(0).validate(), Err(CipherError::ZeroStepInterval));
+    assert!(EnigmaSudnogoDnya::new(&config(0)).is_err());
+}
+
+#[test]
+fn default_interval_stays_out_of_json() {
+    let json = serde_json::to_string(&config(1)).unwrap();
+    assert!(!json.contains("step_interval"));
+    let parsed: ConfigData = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.step_interval, 1);
+    let json = serde_json::to_string(&config(3)).unwrap();
+    assert!(json.contains("\"step_interval\":3"));
+}
+
+fn small(step_every: Vec<u64>, step_interval: usize) -> ConfigData {
+    ConfigData {
+        alphabet: "latin".into(),
+        blocks: vec!["К".into(), "Б".into(), "Ч".into()],
+        rotor_positions: vec![vec![0], vec![5], vec![9]],
+        step_every,
+        step_interval,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn step_every_roundtrips() {
+    for (step_every, interval) in [(vec![1, 26], 1), (vec![3, 5], 2)] {
+        let cfg = ConfigData { step_every, ..config(interval) };
+        let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+        let cipher = machine.encrypt(TEXT);
+        assert_ne!(cipher, EnigmaSudnogoDnya::new(&config(interval)).unwrap().encrypt(TEXT));
+        machine.reset();
+        assert_eq!(machine.decrypt(&cipher).unwrap(), TEXT, "{:?}", cfg.step_every);
+    }
+}
+
+#[test]
+fn slow_blocks_step_on_their_divisor() {
+    let mut machine = EnigmaSudnogoDnya::new(&small(vec![1, 26, 676], 1)).unwrap();
+    machine.encrypt(&"a".repeat(25));
+    assert_eq!(machine.save_state(), vec![vec![25], vec![5], vec![9]]);
+    machine.encrypt("a");
+    assert_eq!(machine.save_state(), vec![vec![0], vec![6], vec![9]]);
+    // 676 символов: средний блок сделал полный оборот, последний — первый шаг
+    machine.encrypt(&"a".repeat(676 - 26));
+    assert_eq!(machine.save_state(), vec![vec![0], vec![5], vec![10]]);
+}
+
+#[test]
+fn step_every_follows_seek_and_parallel() {
+    let cfg = ConfigData { step_every: vec![2, 7], ..config(3) };
+    let whole = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(TEXT);
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    for threads in [2, 3, 7] {
+        assert_eq!(machine.encrypt_parallel(TEXT, threads), whole, "{threads} потоков");
+    }
+    let split = TEXT.char_indices().nth(40).unwrap().0;
+    let head = machine.encrypt(&TEXT[..split]);
+    let mut seeked = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    seeked.advance_by(machine.steps());
+    assert_eq!(seeked.save_state(), machine.save_state());
+    assert_eq!(format!("{head}{}", seeked.encrypt(&TEXT[split..])), whole);
+}
+
+#[test]
+fn step_every_multiplies_the_period() {
+    // три одиночных ротора на 26 позиций: без делителей НОК(26, 26, 26)
+    let plain = EnigmaSudnogoDnya::new(&small(Vec::new(), 1)).unwrap().period();
+    assert_eq!(plain, 26);
+    let cfg = small(vec![1, 26, 676], 1);
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().period(), 26 * 26 * 26);
+    assert_eq!(cfg.period(), 26 * 26 * 26);
+    // взаимно простые делители: НОК(26, 3·26, 5·26)
+    let cfg = small(vec![1, 3, 5], 2);
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().period(), 2 * 15 * 26);
+    assert_eq!(cfg.period(), 2 * 15 * 26);
+
+    // и это действительно период: состояние после него то же, что в начале
+    let mut machine = EnigmaSudnogoDnya::new(&small(vec![1, 3, 5], 1)).unwrap();
+    let start = machine.save_state();
+    let probe = machine.encrypt("attack");
+    machine.reset();
+    machine.encrypt(&"x".repeat(15 * 26));
+    assert_eq!(machine.save_state(), start);
+    assert_eq!(machine.encrypt("attack"), probe);
+}
+
+#[test]
+fn step_every_is_validated() {
+    let cfg = small(vec![1, 2], 1);
+    let mismatch = CipherError::StepEveryBlockCountMismatch { blocks: 3, step_every: 2 };
+    assert_eq!(cfg.validate(), Err(mismatch));
+    assert_eq!(small(vec![1, 0, 2], 1).validate(), Err(CipherError::ZeroStepEvery { block: 1 }));
+}
+
+/// Блоки латиницы без plugboard с заданными стартовыми позициями
+fn odometer(blocks: &[&str], positions: Vec<Vec<usize>>) -> EnigmaSudnogoDnya {
+    let cfg = ConfigData {
+        blocks: blocks.iter().map(|b| b.to_string()).collect(),
+        rotor_positions: positions,
+        ..Default::default()
+    };
+    EnigmaSudnogoDnya::new(&cfg).unwrap()
+}
+
+#[test]
+fn single_rotor_block_steps_every_character() {
+    for k in [1, 2, 5, 26, 27] {
+        let mut machine = odometer(&["К"], vec![vec![24]]);
+        machine.encrypt(&"a".repeat(k));
+        assert_eq!(machine.save_state(), vec![vec![(24 + k) % 26]], "k = {k}");
+    }
+    let mut block = Block::new("К", 26).unwrap();
+    block.load_positions(&[24]);
+    for _ in 0..5 {
+        block.rotate();
+    }
+    assert_eq!(block.save_positions(), [3]);
+}
+
+#[test]
+fn blocks_are_independent_odometers() {
+    let start = vec![vec![25], vec![3, 0], vec![7]];
+    let mut machine = odometer(&["К", "БЧ", "З"], start.clone());
+    machine.encrypt("a");
+    // однороторный блок прошёл через 0, но соседний блок от этого не шагнул
+    // лишний раз: каждый блок сдвинул только свой первый ротор
+    assert_eq!(machine.save_state(), vec![vec![0], vec![4, 0], vec![8]]);
+
+    // 30 символов: 25 + 30 = 55 → 3; 3 + 30 = 33 → 7 и перенос во второй
+    // ротор блока; 7 + 30 = 37 → 11
+    let expected = vec![vec![3], vec![7, 1], vec![11]];
+    let mut machine = odometer(&["К", "БЧ", "З"], start.clone());
+    machine.encrypt(&"a".repeat(30));
+    assert_eq!(machine.save_state(), expected);
+    let mut jumped = odometer(&["К", "БЧ", "З"], start);
+    jumped.advance_by(30);
+    assert_eq!(jumped.save_state(), expected);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::{CharStep, CharTrace, ConfigData, EnigmaSudnogoDnya};
+
+fn config() -> ConfigData {
+    ConfigData {
+        plugboard: vec![('a', 'b')],
+        blocks: vec!["КБ".into(), "Ч".into()],
+        rotor_positions: vec![vec![1, 2], vec![3]],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn one_character_trace_matches_hand_computation() {
+    let (out, trace) = EnigmaSudnogoDnya::new(&config()).unwrap().encrypt_traced("h");
+    // 'h' = 7, plugboard не трогает.
+    // Блок 0: К (сдвиг 1, позиция 1) → 7 + 2 = 9, Б (2, 2) → 9 + 4 = 13.
+    // Блок 1: Ч (3, 3) → 13 + 6 = 19. Рефлектор: 25 - 19 = 6.
+    // Обратно: Ч → 6 - 6 = 0, затем Б → 0 - 4 = 22, К → 22 - 2 = 20 = 'u'.
+    let expected = CharTrace {
+        input: 'h',
+        output: 'u',
+        positions: vec![vec![1, 2], vec![3]],
+        after_plugboard: 7,
+        forward: vec![13, 19],
+        after_reflector: 6,
+        backward: vec![0, 20],
+        after_plugboard_out: 20,
+    };
+    assert_eq!(out, "u");
+    assert_eq!(trace, vec![expected]);
+}
+
+#[test]
+fn trace_agrees_with_encrypt() {
+    let msg = "Hello, World! a-b";
+    let (out, trace) = EnigmaSudnogoDnya::new(&config()).unwrap().encrypt_traced(msg);
+    assert_eq!(out, EnigmaSudnogoDnya::new(&config()).unwrap().encrypt(msg));
+
+    let letters: String = msg.to_lowercase().chars().filter(char::is_ascii_lowercase).collect();
+    assert_eq!(trace.iter().map(|t| t.input).collect::<String>(), letters);
+    // второй символ шифруется уже после шага: каждый блок сдвинул первый ротор
+    assert_eq!(trace[1].positions, vec![vec![2, 2], vec![4]]);
+
+    let json = serde_json::to_string(&trace).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<CharTrace>>(&json).unwrap(), trace);
+}
+
+#[test]
+fn watched_steps_show_positions_before_stepping() {
+    let msg = "Hi, a";
+    let mut steps = Vec::new();
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let out = machine.encrypt_watched(msg, 1, |step| steps.push(step.clone()));
+    assert_eq!(out, EnigmaSudnogoDnya::new(&config()).unwrap().encrypt(msg));
+    // каждый символ, включая не из алфавита; блоков — только первый
+    let first = CharStep { input: 'h', positions: vec![vec![1, 2]], output: 'u' };
+    assert_eq!(steps[0], first);
+    assert_eq!(steps.iter().map(|s| s.input).collect::<String>(), "hi, a");
+    assert_eq!(steps.iter().map(|s| s.output).collect::<String>(), out);
+    let positions: Vec<_> = steps.iter().map(|s| s.positions[0].clone()).collect();
+    assert_eq!(positions, [vec![1, 2], vec![2, 2], vec![3, 2], vec![3, 2], vec![3, 2]]);
+
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let mut count = 0;
+    assert_eq!(machine.decrypt_watched(&out, 5, |step| count += step.positions.len()), "hi, a");
+    assert_eq!(count, 2 * 5, "блоков всего два");
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "json")]
+
+use enigma_sd::{Block, CipherError, ConfigData, EnigmaSudnogoDnya};
+
+fn config_with_blocks(blocks: &[&str]) -> ConfigData {
+    ConfigData {
+        alphabet: "latin".into(),
+        plugboard: Vec::new(),
+        blocks: blocks.iter().map(|b| b.to_string()).collect(),
+        rotor_positions: Vec::new(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn config_without_blocks_needs_an_override() {
+    let mut cfg = ConfigData { plugboard: vec![('a', 'b')], ..config_with_blocks(&[]) };
+    assert_eq!(cfg.validate(), Err(CipherError::NoBlocks));
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).err(), Some(CipherError::NoBlocks));
+    // блоки только числами — тоже блоки
+    let shifts = ConfigData { block_shifts: vec![vec![3]], ..cfg.clone() };
+    assert!(EnigmaSudnogoDnya::new(&shifts).is_ok());
+
+    // с разрешением — прежняя машина: одна и та же замена на каждом символе
+    let key = cfg.fingerprint();
+    cfg.allow_degenerate = true;
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let cipher = machine.encrypt("aaaa");
+    assert!(cipher.chars().all(|c| cipher.starts_with(c)));
+    // разрешение — не часть ключа и в файл не пишется
+    assert_eq!(cfg.fingerprint(), key);
+    assert!(!serde_json::to_string(&cfg).unwrap().contains("degenerate"));
+}
+
+#[test]
+fn empty_block_is_rejected() {
+    assert_eq!(Block::new("", 26).err(), Some(CipherError::EmptyBlock));
+    let cfg = config_with_blocks(&["КБ", ""]);
+    assert_eq!(cfg.validate(), Err(CipherError::EmptyBlock));
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&cfg).err(),
+        Some(CipherError::EmptyBlock)
+    );
+}
+
+#[test]
+fn single_rotor_block_is_valid() {
+    let cfg = config_with_blocks(&["К"]);
+    assert_eq!(cfg.validate(), Ok(()));
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    let cipher = machine.encrypt("hello");
+    assert_eq!(EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt(&cipher), "hello");
+}
+
+#[test]
+fn rotor_limit_is_enforced() {
+    let cfg = config_with_blocks(&["КБЧЗР"]);
+    assert_eq!(
+        cfg.validate_with_max_rotors(4),
+        Err(CipherError::TooManyRotors { block: 0, rotors: 5, max: 4 })
+    );
+    assert_eq!(cfg.validate_with_max_rotors(5), Ok(()));
+}
+
+#[test]
+fn plugboard_pairs_are_checked() {
+    let with_pairs = |pairs: &[(char, char)]| ConfigData {
+        plugboard: pairs.to_vec(),
+        ..config_with_blocks(&["КБ"])
+    };
+    assert_eq!(with_pairs(&[('a', 'b'), ('c', 'd')]).validate(), Ok(()));
+    assert_eq!(
+        with_pairs(&[('a', 'ж')]).validate(),
+        Err(CipherError::PlugboardUnknownChar('ж'))
+    );
+    assert_eq!(
+        with_pairs(&[('a', 'b'), ('b', 'c')]).validate(),
+        Err(CipherError::PlugboardCharReused('b'))
+    );
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&with_pairs(&[('q', 'q')])).err(),
+        Some(CipherError::PlugboardSelfPair('q'))
+    );
+}
+
+#[test]
+fn position_vectors_must_match_blocks() {
+    let cfg = ConfigData {
+        rotor_positions: vec![vec![1, 2]],
+        ..config_with_blocks(&["КБ", "Ч"])
+    };
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&cfg).err(),
+        Some(CipherError::PositionBlockCountMismatch { blocks: 2, positions: 1 })
+    );
+}
+
+#[test]
+fn position_vector_must_match_rotors_in_block() {
+    let short = ConfigData {
+        rotor_positions: vec![vec![1, 2], vec![3, 4]],
+        ..config_with_blocks(&["КБ", "ЧЗР"])
+    };
+    assert_eq!(
+        EnigmaSudnogoDnya::new(&short).err(),
+        Some(CipherError::RotorPositionCountMismatch { block: 1, rotors: 3, positions: 2 })
+    );
+    let long = ConfigData {
+        rotor_positions: vec![vec![1, 2, 0], vec![3, 4, 5]],
+        ..config_with_blocks(&["КБ", "ЧЗР"])
+    };
+    assert_eq!(
+        long.validate(),
+        Err(CipherError::RotorPositionCountMismatch { block: 0, rotors: 2, positions: 3 })
+    );
+}
+
+#[test]
+fn one_position_off_is_incompatible_for_decrypt() {
+    let cfg = ConfigData {
+        plugboard: vec![('a', 'q')],
+        rotor_positions: vec![vec![1, 2], vec![3, 4, 5]],
+        ..config_with_blocks(&["КБ", "ЧЗР"])
+    };
+    assert!(cfg.compatible_for_decrypt(&cfg.clone()));
+    let shifted = ConfigData { rotor_positions: vec![vec![1, 2], vec![3, 4, 6]], ..cfg.clone() };
+    assert!(!cfg.compatible_for_decrypt(&shifted));
+    // и вправду расшифровывает в мусор
+    let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("attack at dawn");
+    let garbage = EnigmaSudnogoDnya::new(&shifted).unwrap().decrypt(&cipher).unwrap();
+    assert_ne!(garbage, "attack at dawn");
+
+    let other_pairs = ConfigData { plugboard: vec![('a', 'r')], ..cfg.clone() };
+    let other_reflector = ConfigData { reflector_kind: "ukw-b".into(), ..cfg.clone() };
+    let other_alphabet = ConfigData { alphabet: "cyrillic".into(), ..cfg.clone() };
+    for other in [other_pairs, other_reflector, other_alphabet] {
+        assert!(!cfg.compatible_for_decrypt(&other));
+        assert!(!other.compatible_for_decrypt(&cfg));
+    }
+    // язык и написание цветов ключа не меняют
+    let same = ConfigData {
+        lang: Some(enigma_sd::messages::Lang::En),
+        blocks: vec!["kb".into(), "чзр".into()],
+        ..cfg.clone()
+    };
+    assert!(cfg.compatible_for_decrypt(&same));
+}
+
+#[test]
+fn equivalent_plugboards_and_positions_are_compatible_for_decrypt() {
+    let cfg = ConfigData {
+        plugboard: vec![('a', 'b'), ('c', 'd')],
+        rotor_positions: vec![vec![0, 0], vec![0, 0, 0]],
+        ..config_with_blocks(&["КБ", "ЧЗР"])
+    };
+    let flipped = ConfigData { plugboard: vec![('b', 'a'), ('c', 'd')], ..cfg.clone() };
+    let reordered = ConfigData { plugboard: vec![('c', 'd'), ('a', 'b')], ..cfg.clone() };
+    let no_positions = ConfigData { rotor_positions: Vec::new(), ..cfg.clone() };
+    let stacked = ConfigData {
+        plugboard: Vec::new(),
+        plugboards: vec![vec![('d', 'c'), ('b', 'a')]],
+        ..cfg.clone()
+    };
+    for other in [flipped, reordered, no_positions, stacked] {
+        assert!(cfg.compatible_for_decrypt(&other));
+        assert!(other.compatible_for_decrypt(&cfg));
+        // и вправду расшифровывает
+        let cipher = EnigmaSudnogoDnya::new(&cfg).unwrap().encrypt("attack at dawn");
+        let plain = EnigmaSudnogoDnya::new(&other).unwrap().decrypt(&cipher).unwrap();
+        assert_eq!(plain, "attack at dawn");
+    }
+    // ненулевая позиция против недостающей — уже другой ключ
+    let moved = ConfigData { rotor_positions: vec![vec![0, 1]], ..cfg.clone() };
+    assert!(!cfg.compatible_for_decrypt(&moved));
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "fs")]
+
+use enigma_sd::vectors::{self, VECTORS_DIR};
+use std::path::Path;
+
+#[test]
+fn builtin_vectors_match_this_build() {
+    for vector in vectors::builtin() {
+        assert!(!vector.ciphertext.is_empty(), "{}", vector.name);
+        assert!(vector.verify().unwrap(), "{}", vector.name);
+    }
+}
+
+#[test]
+fn builtin_vectors_cover_the_directory() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(VECTORS_DIR);
+    let mut on_disk: Vec<String> = vectors::load_dir(&dir)
+        .unwrap()
+        .into_iter()
+        .map(|(_, v)| v.name)
+        .collect();
+    let mut builtin: Vec<String> = vectors::builtin().into_iter().map(|v| v.name).collect();
+    on_disk.sort();
+    builtin.sort();
+    assert_eq!(on_disk, builtin);
+}
+
+#[test]
+fn changed_ciphertext_fails_verification() {
+    let mut vector = vectors::builtin().remove(0);
+    vector.ciphertext.replace_range(..1, "!");
+    assert!(!vector.verify().unwrap());
+}
@@ -0,0 +1,30 @@
+//! Привязки в headless-браузере:
+//! `wasm-pack test --headless --chrome -- --no-default-features --features wasm`.
+//! В обычной сборке файл пуст.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use enigma_sd::vectors;
+use enigma_sd::wasm::{EsdMachine, generate_config};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn kat_vectors_pass_in_browser() {
+    for vector in vectors::builtin() {
+        let json = serde_json::to_string(&vector.config).unwrap();
+        let Ok(mut machine) = EsdMachine::new(&json) else { panic!("{}", vector.name) };
+        assert_eq!(machine.encrypt(&vector.plaintext), vector.ciphertext, "{}", vector.name);
+        machine.reset();
+        assert_eq!(machine.encrypt(&vector.ciphertext), vector.plaintext.to_lowercase());
+    }
+}
+
+#[wasm_bindgen_test]
+fn generated_config_depends_only_on_seed() {
+    let config = |seed| generate_config("secure", seed).ok().unwrap();
+    assert_eq!(config(815), config(815));
+    assert_ne!(config(815), config(816));
+    assert!(EsdMachine::new(&config(815)).is_ok());
+    assert!(generate_config("no such preset", 1).is_err());
+}
@@ -0,0 +1,190 @@
+#![cfg(all(feature = "std", feature = "rand"))]
+
+use enigma_sd::console::Console;
+use enigma_sd::messages::Msg;
+use enigma_sd::wizard::{self, Answer, Outcome, Params, Summary, parse_answer};
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya, PRESETS};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn params() -> Params {
+    Params { alphabet: "latin".into(), blocks: 3, pairs: 4, fixed_pairs: None }
+}
+
+/// Мастер на строке ответов: итог, вывод и параметры каждой генерации
+fn run(params: Params, script: &str) -> (Outcome, String, Vec<Params>) {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut seen = Vec::new();
+    let generate = |p: &Params| {
+        seen.push(p.clone());
+        p.generate(&mut rng, |_| {})
+    };
+    let mut output = Vec::new();
+    let outcome = wizard::run(params, generate, &mut Console::new(script.as_bytes(), &mut output));
+    (outcome.unwrap(), String::from_utf8(output).unwrap(), seen)
+}
+
+/// Весь мастер с первого вопроса; `None` — мастер не дошёл до конфига
+fn setup(script: &str) -> (Option<Outcome>, String, Vec<Params>) {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut seen = Vec::new();
+    let generate = |p: &Params| {
+        seen.push(p.clone());
+        p.generate(&mut rng, |_| {})
+    };
+    let mut output = Vec::new();
+    let outcome = wizard::setup(&mut Console::new(script.as_bytes(), &mut output), generate);
+    (outcome.unwrap(), String::from_utf8(output).unwrap(), seen)
+}
+
+/// Строка сводки с числом роторов — по одной на каждую показанную сводку
+fn rotors_line(cfg: &ConfigData) -> String {
+    Msg::WizardRotors.fill(&[&cfg.rotor_count(), &cfg.block_count()])
+}
+
+#[test]
+fn answers_parse() {
+    assert_eq!(parse_answer("save\n"), Some(Answer::Save));
+    assert_eq!(parse_answer("да"), Some(Answer::Save));
+    assert_eq!(parse_answer(" no "), Some(Answer::Keep));
+    assert_eq!(parse_answer("regen"), Some(Answer::Regenerate));
+    assert_eq!(parse_answer("blocks 12"), Some(Answer::Blocks(12)));
+    assert_eq!(parse_answer("pairs 0"), Some(Answer::Pairs(0)));
+    assert_eq!(parse_answer("blocks 0"), None);
+    assert_eq!(parse_answer("blocks"), None);
+    assert_eq!(parse_answer("pairs x"), None);
+    assert_eq!(parse_answer("save now"), None);
+    assert_eq!(parse_answer(""), None);
+}
+
+#[test]
+fn summary_is_shown_before_the_question() {
+    let (outcome, output, seen) = run(params(), "save\n");
+    assert!(outcome.save);
+    assert_eq!(seen, [params()]);
+    assert_eq!(outcome.cfg.block_count(), 3);
+    assert_eq!(outcome.cfg.plugboard.len(), 4);
+    let summary = output.find(&rotors_line(&outcome.cfg)).unwrap();
+    assert!(summary < output.find(Msg::SaveConfig.text()).unwrap());
+    let period = enigma_sd::format_period(outcome.cfg.period());
+    assert!(output.contains(&Msg::Period.fill(&[&period])));
+}
+
+#[test]
+fn tweaks_regenerate_with_new_params() {
+    let (outcome, output, seen) = run(params(), "blocks 5\nwhat\npairs 2\nregen\nno\n");
+    assert!(!outcome.save);
+    let counts: Vec<(usize, usize)> = seen.iter().map(|p| (p.blocks, p.pairs)).collect();
+    assert_eq!(counts, [(3, 4), (5, 4), (5, 2), (5, 2)]);
+    assert_eq!(outcome.cfg.block_count(), 5);
+    assert_eq!(outcome.cfg.plugboard.len(), 2);
+    assert_eq!(output.matches(Msg::WizardUnknownAnswer.text()).count(), 1);
+    // сводка после каждой генерации, вопрос после каждой сводки и ошибки
+    let strength = Msg::WizardStrength.text().split("{}").next().unwrap();
+    assert_eq!(output.matches(strength).count(), 4);
+    assert_eq!(output.matches(Msg::SaveConfig.text()).count(), 5);
+}
+
+#[test]
+fn impossible_pairs_keep_the_previous_config() {
+    let (first, ..) = run(params(), "no\n");
+    let (outcome, output, seen) = run(params(), "pairs 14\n");
+    // латиница — 26 букв, пар не больше 13; конец ввода — не сохранять
+    assert!(!outcome.save);
+    assert_eq!(seen.len(), 2);
+    assert_eq!(outcome.cfg.blocks, first.cfg.blocks);
+    assert_eq!(outcome.cfg.plugboard, first.cfg.plugboard);
+    assert_eq!(output.matches(&rotors_line(&outcome.cfg)).count(), 1);
+}
+
+#[test]
+fn hand_made_pairs_survive_regeneration() {
+    let fixed = vec![('a', 'z'), ('b', 'y')];
+    let params = Params { fixed_pairs: Some(fixed.clone()), ..params() };
+    let (outcome, ..) = run(params.clone(), "regen\nsave\n");
+    assert_eq!(outcome.cfg.plugboard, fixed);
+    // другое число пар — снова случайные
+    let (outcome, _, seen) = run(params, "pairs 6\nsave\n");
+    assert_eq!(seen[1].fixed_pairs, None);
+    assert_eq!(outcome.cfg.plugboard.len(), 6);
+}
+
+#[test]
+fn throughput_leaves_the_machine_where_it_was() {
+    let cfg = params().generate(&mut StdRng::seed_from_u64(1), |_| {}).unwrap();
+    let mut machine = EnigmaSudnogoDnya::new(&cfg).unwrap();
+    machine.encrypt("warm");
+    let state = machine.save_state();
+    assert!(wizard::throughput(&mut machine) > 0.0);
+    assert_eq!(machine.save_state(), state);
+    let summary = Summary::measure(&cfg).unwrap();
+    assert_eq!((summary.rotors, summary.blocks), (cfg.rotor_count(), 3));
+    assert!(summary.sample_secs() > 0.0);
+}
+
+#[test]
+fn setup_with_a_preset() {
+    let (outcome, output, seen) = setup("1\n1\nsave\n");
+    let outcome = outcome.unwrap();
+    assert!(outcome.save);
+    let preset = &PRESETS[0];
+    let expected = Params {
+        alphabet: "latin".into(),
+        blocks: preset.blocks,
+        pairs: preset.plugboard_pairs,
+        fixed_pairs: None,
+    };
+    assert_eq!(seen, [expected]);
+    assert_eq!(outcome.cfg.alphabet, "latin");
+    // список пресетов, затем сводка и вопрос о сохранении
+    let listed = output.find(preset.localized_name()).unwrap();
+    assert!(listed < output.find(&rotors_line(&outcome.cfg)).unwrap());
+    assert!(output.trim_end().ends_with(Msg::SaveConfig.text().trim_end()));
+}
+
+#[test]
+fn setup_by_hand_checks_pairs() {
+    let script = "2\n0\n1\nа б\nа в\nz y\nlist\n\n2\nno\n";
+    let (outcome, output, seen) = setup(script);
+    let outcome = outcome.unwrap();
+    assert!(!outcome.save);
+    assert_eq!(seen.len(), 1);
+    assert_eq!((seen[0].alphabet.as_str(), seen[0].blocks), ("cyrillic", 2));
+    assert_eq!(outcome.cfg.plugboard, [('а', 'б')]);
+    // занятая буква и буква не из алфавита — подсказки, пара не добавлена
+    assert!(output.contains(&Msg::PairSymbolTaken.fill(&[&'а', &'а'])));
+    assert!(output.contains(&Msg::PairNotInAlphabet.fill(&[&'z'])));
+    assert!(output.contains("  а ↔ б"));
+}
+
+#[test]
+fn setup_stops_at_end_of_input() {
+    for script in ["", "1\n", "1\n0\n2\n"] {
+        let (outcome, _, seen) = setup(script);
+        assert!(outcome.is_none(), "{script:?}");
+        assert!(seen.is_empty());
+    }
+    // после генерации конец ввода — конфиг есть, но не сохраняется
+    let (outcome, ..) = setup("1\n1\n");
+    assert!(!outcome.unwrap().save);
+}
+
+#[test]
+fn setup_lists_every_alphabet() {
+    // номер из меню или имя; непонятный ответ — кириллица
+    for (answer, alphabet) in [("3", "ukrainian"), ("greek", "greek"), ("9", "cyrillic")] {
+        let (outcome, output, _) = setup(&format!("{answer}\n1\n"));
+        assert_eq!(outcome.unwrap().cfg.alphabet, alphabet, "{answer}");
+        assert!(output.contains("4) "), "{output}");
+    }
+}
+
+#[test]
+fn setup_pairs_accept_an_escaped_space() {
+    let script = "latin-ext\n0\n1\n_ x\nlist\n\n2\nno\n";
+    let (outcome, output, _) = setup(script);
+    let outcome = outcome.unwrap();
+    assert_eq!(outcome.cfg.alphabet, "latin-ext");
+    assert_eq!(outcome.cfg.plugboard, [(' ', 'x')]);
+    assert!(output.contains(&format!("  {} ↔ x", wizard::SPACE_KEY)));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use enigma_sd::wrap::{unwrap, wrap};
+use enigma_sd::{ConfigData, EnigmaSudnogoDnya};
+
+fn config() -> ConfigData {
+    common::config("latin", &[('a', 'q')])
+}
+
+const MESSAGE: &str = "attack at dawn, hold the bridge until relieved\nand then fall back";
+
+#[test]
+fn wrap_breaks_every_cols_characters() {
+    assert_eq!(wrap("abcdefg", 3), "abc\ndef\ng");
+    assert_eq!(wrap("abcdef", 3), "abc\ndef", "в конце переноса нет");
+    assert_eq!(wrap("", 3), "");
+    // свои переводы строк тоже считаются символами
+    assert_eq!(wrap("abc\nd", 3), "abc\n\nd");
+    assert_eq!(unwrap("abc\n\nd", 3), "abc\nd");
+}
+
+#[test]
+fn unwrap_restores_the_ciphertext_exactly() {
+    let cipher = EnigmaSudnogoDnya::new(&config()).unwrap().encrypt(MESSAGE);
+    for cols in [1, 5, 16, MESSAGE.len(), MESSAGE.len() + 1] {
+        let wrapped = wrap(&cipher, cols);
+        assert!(wrapped.lines().all(|line| line.chars().count() <= cols));
+        assert_eq!(unwrap(&wrapped, cols), cipher, "cols = {}", cols);
+    }
+    // текст без переносов не меняется
+    assert_eq!(unwrap(&cipher, 16), cipher);
+}
+
+#[test]
+fn decrypting_unwrapped_text_matches_the_plain_ciphertext() {
+    let mut machine = EnigmaSudnogoDnya::new(&config()).unwrap();
+    let cipher = machine.encrypt(MESSAGE);
+    machine.reset();
+    let direct = machine.decrypt(&cipher).unwrap();
+    machine.reset();
+    let wrapped = machine.decrypt(&unwrap(&wrap(&cipher, 7), 7)).unwrap();
+    assert_eq!((direct.as_str(), wrapped.as_str()), (MESSAGE, MESSAGE));
+
+    // с тегом целостности лишние переводы строк не прошли бы проверку
+    let key = config().mac_key("pass");
+    machine.reset();
+    let sealed = machine.encrypt_authenticated(MESSAGE, &key);
+    machine.reset();
+    assert!(machine.decrypt_authenticated(&wrap(&sealed, 7), &key).is_err());
+    machine.reset();
+    let opened = machine.decrypt_authenticated(&unwrap(&wrap(&sealed, 7), 7), &key).unwrap();
+    assert_eq!(opened, MESSAGE);
+}